@@ -0,0 +1,42 @@
+//! Benchmark of the press path of `ChessClock`
+//!
+//! Plays games of many presses under each timing method and reports how long
+//! the presses took to start the opponent's clock, on average and at worst,
+//! as measured by the clock itself. Run with `cargo bench`.
+
+use std::hint::black_box;
+use rusty_chess_clock::{times, ChessClock, Player, Rules, TimingMethod};
+
+/// The presses of each game
+const PRESSES: u32 = 10_000;
+
+/// The presses made before measuring, to warm up caches and the event log
+const WARM_UP: u32 = 1_000;
+
+fn main() {
+    println!(
+        "{:<10} {:>8} {:>12} {:>12}", "method", "presses", "average", "worst"
+    );
+    for method in TimingMethod::ALL {
+        let rules = Rules::new(
+            times::ONE_HOUR, times::ONE_HOUR, times::TWO_SECONDS,
+            Player::Player1, method
+        );
+        let mut clock = ChessClock::new(rules);
+        clock.start();
+        for _ in 0..WARM_UP {
+            clock.switch_player();
+        }
+        clock.reset_press_latency();
+        for _ in 0..PRESSES {
+            black_box(&mut clock).switch_player();
+        }
+
+        let latency = clock.press_latency();
+        println!(
+            "{:<10} {:>8} {:>12?} {:>12?}",
+            method.to_string(), latency.presses(),
+            latency.average().unwrap_or_default(), latency.worst()
+        );
+    }
+}
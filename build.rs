@@ -0,0 +1,23 @@
+// Record the git revision the crate was built from, for `--version`
+use std::{path::Path, process::Command};
+
+fn main() {
+    let revision = Command::new("git")
+        .args(["describe", "--always", "--dirty"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|revision| revision.trim().to_string())
+        .filter(|revision| !revision.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=RUSTY_CHESS_CLOCK_GIT={}", revision);
+
+    // Only watch git when building from a checkout, otherwise cargo would
+    // rerun the script on every build
+    for path in [".git/HEAD", ".git/index"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+// One binary for every frontend, with a subcommand for each
+use std::{env, process};
+use rusty_chess_clock::tui::completions::{self, Shell};
+
+const HELP: &str = "\
+Usage: rusty-clock <COMMAND> [OPTIONS]
+
+Commands:
+  timer                Stopwatches, countdowns and other timers
+  chess                A chess clock for the terminal
+  gui                  A chess clock window
+  heatmap SESSION...   Average time per move by move number, as CSV
+  stats [PLAYER...]    Each player's record over their past games
+  completions SHELL    Print a completion script for bash, zsh or fish
+
+Options:
+  -h, --help           Print this help, or a command's help after it
+  -V, --version        Print the version
+";
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let command = args.next().unwrap_or_default();
+    match command.as_str() {
+        "timer" => rusty_chess_clock::tui::timer::run("rusty-clock timer", args),
+        "chess" => rusty_chess_clock::tui::chess::run("rusty-clock chess", args),
+        "gui" => run_gui(args),
+        "heatmap" => {
+            rusty_chess_clock::tui::heatmap::run("rusty-clock heatmap", args);
+        },
+        "stats" => rusty_chess_clock::tui::stats::run("rusty-clock stats", args),
+        "completions" => print_completions(args.next()),
+        "-h" | "--help" => print!("{}", HELP),
+        "-V" | "--version" => {
+            println!("rusty-clock {}", rusty_chess_clock::VERSION);
+        },
+        "" => {
+            eprint!("{}", HELP);
+            process::exit(1);
+        },
+        _ => {
+            eprintln!("Unknown command {}, see --help", command);
+            process::exit(1);
+        },
+    }
+}
+
+fn print_completions(shell: Option<String>) {
+    match shell.as_deref().and_then(Shell::from_name) {
+        Some(shell) => print!("{}", completions::generate(shell)),
+        None => {
+            eprintln!("Expected a shell, one of bash, zsh or fish");
+            process::exit(1);
+        },
+    }
+}
+
+#[cfg(feature = "gui")]
+fn run_gui(args: impl Iterator<Item = String>) {
+    if let Err(error) = rusty_chess_clock::gui::run("rusty-clock gui", args) {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "gui"))]
+fn run_gui(_args: impl Iterator<Item = String>) {
+    eprintln!("rusty-clock was built without the gui feature");
+    process::exit(1);
+}
@@ -0,0 +1,102 @@
+//! # Branding
+//! The names and icon the front ends present to the desktop
+//!
+//! The icon is drawn in code rather than shipped as an image, so that the
+//! window can set it without an image decoder: a white dial on a dark face
+//! with a single hand at two o'clock.
+
+/// The application name shown to users
+pub const APP_NAME: &str = "Rusty Chess Clock";
+
+/// The reverse-DNS application id. On Linux this is the Wayland app id and
+/// X11 `WM_CLASS`, and must match the name of the installed `.desktop` file.
+pub const APP_ID: &str = "io.github.michaelbennett99.RustyChessClock";
+
+/// The width and height of the window icon in pixels
+pub const ICON_SIZE: u32 = 64;
+
+const FACE: [u8; 4] = [0x20, 0x24, 0x2b, 0xff];
+const RIM: [u8; 4] = [0xf0, 0xf0, 0xf0, 0xff];
+const HAND: [u8; 4] = [0xe0, 0x40, 0x30, 0xff];
+const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+/// Build the title of a window showing a page
+///
+/// macOS already shows the application name in the menu bar, so the title
+/// there is just the page; elsewhere the application name is appended so
+/// the window can be told apart in the task bar.
+///
+/// # Arguments
+/// * `page` - The page the window is showing.
+///
+/// # Returns
+/// The window title.
+pub fn window_title(page: &str) -> String {
+    if cfg!(target_os = "macos") {
+        page.to_string()
+    } else {
+        format!("{} - {}", page, APP_NAME)
+    }
+}
+
+/// Draw the application icon
+///
+/// # Returns
+/// `ICON_SIZE` by `ICON_SIZE` pixels as row-major RGBA bytes.
+pub fn icon_rgba() -> Vec<u8> {
+    let centre = ICON_SIZE as f32 / 2.0;
+    let radius = centre - 1.0;
+    let rim = radius * 0.85;
+    // The hand points at two o'clock, 60 degrees clockwise from twelve
+    let (sin, cos) = (std::f32::consts::PI / 3.0).sin_cos();
+    let hand = (sin, -cos);
+
+    let mut pixels = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let dx = x as f32 + 0.5 - centre;
+            let dy = y as f32 + 0.5 - centre;
+            let distance = (dx * dx + dy * dy).sqrt();
+            let along = dx * hand.0 + dy * hand.1;
+            let across = (dx * hand.1 - dy * hand.0).abs();
+            let colour = if distance > radius {
+                CLEAR
+            } else if distance > rim {
+                RIM
+            } else if (0.0..rim * 0.8).contains(&along) && across < 2.5 {
+                HAND
+            } else {
+                FACE
+            };
+            pixels.extend_from_slice(&colour);
+        }
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(pixels: &[u8], x: u32, y: u32) -> &[u8] {
+        let start = ((y * ICON_SIZE + x) * 4) as usize;
+        &pixels[start..start + 4]
+    }
+
+    #[test]
+    fn test_icon_layout() {
+        let pixels = icon_rgba();
+        assert_eq!(pixels.len(), (ICON_SIZE * ICON_SIZE * 4) as usize);
+        assert_eq!(pixel(&pixels, 0, 0), CLEAR);
+        assert_eq!(pixel(&pixels, ICON_SIZE / 2, 1), RIM);
+        assert_eq!(pixel(&pixels, ICON_SIZE / 2, ICON_SIZE / 2), HAND);
+        assert_eq!(pixel(&pixels, ICON_SIZE / 2, ICON_SIZE - 12), FACE);
+    }
+
+    #[test]
+    fn test_window_title() {
+        let title = window_title("Chess Clock");
+        assert!(title.starts_with("Chess Clock"));
+        assert_eq!(title.contains(APP_NAME), !cfg!(target_os = "macos"));
+    }
+}
@@ -0,0 +1,115 @@
+//! # Burn-in
+//! Protection against image burn-in for clocks left on dedicated displays,
+//! such as OLED televisions running at a club for hours.
+//!
+//! The clock layout is moved slowly around a small circle, a few pixels
+//! from where it would be, so that no pixel shows the same edge all
+//! evening. A clock left stopped for a long time is dimmed as well.
+
+use std::{f32::consts::PI, time::Duration};
+
+/// How far and how often the layout moves, and when a stopped clock dims
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BurnIn {
+    max_shift: f32,
+    period: Duration,
+    dim_after: Duration,
+}
+
+impl BurnIn {
+    /// Create new burn-in protection
+    ///
+    /// # Arguments
+    /// * `max_shift` - How far the layout moves from its place, in pixels.
+    /// * `period` - How long the layout takes to go once around its circle.
+    /// * `dim_after` - How long a clock must be stopped before it dims.
+    pub const fn new(
+        max_shift: f32, period: Duration, dim_after: Duration
+    ) -> Self {
+        Self { max_shift, period, dim_after }
+    }
+
+    pub const fn get_max_shift(&self) -> f32 {
+        self.max_shift
+    }
+
+    pub const fn get_period(&self) -> Duration {
+        self.period
+    }
+
+    pub const fn get_dim_after(&self) -> Duration {
+        self.dim_after
+    }
+
+    pub fn set_max_shift(&mut self, max_shift: f32) {
+        self.max_shift = max_shift;
+    }
+
+    pub fn set_period(&mut self, period: Duration) {
+        self.period = period;
+    }
+
+    pub fn set_dim_after(&mut self, dim_after: Duration) {
+        self.dim_after = dim_after;
+    }
+
+    /// Get how far the layout is moved at a point in time
+    ///
+    /// # Arguments
+    /// * `elapsed` - The time since any fixed instant, such as the Unix
+    ///   epoch, so that every window showing the clock moves together.
+    ///
+    /// # Returns
+    /// The shift right and down in pixels, each from `-max_shift` to
+    /// `max_shift`.
+    pub fn offset(&self, elapsed: Duration) -> (f32, f32) {
+        if self.period.is_zero() {
+            return (0.0, 0.0);
+        }
+        let turns = elapsed.as_secs_f64() / self.period.as_secs_f64();
+        let (sin, cos) = (turns.fract() as f32 * 2.0 * PI).sin_cos();
+        (self.max_shift * cos, self.max_shift * sin)
+    }
+
+    /// Get whether a clock stopped for `stopped_for` should be dimmed
+    pub fn is_dimmed(&self, stopped_for: Duration) -> bool {
+        stopped_for >= self.dim_after
+    }
+}
+
+impl Default for BurnIn {
+    /// A 4 pixel shift once around every minute, dimming after 5 minutes
+    fn default() -> Self {
+        Self::new(4.0, Duration::from_secs(60), Duration::from_secs(5 * 60))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: (f32, f32), b: (f32, f32)) -> bool {
+        (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3
+    }
+
+    #[test]
+    fn test_offset_goes_around() {
+        let burn_in = BurnIn::default();
+        assert!(close(burn_in.offset(Duration::ZERO), (4.0, 0.0)));
+        assert!(close(burn_in.offset(Duration::from_secs(15)), (0.0, 4.0)));
+        assert!(close(burn_in.offset(Duration::from_secs(30)), (-4.0, 0.0)));
+        // A whole number of periods later it is back where it started
+        let later = burn_in.offset(Duration::from_secs(600));
+        assert!(close(later, burn_in.offset(Duration::ZERO)));
+
+        let still = BurnIn::new(4.0, Duration::ZERO, Duration::ZERO);
+        assert_eq!(still.offset(Duration::from_secs(7)), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_dims_after_a_while() {
+        let burn_in = BurnIn::default();
+        assert!(!burn_in.is_dimmed(Duration::from_secs(60)));
+        assert!(burn_in.is_dimmed(Duration::from_secs(300)));
+    }
+}
@@ -1,5 +1,11 @@
-use std::{cmp::min, fmt::Display, time::Duration};
-use crate::{Clock, ClockMode, ClockState, times};
+use std::{
+    cmp::{min, Ordering}, fmt::Display, time::{Duration, Instant, SystemTime}
+};
+use crate::{
+    Clock, ClockEvent, ClockMode, ClockSnapshot, ClockState, compute_state,
+    DurationDisplay, EventRecord, guard::Guard, millis::Millis,
+    MonotonicTime, presets, time_source::SharedTime, TimeSource
+};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Player {
@@ -35,21 +41,32 @@ impl Display for Player {
     }
 }
 
+/// Whether a game is under way
+///
+/// More states may be added, so matches outside the crate need a wildcard.
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[non_exhaustive]
 pub enum Status {
     Stopped,
     Running,
     Finished,
 }
 
+/// How a player's clock is credited for a move
+///
+/// More methods may be added, so matches outside the crate need a wildcard.
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[non_exhaustive]
 pub enum TimingMethod {
     Fischer,
     Bronstein,
+    /// Each turn has a fixed time which is reset on every press, as used for
+    /// billiards and darts. The increment is the time added by an extension.
+    ShotClock,
 }
 
 impl TimingMethod {
-    pub const ALL: [Self; 2] = [Self::Fischer, Self::Bronstein];
+    pub const ALL: [Self; 3] = [Self::Fischer, Self::Bronstein, Self::ShotClock];
 }
 
 impl Display for TimingMethod {
@@ -57,65 +74,498 @@ impl Display for TimingMethod {
         let label = match self {
             Self::Fischer => "Fischer",
             Self::Bronstein => "Bronstein",
+            Self::ShotClock => "Shot Clock",
         };
         write!(f, "{}", label)
     }
 }
 
-#[derive(Debug, Clone)]
+/// What a switch does while the clock is stopped
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum StoppedSwitch {
+    /// The switch is ignored, and a `SwitchIgnored` event is recorded
+    Ignore,
+    /// The player to move is changed without starting the clock
+    #[default]
+    Toggle,
+    /// The player to move is changed and their clock is started
+    StartAndToggle,
+}
+
+impl StoppedSwitch {
+    pub const ALL: [Self; 3] = [Self::Ignore, Self::Toggle, Self::StartAndToggle];
+}
+
+impl Display for StoppedSwitch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            Self::Ignore => "Ignore",
+            Self::Toggle => "Change player",
+            Self::StartAndToggle => "Change player and start",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Whether time spent in the background counts against the player on move,
+/// for shells such as mobile apps that can be suspended
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum BackgroundTime {
+    /// The clock keeps running in the background, as a physical clock would,
+    /// including any time the process was suspended
+    #[default]
+    Counts,
+    /// The clock pauses in the background and resumes in the foreground
+    Paused,
+}
+
+impl BackgroundTime {
+    pub const ALL: [Self; 2] = [Self::Counts, Self::Paused];
+}
+
+impl Display for BackgroundTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            Self::Counts => "Counts",
+            Self::Paused => "Paused",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// An overall time limit on a session, on top of the players' clocks, such
+/// as the time left before the club closes
+///
+/// The session starts with the first start of the clock and keeps running
+/// while the clock is stopped, as the time before closing does.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SessionBudget {
+    time: Duration,
+    adjudicate: bool,
+}
+
+impl SessionBudget {
+    /// Create a new session budget
+    ///
+    /// # Arguments
+    /// * `time` - How long the session lasts.
+    /// * `adjudicate` - Whether the game is finished when the session ends,
+    ///   for an arbiter to adjudicate. Otherwise the end is only recorded.
+    pub const fn new(time: Duration, adjudicate: bool) -> Self {
+        Self { time, adjudicate }
+    }
+
+    pub const fn get_time(&self) -> Duration {
+        self.time
+    }
+
+    pub const fn get_adjudicate(&self) -> bool {
+        self.adjudicate
+    }
+}
+
+/// A default time, after which a player who has not yet pressed their clock
+/// forfeits the game, such as the 30 minutes of FIDE tournaments
+///
+/// The default time counts from the first start of the clock, which for a
+/// `Round` is the start of the round.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DefaultTime {
+    time: Duration,
+    finish: bool,
+}
+
+impl DefaultTime {
+    /// Create a new default time
+    ///
+    /// # Arguments
+    /// * `time` - How long a player has to make their first press.
+    /// * `finish` - Whether the game is finished when a player forfeits.
+    ///   Otherwise the forfeit is only recorded, for the arbiter.
+    pub const fn new(time: Duration, finish: bool) -> Self {
+        Self { time, finish }
+    }
+
+    pub const fn get_time(&self) -> Duration {
+        self.time
+    }
+
+    pub const fn get_finish(&self) -> bool {
+        self.finish
+    }
+}
+
+/// The most stages at which the increment may change in one set of rules
+pub const MAX_INCREMENT_STAGES: usize = 8;
+
+/// A change of the increment from a move on, for schedules such as +2s
+/// until move 40 and +10s after
+///
+/// Moves are counted per player from 1, so the stage applies to a player's
+/// press ending their `from_move`th move and every press after it.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct IncrementStage {
+    from_move: u32,
+    increment: Duration,
+}
+
+impl IncrementStage {
+    /// Create a new increment stage
+    ///
+    /// # Arguments
+    /// * `from_move` - The first move the increment is given for.
+    /// * `increment` - The increment, or delay under Bronstein, from then on.
+    pub const fn new(from_move: u32, increment: Duration) -> Self {
+        Self { from_move, increment }
+    }
+
+    pub const fn get_from_move(&self) -> u32 {
+        self.from_move
+    }
+
+    pub const fn get_increment(&self) -> Duration {
+        self.increment
+    }
+}
+
+/// How the times of a set of rules are rounded before a game, for clubs that
+/// want base times in steps of 30 seconds or increments in whole seconds
+///
+/// Each time is rounded to the nearest multiple of its step, halves up, and
+/// a step of zero leaves the time as entered. A base time is never rounded
+/// down to zero, as the player would have no time to play.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct NormalizationPolicy {
+    time_step: Duration,
+    increment_step: Duration,
+}
+
+impl NormalizationPolicy {
+    /// Leave every time as it was entered
+    pub const NONE: Self = Self::new(Duration::ZERO, Duration::ZERO);
+
+    /// Create a new normalization policy
+    ///
+    /// # Arguments
+    /// * `time_step` - The step the players' base times are rounded to.
+    /// * `increment_step` - The step increments, and delays under
+    ///   Bronstein, are rounded to.
+    pub const fn new(time_step: Duration, increment_step: Duration) -> Self {
+        Self { time_step, increment_step }
+    }
+
+    pub const fn get_time_step(&self) -> Duration {
+        self.time_step
+    }
+
+    pub const fn get_increment_step(&self) -> Duration {
+        self.increment_step
+    }
+
+    /// Round a player's base time to the time step, to no less than one step
+    fn round_time(&self, time: Duration) -> Duration {
+        match round_to_step(time, self.time_step) {
+            rounded if rounded.is_zero() && !time.is_zero() => self.time_step,
+            rounded => rounded,
+        }
+    }
+
+    fn round_increment(&self, increment: Duration) -> Duration {
+        round_to_step(increment, self.increment_step)
+    }
+}
+
+/// Round `time` to the nearest multiple of `step`, halves up, or leave it be
+/// if `step` is zero
+fn round_to_step(time: Duration, step: Duration) -> Duration {
+    if step.is_zero() {
+        return time;
+    }
+    let steps = (time.as_nanos() + step.as_nanos() / 2) / step.as_nanos();
+    u32::try_from(steps).ok()
+        .and_then(|steps| step.checked_mul(steps))
+        .unwrap_or(time)
+}
+
+/// The reason an adjustment to the clocks was rejected
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AdjustmentError {
+    /// The rules require a reason for every adjustment
+    MissingReason,
+    /// The reason has a line break or other control character, which can not
+    /// be recorded in the event log
+    InvalidReason,
+    /// The rules are strict and the game is under way, so only an arbiter
+    /// may adjust the clocks
+    Strict,
+}
+
+impl Display for AdjustmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            Self::MissingReason => "a reason is required for adjustments",
+            Self::InvalidReason => {
+                "a reason can not contain line breaks or control characters"
+            },
+            Self::Strict => {
+                "the clocks can only be adjusted by the arbiter once a strict \
+                 game is under way"
+            },
+        };
+        write!(f, "{}", label)
+    }
+}
+
+impl std::error::Error for AdjustmentError {}
+
+/// The reason a set of rules was rejected
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RulesError {
+    /// The player has no starting time, so the game would finish as soon as
+    /// it was created
+    ZeroTime(Player),
+    /// The increment changes at more than `MAX_INCREMENT_STAGES` stages
+    TooManyIncrementStages,
+}
+
+impl Display for RulesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ZeroTime(player) => write!(
+                f, "{} has no time, so the game would be over before it \
+                    starts", player
+            ),
+            Self::TooManyIncrementStages => write!(
+                f, "the increment can change at most {} times",
+                MAX_INCREMENT_STAGES
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Rules {
     player1_time: Duration,
     player2_time: Duration,
     increment: Duration,
     starter: Player,
     timing_method: TimingMethod,
+    extensions: u32,
+    require_reasons: bool,
+    strict: bool,
+    idle_timeout: Option<Duration>,
+    stopped_switch: StoppedSwitch,
+    background_time: BackgroundTime,
+    session_budget: Option<SessionBudget>,
+    default_time: Option<DefaultTime>,
+    pause_allowance: Option<Duration>,
+    increment_stages: [IncrementStage; MAX_INCREMENT_STAGES],
+    increment_stage_count: usize,
 }
 
 impl Rules {
-    pub fn new(
+    pub const fn new(
         player1_time: Duration, player2_time: Duration,
         increment: Duration, starter: Player, timing_method: TimingMethod
     ) -> Self {
-        Self { player1_time, player2_time, increment, starter, timing_method }
+        Self {
+            player1_time, player2_time, increment, starter, timing_method,
+            extensions: 0,
+            require_reasons: false,
+            strict: false,
+            idle_timeout: None,
+            stopped_switch: StoppedSwitch::Toggle,
+            background_time: BackgroundTime::Counts,
+            session_budget: None,
+            default_time: None,
+            pause_allowance: None,
+            increment_stages: [
+                IncrementStage::new(0, Duration::ZERO); MAX_INCREMENT_STAGES
+            ],
+            increment_stage_count: 0,
+        }
+    }
+
+    /// Rules for a shot clock giving both players `turn_time` per turn, and
+    /// `extensions` extensions of `extension_time` each per game
+    pub const fn shot_clock(
+        turn_time: Duration, extension_time: Duration, extensions: u32
+    ) -> Self {
+        let mut rules = Self::new(
+            turn_time, turn_time,
+            extension_time, Player::Player1, TimingMethod::ShotClock
+        );
+        rules.extensions = extensions;
+        rules
     }
 
-    pub fn default() -> Self {
+    /// Rules giving time odds by rating, so that players of different
+    /// strengths can have a fair game
+    ///
+    /// The weaker player gets `base`, and the stronger player loses one
+    /// percent of `base` for every 10 points between them, up to 90 percent,
+    /// rounded down to the second. With a very short base this can leave the
+    /// stronger player no time, which `validate` reports. The rules are
+    /// Fischer with no increment and player 1 to start, to be adjusted as
+    /// needed.
+    ///
+    /// # Arguments
+    /// * `r1` - The rating of player 1.
+    /// * `r2` - The rating of player 2.
+    /// * `base` - The time of the weaker player.
+    pub fn handicap_from_ratings(r1: u32, r2: u32, base: Duration) -> Self {
+        const POINTS_PER_PERCENT: u32 = 10;
+        const MAX_PERCENT: u32 = 90;
+
+        let percent = min(r1.abs_diff(r2) / POINTS_PER_PERCENT, MAX_PERCENT);
+        let kept = u128::from(Millis::from(base).0)
+            * u128::from(100 - percent) / 100;
+        let stronger = Duration::from_secs((kept / 1_000) as u64);
+        let (player1_time, player2_time) = match r1.cmp(&r2) {
+            Ordering::Greater => (stronger, base),
+            Ordering::Less => (base, stronger),
+            Ordering::Equal => (base, base),
+        };
         Self::new(
-            times::TEN_MINUTES,
-            times::TEN_MINUTES,
-            times::FIVE_SECONDS,
-            Player::Player1,
+            player1_time, player2_time, Duration::ZERO, Player::Player1,
             TimingMethod::Fischer
         )
     }
 
-    pub fn get_player1_time(&self) -> Duration {
+    pub const fn get_player1_time(&self) -> Duration {
         self.player1_time
     }
 
-    pub fn get_player2_time(&self) -> Duration {
+    pub const fn get_player2_time(&self) -> Duration {
         self.player2_time
     }
 
-    pub fn get_time(&self, state: Player) -> Duration {
+    pub const fn get_time(&self, state: Player) -> Duration {
         match state {
             Player::Player1 => self.player1_time,
             Player::Player2 => self.player2_time,
         }
     }
 
-    pub fn get_increment(&self) -> Duration {
+    pub const fn get_increment(&self) -> Duration {
         self.increment
     }
 
-    pub fn get_timing_method(&self) -> TimingMethod {
+    pub const fn get_timing_method(&self) -> TimingMethod {
         self.timing_method
     }
 
-    pub fn get_starter(&self) -> Player {
+    pub const fn get_starter(&self) -> Player {
         self.starter
     }
 
+    /// Returns whether the rules give both players some time, so that a game
+    /// played under them does not finish before it starts
+    ///
+    /// This is a `const fn`, so presets can be checked at compile time.
+    pub const fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    /// Check that a game played under the rules can be played at all
+    ///
+    /// # Returns
+    /// The first problem found with the rules, if there is one.
+    pub const fn validate(&self) -> Result<(), RulesError> {
+        if self.player1_time.is_zero() {
+            Err(RulesError::ZeroTime(Player::Player1))
+        } else if self.player2_time.is_zero() {
+            Err(RulesError::ZeroTime(Player::Player2))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Get the number of extensions each player may use per game
+    pub const fn get_extensions(&self) -> u32 {
+        self.extensions
+    }
+
+    /// Get whether every adjustment of the clocks must give a reason
+    pub const fn get_require_reasons(&self) -> bool {
+        self.require_reasons
+    }
+
+    /// Get whether the clocks are locked against adjustment once the game is
+    /// under way
+    pub const fn get_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Get how long the clock may run without a press before it pauses
+    /// itself, if it does at all
+    pub const fn get_idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout
+    }
+
+    /// Get what a switch does while the clock is stopped
+    pub const fn get_stopped_switch(&self) -> StoppedSwitch {
+        self.stopped_switch
+    }
+
+    /// Get whether time spent in the background counts
+    pub const fn get_background_time(&self) -> BackgroundTime {
+        self.background_time
+    }
+
+    /// Get the overall time limit on the session, if there is one
+    pub const fn get_session_budget(&self) -> Option<SessionBudget> {
+        self.session_budget
+    }
+
+    /// Get the time players have to arrive before they forfeit, if there
+    /// is one
+    pub const fn get_default_time(&self) -> Option<DefaultTime> {
+        self.default_time
+    }
+
+    /// Get how long each player may pause the game for breaks, if the rules
+    /// allow breaks at all
+    pub const fn get_pause_allowance(&self) -> Option<Duration> {
+        self.pause_allowance
+    }
+
+    /// Get the stages at which the increment changes, in order of move
+    pub fn get_increment_stages(&self) -> &[IncrementStage] {
+        &self.increment_stages[..self.increment_stage_count]
+    }
+
+    /// Get the increment given for a player's `move_number`th move, counting
+    /// from 1, which is that of the last stage started by then, or the
+    /// increment of the rules before any stage
+    pub fn increment_at(&self, move_number: u32) -> Duration {
+        self.get_increment_stages().iter()
+            .take_while(|stage| stage.from_move <= move_number)
+            .last()
+            .map_or(self.increment, |stage| stage.increment)
+    }
+
+    /// Get how times are kept in range under the rules
+    ///
+    /// The increment is the one the rules start with. Use `policy_at` for
+    /// the increment of a later move when the rules have stages.
+    pub const fn policy(&self) -> SaturationPolicy {
+        SaturationPolicy {
+            timing_method: self.timing_method,
+            increment: self.increment,
+        }
+    }
+
+    /// Get how times are kept in range for a player's `move_number`th move
+    pub fn policy_at(&self, move_number: u32) -> SaturationPolicy {
+        SaturationPolicy {
+            timing_method: self.timing_method,
+            increment: self.increment_at(move_number),
+        }
+    }
+
     pub fn set_time(&mut self, state: Player, time: Duration) {
         match state {
             Player::Player1 => self.player1_time = time,
@@ -123,10 +573,53 @@ impl Rules {
         }
     }
 
+    /// Give both players the same starting time
+    pub fn set_both_times(&mut self, time: Duration) {
+        self.player1_time = time;
+        self.player2_time = time;
+    }
+
+    /// Exchange the two players' starting times, as when a handicap should
+    /// go to the other player
+    pub fn swap_players(&mut self) {
+        std::mem::swap(&mut self.player1_time, &mut self.player2_time);
+    }
+
+    /// Get a copy of the rules with the players exchanged, for a rematch
+    /// with colours reversed
+    ///
+    /// The starting times and the starter are exchanged. The increment,
+    /// extensions and other settings apply to both players alike, so are
+    /// kept as they are.
+    pub fn swapped(&self) -> Self {
+        let mut rules = self.clone();
+        rules.swap_players();
+        rules.starter = rules.starter.other();
+        rules
+    }
+
     pub fn set_increment(&mut self, increment: Duration) {
         self.increment = increment;
     }
 
+    /// Get a copy of the rules with the base times and increments rounded
+    /// as `policy` asks
+    ///
+    /// Front ends compare the result with the rules as entered, so that
+    /// the players can be told of any time that was rounded.
+    pub fn normalized(&self, policy: NormalizationPolicy) -> Self {
+        let mut rules = self.clone();
+        for player in Player::ALL {
+            rules.set_time(player, policy.round_time(self.get_time(player)));
+        }
+        rules.increment = policy.round_increment(self.increment);
+        let count = rules.increment_stage_count;
+        for stage in &mut rules.increment_stages[..count] {
+            stage.increment = policy.round_increment(stage.increment);
+        }
+        rules
+    }
+
     pub fn set_timing_method(&mut self, timing_method: TimingMethod) {
         self.timing_method = timing_method;
     }
@@ -134,122 +627,2661 @@ impl Rules {
     pub fn set_starter(&mut self, starter: Player) {
         self.starter = starter;
     }
+
+    pub fn set_extensions(&mut self, extensions: u32) {
+        self.extensions = extensions;
+    }
+
+    /// Set whether every adjustment of the clocks must give a reason, for
+    /// arbiters who need to account for their corrections
+    pub fn set_require_reasons(&mut self, require_reasons: bool) {
+        self.require_reasons = require_reasons;
+    }
+
+    /// Set whether the clocks are locked against adjustment from the first
+    /// press, as for rated and official games
+    ///
+    /// Under strict rules, time can only be added or subtracted after the
+    /// first press with the arbiter's override, and every refused attempt is
+    /// recorded in the event log. Penalties are still given, as they are the
+    /// arbiter's to give.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Set how long the clock may run without a press before it pauses
+    /// itself, so that an abandoned game does not run to zero. `None`, the
+    /// default, never pauses.
+    pub fn set_idle_timeout(&mut self, idle_timeout: Option<Duration>) {
+        self.idle_timeout = idle_timeout;
+    }
+
+    /// Set what a switch does while the clock is stopped
+    pub fn set_stopped_switch(&mut self, stopped_switch: StoppedSwitch) {
+        self.stopped_switch = stopped_switch;
+    }
+
+    /// Set whether time spent in the background counts against the player
+    /// on move
+    pub fn set_background_time(&mut self, background_time: BackgroundTime) {
+        self.background_time = background_time;
+    }
+
+    /// Set the overall time limit on the session. `None`, the default, has
+    /// no limit.
+    pub fn set_session_budget(&mut self, session_budget: Option<SessionBudget>) {
+        self.session_budget = session_budget;
+    }
+
+    /// Set the time players have to make their first press before they
+    /// forfeit. `None`, the default, has no limit.
+    pub fn set_default_time(&mut self, default_time: Option<DefaultTime>) {
+        self.default_time = default_time;
+    }
+
+    /// Set how long each player may pause the game for breaks, such as to go
+    /// to the bathroom, over the whole game. A player going over it is
+    /// recorded for the arbiter, but the game is not stopped. `None`, the
+    /// default, does not count breaks.
+    pub fn set_pause_allowance(&mut self, pause_allowance: Option<Duration>) {
+        self.pause_allowance = pause_allowance;
+    }
+
+    /// Set the stages at which the increment changes. The increment of the
+    /// rules applies until the first stage; a later stage for the same move
+    /// replaces an earlier one. No stages, the default, keeps the increment
+    /// for the whole game.
+    ///
+    /// Fails, leaving the stages as they were, if there are more than
+    /// `MAX_INCREMENT_STAGES` moves at which the increment changes.
+    pub fn set_increment_stages(
+        &mut self, stages: &[IncrementStage]
+    ) -> Result<(), RulesError> {
+        let mut stages = stages.to_vec();
+        stages.reverse();
+        stages.sort_by_key(|stage| stage.from_move);
+        stages.dedup_by_key(|stage| stage.from_move);
+        if stages.len() > MAX_INCREMENT_STAGES {
+            return Err(RulesError::TooManyIncrementStages);
+        }
+        self.increment_stages[..stages.len()].copy_from_slice(&stages);
+        self.increment_stage_count = stages.len();
+        Ok(())
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        presets::RAPID_10_5
+    }
+}
+
+/// How the times of a game are kept in range, however the rules are set
+///
+/// A clock never reads below zero. Taking more time from a player than they
+/// have left leaves them at zero, and if their clock is running they have
+/// flagged, just as if the time had run out on its own. A stopped clock
+/// taken to zero finishes the game with that player flagged.
+///
+/// Time given to a player is given in full, with the largest time a clock
+/// can hold as the only limit. A Fischer increment larger than the starting
+/// time is allowed, and lets a player bank more than they started with. A
+/// Bronstein delay gives back at most the time the move used, so however
+/// large it is a player never gains time by moving; with no starting time
+/// there is nothing to delay, and such rules fail `Rules::validate`.
+///
+/// The math is done in whole milliseconds, so times come out the same on
+/// every platform and in every replay.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SaturationPolicy {
+    timing_method: TimingMethod,
+    increment: Duration,
+}
+
+impl SaturationPolicy {
+    /// Get the time a player is given for a move
+    ///
+    /// # Arguments
+    /// * `used` - How long the move took.
+    ///
+    /// # Returns
+    /// The increment under Fischer, the smaller of the increment and `used`
+    /// under Bronstein, and nothing for a shot clock, which is reset instead.
+    pub fn credit(&self, used: Duration) -> Duration {
+        let increment = Millis::from(self.increment);
+        match self.timing_method {
+            TimingMethod::Fischer => increment.into(),
+            TimingMethod::Bronstein => {
+                min(Millis::from(used), increment).into()
+            },
+            TimingMethod::ShotClock => Duration::ZERO,
+        }
+    }
+
+    /// Get the time a player has after `time` is given to them
+    pub fn add(&self, remaining: Duration, time: Duration) -> Duration {
+        Millis::from(remaining).saturating_add(Millis::from(time)).into()
+    }
+
+    /// Get the time a player has after `time` is taken from them, which is
+    /// zero if they have less than `time` left
+    pub fn subtract(&self, remaining: Duration, time: Duration) -> Duration {
+        Millis::from(remaining).saturating_sub(Millis::from(time)).into()
+    }
 }
 
+/// A clock for a two player game
+///
+/// Every change to the clock is recorded as an event in its log, and the
+/// clocks only change by applying those events, so the log is a complete
+/// account of the game that `ChessClock::replay` can rebuild it from.
 #[derive(Debug)]
 pub struct ChessClock {
-    clocks: [Clock; 2],
+    clocks: [Clock<SharedTime>; 2],
+    source: SharedTime,
     state: Player,
     rules: Rules,
+    extensions_used: [u32; 2],
+    created: Instant,
+    events: Vec<EventRecord>,
+    press_starts_clock: bool,
+    background: Option<Background>,
+    /// When the running clock was last started, for counting think time
+    turn_start: Option<Instant>,
+    /// The think time of each player's finished turns
+    think_time: [Duration; 2],
+    /// The longest gap between updates of a running clock before it is
+    /// recorded as a delay, if ticks are watched
+    tick_threshold: Option<Duration>,
+    /// When the clock was last updated while running
+    last_tick: Option<Instant>,
+    /// How soon after a press another is ignored, if presses are debounced
+    debounce: Option<Duration>,
+    /// The arbiter whose PIN overrides strict rules
+    arbiter: Guard,
+    /// Whether the arbiter has allowed the next adjustment
+    overridden: bool,
+    /// The clocks as of the last event, to audit the time charged since
+    ledger: Ledger,
+    press_latency: PressLatency,
+}
+
+/// How long presses took to hand the clock over to the opponent
+///
+/// A press is measured from the instant it was made, which is also when the
+/// player's clock stops, to when `switch_player` has finished and the
+/// opponent's clock is running, so it includes the bookkeeping of the update,
+/// status and event log. Only presses of a running clock are counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PressLatency {
+    presses: u32,
+    total: Duration,
+    worst: Duration,
+    last: Duration,
+}
+
+impl PressLatency {
+    fn record(&mut self, latency: Duration) {
+        self.presses += 1;
+        self.total += latency;
+        self.worst = self.worst.max(latency);
+        self.last = latency;
+    }
+
+    /// Get the number of presses measured
+    pub fn presses(&self) -> u32 {
+        self.presses
+    }
+
+    /// Get the longest any press took
+    pub fn worst(&self) -> Duration {
+        self.worst
+    }
+
+    /// Get how long the latest press took
+    pub fn last(&self) -> Duration {
+        self.last
+    }
+
+    /// Get the average time a press took
+    ///
+    /// # Returns
+    /// The average, or `None` if no presses have been measured.
+    pub fn average(&self) -> Option<Duration> {
+        (self.presses > 0).then(|| self.total / self.presses)
+    }
+}
+
+/// What each clock read and how long each player had thought at an instant,
+/// so that the time charged after it can be checked against the time spent
+#[derive(Debug, Clone, Copy)]
+struct Ledger {
+    at: Instant,
+    remaining: [Duration; 2],
+    think_time: [Duration; 2],
+}
+
+/// When a clock went into the background, and whether it was running
+#[derive(Debug, Clone, Copy)]
+struct Background {
+    instant: Instant,
+    timestamp: SystemTime,
+    running: bool,
 }
 
 impl ChessClock {
+    /// Create a stopped clock for a game under `rules`
+    ///
+    /// The rules are not checked, so invalid rules are accepted: a clock for
+    /// rules that give a player no time is finished from the start. Use
+    /// `try_new` for rules from anywhere but the code itself, as the
+    /// settings pages, rounds and rematches do. Only games being rebuilt,
+    /// such as by `replay`, skip the check, as their rules were checked when
+    /// they began.
     pub fn new(rules: Rules) -> Self {
+        Self::with_source(rules, MonotonicTime)
+    }
+
+    /// Create a stopped clock for a game under `rules`, reading the time
+    /// from `source`
+    ///
+    /// A `ScaledTime` source plays the game faster or slower than real time,
+    /// such as to demo a front end. The rules are not checked, as for `new`.
+    pub fn with_source<T>(rules: Rules, source: T) -> Self
+    where
+        T: TimeSource + Send + Sync + 'static,
+    {
+        let source = SharedTime::new(source);
+        let created = source.now();
         Self {
+            ledger: Ledger {
+                at: created,
+                remaining: [rules.player1_time, rules.player2_time],
+                think_time: [Duration::ZERO; 2],
+            },
             clocks: [
-                Clock::new(
+                Clock::with_source(
                     ClockMode::CountDown,
-                    Some(rules.player1_time)
+                    Some(rules.player1_time),
+                    source.clone()
                 ),
-                Clock::new(
+                Clock::with_source(
                     ClockMode::CountDown,
-                    Some(rules.player2_time)
+                    Some(rules.player2_time),
+                    source.clone()
                 ),
             ],
+            source,
             state: rules.starter,
             rules,
+            extensions_used: [0, 0],
+            created,
+            events: Vec::new(),
+            press_starts_clock: false,
+            background: None,
+            turn_start: None,
+            think_time: [Duration::ZERO; 2],
+            tick_threshold: None,
+            last_tick: None,
+            debounce: None,
+            arbiter: Guard::open(),
+            overridden: false,
+            press_latency: PressLatency::default(),
         }
     }
 
-    pub fn default() -> Self {
-        Self::new(Rules::new(
-            times::TEN_MINUTES,
-            times::TEN_MINUTES,
-            times::FIVE_SECONDS,
-            Player::Player1,
-            TimingMethod::Fischer
-        ))
+    /// Get the current time, from the clock's time source
+    pub fn now(&self) -> Instant {
+        self.source.now()
     }
 
-    pub fn active_player(&self) -> Player {
-        self.state
+    /// Get the clock's time source, for another clock to share
+    #[cfg_attr(not(feature = "gui"), allow(dead_code))]
+    pub(crate) fn source(&self) -> &SharedTime {
+        &self.source
     }
 
-    pub fn read(&self) -> (Duration, Duration) {
-        (
-            self.clocks[Player::Player1.index()].read(),
-            self.clocks[Player::Player2.index()].read(),
-        )
+    /// Create a stopped clock for a game under `rules`, if they are valid
+    pub fn try_new(rules: Rules) -> Result<Self, RulesError> {
+        Self::try_with_source(rules, MonotonicTime)
     }
 
-    pub fn update(&mut self) {
-        self.clocks.iter_mut()
-            .for_each(|clock| { clock.read_and_update(); });
+    /// Create a stopped clock for a game under `rules` reading the time from
+    /// `source`, if the rules are valid
+    pub fn try_with_source<T>(
+        rules: Rules, source: T
+    ) -> Result<Self, RulesError>
+    where
+        T: TimeSource + Send + Sync + 'static,
+    {
+        rules.validate()?;
+        Ok(Self::with_source(rules, source))
     }
 
-    pub fn status(&self) -> Status {
-        let (t1, t2) = self.read();
-        let (s1, s2) = (
-            self.clocks[Player::Player1.index()].state(),
-            self.clocks[Player::Player2.index()].state()
-        );
+    /// Rebuilds a stopped chess clock with the given remaining times, used
+    /// when recovering an interrupted game
+    #[cfg_attr(not(feature = "persistence"), allow(dead_code))]
+    pub(crate) fn restore(
+        rules: Rules, times: (Duration, Duration), active: Player,
+        finished: bool
+    ) -> Self {
+        let mut clock = Self::new(rules);
+        clock.clocks[Player::Player1.index()].reset(Some(times.0));
+        clock.clocks[Player::Player2.index()].reset(Some(times.1));
+        clock.state = active;
+        clock.ledger = clock.ledger_at(clock.now());
+        if finished {
+            clock.finish();
+        }
+        clock
+    }
 
-        match (t1.as_secs_f64() * t2.as_secs_f64(), s1, s2) {
-            (0.0, _, _) => Status::Finished,
-            (_, ClockState::Finished, ClockState::Finished) => Status::Finished,
-            (_, ClockState::Stopped, ClockState::Stopped) => Status::Stopped,
-            _ => Status::Running,
+    /// Replaces the event log, used when reloading a saved game
+    #[cfg_attr(not(feature = "persistence"), allow(dead_code))]
+    pub(crate) fn restore_events(&mut self, events: Vec<EventRecord>) {
+        self.events = events;
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Get the log of everything that has happened to the clock, oldest first
+    pub fn events(&self) -> &[EventRecord] {
+        &self.events
+    }
+
+    /// Rebuilds a clock by applying recorded events in order
+    ///
+    /// The clock is event sourced: every change to its state is an event in
+    /// the log, and its state is the result of applying those events one by
+    /// one. Replaying the log of a clock under the same rules therefore gives
+    /// a clock in the same state, which can be read at any later instant.
+    pub fn replay(rules: Rules, events: &[EventRecord]) -> Self {
+        Self::replay_with(rules, events, |_, _| {})
+    }
+
+    /// Replays events like `replay`, showing `inspect` the clock as it was
+    /// just before each event was applied
+    pub(crate) fn replay_with(
+        rules: Rules,
+        events: &[EventRecord],
+        mut inspect: impl FnMut(&Self, &EventRecord),
+    ) -> Self {
+        let mut clock = Self::new(rules);
+        if let Some(first) = events.first() {
+            clock.created = first.instant()
+                .checked_sub(first.elapsed())
+                .unwrap_or(first.instant());
+        }
+        for record in events {
+            inspect(&clock, record);
+            clock.apply_audited(record);
+            clock.events.push(record.clone());
         }
+        clock
     }
 
-    fn start_current(&mut self) {
-        self.clocks[self.state.index()].start();
+    /// Records `event` as happening at `at` and applies it
+    ///
+    /// Any time lost or gained by applying it is recorded straight after as
+    /// a `Discrepancy`, and fails a debug assertion.
+    fn emit(&mut self, event: ClockEvent, at: Instant) {
+        let record = EventRecord::at(
+            event, at, at.saturating_duration_since(self.created),
+            SystemTime::now()
+        );
+        let discrepancies = self.apply_audited(&record);
+        self.events.push(record);
+        debug_assert!(
+            discrepancies.is_empty(), "clock time lost or gained: {:?}",
+            discrepancies
+        );
+        for discrepancy in discrepancies {
+            self.emit(discrepancy, at);
+        }
     }
 
-    pub fn start(&mut self) {
-        self.start_current();
+    /// Checks the whole event log for time lost or gained by the clocks
+    ///
+    /// The log is replayed, checking that between events each clock went
+    /// down by exactly the time it ran, and that each event changed the
+    /// clocks by exactly what it should, such as the increment for a press
+    /// under the timing method. Discrepancies not already in the log are
+    /// recorded in it as `ClockEvent::Discrepancy`.
+    ///
+    /// # Returns
+    /// Whether the log passed, with no discrepancies.
+    pub fn audit(&mut self) -> bool {
+        let mut replayed = Self::new(self.rules.clone());
+        replayed.created = self.created;
+        let mut discrepancies = Vec::new();
+        for record in &self.events {
+            discrepancies.extend(replayed.apply_audited(record));
+            replayed.events.push(record.clone());
+        }
+
+        let passed = discrepancies.is_empty();
+        let at = self.now();
+        for discrepancy in discrepancies {
+            let recorded = self.events.iter()
+                .any(|record| record.event() == &discrepancy);
+            if !recorded {
+                self.emit(discrepancy, at);
+            }
+        }
+        passed
     }
 
-    pub fn switch_player(&mut self) {
-        self.update();
+    /// Applies an event, checking the clocks against the ledger before it
+    /// and against what the event should do to them after it
+    ///
+    /// # Returns
+    /// A `Discrepancy` for each clock found to be wrong.
+    fn apply_audited(&mut self, record: &EventRecord) -> Vec<ClockEvent> {
+        let at = record.instant();
+        let mut discrepancies = Vec::new();
+        let mut check = |player: Player, expected, actual| {
+            if expected != actual {
+                discrepancies.push(
+                    ClockEvent::Discrepancy { player, expected, actual }
+                );
+            }
+        };
 
-        let current = self.state;
-        let new = current.other();
-        let current_status = self.status();
-
-        if let Status::Running = current_status {
-            // handle timing and stop current clock
-            let running_time = self.clocks[current.index()]
-                .read_running();
-            self.clocks[current.index()].stop();
-
-            // add increment to the current clock
-            match self.rules.get_timing_method() {
-                TimingMethod::Fischer => {
-                    self.clocks[current.index()].add(self.rules.increment);
-                }
-                TimingMethod::Bronstein => {
-                    self.clocks[current.index()].add(min(
-                        running_time, self.rules.increment
-                    ));
-                }
+        // Time that went backwards, or an event recorded out of order, has
+        // nothing to check against
+        let in_order = at >= self.ledger.at
+            && record.event() != &ClockEvent::TimeAnomaly(self.state);
+        let before = self.ledger_at(at);
+        if in_order {
+            for player in Player::ALL {
+                let index = player.index();
+                let ran = before.think_time[index]
+                    .saturating_sub(self.ledger.think_time[index]);
+                let expected = self.ledger.remaining[index].saturating_sub(ran);
+                check(player, expected, before.remaining[index]);
             }
+        }
 
-            // start the next clock
-            self.clocks[new.index()].start();
-            self.state = new;
-        } else if let Status::Finished = current_status {
-            // do nothing
-        } else {
-            self.state = new;
+        let expected = self.expected_after(record.event(), &before);
+        self.apply(record);
+        self.ledger = self.ledger_at(at);
+        if let Some(expected) = expected {
+            for player in Player::ALL {
+                let index = player.index();
+                check(player, expected[index], self.ledger.remaining[index]);
+            }
         }
+        discrepancies
     }
 
-    pub fn stop(&mut self) {
-        self.clocks[self.state.index()].stop();
+    /// Get the clocks and think times at `at`
+    fn ledger_at(&self, at: Instant) -> Ledger {
+        let (player1, player2) = self.read_at(at);
+        Ledger {
+            at,
+            remaining: [player1, player2],
+            think_time: Player::ALL
+                .map(|player| self.think_time_at(player, at)),
+        }
     }
 
-    pub fn finish(&mut self) {
-        self.clocks.iter_mut().for_each(|clock| clock.finish());
+    /// Get what each clock should read just after `event`, given the clocks
+    /// just before it, or `None` if the clocks can not tell
+    fn expected_after(
+        &self, event: &ClockEvent, before: &Ledger
+    ) -> Option<[Duration; 2]> {
+        let mut expected = before.remaining;
+        let policy = self.rules.policy();
+        let mut give = |player: Player, time| {
+            let index = player.index();
+            expected[index] = policy.add(expected[index], time);
+        };
+        match event {
+            ClockEvent::Pressed(_)
+                if self.rules.timing_method == TimingMethod::ShotClock =>
+            {
+                return Some(
+                    Player::ALL.map(|player| self.rules.get_time(player))
+                );
+            },
+            ClockEvent::Pressed(player) => {
+                let move_number = self.moves_made(*player) + 1;
+                let used = self.turn_time_at(*player, before.at);
+                give(*player, self.rules.policy_at(move_number).credit(used));
+            },
+            ClockEvent::Extended(player) => give(*player, self.rules.increment),
+            ClockEvent::TimeAdded { player, time, .. } => give(*player, *time),
+            ClockEvent::Penalty { offender, time, .. } => {
+                give(offender.other(), *time);
+            },
+            ClockEvent::TimeSubtracted { player, time, .. } => {
+                let index = player.index();
+                expected[index] = policy.subtract(expected[index], *time);
+            },
+            // Time spent suspended in the background is charged by however
+            // long the device says it was away, which the clocks can not tell
+            ClockEvent::Foregrounded { .. } => return None,
+            _ => {},
+        }
+        Some(expected)
+    }
+
+    /// Applies the change recorded by an event to the clocks
+    ///
+    /// This is the only place the state of the game changes, so that it is
+    /// always the result of the event log. The checks that decide whether an
+    /// event happens at all are made by the caller before it is emitted.
+    fn apply(&mut self, record: &EventRecord) {
+        let at = record.instant();
+        match record.event() {
+            ClockEvent::Started(player) => {
+                self.state = *player;
+                self.clocks[player.index()].start_at(at);
+                self.turn_start = Some(at);
+            },
+            ClockEvent::Stopped(player) | ClockEvent::IdlePaused(player) => {
+                self.clocks[player.index()].stop_at(at);
+                self.end_turn(*player, at);
+            },
+            ClockEvent::PausedBy(_) => {
+                // The break is charged to whoever took it, but it is the
+                // clock of the player on move that stops
+                let active = self.state;
+                self.clocks[active.index()].stop_at(at);
+                self.end_turn(active, at);
+            },
+            ClockEvent::Pressed(player) => {
+                // The press is not in the log yet, so this is its move
+                let move_number = self.moves_made(*player) + 1;
+                let credit = self.rules.policy_at(move_number)
+                    .credit(self.turn_time_at(*player, at));
+                self.end_turn(*player, at);
+                self.turn_start = Some(at);
+                let clock = &mut self.clocks[player.index()];
+                clock.stop_at(at);
+
+                // add increment to the current clock
+                if self.rules.timing_method == TimingMethod::ShotClock {
+                    for player in Player::ALL {
+                        self.clocks[player.index()]
+                            .reset(Some(self.rules.get_time(player)));
+                    }
+                } else {
+                    self.give(*player, credit, at);
+                }
+
+                // start the next clock
+                let new = player.other();
+                self.clocks[new.index()].start_at(at);
+                self.state = new;
+            },
+            ClockEvent::ActivePlayerChanged(player) => self.state = *player,
+            ClockEvent::SwitchIgnored(_) | ClockEvent::PressDebounced(_) => {},
+            ClockEvent::Extended(player) => {
+                self.give(*player, self.rules.increment, at);
+                self.extensions_used[player.index()] += 1;
+            },
+            ClockEvent::Flagged(player) => {
+                self.clocks[player.index()].read_and_update_at(at);
+                self.end_turn(*player, at);
+            },
+            ClockEvent::TimeAnomaly(player) => {
+                self.clocks[player.index()].correct_anomaly_at(at);
+                // As on the clock, the turn carries on from now
+                if self.turn_start.is_some() {
+                    self.turn_start = Some(at);
+                }
+            },
+            ClockEvent::Backgrounded(player) => {
+                let clock = &mut self.clocks[player.index()];
+                let running = matches!(clock.state(), ClockState::Running(_));
+                let paused = self.rules.background_time
+                    == BackgroundTime::Paused;
+                if running && paused {
+                    clock.stop_at(at);
+                    self.end_turn(*player, at);
+                }
+                self.background = Some(Background {
+                    instant: at,
+                    timestamp: record.timestamp(),
+                    running,
+                });
+            },
+            ClockEvent::Foregrounded { player, away } => {
+                let Some(background) = self.background.take() else {
+                    return;
+                };
+                if background.running {
+                    match self.rules.background_time {
+                        BackgroundTime::Paused => {
+                            self.clocks[player.index()].start_at(at);
+                            self.turn_start = Some(at);
+                        },
+                        BackgroundTime::Counts => {
+                            let seen = Millis::from(
+                                at.saturating_duration_since(background.instant)
+                            );
+                            let suspended = Millis::from(*away)
+                                .saturating_sub(seen);
+                            self.take(*player, suspended.into(), at);
+                            self.think_time[player.index()] += suspended.into();
+                        },
+                    }
+                }
+            },
+            ClockEvent::TimeAdded { player, time, .. } => {
+                self.give(*player, *time, at);
+            },
+            ClockEvent::TimeSubtracted { player, time, .. } => {
+                self.take(*player, *time, at);
+            },
+            ClockEvent::Penalty { offender, time, .. } => {
+                self.give(offender.other(), *time, at);
+            },
+            ClockEvent::SessionExpired
+            | ClockEvent::ForfeitDue(_)
+            | ClockEvent::AdjustmentRefused(_)
+            | ClockEvent::PauseAllowanceExceeded(_)
+            | ClockEvent::Discrepancy { .. }
+            | ClockEvent::TickDelay { .. } => {},
+            ClockEvent::Finished => {
+                self.clocks.iter_mut().for_each(|clock| clock.finish_at(at));
+                self.end_turn(self.state, at);
+            },
+        }
+    }
+
+    /// Adds the turn in progress, if there is one, to `player`'s think time
+    fn end_turn(&mut self, player: Player, at: Instant) {
+        if let Some(start) = self.turn_start.take() {
+            self.think_time[player.index()] +=
+                at.saturating_duration_since(start);
+        }
+    }
+
+    /// Gives `time` to `player` at `at`, under the saturation policy
+    fn give(&mut self, player: Player, time: Duration, at: Instant) {
+        let clock = &mut self.clocks[player.index()];
+        let remaining = self.rules.policy().add(clock.read_at(at), time);
+        clock.set_at(remaining, at);
+    }
+
+    /// Takes `time` from `player` at `at`, under the saturation policy
+    fn take(&mut self, player: Player, time: Duration, at: Instant) {
+        let clock = &mut self.clocks[player.index()];
+        let remaining = self.rules.policy().subtract(clock.read_at(at), time);
+        clock.set_at(remaining, at);
+    }
+
+    pub fn active_player(&self) -> Player {
+        self.state
+    }
+
+    /// Get whether `player` is the one on move
+    pub fn is_active(&self, player: Player) -> bool {
+        self.state == player
+    }
+
+    /// Get the player waiting for the player on move
+    pub fn opponent(&self) -> Player {
+        self.state.other()
+    }
+
+    /// Get the time left on the clock of the player on move
+    pub fn active_clock_remaining(&self) -> Duration {
+        self.clocks[self.state.index()].read()
+    }
+
+    pub fn read(&self) -> (Duration, Duration) {
+        self.read_at(self.now())
+    }
+
+    /// Read both clocks as they will be at `at`, if nothing happens before
+    /// then
+    pub fn read_at(&self, at: Instant) -> (Duration, Duration) {
+        (
+            self.clocks[Player::Player1.index()].read_at(at),
+            self.clocks[Player::Player2.index()].read_at(at),
+        )
+    }
+
+    /// Get how much of a player's starting time is left
+    ///
+    /// # Returns
+    /// The time left as a fraction of the player's starting time, from 0.0
+    /// to 1.0. Time gained from increments beyond the starting time is
+    /// clamped to 1.0, and a player with no starting time reads as 0.0.
+    pub fn fraction_remaining(&self, player: Player) -> f32 {
+        let start = Millis::from(self.rules.get_time(player));
+        Millis::from(self.clocks[player.index()].read()).fraction_of(start)
+    }
+
+    pub fn update(&mut self) {
+        let at = self.now();
+        self.watch_tick(at);
+        self.update_at(at);
+    }
+
+    /// Sets the longest gap between calls to `update` of a running clock
+    /// before it is recorded as a `TickDelay`
+    ///
+    /// Shells that redraw on a timer call `update` on every tick, so a long
+    /// gap means the event loop stalled and the times shown may have jumped.
+    /// `None`, the default, does not watch the ticks.
+    pub fn set_tick_watchdog(&mut self, threshold: Option<Duration>) {
+        self.tick_threshold = threshold;
+    }
+
+    /// Sets how soon after a press another press is ignored
+    ///
+    /// Hardware buttons can bounce and nervous players can press twice, which
+    /// would end the opponent's turn for them straight away. A press within
+    /// `window` of the previous one is ignored instead, and recorded as a
+    /// `PressDebounced` event against the player who pressed. Only
+    /// `switch_player` is debounced, as it can not tell who pressed: `press`
+    /// already ignores a player who is not on move, so a quick reply by the
+    /// opponent is never dropped. `None`, the default, takes every press.
+    pub fn set_debounce(&mut self, window: Option<Duration>) {
+        self.debounce = window;
+    }
+
+    /// Get whether a press at `at` comes within the debounce window of the
+    /// previous press
+    fn is_bounce(&self, at: Instant) -> bool {
+        let Some(window) = self.debounce else {
+            return false;
+        };
+        self.events.iter().rev()
+            .find(|record| matches!(record.event(), ClockEvent::Pressed(_)))
+            .is_some_and(|record| {
+                at.saturating_duration_since(record.instant()) < window
+            })
+    }
+
+    /// Get the gap before the latest late update, if it was within `within`
+    /// of now, so that a shell can warn that the display may have jumped
+    pub fn recent_tick_delay(&self, within: Duration) -> Option<Duration> {
+        let now = self.now();
+        self.events.iter().rev().find_map(|record| match record.event() {
+            ClockEvent::TickDelay { gap, .. } => Some((record.instant(), *gap)),
+            _ => None,
+        })
+            .filter(|(at, _)| now.saturating_duration_since(*at) <= within)
+            .map(|(_, gap)| gap)
+    }
+
+    fn watch_tick(&mut self, at: Instant) {
+        let running = self.status_at(at) == Status::Running;
+        if let (Some(threshold), Some(last), true) =
+            (self.tick_threshold, self.last_tick, running)
+        {
+            // A clock started since the last tick was not running for all
+            // of the gap
+            let since = self.turn_start.map_or(last, |start| start.max(last));
+            let gap = at.saturating_duration_since(since);
+            if gap > threshold {
+                let player = self.state;
+                self.emit(ClockEvent::TickDelay { player, gap }, at);
+            }
+        }
+        // Stopped clocks are not redrawn, so a gap only counts while running
+        self.last_tick = running.then_some(at);
+    }
+
+    fn update_at(&mut self, at: Instant) {
+        for player in Player::ALL {
+            let anomalous = matches!(
+                self.clocks[player.index()].state(),
+                ClockState::Running(start) if at < start
+            );
+            if anomalous {
+                self.emit(ClockEvent::TimeAnomaly(player), at);
+            }
+        }
+        for player in Player::ALL {
+            let clock = &self.clocks[player.index()];
+            let running = matches!(clock.state(), ClockState::Running(_));
+            if running && clock.read_at(at).is_zero() {
+                self.emit(ClockEvent::Flagged(player), at);
+            }
+        }
+
+        let expired = self.session_remaining_at(at)
+            .is_some_and(|remaining| remaining.is_zero());
+        let recorded = self.events.iter()
+            .any(|record| record.event() == &ClockEvent::SessionExpired);
+        if expired && !recorded && self.status_at(at) != Status::Finished {
+            self.emit(ClockEvent::SessionExpired, at);
+            let adjudicate = self.rules.session_budget
+                .is_some_and(|budget| budget.adjudicate);
+            if adjudicate {
+                if self.status_at(at) == Status::Running {
+                    self.emit(ClockEvent::Stopped(self.state), at);
+                }
+                self.emit(ClockEvent::Finished, at);
+            }
+        }
+
+        self.check_default_time(at);
+        self.check_pause_allowance(at);
+
+        if let Some(timeout) = self.rules.idle_timeout {
+            if self.clocks[self.state.index()].read_running_at(at) >= timeout {
+                self.emit(ClockEvent::IdlePaused(self.state), at);
+            }
+        }
+    }
+
+    /// Records a forfeit if the player on move has not pressed their clock
+    /// within the default time, finishing the game if the rules say so
+    ///
+    /// Only the player on move can forfeit, as their opponent may be waiting
+    /// for the first move. The default time is only checked once, so a
+    /// player who arrives late but is allowed to play does not make their
+    /// opponent forfeit in turn.
+    fn check_default_time(&mut self, at: Instant) {
+        let Some(rule) = self.rules.default_time else {
+            return;
+        };
+        let Some(started) = self.first_start() else {
+            return;
+        };
+        if at.saturating_duration_since(started) < rule.time
+            || self.status_at(at) == Status::Finished
+            || self.forfeited().is_some()
+            || self.moves_made(self.state) > 0
+        {
+            return;
+        }
+        self.emit(ClockEvent::ForfeitDue(self.state), at);
+        if rule.finish {
+            if self.status_at(at) == Status::Running {
+                self.emit(ClockEvent::Stopped(self.state), at);
+            }
+            self.emit(ClockEvent::Finished, at);
+        }
+    }
+
+    /// Records each player whose breaks have gone over the pause allowance,
+    /// once per game
+    fn check_pause_allowance(&mut self, at: Instant) {
+        let Some(allowance) = self.rules.pause_allowance else {
+            return;
+        };
+        for player in Player::ALL {
+            let recorded = self.events.iter().any(|record| {
+                record.event() == &ClockEvent::PauseAllowanceExceeded(player)
+            });
+            if !recorded && self.pause_used_at(player, at) > allowance {
+                self.emit(ClockEvent::PauseAllowanceExceeded(player), at);
+            }
+        }
+    }
+
+    /// Get when the clock was first started, if it has been
+    fn first_start(&self) -> Option<Instant> {
+        self.events.iter()
+            .find(|record| matches!(record.event(), ClockEvent::Started(_)))
+            .map(EventRecord::instant)
+    }
+
+    /// Get how long `player` has spent thinking, which is the total time
+    /// their clock has run
+    ///
+    /// Unlike the time on their clock, this is not changed by increments or
+    /// adjustments, so it is the time they actually used.
+    pub fn think_time(&self, player: Player) -> Duration {
+        self.think_time_at(player, self.now())
+    }
+
+    pub(crate) fn think_time_at(
+        &self, player: Player, at: Instant
+    ) -> Duration {
+        let current = match self.turn_start {
+            Some(start) if self.state == player => {
+                at.saturating_duration_since(start)
+            },
+            _ => Duration::ZERO,
+        };
+        self.think_time[player.index()] + current
+    }
+
+    /// Get how long `player` has been thinking in the turn in progress, if it
+    /// is theirs, since their clock was last started
+    fn turn_time_at(&self, player: Player, at: Instant) -> Duration {
+        self.think_time_at(player, at)
+            .saturating_sub(self.think_time[player.index()])
+    }
+
+    /// Get the time played since the game started, leaving out the time it
+    /// was stopped
+    ///
+    /// Only one clock runs at a time, so this is the sum of both players'
+    /// think times.
+    pub fn elapsed_game_time(&self) -> Duration {
+        let at = self.now();
+        Player::ALL.iter()
+            .map(|player| self.think_time_at(*player, at))
+            .sum()
+    }
+
+    /// Get how long is left of the session, if the rules limit it
+    ///
+    /// The session starts with the first start of the clock, so this is the
+    /// whole budget until then, and it runs on while the clock is stopped.
+    pub fn session_remaining(&self) -> Option<Duration> {
+        self.session_remaining_at(self.now())
+    }
+
+    fn session_remaining_at(&self, at: Instant) -> Option<Duration> {
+        let budget = self.rules.session_budget?;
+        let used = self.first_start().map_or(Duration::ZERO, |started| {
+            at.saturating_duration_since(started)
+        });
+        Some(budget.time.saturating_sub(used))
+    }
+
+    /// Get the player who forfeited by not arriving within the default
+    /// time, if either did
+    pub fn forfeited(&self) -> Option<Player> {
+        self.events.iter().find_map(|record| match record.event() {
+            ClockEvent::ForfeitDue(player) => Some(*player),
+            _ => None,
+        })
+    }
+
+    /// Get the player who ran out of time first, if either has
+    ///
+    /// The first `Flagged` event in the log decides. Without one, as when
+    /// both clocks reach zero in the same update or a game is restored with
+    /// a clock at zero, a player whose clock reads zero has flagged. If both
+    /// read zero the player on move is taken to have flagged first, since
+    /// only the clock of the player on move runs down.
+    pub fn flagged(&self) -> Option<Player> {
+        self.flagged_at(self.now())
+    }
+
+    fn flagged_at(&self, at: Instant) -> Option<Player> {
+        let logged = self.events.iter().find_map(|record| match record.event() {
+            ClockEvent::Flagged(player) => Some(*player),
+            _ => None,
+        });
+        if logged.is_some() {
+            return logged;
+        }
+        let (t1, t2) = self.read_at(at);
+        match (t1.is_zero(), t2.is_zero()) {
+            (true, true) => Some(self.state),
+            (true, false) => Some(Player::Player1),
+            (false, true) => Some(Player::Player2),
+            (false, false) => None,
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        self.status_at(self.now())
+    }
+
+    /// Take a snapshot of the clock as it will be at `at`, if nothing happens
+    /// before then
+    pub fn snapshot(&self, at: Instant) -> ClockSnapshot {
+        ClockSnapshot {
+            times: self.read_at(at),
+            active_player: self.state,
+            status: self.status_at(at),
+            move_number: self.move_number(),
+            flagged: self.flagged_at(at),
+        }
+    }
+
+    /// Get what the clock showed at `at`, from the events up to then
+    ///
+    /// Unlike `snapshot`, nothing that happened after `at` is counted, so
+    /// this answers what the clock showed at any moment of the game.
+    pub fn state_at(&self, at: Instant) -> ClockSnapshot {
+        let seen = self.events.partition_point(|record| record.instant() <= at);
+        compute_state(&self.rules, &self.events[..seen], at)
+    }
+
+    /// Get what the clock showed as move `n` was played, counting from 1
+    ///
+    /// This is the instant the starter pressed the clock to complete the
+    /// move, with any increment for it added and the other player on move.
+    ///
+    /// # Returns
+    /// The state, or `None` if move `n` has not been played.
+    pub fn state_at_move(&self, n: u32) -> Option<ClockSnapshot> {
+        let pressed = ClockEvent::Pressed(self.rules.get_starter());
+        let index = usize::try_from(n.checked_sub(1)?).ok()?;
+        let record = self.events.iter()
+            .filter(|record| record.event() == &pressed)
+            .nth(index)?;
+        Some(self.state_at(record.instant()))
+    }
+
+    fn status_at(&self, at: Instant) -> Status {
+        let (t1, t2) = self.read_at(at);
+        let (s1, s2) = (
+            self.clocks[Player::Player1.index()].state(),
+            self.clocks[Player::Player2.index()].state()
+        );
+
+        match (t1.is_zero() || t2.is_zero(), s1, s2) {
+            (true, _, _) => Status::Finished,
+            (_, ClockState::Finished, ClockState::Finished) => Status::Finished,
+            (_, ClockState::Stopped, ClockState::Stopped) => Status::Stopped,
+            _ => Status::Running,
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.start_at(self.now());
+    }
+
+    /// Starts the clock as at `at`, which may be a little in the past
+    pub(crate) fn start_at(&mut self, at: Instant) {
+        if self.status_at(at) == Status::Stopped {
+            self.emit(ClockEvent::Started(self.state), at);
+        }
+    }
+
+    /// Sets whether the first switch of a game that has not been started
+    /// also starts it
+    ///
+    /// Players usually begin a game by simply pressing the clock, so with
+    /// this set the first `switch_player` starts the clock and then
+    /// registers the press as a normal move. `press` knows whose lever it
+    /// is, so as over the board the waiting player's press starts the
+    /// starter's clock, with no move, and the starter's press before then is
+    /// ignored. Once the game has been started, switching while stopped
+    /// behaves as before.
+    pub fn press_starts_clock(&mut self, enabled: bool) {
+        self.press_starts_clock = enabled;
+    }
+
+    fn has_started(&self) -> bool {
+        self.events.iter()
+            .any(|record| matches!(record.event(), ClockEvent::Started(_)))
+    }
+
+    pub fn switch_player(&mut self) {
+        self.switch_player_at(self.now(), true);
+    }
+
+    /// Ends the turn of the player on move as at `at`, unless `debounce` is
+    /// set and the press comes within the debounce window of the previous one
+    fn switch_player_at(&mut self, at: Instant, debounce: bool) {
+        if self.press_starts_clock && !self.has_started() {
+            self.start_at(at);
+        }
+        self.update_at(at);
+
+        let current = self.state;
+        let new = current.other();
+        match self.status_at(at) {
+            // A bounce repeats the press that put `current` on move
+            Status::Running if debounce && self.is_bounce(at) => {
+                self.emit(ClockEvent::PressDebounced(new), at);
+            },
+            Status::Running => {
+                self.emit(ClockEvent::Pressed(current), at);
+                self.press_latency
+                    .record(self.now().saturating_duration_since(at));
+            },
+            Status::Finished => {},
+            Status::Stopped => match self.rules.stopped_switch {
+                StoppedSwitch::Ignore => {
+                    self.emit(ClockEvent::SwitchIgnored(current), at);
+                }
+                StoppedSwitch::Toggle => {
+                    self.emit(ClockEvent::ActivePlayerChanged(new), at);
+                }
+                StoppedSwitch::StartAndToggle => {
+                    self.emit(ClockEvent::ActivePlayerChanged(new), at);
+                    self.start_at(at);
+                }
+            },
+        }
+    }
+
+    /// Get how long the presses of this clock have taken to start the
+    /// opponent's clock, to check that the bookkeeping of a press never costs
+    /// a player noticeable time
+    pub fn press_latency(&self) -> PressLatency {
+        self.press_latency
+    }
+
+    /// Forgets the presses measured so far, such as after warming up
+    pub fn reset_press_latency(&mut self) {
+        self.press_latency = PressLatency::default();
+    }
+
+    /// Tells the clock that the shell has gone into the background, and may be
+    /// suspended
+    ///
+    /// Under `BackgroundTime::Paused` a running clock is stopped until
+    /// `on_foreground`. Calling this while already in the background does
+    /// nothing.
+    pub fn on_background(&mut self) {
+        if self.background.is_some() {
+            return;
+        }
+        let at = self.now();
+        self.update_at(at);
+        self.emit(ClockEvent::Backgrounded(self.state), at);
+    }
+
+    /// Tells the clock that the shell is back in the foreground
+    ///
+    /// Under `BackgroundTime::Paused` a clock that was running is started
+    /// again. Under `BackgroundTime::Counts` the running clock is also
+    /// charged for any time the process spent suspended, which the
+    /// monotonic clock may not have seen; this is measured with the wall
+    /// clock, so a change of the system time while in the background is
+    /// charged too. Calling this while not in the background does nothing.
+    pub fn on_foreground(&mut self) {
+        let Some(background) = self.background else {
+            return;
+        };
+        let away = SystemTime::now()
+            .duration_since(background.timestamp)
+            .unwrap_or_default();
+        let at = self.now();
+        self.emit(ClockEvent::Foregrounded { player: self.state, away }, at);
+        self.update_at(at);
+    }
+
+    /// Get whether the screen should be kept awake, which is while the game
+    /// is running, so that a shell can hold a wake lock only when needed
+    pub fn wants_screen_awake(&self) -> bool {
+        self.status() == Status::Running
+    }
+
+    /// Get the number of moves `player` has completed by pressing the clock
+    pub fn moves_made(&self, player: Player) -> u32 {
+        self.events.iter()
+            .filter(|record| record.event() == &ClockEvent::Pressed(player))
+            .count() as u32
+    }
+
+    /// Get the number of the move being played, counting from 1
+    ///
+    /// As in chess notation a move is one turn by each player, so the number
+    /// goes up when the starting player is back on move.
+    pub fn move_number(&self) -> u32 {
+        let starter = self.rules.get_starter();
+        let on_move = u32::from(self.state == starter);
+        self.moves_made(starter) + on_move
+    }
+
+    /// Get the increment the player on move will be given for their move,
+    /// for display when the rules change it over the game
+    pub fn current_increment(&self) -> Duration {
+        self.rules.increment_at(self.moves_made(self.state) + 1)
+    }
+
+    /// Get the number of extensions `player` has left this game
+    pub fn extensions_remaining(&self, player: Player) -> u32 {
+        self.rules.extensions
+            .saturating_sub(self.extensions_used[player.index()])
+    }
+
+    /// Uses one of the active player's shot clock extensions
+    ///
+    /// The increment is added to the active player's running clock. Returns
+    /// whether an extension was used, which is only possible for a shot
+    /// clock that is running while the player has an extension left.
+    pub fn extend(&mut self) -> bool {
+        let at = self.now();
+        self.update_at(at);
+
+        let player = self.state;
+        let allowed = self.rules.timing_method == TimingMethod::ShotClock
+            && self.status_at(at) == Status::Running
+            && self.extensions_remaining(player) > 0;
+
+        if allowed {
+            self.emit(ClockEvent::Extended(player), at);
+        }
+        allowed
+    }
+
+    /// Adds `time` to the clock of `player`
+    ///
+    /// The adjustment and its reason are recorded in the event log. Fails if
+    /// the rules require reasons and none is given, or if strict rules lock
+    /// the clocks, which is recorded too.
+    pub fn add_time(
+        &mut self, player: Player, time: Duration, reason: Option<&str>
+    ) -> Result<(), AdjustmentError> {
+        let reason = self.check_adjustment(player, reason)?;
+        let event = ClockEvent::TimeAdded { player, time, reason };
+        self.emit(event, self.now());
+        Ok(())
+    }
+
+    /// Subtracts `time` from the clock of `player`, stopping at zero
+    ///
+    /// The adjustment and its reason are recorded in the event log, followed
+    /// by a flag if it empties a running clock, as described by
+    /// `SaturationPolicy`. Fails as `add_time` does.
+    pub fn subtract_time(
+        &mut self, player: Player, time: Duration, reason: Option<&str>
+    ) -> Result<(), AdjustmentError> {
+        let reason = self.check_adjustment(player, reason)?;
+        let event = ClockEvent::TimeSubtracted { player, time, reason };
+        let at = self.now();
+        self.emit(event, at);
+        // A running clock taken to zero has flagged
+        self.update_at(at);
+        Ok(())
+    }
+
+    /// Penalises `offender` by adding `time` to their opponent's clock, as
+    /// for an illegal move under FIDE rules
+    ///
+    /// The penalty and its reason are recorded in the event log. Fails as
+    /// `add_time` does, since it moves time between the clocks.
+    pub fn apply_penalty(
+        &mut self, offender: Player, time: Duration, reason: Option<&str>
+    ) -> Result<(), AdjustmentError> {
+        let reason = self.check_adjustment(offender.other(), reason)?;
+        let event = ClockEvent::Penalty { offender, time, reason };
+        self.emit(event, self.now());
+        Ok(())
+    }
+
+    /// Sets the arbiter whose PIN overrides strict rules
+    ///
+    /// Without a PIN, nothing overrides them.
+    pub fn set_arbiter(&mut self, arbiter: Guard) {
+        self.arbiter = arbiter;
+    }
+
+    /// Get the arbiter whose PIN overrides strict rules
+    pub fn arbiter(&self) -> &Guard {
+        &self.arbiter
+    }
+
+    /// Allows the next adjustment of a strict game, if `pin` is the
+    /// arbiter's PIN
+    ///
+    /// # Returns
+    /// Whether the override was accepted, which it never is without an
+    /// arbiter PIN.
+    pub fn authorise(&mut self, pin: &str) -> bool {
+        let accepted = self.arbiter.has_pin() && self.arbiter.unlock(pin);
+        self.overridden |= accepted;
+        accepted
+    }
+
+    /// Get whether adding or subtracting time, or a penalty, would be
+    /// refused, which it is from the first press of a game under strict rules until the arbiter
+    /// overrides it
+    pub fn adjustments_locked(&self) -> bool {
+        self.rules.strict && !self.overridden && self.events.iter()
+            .any(|record| matches!(record.event(), ClockEvent::Pressed(_)))
+    }
+
+    /// Checks an adjustment of `player`'s clock against strict rules and
+    /// then for a reason, recording it if strict rules refuse it and using
+    /// up the arbiter's override if it is allowed
+    fn check_adjustment(
+        &mut self, player: Player, reason: Option<&str>
+    ) -> Result<Option<String>, AdjustmentError> {
+        if self.adjustments_locked() {
+            self.emit(ClockEvent::AdjustmentRefused(player), self.now());
+            return Err(AdjustmentError::Strict);
+        }
+        let reason = self.check_reason(reason)?;
+        self.overridden = false;
+        Ok(reason)
+    }
+
+    fn check_reason(
+        &self, reason: Option<&str>
+    ) -> Result<Option<String>, AdjustmentError> {
+        let reason = reason
+            .map(str::trim)
+            .filter(|reason| !reason.is_empty())
+            .map(str::to_string);
+        // The reason ends its line of the log, so a line break would split it
+        let control = reason.as_ref()
+            .is_some_and(|reason| reason.contains(char::is_control));
+        if control {
+            return Err(AdjustmentError::InvalidReason);
+        }
+        if reason.is_none() && self.rules.require_reasons {
+            return Err(AdjustmentError::MissingReason);
+        }
+        Ok(reason)
+    }
+
+    /// Registers a press of `player`'s side of the clock
+    ///
+    /// Pressing ends the turn of the active player, so a press by the player
+    /// who is not on move is ignored. This is the entry point for physical
+    /// levers and buttons, which cannot know whose turn it is. Before the
+    /// game has started, see `press_starts_clock`.
+    pub fn press(&mut self, player: Player) {
+        if self.press_starts_clock && !self.has_started() {
+            // The waiting player starts the game by starting the starter's
+            // clock, which is not a move
+            if player != self.state {
+                self.start_at(self.now());
+            }
+            return;
+        }
+        // A second press by the same player is already ignored here, so
+        // presses are not debounced, and a quick reply still ends the turn
+        if player == self.state {
+            self.switch_player_at(self.now(), false);
+        }
+    }
+
+    /// Describes the state machine of the clock under its rules as a Graphviz
+    /// digraph
+    ///
+    /// Nodes are the stopped and running states for each player plus the
+    /// finished state, and edges are labelled with the call that takes the
+    /// clock between them. The starting state is marked with a double border.
+    pub fn state_machine_dot(&self) -> String {
+        let increment = DurationDisplay::from(self.rules.increment);
+        let press_label = match self.rules.timing_method {
+            TimingMethod::Fischer => format!("press / +{}", increment),
+            TimingMethod::Bronstein => {
+                format!("press / +min(used, {})", increment)
+            }
+            TimingMethod::ShotClock => "press / reset".to_string(),
+        };
+
+        let mut dot = String::from("digraph chess_clock {\n");
+        let base = if self.rules.player1_time == self.rules.player2_time {
+            DurationDisplay::from(self.rules.player1_time).to_string()
+        } else {
+            format!(
+                "{}/{}",
+                DurationDisplay::from(self.rules.player1_time),
+                DurationDisplay::from(self.rules.player2_time)
+            )
+        };
+        dot.push_str(&format!(
+            "    label=\"{} {} + {}\";\n",
+            self.rules.timing_method, base, increment
+        ));
+        dot.push_str("    finished [label=\"Finished\"];\n");
+
+        for player in Player::ALL {
+            let n = player.index() + 1;
+            let other = player.other().index() + 1;
+            let peripheries = if player == self.rules.starter { 2 } else { 1 };
+            dot.push_str(&format!(
+                "    stopped{n} [label=\"Stopped\\n{player} to move\", \
+                 peripheries={peripheries}];\n"
+            ));
+            dot.push_str(&format!(
+                "    running{n} [label=\"Running\\n{player} to move\"];\n"
+            ));
+            dot.push_str(&format!(
+                "    stopped{n} -> running{n} [label=\"start\"];\n"
+            ));
+            dot.push_str(&format!(
+                "    running{n} -> stopped{n} [label=\"stop\"];\n"
+            ));
+            dot.push_str(&format!(
+                "    running{n} -> running{other} [label=\"{press_label}\"];\n"
+            ));
+            match self.rules.stopped_switch {
+                StoppedSwitch::Ignore => {},
+                StoppedSwitch::Toggle => dot.push_str(&format!(
+                    "    stopped{n} -> stopped{other} [label=\"switch\"];\n"
+                )),
+                StoppedSwitch::StartAndToggle => dot.push_str(&format!(
+                    "    stopped{n} -> running{other} [label=\"switch\"];\n"
+                )),
+            }
+            dot.push_str(&format!(
+                "    running{n} -> finished [label=\"{player} flags\"];\n"
+            ));
+            dot.push_str(&format!(
+                "    stopped{n} -> finished [label=\"finish\"];\n"
+            ));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn stop(&mut self) {
+        self.stop_at(self.now());
+    }
+
+    /// Stops the clock as at `at`, which may be a little in the past
+    pub(crate) fn stop_at(&mut self, at: Instant) {
+        self.update_at(at);
+        if self.status_at(at) == Status::Running {
+            self.emit(ClockEvent::Stopped(self.state), at);
+        }
+    }
+
+    /// Pauses the game for a break taken by `player`, such as to go to the
+    /// bathroom, stopping the running clock
+    ///
+    /// The break lasts until the clock is started again, and its time is
+    /// charged to `player`'s pause allowance. Does nothing unless the clock
+    /// is running.
+    pub fn pause_for(&mut self, player: Player) {
+        let at = self.now();
+        self.update_at(at);
+        if self.status_at(at) == Status::Running {
+            self.emit(ClockEvent::PausedBy(player), at);
+        }
+    }
+
+    /// Get the player on a break, if the game is paused for one
+    pub fn on_break(&self) -> Option<Player> {
+        let mut on_break = None;
+        for record in &self.events {
+            match record.event() {
+                ClockEvent::PausedBy(player) => on_break = Some(*player),
+                ClockEvent::Started(_) | ClockEvent::Finished => {
+                    on_break = None
+                },
+                _ => {},
+            }
+        }
+        on_break
+    }
+
+    /// Get how long `player` has paused the game for breaks, including a
+    /// break in progress
+    pub fn pause_used(&self, player: Player) -> Duration {
+        self.pause_used_at(player, self.now())
+    }
+
+    fn pause_used_at(&self, player: Player, at: Instant) -> Duration {
+        let mut used = Duration::ZERO;
+        let mut since = None;
+        for record in &self.events {
+            match record.event() {
+                ClockEvent::PausedBy(taker) if *taker == player => {
+                    since = Some(record.instant());
+                },
+                ClockEvent::PausedBy(_)
+                | ClockEvent::Started(_)
+                | ClockEvent::Finished => {
+                    if let Some(start) = since.take() {
+                        used += record.instant()
+                            .saturating_duration_since(start);
+                    }
+                },
+                _ => {},
+            }
+        }
+        if let Some(start) = since {
+            used += at.saturating_duration_since(start);
+        }
+        used
+    }
+
+    /// Get how much of `player`'s pause allowance is left, if the rules give
+    /// one
+    pub fn pause_remaining(&self, player: Player) -> Option<Duration> {
+        let allowance = self.rules.pause_allowance?;
+        Some(allowance.saturating_sub(self.pause_used(player)))
+    }
+
+    pub fn finish(&mut self) {
+        let finished = self.clocks.iter()
+            .all(|clock| clock.state() == ClockState::Finished);
+        if !finished {
+            self.emit(ClockEvent::Finished, self.now());
+        }
+    }
+}
+
+impl Default for ChessClock {
+    fn default() -> Self {
+        Self::new(presets::RAPID_10_5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{times, ManualTime, Sleep};
+
+    #[test]
+    fn test_press_by_active_player_switches() {
+        let mut clock = ChessClock::default();
+        clock.start();
+
+        clock.press(Player::Player1);
+        assert_eq!(clock.active_player(), Player::Player2);
+        assert_eq!(clock.status(), Status::Running);
+    }
+
+    #[test]
+    fn test_press_latency() {
+        let mut clock = ChessClock::default();
+        assert_eq!(clock.press_latency().average(), None);
+        clock.switch_player();
+        clock.start();
+        for _ in 0..10 {
+            clock.switch_player();
+        }
+        clock.press(Player::Player1);
+
+        let latency = clock.press_latency();
+        assert_eq!(latency.presses(), 10);
+        assert!(latency.last() <= latency.worst());
+        assert!(latency.average().unwrap() <= latency.worst());
+        assert!(latency.worst() < times::ONE_SECOND);
+        clock.reset_press_latency();
+        assert_eq!(clock.press_latency(), PressLatency::default());
+    }
+
+    #[test]
+    fn test_debounce_ignores_quick_second_press() {
+        let mut clock = ChessClock::default();
+        clock.set_debounce(Some(Duration::from_millis(50)));
+        clock.start();
+        clock.switch_player();
+        clock.switch_player();
+        assert_eq!(clock.active_player(), Player::Player2);
+        assert_eq!(
+            clock.events().last().unwrap().event(),
+            &ClockEvent::PressDebounced(Player::Player1)
+        );
+
+        Duration::from_millis(60).sleep();
+        clock.press(Player::Player2);
+        assert_eq!(clock.active_player(), Player::Player1);
+        assert_eq!(clock.moves_made(Player::Player2), 1);
+    }
+
+    #[test]
+    fn test_debounce_keeps_quick_reply() {
+        let mut clock = ChessClock::default();
+        clock.set_debounce(Some(times::ONE_MINUTE));
+        clock.start();
+        clock.press(Player::Player1);
+        clock.press(Player::Player1);
+        clock.press(Player::Player2);
+        assert_eq!(clock.active_player(), Player::Player1);
+        assert_eq!(clock.moves_made(Player::Player1), 1);
+        assert_eq!(clock.moves_made(Player::Player2), 1);
+        assert!(!clock.events().iter().any(|record| {
+            matches!(record.event(), ClockEvent::PressDebounced(_))
+        }));
+    }
+
+    #[test]
+    fn test_shot_clock_resets_on_press() {
+        let mut clock = ChessClock::new(Rules::shot_clock(
+            times::ONE_MINUTE, times::FIVE_SECONDS, 1
+        ));
+        clock.start();
+        Duration::from_secs(1).sleep();
+
+        clock.press(Player::Player1);
+        let (t1, t2) = clock.read();
+        assert_eq!(t1, times::ONE_MINUTE);
+        assert_eq!(DurationDisplay::from(t2).to_string(), "01:00");
+        assert_eq!(clock.active_player(), Player::Player2);
+    }
+
+    #[test]
+    fn test_shot_clock_extensions() {
+        let mut clock = ChessClock::new(Rules::shot_clock(
+            times::ONE_MINUTE, times::FIVE_SECONDS, 1
+        ));
+        assert!(!clock.extend());
+
+        clock.start();
+        assert!(clock.extend());
+        assert_eq!(clock.extensions_remaining(Player::Player1), 0);
+        assert_eq!(DurationDisplay::from(clock.read().0).to_string(), "01:05");
+        assert!(!clock.extend());
+
+        clock.press(Player::Player1);
+        assert_eq!(clock.extensions_remaining(Player::Player2), 1);
+        assert!(clock.extend());
+    }
+
+    #[test]
+    fn test_extend_requires_shot_clock() {
+        let mut clock = ChessClock::default();
+        clock.start();
+        assert!(!clock.extend());
+    }
+
+    #[test]
+    fn test_events_are_recorded() {
+        let mut clock = ChessClock::new(Rules::new(
+            Duration::from_millis(100), times::ONE_MINUTE,
+            Duration::ZERO, Player::Player1, TimingMethod::Fischer
+        ));
+        clock.switch_player();
+        clock.switch_player();
+        clock.start();
+        clock.stop();
+        clock.start();
+        Duration::from_millis(50).sleep();
+        clock.press(Player::Player1);
+        clock.press(Player::Player2);
+        Duration::from_millis(100).sleep();
+        clock.update();
+        clock.finish();
+        clock.finish();
+
+        let events: Vec<ClockEvent> = clock.events().iter()
+            .map(|record| record.event().clone())
+            .collect();
+        assert_eq!(events, [
+            ClockEvent::ActivePlayerChanged(Player::Player2),
+            ClockEvent::ActivePlayerChanged(Player::Player1),
+            ClockEvent::Started(Player::Player1),
+            ClockEvent::Stopped(Player::Player1),
+            ClockEvent::Started(Player::Player1),
+            ClockEvent::Pressed(Player::Player1),
+            ClockEvent::Pressed(Player::Player2),
+            ClockEvent::Flagged(Player::Player1),
+            ClockEvent::Finished,
+        ]);
+
+        let elapsed: Vec<Duration> = clock.events().iter()
+            .map(EventRecord::elapsed)
+            .collect();
+        assert!(elapsed.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn test_adjustments() {
+        let mut clock = ChessClock::default();
+        clock.add_time(Player::Player1, times::ONE_MINUTE, Some("Clock fault"))
+            .unwrap();
+        clock.subtract_time(Player::Player2, times::ONE_MINUTE, None).unwrap();
+        clock.apply_penalty(
+            Player::Player2, times::ONE_MINUTE, Some("  Illegal move ")
+        ).unwrap();
+        assert_eq!(
+            clock.read(),
+            (times::TEN_MINUTES + 2 * times::ONE_MINUTE,
+             times::TEN_MINUTES - times::ONE_MINUTE)
+        );
+
+        let events: Vec<&ClockEvent> = clock.events().iter()
+            .map(EventRecord::event)
+            .collect();
+        assert_eq!(events[0], &ClockEvent::TimeAdded {
+            player: Player::Player1,
+            time: times::ONE_MINUTE,
+            reason: Some("Clock fault".to_string()),
+        });
+        assert_eq!(events[1], &ClockEvent::TimeSubtracted {
+            player: Player::Player2,
+            time: times::ONE_MINUTE,
+            reason: None,
+        });
+        assert_eq!(events[2], &ClockEvent::Penalty {
+            offender: Player::Player2,
+            time: times::ONE_MINUTE,
+            reason: Some("Illegal move".to_string()),
+        });
+    }
+
+    #[test]
+    fn test_adjustments_can_require_reasons() {
+        let mut rules = Rules::default();
+        rules.set_require_reasons(true);
+        let mut clock = ChessClock::new(rules);
+
+        assert_eq!(
+            clock.add_time(Player::Player1, times::ONE_MINUTE, None),
+            Err(AdjustmentError::MissingReason)
+        );
+        assert_eq!(
+            clock.apply_penalty(Player::Player1, times::ONE_MINUTE, Some(" ")),
+            Err(AdjustmentError::MissingReason)
+        );
+        assert_eq!(clock.read(), (times::TEN_MINUTES, times::TEN_MINUTES));
+        assert!(clock.events().is_empty());
+
+        assert!(clock.subtract_time(
+            Player::Player1, times::ONE_MINUTE, Some("Arbiter decision")
+        ).is_ok());
+    }
+
+    #[test]
+    fn test_reasons_stay_on_one_line() {
+        let mut clock = ChessClock::default();
+        for reason in ["Illegal\nmove", "Illegal\r\nmove", "Touch\tmove"] {
+            let result = clock.add_time(
+                Player::Player1, times::ONE_MINUTE, Some(reason)
+            );
+            assert_eq!(result, Err(AdjustmentError::InvalidReason));
+        }
+        assert!(clock.events().is_empty());
+
+        // Surrounding whitespace, line breaks included, is trimmed as before
+        let reason = "  Knocked over the clock  \n";
+        clock.apply_penalty(Player::Player2, times::ONE_MINUTE, Some(reason))
+            .unwrap();
+        let penalty = ClockEvent::Penalty {
+            offender: Player::Player2,
+            time: times::ONE_MINUTE,
+            reason: Some("Knocked over the clock".to_string()),
+        };
+        assert_eq!(clock.events().last().unwrap().event(), &penalty);
+    }
+
+    #[test]
+    fn test_strict_rules_lock_adjustments() {
+        let mut rules = Rules::default();
+        rules.set_strict(true);
+        let mut clock = ChessClock::new(rules);
+
+        // Before the first press the clocks may still be set up
+        assert!(clock.add_time(Player::Player1, times::ONE_MINUTE, None).is_ok());
+        clock.start();
+        clock.switch_player();
+        assert!(clock.adjustments_locked());
+        assert_eq!(
+            clock.subtract_time(Player::Player2, times::ONE_MINUTE, None),
+            Err(AdjustmentError::Strict)
+        );
+        assert_eq!(
+            clock.events().last().unwrap().event(),
+            &ClockEvent::AdjustmentRefused(Player::Player2)
+        );
+        // A penalty moves time between the clocks, so is locked too
+        assert_eq!(
+            clock.apply_penalty(Player::Player1, times::ONE_MINUTE, None),
+            Err(AdjustmentError::Strict)
+        );
+        assert_eq!(
+            clock.events().last().unwrap().event(),
+            &ClockEvent::AdjustmentRefused(Player::Player2)
+        );
+
+        // Nothing overrides the rules without an arbiter PIN
+        assert!(!clock.authorise(""));
+        clock.set_arbiter(Guard::with_pin("1234").unwrap());
+        assert!(!clock.authorise("4321"));
+        assert!(clock.authorise("1234"));
+        assert!(!clock.adjustments_locked());
+        assert!(clock.add_time(Player::Player2, times::ONE_MINUTE, None).is_ok());
+        assert!(clock.authorise("1234"));
+        assert!(clock.apply_penalty(Player::Player1, times::ONE_MINUTE, None)
+            .is_ok());
+
+        // The override allows a single adjustment
+        assert!(clock.adjustments_locked());
+        assert_eq!(
+            clock.add_time(Player::Player2, times::ONE_MINUTE, None),
+            Err(AdjustmentError::Strict)
+        );
+    }
+
+    #[test]
+    fn test_state_machine_dot() {
+        let mut rules = Rules::default();
+        rules.set_starter(Player::Player2);
+        rules.set_timing_method(TimingMethod::Bronstein);
+        let dot = ChessClock::new(rules).state_machine_dot();
+
+        assert!(dot.starts_with("digraph chess_clock {"));
+        assert!(dot.contains("label=\"Bronstein 10:00 + 00:05\""));
+        assert!(dot.contains(
+            "stopped2 [label=\"Stopped\\nPlayer 2 to move\", peripheries=2]"
+        ));
+        assert!(dot.contains(
+            "stopped1 [label=\"Stopped\\nPlayer 1 to move\", peripheries=1]"
+        ));
+        assert!(dot.contains(
+            "running1 -> running2 [label=\"press / +min(used, 00:05)\"]"
+        ));
+        assert!(dot.contains("running2 -> finished [label=\"Player 2 flags\"]"));
+        assert!(dot.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn test_stopped_switch_behaviours() {
+        let mut rules = Rules::default();
+        rules.set_stopped_switch(StoppedSwitch::Ignore);
+        let mut clock = ChessClock::new(rules.clone());
+        clock.switch_player();
+        assert_eq!(clock.active_player(), Player::Player1);
+        assert_eq!(clock.status(), Status::Stopped);
+        assert_eq!(
+            clock.events().last().unwrap().event(),
+            &ClockEvent::SwitchIgnored(Player::Player1)
+        );
+        assert!(!clock.state_machine_dot().contains("stopped1 -> stopped2"));
+
+        rules.set_stopped_switch(StoppedSwitch::StartAndToggle);
+        let mut clock = ChessClock::new(rules);
+        clock.switch_player();
+        assert_eq!(clock.active_player(), Player::Player2);
+        assert_eq!(clock.status(), Status::Running);
+        let events: Vec<&ClockEvent> = clock.events().iter()
+            .map(EventRecord::event)
+            .collect();
+        assert_eq!(events, vec![
+            &ClockEvent::ActivePlayerChanged(Player::Player2),
+            &ClockEvent::Started(Player::Player2),
+        ]);
+        assert!(clock.state_machine_dot().contains(
+            "stopped1 -> running2 [label=\"switch\"]"
+        ));
+    }
+
+    #[test]
+    fn test_turn_queries() {
+        let mut clock = ChessClock::new(Rules::new(
+            times::TEN_MINUTES, times::FIVE_MINUTES, Duration::ZERO,
+            Player::Player1, TimingMethod::Fischer
+        ));
+        assert!(clock.is_active(Player::Player1));
+        assert!(!clock.is_active(Player::Player2));
+        assert_eq!(clock.opponent(), Player::Player2);
+        assert_eq!(clock.active_clock_remaining(), times::TEN_MINUTES);
+
+        clock.start();
+        clock.switch_player();
+        clock.stop();
+        assert!(clock.is_active(Player::Player2));
+        assert_eq!(clock.opponent(), Player::Player1);
+        assert_eq!(clock.active_clock_remaining(), clock.read().1);
+    }
+
+    #[test]
+    fn test_move_number() {
+        let mut clock = ChessClock::new(Rules::new(
+            times::TEN_MINUTES, times::TEN_MINUTES, Duration::ZERO,
+            Player::Player2, TimingMethod::Fischer
+        ));
+        assert_eq!(clock.move_number(), 1);
+
+        clock.start();
+        clock.switch_player();
+        assert_eq!(clock.move_number(), 1);
+        assert_eq!(clock.moves_made(Player::Player2), 1);
+        assert_eq!(clock.moves_made(Player::Player1), 0);
+
+        clock.switch_player();
+        clock.switch_player();
+        assert_eq!(clock.move_number(), 2);
+        assert_eq!(clock.moves_made(Player::Player2), 2);
+        assert_eq!(clock.moves_made(Player::Player1), 1);
+    }
+
+    #[test]
+    fn test_increment_stages() {
+        let mut rules = Rules::new(
+            times::TEN_MINUTES, times::TEN_MINUTES, times::TWO_SECONDS,
+            Player::Player1, TimingMethod::Fischer
+        );
+        rules.set_increment_stages(&[
+            IncrementStage::new(3, times::TEN_SECONDS),
+            IncrementStage::new(2, times::FIVE_SECONDS),
+            IncrementStage::new(3, times::THREE_SECONDS),
+        ]).unwrap();
+        assert_eq!(rules.get_increment_stages(), [
+            IncrementStage::new(2, times::FIVE_SECONDS),
+            IncrementStage::new(3, times::THREE_SECONDS),
+        ]);
+        assert_eq!(rules.increment_at(1), times::TWO_SECONDS);
+        assert_eq!(rules.increment_at(2), times::FIVE_SECONDS);
+        assert_eq!(rules.increment_at(40), times::THREE_SECONDS);
+        let many: Vec<_> = (1..=MAX_INCREMENT_STAGES as u32 + 1)
+            .map(|from_move| IncrementStage::new(from_move, Duration::ZERO))
+            .collect();
+        assert_eq!(
+            rules.clone().set_increment_stages(&many),
+            Err(RulesError::TooManyIncrementStages)
+        );
+
+        let mut clock = ChessClock::new(rules);
+        assert_eq!(clock.current_increment(), times::TWO_SECONDS);
+        clock.start();
+        clock.press(Player::Player1);
+        assert!(clock.read().0 > times::TEN_MINUTES);
+        assert!(clock.read().0 <= times::TEN_MINUTES + times::TWO_SECONDS);
+        clock.press(Player::Player2);
+        assert_eq!(clock.current_increment(), times::FIVE_SECONDS);
+        clock.press(Player::Player1);
+        assert!(clock.read().0 > times::TEN_MINUTES + times::FIVE_SECONDS);
+        assert_eq!(clock.current_increment(), times::FIVE_SECONDS);
+    }
+
+    #[test]
+    fn test_idle_timeout_pauses() {
+        let mut rules = Rules::default();
+        rules.set_idle_timeout(Some(Duration::from_millis(100)));
+        let mut clock = ChessClock::new(rules);
+
+        clock.start();
+        Duration::from_millis(60).sleep();
+        clock.switch_player();
+        Duration::from_millis(60).sleep();
+        clock.update();
+        assert_eq!(clock.status(), Status::Running);
+
+        Duration::from_millis(60).sleep();
+        clock.update();
+        assert_eq!(clock.status(), Status::Stopped);
+        assert_eq!(
+            clock.events().last().unwrap().event(),
+            &ClockEvent::IdlePaused(Player::Player2)
+        );
+
+        clock.start();
+        assert_eq!(clock.status(), Status::Running);
+    }
+
+    #[test]
+    fn test_pause_allowance() {
+        let mut rules = Rules::default();
+        rules.set_pause_allowance(Some(Duration::from_millis(100)));
+        let mut clock = ChessClock::new(rules);
+        assert_eq!(
+            clock.pause_remaining(Player::Player1),
+            Some(Duration::from_millis(100))
+        );
+
+        // A break is only taken while the clock runs
+        clock.pause_for(Player::Player1);
+        assert_eq!(clock.on_break(), None);
+
+        clock.start();
+        clock.switch_player();
+        // Player 1 leaves the board on their opponent's time
+        clock.pause_for(Player::Player1);
+        assert_eq!(clock.status(), Status::Stopped);
+        assert_eq!(clock.on_break(), Some(Player::Player1));
+        let before = clock.read();
+        Duration::from_millis(60).sleep();
+        clock.update();
+        assert_eq!(clock.read(), before);
+
+        clock.start();
+        assert_eq!(clock.on_break(), None);
+        assert_eq!(clock.active_player(), Player::Player2);
+        let used = clock.pause_used(Player::Player1);
+        assert!(used >= Duration::from_millis(60));
+        assert_eq!(clock.pause_used(Player::Player2), Duration::ZERO);
+        Duration::from_millis(20).sleep();
+        assert_eq!(clock.pause_used(Player::Player1), used);
+
+        clock.pause_for(Player::Player1);
+        Duration::from_millis(60).sleep();
+        clock.update();
+        clock.update();
+        let exceeded: Vec<_> = clock.events().iter()
+            .filter(|record| matches!(
+                record.event(), ClockEvent::PauseAllowanceExceeded(_)
+            ))
+            .collect();
+        assert_eq!(exceeded.len(), 1);
+        assert_eq!(
+            exceeded[0].event(),
+            &ClockEvent::PauseAllowanceExceeded(Player::Player1)
+        );
+        assert_eq!(clock.pause_remaining(Player::Player1), Some(Duration::ZERO));
+
+        // The breaks are rebuilt from the log
+        let replayed = ChessClock::replay(
+            clock.rules().clone(), clock.events()
+        );
+        assert_eq!(replayed.on_break(), Some(Player::Player1));
+        assert_eq!(replayed.status(), Status::Stopped);
+    }
+
+    #[test]
+    fn test_zero_times_are_rejected() {
+        let mut rules = Rules::default();
+        assert!(ChessClock::try_new(rules.clone()).is_ok());
+
+        rules.set_time(Player::Player2, Duration::ZERO);
+        assert!(!rules.is_valid());
+        assert_eq!(
+            ChessClock::try_new(rules.clone()).unwrap_err(),
+            RulesError::ZeroTime(Player::Player2)
+        );
+        rules.set_both_times(Duration::ZERO);
+        assert_eq!(rules.validate(), Err(RulesError::ZeroTime(Player::Player1)));
+        // Unchecked clocks for such rules are finished from the start
+        assert_eq!(ChessClock::new(rules).status(), Status::Finished);
+    }
+
+    #[test]
+    fn test_clock_reads_its_time_source() {
+        let rules = Rules::new(
+            times::ONE_MINUTE, times::ONE_MINUTE, Duration::ZERO,
+            Player::Player1, TimingMethod::Fischer
+        );
+        let time = ManualTime::new();
+        let mut clock = ChessClock::with_source(rules, time.clone());
+        clock.start();
+        time.advance(Duration::from_secs(10));
+        clock.switch_player();
+        time.advance(Duration::from_secs(25));
+        assert_eq!(
+            clock.read(),
+            (Duration::from_secs(50), Duration::from_secs(35))
+        );
+        assert_eq!(clock.elapsed_game_time(), Duration::from_secs(35));
+    }
+
+    #[test]
+    fn test_increment_larger_than_base_time() {
+        let rules = Rules::new(
+            times::FIVE_SECONDS, times::FIVE_SECONDS, times::ONE_MINUTE,
+            Player::Player1, TimingMethod::Fischer
+        );
+        let mut clock = ChessClock::new(rules.clone());
+        clock.start();
+        clock.press(Player::Player1);
+        let (t1, _) = clock.read();
+        assert!(t1 > times::ONE_MINUTE);
+        assert_eq!(clock.fraction_remaining(Player::Player1), 1.0);
+
+        // A Bronstein delay only gives back the time the move used
+        let mut rules = rules;
+        rules.set_timing_method(TimingMethod::Bronstein);
+        let mut clock = ChessClock::new(rules);
+        clock.start();
+        Duration::from_millis(20).sleep();
+        clock.press(Player::Player1);
+        assert!(clock.read().0 <= times::FIVE_SECONDS);
+    }
+
+    #[test]
+    fn test_bronstein_with_zero_base() {
+        let rules = Rules::new(
+            Duration::ZERO, times::ONE_MINUTE, times::FIVE_SECONDS,
+            Player::Player1, TimingMethod::Bronstein
+        );
+        assert_eq!(
+            ChessClock::try_new(rules.clone()).unwrap_err(),
+            RulesError::ZeroTime(Player::Player1)
+        );
+        let mut clock = ChessClock::new(rules);
+        clock.start();
+        clock.press(Player::Player1);
+        assert_eq!(clock.read().0, Duration::ZERO);
+        assert_eq!(clock.flagged(), Some(Player::Player1));
+    }
+
+    #[test]
+    fn test_subtracting_below_zero_while_running_flags() {
+        let mut clock = ChessClock::default();
+        clock.start();
+        clock.subtract_time(Player::Player1, times::ONE_HOUR, None).unwrap();
+        assert_eq!(clock.read().0, Duration::ZERO);
+        assert_eq!(clock.status(), Status::Finished);
+        assert_eq!(
+            clock.events().last().unwrap().event(),
+            &ClockEvent::Flagged(Player::Player1)
+        );
+
+        // The waiting player is not flagged until the game is over
+        let mut clock = ChessClock::default();
+        clock.start();
+        clock.subtract_time(Player::Player2, times::ONE_HOUR, None).unwrap();
+        assert_eq!(clock.read().1, Duration::ZERO);
+        assert_eq!(clock.flagged(), Some(Player::Player2));
+    }
+
+    #[test]
+    fn test_saturation_policy() {
+        let mut rules = Rules::default();
+        let policy = rules.policy();
+        assert_eq!(policy.credit(times::ONE_SECOND), times::FIVE_SECONDS);
+        assert_eq!(
+            policy.subtract(times::ONE_SECOND, times::ONE_MINUTE),
+            Duration::ZERO
+        );
+        assert_eq!(
+            policy.add(Duration::MAX, times::ONE_SECOND),
+            Duration::from_millis(u64::MAX)
+        );
+
+        rules.set_timing_method(TimingMethod::Bronstein);
+        assert_eq!(rules.policy().credit(times::ONE_SECOND), times::ONE_SECOND);
+        rules.set_timing_method(TimingMethod::ShotClock);
+        assert_eq!(rules.policy().credit(times::ONE_SECOND), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_session_budget() {
+        let mut rules = Rules::default();
+        assert_eq!(ChessClock::new(rules.clone()).session_remaining(), None);
+
+        rules.set_session_budget(
+            Some(SessionBudget::new(Duration::from_millis(50), false))
+        );
+        let mut clock = ChessClock::new(rules.clone());
+        assert_eq!(clock.session_remaining(), Some(Duration::from_millis(50)));
+        clock.start();
+        clock.stop();
+        // The session runs on while the clock is stopped
+        Duration::from_millis(60).sleep();
+        assert_eq!(clock.session_remaining(), Some(Duration::ZERO));
+        clock.update();
+        clock.update();
+        assert_eq!(clock.status(), Status::Stopped);
+        let expiries = clock.events().iter()
+            .filter(|record| record.event() == &ClockEvent::SessionExpired)
+            .count();
+        assert_eq!(expiries, 1);
+
+        rules.set_session_budget(
+            Some(SessionBudget::new(Duration::from_millis(50), true))
+        );
+        let mut clock = ChessClock::new(rules);
+        clock.start();
+        Duration::from_millis(60).sleep();
+        clock.update();
+        assert_eq!(clock.status(), Status::Finished);
+        assert_eq!(clock.flagged(), None);
+        let events: Vec<&ClockEvent> = clock.events().iter()
+            .map(EventRecord::event)
+            .collect();
+        assert_eq!(events, vec![
+            &ClockEvent::Started(Player::Player1),
+            &ClockEvent::SessionExpired,
+            &ClockEvent::Stopped(Player::Player1),
+            &ClockEvent::Finished,
+        ]);
+    }
+
+    #[test]
+    fn test_default_time() {
+        let mut rules = Rules::default();
+        rules.set_default_time(
+            Some(DefaultTime::new(Duration::from_millis(30), false))
+        );
+        let mut clock = ChessClock::new(rules.clone());
+        clock.start();
+        clock.update();
+        assert_eq!(clock.forfeited(), None);
+        Duration::from_millis(40).sleep();
+        clock.update();
+        clock.update();
+        assert_eq!(clock.forfeited(), Some(Player::Player1));
+        assert_eq!(clock.status(), Status::Running);
+        // Once allowed to play, the late player does not pass the forfeit on
+        clock.press(Player::Player1);
+        clock.update();
+        let forfeits = clock.events().iter()
+            .filter(|record| {
+                matches!(record.event(), ClockEvent::ForfeitDue(_))
+            })
+            .count();
+        assert_eq!(forfeits, 1);
+
+        rules.set_default_time(
+            Some(DefaultTime::new(Duration::from_millis(30), true))
+        );
+        let mut clock = ChessClock::new(rules);
+        clock.start();
+        clock.press(Player::Player1);
+        Duration::from_millis(40).sleep();
+        clock.update();
+        assert_eq!(clock.forfeited(), Some(Player::Player2));
+        assert_eq!(clock.status(), Status::Finished);
+        let events: Vec<&ClockEvent> = clock.events().iter()
+            .map(EventRecord::event)
+            .collect();
+        assert_eq!(events, vec![
+            &ClockEvent::Started(Player::Player1),
+            &ClockEvent::Pressed(Player::Player1),
+            &ClockEvent::ForfeitDue(Player::Player2),
+            &ClockEvent::Stopped(Player::Player2),
+            &ClockEvent::Finished,
+        ]);
+    }
+
+    #[test]
+    fn test_handicap_from_ratings() {
+        let even = Rules::handicap_from_ratings(1500, 1500, times::TEN_MINUTES);
+        assert_eq!(even.get_player1_time(), times::TEN_MINUTES);
+        assert_eq!(even.get_player2_time(), times::TEN_MINUTES);
+
+        // 150 points takes 15% from the stronger player
+        let rules = Rules::handicap_from_ratings(
+            1850, 2000, times::TEN_MINUTES
+        );
+        assert_eq!(rules.get_player1_time(), times::TEN_MINUTES);
+        assert_eq!(rules.get_player2_time(), Duration::from_secs(510));
+        assert_eq!(
+            Rules::handicap_from_ratings(2000, 1850, times::TEN_MINUTES)
+                .get_player1_time(),
+            Duration::from_secs(510)
+        );
+
+        // Huge gaps still leave the stronger player a tenth of the time,
+        // rounded down to the second
+        let rules = Rules::handicap_from_ratings(
+            2800, 800, Duration::from_millis(65_500)
+        );
+        assert_eq!(rules.get_player1_time(), Duration::from_secs(6));
+        assert!(rules.is_valid());
+    }
+
+    #[test]
+    fn test_think_time() {
+        let mut clock = ChessClock::default();
+        assert_eq!(clock.elapsed_game_time(), Duration::ZERO);
+
+        clock.start();
+        Duration::from_millis(40).sleep();
+        clock.press(Player::Player1);
+        Duration::from_millis(20).sleep();
+        clock.stop();
+        // Time while stopped and adjustments do not count
+        Duration::from_millis(40).sleep();
+        clock.add_time(Player::Player2, times::ONE_MINUTE, None).unwrap();
+
+        let (think1, think2) = (
+            clock.think_time(Player::Player1), clock.think_time(Player::Player2)
+        );
+        assert!(think1 >= Duration::from_millis(40));
+        assert!(think2 >= Duration::from_millis(20));
+        assert_eq!(clock.elapsed_game_time(), think1 + think2);
+
+        // The turn in progress counts towards the running player
+        clock.start();
+        Duration::from_millis(20).sleep();
+        assert!(
+            clock.think_time(Player::Player2)
+                >= think2 + Duration::from_millis(20)
+        );
+        assert_eq!(clock.think_time(Player::Player1), think1);
+
+        let replayed = ChessClock::replay(Rules::default(), clock.events());
+        let at = Instant::now();
+        assert_eq!(
+            replayed.think_time_at(Player::Player2, at),
+            clock.think_time_at(Player::Player2, at)
+        );
+    }
+
+    #[test]
+    fn test_tick_watchdog() {
+        let mut clock = ChessClock::default();
+        clock.set_tick_watchdog(Some(Duration::from_millis(30)));
+        clock.start();
+        clock.update();
+        Duration::from_millis(10).sleep();
+        clock.update();
+        assert_eq!(clock.recent_tick_delay(times::ONE_MINUTE), None);
+
+        Duration::from_millis(50).sleep();
+        clock.update();
+        let gap = clock.recent_tick_delay(times::ONE_MINUTE).unwrap();
+        assert!(gap >= Duration::from_millis(50));
+        assert!(matches!(
+            clock.events().last().unwrap().event(),
+            ClockEvent::TickDelay { player: Player::Player1, .. }
+        ));
+        Duration::from_millis(10).sleep();
+        assert_eq!(clock.recent_tick_delay(Duration::from_millis(5)), None);
+
+        // Time stopped between ticks is not a delay
+        let events = clock.events().len();
+        clock.stop();
+        Duration::from_millis(50).sleep();
+        clock.start();
+        clock.update();
+        assert_eq!(clock.events().len(), events + 2);
+    }
+
+    #[test]
+    fn test_rules_time_helpers() {
+        let mut rules = Rules::new(
+            times::FIVE_MINUTES, times::TEN_MINUTES, Duration::ZERO,
+            Player::Player1, TimingMethod::Fischer
+        );
+        rules.swap_players();
+        assert_eq!(rules.get_player1_time(), times::TEN_MINUTES);
+        assert_eq!(rules.get_player2_time(), times::FIVE_MINUTES);
+        assert_eq!(rules.get_starter(), Player::Player1);
+
+        let swapped = rules.swapped();
+        assert_eq!(swapped.get_player1_time(), times::FIVE_MINUTES);
+        assert_eq!(swapped.get_player2_time(), times::TEN_MINUTES);
+        assert_eq!(swapped.get_starter(), Player::Player2);
+        assert_eq!(swapped.swapped().get_starter(), Player::Player1);
+
+        rules.set_both_times(times::THREE_MINUTES);
+        assert_eq!(rules.get_player1_time(), times::THREE_MINUTES);
+        assert_eq!(rules.get_player2_time(), times::THREE_MINUTES);
+    }
+
+    #[test]
+    fn test_normalized_rules() {
+        let mut rules = Rules::new(
+            Duration::from_millis(569_400), Duration::from_secs(10),
+            Duration::from_millis(2_500), Player::Player1, TimingMethod::Fischer
+        );
+        rules.set_increment_stages(&[
+            IncrementStage::new(40, Duration::from_millis(9_400))
+        ]).unwrap();
+        assert_eq!(rules.normalized(NormalizationPolicy::NONE), rules);
+
+        let policy = NormalizationPolicy::new(
+            times::THIRTY_SECONDS, times::ONE_SECOND
+        );
+        let normalized = rules.normalized(policy);
+        assert_eq!(normalized.get_player1_time(), Duration::from_secs(570));
+        // A base time is kept to at least one step
+        assert_eq!(normalized.get_player2_time(), times::THIRTY_SECONDS);
+        assert_eq!(normalized.get_increment(), Duration::from_secs(3));
+        assert_eq!(
+            normalized.get_increment_stages()[0].get_increment(),
+            Duration::from_secs(9)
+        );
+        assert_eq!(normalized.normalized(policy), normalized);
+    }
+
+    #[test]
+    fn test_audit() {
+        let mut rules = presets::RAPID_10_5;
+        rules.set_timing_method(TimingMethod::Bronstein);
+        let mut clock = ChessClock::new(rules);
+        clock.start();
+        Duration::from_millis(10).sleep();
+        clock.add_time(Player::Player1, times::ONE_MINUTE, None).unwrap();
+        Duration::from_millis(10).sleep();
+        clock.switch_player();
+        clock.switch_player();
+        clock.subtract_time(Player::Player2, times::ONE_SECOND, None).unwrap();
+        clock.finish();
+        assert!(clock.audit());
+        assert!(!clock.events().iter().any(|record| {
+            matches!(record.event(), ClockEvent::Discrepancy { .. })
+        }));
+
+        // A flag that never happened ends the turn but leaves the clock
+        // running, so the time it runs on is charged to no one
+        let mut clock = ChessClock::default();
+        let start = Instant::now();
+        for (event, millis) in [
+            (ClockEvent::Started(Player::Player1), 0),
+            (ClockEvent::Flagged(Player::Player1), 10),
+            (ClockEvent::Stopped(Player::Player1), 30),
+        ] {
+            let elapsed = Duration::from_millis(millis);
+            clock.events.push(EventRecord::at(
+                event, start + elapsed, elapsed, SystemTime::now()
+            ));
+        }
+        assert!(!clock.audit());
+        let discrepancies = |clock: &ChessClock| clock.events().iter()
+            .filter(|record| {
+                matches!(record.event(), ClockEvent::Discrepancy { .. })
+            })
+            .count();
+        assert_eq!(discrepancies(&clock), 1);
+        assert_eq!(
+            clock.events().last().unwrap().event(),
+            &ClockEvent::Discrepancy {
+                player: Player::Player1,
+                expected: times::TEN_MINUTES - Duration::from_millis(10),
+                actual: times::TEN_MINUTES - Duration::from_millis(30),
+            }
+        );
+        assert!(!clock.audit());
+        assert_eq!(discrepancies(&clock), 1);
+    }
+
+    #[test]
+    fn test_replay_rebuilds_state() {
+        let mut rules = Rules::default();
+        rules.set_timing_method(TimingMethod::Bronstein);
+        let mut clock = ChessClock::new(rules.clone());
+        clock.start();
+        Duration::from_millis(30).sleep();
+        clock.switch_player();
+        Duration::from_millis(20).sleep();
+        clock.apply_penalty(Player::Player2, times::ONE_MINUTE, None).unwrap();
+        clock.switch_player();
+        clock.stop();
+        clock.switch_player();
+
+        let replayed = ChessClock::replay(rules, clock.events());
+        let later = Instant::now() + times::ONE_SECOND;
+        assert_eq!(replayed.read_at(later), clock.read_at(later));
+        assert_eq!(replayed.active_player(), clock.active_player());
+        assert_eq!(replayed.status(), clock.status());
+        assert_eq!(replayed.events().len(), clock.events().len());
+        assert_eq!(replayed.move_number(), clock.move_number());
+    }
+
+    #[test]
+    fn test_state_at_past_instants() {
+        let mut clock = ChessClock::new(Rules::new(
+            times::ONE_MINUTE, times::ONE_MINUTE, times::FIVE_SECONDS,
+            Player::Player1, TimingMethod::Fischer
+        ));
+        let before = Instant::now();
+        clock.start();
+        Duration::from_millis(20).sleep();
+        clock.switch_player();
+        let after_move_1 = clock.snapshot(Instant::now());
+        Duration::from_millis(20).sleep();
+        clock.switch_player();
+        Duration::from_millis(20).sleep();
+        clock.switch_player();
+        clock.stop();
+
+        let start = clock.state_at(before);
+        assert_eq!(start.status, Status::Stopped);
+        assert_eq!(start.times, (times::ONE_MINUTE, times::ONE_MINUTE));
+        assert_eq!(start.move_number, 1);
+
+        let move_1 = clock.state_at_move(1).unwrap();
+        assert_eq!(move_1.active_player, Player::Player2);
+        assert_eq!(move_1.move_number, 1);
+        assert_eq!(move_1.status, Status::Running);
+        assert_eq!(move_1.times.0, after_move_1.times.0);
+        assert!(move_1.times.1 >= after_move_1.times.1);
+
+        let move_2 = clock.state_at_move(2).unwrap();
+        assert_eq!(move_2.move_number, 2);
+        assert_eq!(move_2.times.0, clock.read().0);
+        assert_eq!(clock.state_at_move(0), None);
+        assert_eq!(clock.state_at_move(3), None);
+    }
+
+    #[test]
+    fn test_background_paused() {
+        let mut rules = Rules::default();
+        rules.set_background_time(BackgroundTime::Paused);
+        let mut clock = ChessClock::new(rules);
+        clock.start();
+        assert!(clock.wants_screen_awake());
+
+        clock.on_background();
+        assert_eq!(clock.status(), Status::Stopped);
+        assert!(!clock.wants_screen_awake());
+        let (before, _) = clock.read();
+        Duration::from_millis(50).sleep();
+        assert_eq!(clock.read().0, before);
+
+        clock.on_foreground();
+        assert_eq!(clock.status(), Status::Running);
+        assert!(matches!(
+            clock.events().last().unwrap().event(),
+            ClockEvent::Foregrounded { player: Player::Player1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_background_counts_suspended_time() {
+        let mut clock = ChessClock::default();
+        clock.start();
+        clock.on_background();
+        assert_eq!(clock.status(), Status::Running);
+
+        // Simulate the process being suspended for a minute, which the wall
+        // clock sees but the monotonic clock does not
+        let background = clock.background.as_mut().unwrap();
+        background.timestamp -= times::ONE_MINUTE;
+        clock.on_foreground();
+
+        let (remaining, _) = clock.read();
+        assert!(remaining <= times::TEN_MINUTES - times::ONE_MINUTE);
+        assert!(remaining > times::TEN_MINUTES - 2 * times::ONE_MINUTE);
+        // Foregrounding again does nothing
+        clock.on_foreground();
+        assert_eq!(
+            clock.events().iter()
+                .filter(|record| matches!(
+                    record.event(), ClockEvent::Foregrounded { .. }
+                ))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_time_anomaly_is_recorded() {
+        let mut clock = ChessClock::default();
+        clock.start();
+        // Simulate a time source that has jumped back past the start
+        let active = clock.active_player().index();
+        clock.clocks[active].stop();
+        clock.clocks[active].start_at(Instant::now() + times::ONE_HOUR);
+
+        clock.update();
+        assert_eq!(clock.status(), Status::Running);
+        assert_eq!(
+            clock.events().last().unwrap().event(),
+            &ClockEvent::TimeAnomaly(clock.active_player())
+        );
+        clock.update();
+        assert_eq!(clock.events().len(), 2);
+    }
+
+    #[test]
+    fn test_flagged() {
+        let rules = Rules::new(
+            Duration::from_millis(20), times::TEN_MINUTES, Duration::ZERO,
+            Player::Player1, TimingMethod::Fischer
+        );
+        let mut clock = ChessClock::new(rules);
+        assert_eq!(clock.flagged(), None);
+
+        clock.start();
+        Duration::from_millis(40).sleep();
+        assert_eq!(clock.flagged(), Some(Player::Player1));
+        clock.update();
+        assert_eq!(clock.flagged(), Some(Player::Player1));
+
+        // A later flag of the opponent does not change who flagged first
+        clock.subtract_time(Player::Player2, times::TEN_MINUTES, None).unwrap();
+        assert_eq!(clock.flagged(), Some(Player::Player1));
+    }
+
+    #[test]
+    fn test_flagged_simultaneous_zero_blames_player_on_move() {
+        for active in Player::ALL {
+            let clock = ChessClock::restore(
+                Rules::default(), (Duration::ZERO, Duration::ZERO), active,
+                false
+            );
+            assert_eq!(clock.status(), Status::Finished);
+            assert_eq!(clock.flagged(), Some(active));
+        }
+
+        let clock = ChessClock::restore(
+            Rules::default(), (times::ONE_MINUTE, Duration::ZERO),
+            Player::Player1, false
+        );
+        assert_eq!(clock.flagged(), Some(Player::Player2));
+    }
+
+    #[test]
+    fn test_press_starts_clock() {
+        let mut clock = ChessClock::default();
+        clock.press_starts_clock(true);
+        clock.switch_player();
+
+        assert_eq!(clock.status(), Status::Running);
+        assert_eq!(clock.active_player(), Player::Player2);
+        let events: Vec<&ClockEvent> = clock.events().iter()
+            .map(EventRecord::event)
+            .collect();
+        assert_eq!(events, vec![
+            &ClockEvent::Started(Player::Player1),
+            &ClockEvent::Pressed(Player::Player1),
+        ]);
+
+        // Only the first switch of the game starts the clock
+        clock.stop();
+        clock.switch_player();
+        assert_eq!(clock.status(), Status::Stopped);
+        assert_eq!(clock.active_player(), Player::Player1);
+
+        // Over the board, the waiting player's lever starts the game
+        let mut clock = ChessClock::default();
+        clock.press_starts_clock(true);
+        clock.press(Player::Player1);
+        assert_eq!(clock.status(), Status::Stopped);
+        assert!(clock.events().is_empty());
+        clock.press(Player::Player2);
+        assert_eq!(clock.status(), Status::Running);
+        assert_eq!(clock.active_player(), Player::Player1);
+        let events: Vec<&ClockEvent> = clock.events().iter()
+            .map(EventRecord::event)
+            .collect();
+        assert_eq!(events, vec![&ClockEvent::Started(Player::Player1)]);
+        assert_eq!(clock.moves_made(Player::Player1), 0);
+
+        clock.press(Player::Player1);
+        assert_eq!(clock.active_player(), Player::Player2);
+        assert_eq!(clock.moves_made(Player::Player1), 1);
+    }
+
+    #[test]
+    fn test_switch_without_press_starts_clock() {
+        let mut clock = ChessClock::default();
+        clock.switch_player();
+        assert_eq!(clock.status(), Status::Stopped);
+        assert_eq!(clock.active_player(), Player::Player2);
+    }
+
+    #[test]
+    fn test_fraction_remaining() {
+        let rules = Rules::new(
+            times::TEN_MINUTES, Duration::ZERO, times::TEN_MINUTES,
+            Player::Player1, TimingMethod::Fischer
+        );
+        let mut clock = ChessClock::new(rules);
+        assert_eq!(clock.fraction_remaining(Player::Player1), 1.0);
+        assert_eq!(clock.fraction_remaining(Player::Player2), 0.0);
+
+        clock.subtract_time(Player::Player1, times::FIVE_MINUTES, None).unwrap();
+        assert_eq!(clock.fraction_remaining(Player::Player1), 0.5);
+
+        clock.add_time(Player::Player1, times::TEN_MINUTES, None).unwrap();
+        assert_eq!(clock.fraction_remaining(Player::Player1), 1.0);
+    }
+
+    #[test]
+    fn test_press_by_waiting_player_is_ignored() {
+        let mut clock = ChessClock::default();
+        clock.start();
+
+        clock.press(Player::Player2);
+        assert_eq!(clock.active_player(), Player::Player1);
+        assert_eq!(clock.status(), Status::Running);
     }
 }
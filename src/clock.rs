@@ -1,13 +1,22 @@
-use crate::DurationDisplay;
-use std::{fmt::Display, time::{Duration, Instant}};
-
-const TEN_MINUTES: Duration = Duration::from_secs(60 * 10);
+use crate::{
+    DurationDisplay, MonotonicTime, TimeParts, TimeSource, times::TEN_MINUTES
+};
+use std::{
+    fmt::{self, Debug, Display},
+    ops::{AddAssign, SubAssign},
+    time::{Duration, Instant},
+};
 
 /// ClockState records whether the clock is running or stopped, and the time at
 /// which it was last started if it is running.
+///
+/// A clock paused by the user is `Paused` rather than `Stopped`, so that front
+/// ends can tell a pause apart from a clock that has not started or a
+/// countdown that has run out.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ClockState {
     Running(Instant),
+    Paused,
     Stopped,
     Finished,
 }
@@ -17,6 +26,60 @@ pub enum ClockState {
 pub enum ClockMode {
     CountUp,
     CountDown,
+    /// Count down, then carry on past zero into overtime rather than
+    /// stopping, reading how far into overtime the clock is
+    CountDownOvertime,
+}
+
+impl ClockMode {
+    /// Get whether the clock counts down, into overtime or not
+    pub fn is_countdown(&self) -> bool {
+        matches!(self, Self::CountDown | Self::CountDownOvertime)
+    }
+}
+
+/// A change to a clock, passed to the callbacks registered with
+/// [`Clock::on_event`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockChange {
+    /// The clock was started
+    Started,
+    /// The clock was paused
+    Paused,
+    /// A paused clock was resumed
+    Resumed,
+    /// The clock was stopped, or a paused clock was stopped where it was
+    Stopped,
+    /// The clock was reset to a new time
+    Reset,
+    /// A countdown ran out, or went into overtime, noticed when the clock
+    /// was next updated or when time was subtracted
+    ReachedZero,
+    /// The clock was finished
+    Finished,
+    /// An alarm added with [`Clock::add_alarm`] fired, with its tag
+    Alarm(&'static str),
+}
+
+/// An alarm which fires once its clock passes a threshold
+#[derive(Clone, Copy, Debug)]
+struct Alarm {
+    threshold: Duration,
+    tag: &'static str,
+    fired: bool,
+}
+
+/// The callbacks registered with a clock
+///
+/// They must be `Send` and `Sync`, so that a clock can still be shared
+/// between threads.
+#[derive(Default)]
+struct Callbacks(Vec<Box<dyn FnMut(ClockChange) + Send + Sync>>);
+
+impl Debug for Callbacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Callbacks({})", self.0.len())
+    }
 }
 
 /// A simple clock that can be started and stopped
@@ -26,33 +89,118 @@ pub enum ClockMode {
 /// Works by keeping track of the last start time and the total time that has
 /// passed between the last reset and the last stop.
 ///
-/// When counting down, the clock automatically stops when it reaches zero.
+/// When counting down, the clock automatically stops when it reaches zero,
+/// unless it is in `CountDownOvertime` mode, when it counts on into
+/// overtime.
+///
+/// The current time is read from a `TimeSource`, by default the monotonic
+/// clock of the operating system.
 #[derive(Debug)]
-pub struct Clock {
+pub struct Clock<T = MonotonicTime> {
     already_elapsed: Duration,
     state: ClockState,
-    mode: ClockMode
+    mode: ClockMode,
+    /// Whether `already_elapsed` is time into overtime rather than time left
+    overtime: bool,
+    source: T,
+    callbacks: Callbacks,
+    alarms: Vec<Alarm>,
+    laps: Vec<Duration>,
+    /// What the clock read at the last lap, or when it was last reset, and
+    /// whether that was overtime
+    lap_start: (Duration, bool),
 }
 
 impl Clock {
     /// Constructs a new stopped clock with the given time
     pub fn new(mode: ClockMode, start: Option<Duration>) -> Clock {
+        Self::with_source(mode, start, MonotonicTime)
+    }
+}
+
+impl<T: TimeSource> Clock<T> {
+    /// Constructs a new stopped clock with the given time, reading the
+    /// current time from `source`
+    pub fn with_source(
+        mode: ClockMode, start: Option<Duration>, source: T
+    ) -> Self {
         let elapsed = match (mode, start) {
             (_, Some(start)) => start,
             (ClockMode::CountUp, None) => Duration::ZERO,
-            (ClockMode::CountDown, None) => TEN_MINUTES,
+            (_, None) => TEN_MINUTES,
         };
 
         Clock {
             already_elapsed: elapsed,
             state: ClockState::Stopped,
-            mode
+            mode,
+            overtime: false,
+            source,
+            callbacks: Callbacks::default(),
+            alarms: Vec::new(),
+            laps: Vec::new(),
+            lap_start: (elapsed, false),
         }
     }
 
-    /// Initialise a new clock that counts up from 0 with no start time
-    pub fn default() -> Self {
-        Self::new(ClockMode::CountUp, None)
+    /// Get where the clock reads the current time from
+    pub fn source(&self) -> &T {
+        &self.source
+    }
+
+    /// Registers `callback` to be called with every change to the clock, as
+    /// it happens, so that front ends need not poll the state for changes
+    ///
+    /// Callbacks are called in the order they were registered. A countdown
+    /// is only seen to reach zero when it is updated, as by
+    /// `read_and_update` or `state_and_update`.
+    pub fn on_event<F>(&mut self, callback: F)
+    where
+        F: FnMut(ClockChange) + Send + Sync + 'static,
+    {
+        self.callbacks.0.push(Box::new(callback));
+    }
+
+    /// Adds an alarm, firing once the clock reads `threshold` or less when
+    /// counting down, or `threshold` or more when counting up
+    ///
+    /// The alarm is checked whenever the clock is updated, as by
+    /// `read_and_update` or `state_and_update`, and fires only once, as a
+    /// `ClockChange::Alarm` with `tag` passed to the `on_event` callbacks.
+    /// Resetting the clock sets its alarms to fire again.
+    pub fn add_alarm(&mut self, threshold: Duration, tag: &'static str) {
+        self.alarms.push(Alarm { threshold, tag, fired: false });
+    }
+
+    /// Removes every alarm
+    pub fn clear_alarms(&mut self) {
+        self.alarms.clear();
+    }
+
+    /// Fires each alarm that the clock has passed at `time` for the first
+    /// time
+    fn check_alarms(&mut self, time: Duration, overtime: bool) {
+        let mut fired = Vec::new();
+        let countdown = self.mode.is_countdown();
+        for alarm in self.alarms.iter_mut().filter(|alarm| !alarm.fired) {
+            let passed = match countdown {
+                true => overtime || time <= alarm.threshold,
+                false => time >= alarm.threshold,
+            };
+            if passed {
+                alarm.fired = true;
+                fired.push(alarm.tag);
+            }
+        }
+        for tag in fired {
+            self.notify(ClockChange::Alarm(tag));
+        }
+    }
+
+    fn notify(&mut self, change: ClockChange) {
+        for callback in &mut self.callbacks.0 {
+            callback(change);
+        }
     }
 
     /// Read the current time on the clock
@@ -60,28 +208,105 @@ impl Clock {
     /// This is a read-only function, and it will not update the state of the
     /// clock.
     pub fn read(&self) -> Duration {
-        match (&self.state, &self.mode) {
-            (ClockState::Running(start), ClockMode::CountUp) => {
-                let now = Instant::now();
-                let elapsed = now - *start;
-                self.already_elapsed + elapsed
+        self.read_at(self.source.now())
+    }
+
+    /// Read the time the clock shows at `now`
+    ///
+    /// In `CountDownOvertime` mode, this is how far into overtime the clock
+    /// is once it has passed zero, as told by `is_overtime_at`.
+    ///
+    /// This is a read-only function, and it will not update the state of the
+    /// clock.
+    pub fn read_at(&self, now: Instant) -> Duration {
+        self.read_signed_at(now).0
+    }
+
+    /// Read the time the clock shows at `now`, and whether it is overtime
+    fn read_signed_at(&self, now: Instant) -> (Duration, bool) {
+        let ClockState::Running(start) = self.state else {
+            return (self.already_elapsed, self.overtime);
+        };
+        let running = running_since(start, now);
+        match self.mode {
+            ClockMode::CountUp => (self.already_elapsed + running, false),
+            ClockMode::CountDown => {
+                (self.already_elapsed.saturating_sub(running), false)
+            },
+            ClockMode::CountDownOvertime => match self.overtime {
+                true => (self.already_elapsed + running, true),
+                false if running > self.already_elapsed => {
+                    (running - self.already_elapsed, true)
+                },
+                false => (self.already_elapsed - running, false),
             },
-            (ClockState::Running(start), ClockMode::CountDown) => {
-                let now = Instant::now();
-                let elapsed = now - *start;
-                self.already_elapsed.saturating_sub(elapsed)
-            }
-            (_, _) => self.already_elapsed,
         }
     }
 
+    /// Get whether a `CountDownOvertime` clock has passed zero, so that it
+    /// reads the time into overtime
+    pub fn is_overtime(&self) -> bool {
+        self.is_overtime_at(self.source.now())
+    }
+
+    /// Get whether a `CountDownOvertime` clock has passed zero by `now`
+    pub fn is_overtime_at(&self, now: Instant) -> bool {
+        self.read_signed_at(now).1
+    }
+
+    /// Keeps the time the clock shows at `now`, as the clock is stopped or
+    /// rebased
+    fn settle_at(&mut self, now: Instant) {
+        (self.already_elapsed, self.overtime) = self.read_signed_at(now);
+    }
+
+    /// Moves the time of a `CountDownOvertime` clock by `time`, up if `add`
+    /// and down otherwise, crossing zero into or out of overtime
+    fn shift_at(&mut self, time: Duration, add: bool, now: Instant) {
+        let was_overtime = self.overtime;
+        self.settle_at(now);
+        let (time_left, towards_zero) = match (self.overtime, add) {
+            (false, true) | (true, false) => {
+                (self.already_elapsed.saturating_add(time), false)
+            },
+            (false, false) | (true, true) => {
+                (self.already_elapsed.abs_diff(time), true)
+            },
+        };
+        if towards_zero && time > self.already_elapsed {
+            self.overtime = !self.overtime;
+        }
+        self.already_elapsed = time_left;
+        if let ClockState::Running(_) = self.state {
+            self.state = ClockState::Running(now);
+        }
+        if self.overtime && !was_overtime {
+            self.notify(ClockChange::ReachedZero);
+        }
+    }
+
+    /// Read the current time on the clock split into hours, minutes, seconds
+    /// and milliseconds
+    ///
+    /// This is a read-only function, and it will not update the state of the
+    /// clock.
+    pub fn read_parts(&self) -> TimeParts {
+        TimeParts::from(self.read())
+    }
+
     /// Read the amount of time that has passed since the clock was last started
     ///
     /// This is a read-only function, and it will not update the state of the
     /// clock.
     pub fn read_running(&self) -> Duration {
+        self.read_running_at(self.source.now())
+    }
+
+    /// Read the amount of time between the clock last being started and
+    /// `now`
+    pub fn read_running_at(&self, now: Instant) -> Duration {
         match self.state {
-            ClockState::Running(start) => Instant::now() - start,
+            ClockState::Running(start) => running_since(start, now),
             _ => Duration::ZERO,
         }
     }
@@ -90,9 +315,16 @@ impl Clock {
     /// if necessary
     ///
     /// If the clock is in CountDown mode and the time is zero, the clock will
-    /// be stopped.
+    /// be stopped. A `CountDownOvertime` clock carries on into overtime.
     pub fn read_and_update(&mut self) -> Duration {
-        let time = self.read();
+        self.read_and_update_at(self.source.now())
+    }
+
+    /// Read the time the clock shows at `now` and update the state of the
+    /// clock if necessary, as for `read_and_update`
+    pub fn read_and_update_at(&mut self, now: Instant) -> Duration {
+        let (time, overtime) = self.read_signed_at(now);
+        self.check_alarms(time, overtime);
 
         let is_running = matches!(self.state, ClockState::Running(_));
         let is_countdown = self.mode == ClockMode::CountDown;
@@ -101,6 +333,12 @@ impl Clock {
         if is_running && is_countdown && is_zero {
             self.already_elapsed = Duration::ZERO;
             self.state = ClockState::Stopped;
+            self.notify(ClockChange::ReachedZero);
+        } else if is_running && overtime && !self.overtime {
+            // Count on from zero, now into overtime
+            self.settle_at(now);
+            self.state = ClockState::Running(now);
+            self.notify(ClockChange::ReachedZero);
         }
 
         time
@@ -127,8 +365,84 @@ impl Clock {
     ///
     /// If the clock is already running, this does nothing
     pub fn start(&mut self) {
+        self.start_at(self.source.now());
+    }
+
+    /// Starts the clock as if it had been started at `at`
+    ///
+    /// If the clock is already running, this does nothing
+    pub fn start_at(&mut self, at: Instant) {
         if let ClockState::Stopped = self.state {
-            self.state = ClockState::Running(Instant::now());
+            self.state = ClockState::Running(at);
+            self.notify(ClockChange::Started);
+        }
+    }
+
+    /// Pauses the clock, keeping its time until it is resumed
+    ///
+    /// If the clock is not running, this does nothing.
+    pub fn pause(&mut self) {
+        self.pause_at(self.source.now());
+    }
+
+    /// Pauses the clock as if it had been paused at `now`
+    ///
+    /// If the clock is not running, this does nothing.
+    pub fn pause_at(&mut self, now: Instant) {
+        if let ClockState::Running(_) = self.state {
+            self.settle_at(now);
+            self.state = ClockState::Paused;
+            self.notify(ClockChange::Paused);
+        }
+    }
+
+    /// Resumes a paused clock
+    ///
+    /// If the clock is not paused, this does nothing, so a stopped clock is
+    /// only ever started with `start`.
+    pub fn resume(&mut self) {
+        self.resume_at(self.source.now());
+    }
+
+    /// Resumes a paused clock as if it had been resumed at `at`
+    ///
+    /// If the clock is not paused, this does nothing.
+    pub fn resume_at(&mut self, at: Instant) {
+        if let ClockState::Paused = self.state {
+            self.state = ClockState::Running(at);
+            self.notify(ClockChange::Resumed);
+        }
+    }
+
+    /// Get whether the clock is paused, to be resumed where it left off
+    pub fn is_paused(&self) -> bool {
+        self.state == ClockState::Paused
+    }
+
+    /// Checks that time has not gone backwards since the clock was started
+    ///
+    /// A time source that is not monotonic can report a time before the
+    /// start. Reads are clamped to zero running time in that case, and this
+    /// moves the start up to now so that the clock carries on from there.
+    ///
+    /// # Returns
+    /// Whether an anomaly was found and corrected.
+    pub fn correct_anomaly(&mut self) -> bool {
+        self.correct_anomaly_at(self.source.now())
+    }
+
+    /// Checks that `now` is not before the clock was started, as for
+    /// `correct_anomaly`
+    pub fn correct_anomaly_at(&mut self, now: Instant) -> bool {
+        match self.state {
+            ClockState::Running(start) => {
+                let anomalous = now.checked_duration_since(start).is_none();
+                if anomalous {
+                    self.state = ClockState::Running(now);
+                }
+                anomalous
+            },
+            _ => false,
         }
     }
 
@@ -136,12 +450,25 @@ impl Clock {
     ///
     /// If the clock is already stopped, this does nothing.
     pub fn stop(&mut self) {
-        // If the clock is running, read the current time and set the elapsed
-        // time to the current time
-        if let ClockState::Running(_) = self.state {
-            self.already_elapsed = self.read();
-            self.state = ClockState::Stopped;
+        self.stop_at(self.source.now());
+    }
+
+    /// Stops the clock as if it had been stopped at `now`
+    ///
+    /// A paused clock is stopped where it was paused. If the clock is already
+    /// stopped, this does nothing.
+    pub fn stop_at(&mut self, now: Instant) {
+        // If the clock is running, read the time at `now` and set the
+        // elapsed time to it
+        match self.state {
+            ClockState::Running(_) => {
+                self.settle_at(now);
+                self.state = ClockState::Stopped;
+            },
+            ClockState::Paused => self.state = ClockState::Stopped,
+            _ => return,
         }
+        self.notify(ClockChange::Stopped);
     }
 
     /// Resets the clock
@@ -149,7 +476,12 @@ impl Clock {
     /// Sets the elapsed time to start (or zero) and stops the clock
     pub fn reset(&mut self, start: Option<Duration>) {
         self.already_elapsed = start.unwrap_or(Duration::ZERO);
+        self.overtime = false;
         self.state = ClockState::Stopped;
+        self.alarms.iter_mut().for_each(|alarm| alarm.fired = false);
+        self.laps.clear();
+        self.lap_start = (self.already_elapsed, false);
+        self.notify(ClockChange::Reset);
     }
 
     /// Resets the clock to zero
@@ -160,47 +492,214 @@ impl Clock {
     }
 
     /// Adds time to the clock
+    ///
+    /// Time added to a clock in overtime takes it back towards zero, and out
+    /// of overtime if there is enough.
     pub fn add(&mut self, time: Duration) {
+        if self.mode == ClockMode::CountDownOvertime {
+            self.shift_at(time, true, self.source.now());
+            return;
+        }
         self.already_elapsed = self.already_elapsed.saturating_add(time);
     }
 
     /// Subtracts time from the clock
     ///
     /// If the time to subtract is greater than the current time on the clock,
-    /// the clock will be set to zero.
+    /// the clock will be set to zero, or a `CountDownOvertime` clock goes
+    /// that far into overtime.
     pub fn subtract(&mut self, time: Duration) {
+        self.subtract_at(time, self.source.now());
+    }
+
+    /// Subtracts time from the clock as if it were subtracted at `now`
+    ///
+    /// A running clock carries on running from `now`.
+    pub fn subtract_at(&mut self, time: Duration, now: Instant) {
+        if self.mode == ClockMode::CountDownOvertime {
+            self.shift_at(time, false, now);
+            return;
+        }
         if let ClockState::Running(_) = self.state {
-            let total_time = self.read();
-            self.reset(Some(total_time.saturating_sub(time)));
+            self.already_elapsed = self.read_at(now).saturating_sub(time);
             if self.already_elapsed > Duration::ZERO {
-                self.start();
+                self.state = ClockState::Running(now);
+            } else {
+                self.state = ClockState::Stopped;
+                if self.mode == ClockMode::CountDown {
+                    self.notify(ClockChange::ReachedZero);
+                }
             }
         } else {
             self.already_elapsed = self.already_elapsed.saturating_sub(time);
         }
     }
 
+    /// Records a lap, the time the clock has run since the previous lap, or
+    /// since it was reset for the first
+    ///
+    /// # Returns
+    /// The time of the lap.
+    pub fn lap(&mut self) -> Duration {
+        self.lap_at(self.source.now())
+    }
+
+    /// Records a lap as if it were recorded at `now`, as for `lap`
+    pub fn lap_at(&mut self, now: Instant) -> Duration {
+        let reading = self.read_signed_at(now);
+        let run = self.progress(reading) - self.progress(self.lap_start);
+        let lap = Duration::from_nanos(run.clamp(0, u64::MAX as i128) as u64);
+        self.laps.push(lap);
+        self.lap_start = reading;
+        lap
+    }
+
+    /// Get the time of every lap recorded since the clock was reset, oldest
+    /// first
+    pub fn laps(&self) -> &[Duration] {
+        &self.laps
+    }
+
+    /// How far a reading of the clock is along its direction of counting, in
+    /// nanoseconds, so that the time run between two readings is their
+    /// difference
+    fn progress(&self, (time, overtime): (Duration, bool)) -> i128 {
+        let nanos = time.as_nanos() as i128;
+        match self.mode.is_countdown() && !overtime {
+            true => -nanos,
+            false => nanos,
+        }
+    }
+
+    /// Sets the time the clock shows at `now`
+    ///
+    /// A running clock carries on running from `now`, even from zero, so that
+    /// the next update of a countdown sees that its time has run out.
+    pub fn set_at(&mut self, time: Duration, now: Instant) {
+        self.already_elapsed = time;
+        self.overtime = false;
+        if let ClockState::Running(_) = self.state {
+            self.state = ClockState::Running(now);
+        }
+    }
+
     pub fn finish(&mut self) {
-        self.stop();
+        self.finish_at(self.source.now());
+    }
+
+    /// Finishes the clock as if it had been finished at `now`, stopping it
+    /// there first if it is running
+    pub fn finish_at(&mut self, now: Instant) {
+        if self.state == ClockState::Finished {
+            return;
+        }
+        self.stop_at(now);
         self.state = ClockState::Finished;
+        self.notify(ClockChange::Finished);
     }
+
+    /// Iterate over the formatted readings of the clock every `interval`
+    ///
+    /// The first frame is read straight away, and each later one is read as
+    /// close as possible to a whole number of intervals after it, blocking
+    /// the thread in between with the clock's `TimeSource::sleep_until`, so
+    /// the intervals are in the source's time. A countdown ends after the frame reading zero,
+    /// unless it goes on into overtime; otherwise the frames go on for as
+    /// long as they are taken.
+    ///
+    /// There is no async `Stream` of frames, by design: the library takes
+    /// no `futures` dependency for the trait, and an async front end can read
+    /// the clock on its own runtime's timer instead, as the GUI does.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rusty_chess_clock::{Clock, ClockMode};
+    ///
+    /// let clock = Clock::new(ClockMode::CountDown, Some(Duration::ZERO));
+    /// let frames: Vec<String> = clock.frames(Duration::from_millis(10))
+    ///     .collect();
+    /// assert_eq!(frames, ["00:00"]);
+    /// ```
+    pub fn frames(&self, interval: Duration) -> Frames<'_, T> {
+        Frames { clock: self, interval, next: None, done: false }
+    }
+}
+
+/// An iterator over the formatted readings of a clock at a fixed rate,
+/// created by [`Clock::frames`]
+#[derive(Debug)]
+pub struct Frames<'a, T = MonotonicTime> {
+    clock: &'a Clock<T>,
+    interval: Duration,
+    next: Option<Instant>,
+    done: bool,
 }
 
-impl Display for Clock {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let duration = DurationDisplay::from(self.read());
+impl<T: TimeSource> Iterator for Frames<'_, T> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.done {
+            return None;
+        }
+        let due = *self.next.get_or_insert_with(|| self.clock.source.now());
+        // The source waits, so a scaled or manual time sets the pace
+        self.clock.source.sleep_until(due);
+        // Frames are scheduled from the first, so slow readers don't drift
+        self.next = Some(due + self.interval);
+
+        let (time, overtime) = self.clock.read_signed_at(due);
+        self.done = self.clock.mode == ClockMode::CountDown && time.is_zero();
+        let sign = if overtime { "+" } else { "" };
+        Some(format!("{}{}", sign, DurationDisplay::from(time)))
+    }
+}
+
+/// The time from `start` to `now`, or zero if `start` is after `now`
+fn running_since(start: Instant, now: Instant) -> Duration {
+    now.checked_duration_since(start).unwrap_or(Duration::ZERO)
+}
+
+impl Default for Clock {
+    /// Initialise a new clock that counts up from 0 with no start time
+    fn default() -> Self {
+        Self::new(ClockMode::CountUp, None)
+    }
+}
+
+/// Shows the time on the clock, with a `+` in front of time into overtime
+impl<T: TimeSource> Display for Clock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (time, overtime) = self.read_signed_at(self.source.now());
+        let duration = DurationDisplay::from(time);
+        let sign = if overtime { "+" } else { "" };
         if f.alternate() {
-            write!(f, "{:#}", duration)
+            write!(f, "{}{:#}", sign, duration)
         } else {
-            write!(f, "{}", duration)
+            write!(f, "{}{}", sign, duration)
         }
     }
 }
 
+/// `clock += time` adds time to the clock, as [`Clock::add`]
+impl<T: TimeSource> AddAssign<Duration> for Clock<T> {
+    fn add_assign(&mut self, time: Duration) {
+        self.add(time);
+    }
+}
+
+/// `clock -= time` subtracts time from the clock, as [`Clock::subtract`]
+impl<T: TimeSource> SubAssign<Duration> for Clock<T> {
+    fn sub_assign(&mut self, time: Duration) {
+        self.subtract(time);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Sleep;
+    use crate::{ManualTime, Sleep};
+    use std::sync::{Arc, Mutex};
 
     #[test]
     /// Test that the default clock is at 0, stopped and counting up
@@ -211,6 +710,31 @@ mod tests {
         assert_eq!(clock.mode, ClockMode::CountUp);
     }
 
+    #[test]
+    /// Test that a stopped clock reads back as its parts
+    fn test_clock_read_parts() {
+        let clock = Clock::new(
+            ClockMode::CountDown, Some(Duration::from_millis(5_025_500))
+        );
+        assert_eq!(clock.read_parts(), TimeParts {
+            hours: 1, minutes: 23, seconds: 45, millis: 500
+        });
+    }
+
+    #[test]
+    /// Test that a start time in the future is clamped and then corrected
+    fn test_clock_time_anomaly() {
+        let mut clock = Clock::new(ClockMode::CountDown, Some(TEN_MINUTES));
+        clock.start_at(Instant::now() + Duration::from_secs(60));
+        assert_eq!(clock.read(), TEN_MINUTES);
+        assert_eq!(clock.read_running(), Duration::ZERO);
+
+        assert!(clock.correct_anomaly());
+        assert!(!clock.correct_anomaly());
+        Duration::from_millis(20).sleep();
+        assert!(clock.read() < TEN_MINUTES);
+    }
+
     #[test]
     /// Test that the clock behaves as expected when counting up
     fn test_clock_count_up() {
@@ -274,6 +798,242 @@ mod tests {
         assert_eq!(clock.to_string(), "00:00");
     }
 
+    #[test]
+    fn test_clock_pause_resume() {
+        let mut clock = Clock::new(ClockMode::CountDown, Some(TEN_MINUTES));
+        // Only a running clock can be paused, and only a paused one resumed
+        clock.pause();
+        assert_eq!(clock.state(), ClockState::Stopped);
+        clock.resume();
+        assert_eq!(clock.state(), ClockState::Stopped);
+
+        clock.start();
+        Duration::from_millis(20).sleep();
+        clock.pause();
+        assert!(clock.is_paused());
+        let paused = clock.read();
+        Duration::from_millis(20).sleep();
+        assert_eq!(clock.read(), paused);
+
+        // Starting does not resume a pause
+        clock.start();
+        assert!(clock.is_paused());
+        clock.resume();
+        assert!(matches!(clock.state(), ClockState::Running(_)));
+        Duration::from_millis(20).sleep();
+        assert!(clock.read() < paused);
+
+        clock.pause();
+        clock.stop();
+        assert_eq!(clock.state(), ClockState::Stopped);
+        assert!(clock.read() < paused);
+
+        // A countdown that runs out is stopped, not paused
+        let mut clock = Clock::new(
+            ClockMode::CountDown, Some(Duration::from_millis(10))
+        );
+        clock.start();
+        Duration::from_millis(20).sleep();
+        assert_eq!(clock.state_and_update(), ClockState::Stopped);
+    }
+
+    #[test]
+    /// Test that a clock on a manual time only moves when the time does
+    fn test_clock_manual_time() {
+        let time = ManualTime::new();
+        let mut clock = Clock::with_source(
+            ClockMode::CountDown, Some(Duration::from_secs(5)), time.clone()
+        );
+        clock.start();
+        assert_eq!(clock.read(), Duration::from_secs(5));
+
+        time.advance(Duration::from_millis(1_500));
+        assert_eq!(clock.read(), Duration::from_millis(3_500));
+        clock.pause();
+        time.advance(Duration::from_secs(60));
+        assert_eq!(clock.to_string(), "00:04");
+
+        clock.resume();
+        time.advance(Duration::from_secs(4));
+        assert_eq!(clock.state_and_update(), ClockState::Stopped);
+        assert_eq!(clock.read(), Duration::ZERO);
+    }
+
+    #[test]
+    /// Test that callbacks hear every change to the clock, in order
+    fn test_clock_on_event() {
+        let time = ManualTime::new();
+        let mut clock = Clock::with_source(
+            ClockMode::CountDown, Some(Duration::from_secs(5)), time.clone()
+        );
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        clock.on_event(move |change| log.lock().unwrap().push(change));
+
+        clock.start();
+        clock.start();
+        clock.pause();
+        clock.resume();
+        time.advance(Duration::from_secs(5));
+        clock.read();
+        assert_eq!(seen.lock().unwrap().len(), 3);
+        clock.state_and_update();
+        clock.reset(Some(Duration::from_secs(5)));
+        clock.start();
+        clock.subtract(Duration::from_secs(10));
+        clock.finish();
+        clock.finish();
+
+        assert_eq!(*seen.lock().unwrap(), [
+            ClockChange::Started,
+            ClockChange::Paused,
+            ClockChange::Resumed,
+            ClockChange::ReachedZero,
+            ClockChange::Reset,
+            ClockChange::Started,
+            ClockChange::ReachedZero,
+            ClockChange::Finished,
+        ]);
+    }
+
+    #[test]
+    /// Test that alarms fire once as the clock passes them, either way
+    fn test_clock_alarms() {
+        let time = ManualTime::new();
+        let mut clock = Clock::with_source(
+            ClockMode::CountDown, Some(Duration::from_secs(60)), time.clone()
+        );
+        clock.add_alarm(Duration::from_secs(30), "low");
+        clock.add_alarm(Duration::from_secs(10), "critical");
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        clock.on_event(move |change| {
+            if let ClockChange::Alarm(tag) = change {
+                log.lock().unwrap().push(tag);
+            }
+        });
+
+        clock.start();
+        time.advance(Duration::from_secs(29));
+        clock.read_and_update();
+        assert!(seen.lock().unwrap().is_empty());
+        time.advance(Duration::from_secs(1));
+        clock.read_and_update();
+        clock.read_and_update();
+        assert_eq!(*seen.lock().unwrap(), ["low"]);
+        time.advance(Duration::from_secs(60));
+        clock.read_and_update();
+        assert_eq!(*seen.lock().unwrap(), ["low", "critical"]);
+
+        // A reset sets the alarms to fire again
+        clock.reset(Some(Duration::from_secs(20)));
+        clock.read_and_update();
+        assert_eq!(*seen.lock().unwrap(), ["low", "critical", "low"]);
+
+        let mut stopwatch = Clock::with_source(
+            ClockMode::CountUp, None, time.clone()
+        );
+        stopwatch.add_alarm(Duration::from_secs(5), "up");
+        let log = Arc::clone(&seen);
+        stopwatch.on_event(move |change| {
+            if let ClockChange::Alarm(tag) = change {
+                log.lock().unwrap().push(tag);
+            }
+        });
+        stopwatch.start();
+        time.advance(Duration::from_secs(5));
+        stopwatch.state_and_update();
+        assert_eq!(seen.lock().unwrap().last(), Some(&"up"));
+    }
+
+    #[test]
+    /// Test that an overtime countdown counts on past zero
+    fn test_clock_overtime() {
+        let time = ManualTime::new();
+        let mut clock = Clock::with_source(
+            ClockMode::CountDownOvertime, Some(Duration::from_secs(5)),
+            time.clone()
+        );
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        clock.on_event(move |change| log.lock().unwrap().push(change));
+        clock.start();
+        time.advance(Duration::from_secs(3));
+        assert_eq!(clock.read_and_update(), Duration::from_secs(2));
+        assert!(!clock.is_overtime());
+
+        time.advance(Duration::from_secs(4));
+        assert_eq!(clock.read_and_update(), Duration::from_secs(2));
+        assert!(clock.is_overtime());
+        assert!(matches!(clock.state(), ClockState::Running(_)));
+        assert_eq!(clock.to_string(), "+00:02");
+        time.advance(Duration::from_secs(10));
+        clock.read_and_update();
+        assert_eq!(clock.read(), Duration::from_secs(12));
+        assert_eq!(seen.lock().unwrap().iter()
+            .filter(|change| **change == ClockChange::ReachedZero)
+            .count(), 1);
+
+        // Time moves the clock across zero either way, and stopping keeps
+        // the overtime
+        clock.add(Duration::from_secs(20));
+        assert!(!clock.is_overtime());
+        assert_eq!(clock.read(), Duration::from_secs(8));
+        clock.subtract(Duration::from_secs(9));
+        assert!(clock.is_overtime());
+        assert_eq!(clock.read(), Duration::from_secs(1));
+        time.advance(Duration::from_secs(1));
+        clock.stop();
+        time.advance(Duration::from_secs(5));
+        assert_eq!(clock.to_string(), "+00:02");
+
+        clock.reset(Some(Duration::from_secs(5)));
+        assert!(!clock.is_overtime());
+        assert_eq!(clock.to_string(), "00:05");
+    }
+
+    #[test]
+    fn test_clocks_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Clock>();
+        assert_send_sync::<Clock<ManualTime>>();
+        assert_send_sync::<crate::ChessClock>();
+    }
+
+    #[test]
+    /// Test that laps are the time run between them, in either direction
+    fn test_clock_laps() {
+        let time = ManualTime::new();
+        let mut stopwatch = Clock::with_source(
+            ClockMode::CountUp, None, time.clone()
+        );
+        stopwatch.start();
+        time.advance(Duration::from_secs(12));
+        assert_eq!(stopwatch.lap(), Duration::from_secs(12));
+        time.advance(Duration::from_secs(3));
+        stopwatch.pause();
+        time.advance(Duration::from_secs(60));
+        stopwatch.resume();
+        time.advance(Duration::from_secs(5));
+        assert_eq!(stopwatch.lap(), Duration::from_secs(8));
+        assert_eq!(
+            stopwatch.laps(), [Duration::from_secs(12), Duration::from_secs(8)]
+        );
+        stopwatch.reset(None);
+        assert!(stopwatch.laps().is_empty());
+
+        let mut countdown = Clock::with_source(
+            ClockMode::CountDownOvertime, Some(Duration::from_secs(10)),
+            time.clone()
+        );
+        countdown.start();
+        time.advance(Duration::from_secs(4));
+        assert_eq!(countdown.lap(), Duration::from_secs(4));
+        time.advance(Duration::from_secs(9));
+        assert_eq!(countdown.lap(), Duration::from_secs(9));
+        assert!(countdown.is_overtime());
+    }
+
     #[test]
     fn test_clock_add_subtract() {
         let mut clock = Clock::new(ClockMode::CountUp, None);
@@ -308,4 +1068,49 @@ mod tests {
         assert_eq!(clock.to_string(), "00:00");
         assert_eq!(clock.state(), ClockState::Stopped);
     }
+
+    #[test]
+    fn test_clock_operators() {
+        let mut clock = Clock::new(ClockMode::CountDown, Some(TEN_MINUTES));
+        clock += Duration::from_secs(30);
+        assert_eq!(clock.to_string(), "10:30");
+        clock -= Duration::from_secs(45);
+        assert_eq!(clock.to_string(), "09:45");
+
+        clock -= TEN_MINUTES;
+        assert_eq!(clock.read(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_clock_frames() {
+        let mut clock = Clock::new(
+            ClockMode::CountDown, Some(Duration::from_millis(2_500))
+        );
+        clock.start();
+        let start = Instant::now();
+        let frames: Vec<String> = clock.frames(Duration::from_secs(1))
+            .collect();
+        // The last half second shows as 00:00 before it runs out
+        assert_eq!(frames, ["00:02", "00:01", "00:00", "00:00"]);
+        assert!(start.elapsed() >= Duration::from_secs(3));
+
+        let stopwatch = Clock::default();
+        let mut frames = stopwatch.frames(Duration::from_millis(1)).take(5);
+        assert!(frames.all(|frame| frame == "00:00"));
+    }
+
+    #[test]
+    fn test_clock_frames_wait_on_source() {
+        let time = ManualTime::new();
+        let mut clock = Clock::with_source(
+            ClockMode::CountDown, Some(Duration::from_millis(2_500)),
+            time.clone()
+        );
+        clock.start();
+        let frames: Vec<String> = clock.frames(Duration::from_secs(1))
+            .collect();
+        // No real time passes, so each frame reads a whole interval apart
+        assert_eq!(frames, ["00:03", "00:02", "00:01", "00:00"]);
+        assert_eq!(time.elapsed(), Duration::from_secs(3));
+    }
 }
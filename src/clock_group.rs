@@ -0,0 +1,157 @@
+//! # ClockGroup
+//! A group of labelled clocks that are started and stopped together, each with
+//! its own alarms, such as the section countdowns of an exam.
+
+use std::time::Duration;
+use crate::{Clock, ClockMode};
+
+/// An alarm which fires once its clock reads at or below a threshold
+#[derive(Debug)]
+struct Alarm {
+    threshold: Duration,
+    fired: bool,
+}
+
+#[derive(Debug)]
+struct Entry {
+    label: String,
+    clock: Clock,
+    alarms: Vec<Alarm>,
+}
+
+/// A group of labelled clocks
+#[derive(Debug, Default)]
+pub struct ClockGroup {
+    entries: Vec<Entry>,
+}
+
+impl ClockGroup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a clock to the group, returning its index
+    pub fn add(&mut self, label: &str, clock: Clock) -> usize {
+        self.entries.push(Entry {
+            label: label.to_string(),
+            clock,
+            alarms: Vec::new(),
+        });
+        self.entries.len() - 1
+    }
+
+    /// Adds a stopped countdown of `duration` to the group, returning its
+    /// index
+    pub fn add_countdown(&mut self, label: &str, duration: Duration) -> usize {
+        self.add(label, Clock::new(ClockMode::CountDown, Some(duration)))
+    }
+
+    /// Adds an alarm to the clock at `index`, firing once the clock reads
+    /// `threshold` or less
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no clock at `index`.
+    pub fn add_alarm(&mut self, index: usize, threshold: Duration) {
+        self.entries[index].alarms.push(Alarm { threshold, fired: false });
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn label(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(|entry| entry.label.as_str())
+    }
+
+    pub fn clock(&self, index: usize) -> Option<&Clock> {
+        self.entries.get(index).map(|entry| &entry.clock)
+    }
+
+    /// Iterates over the label and clock of every entry in the group
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Clock)> {
+        self.entries.iter()
+            .map(|entry| (entry.label.as_str(), &entry.clock))
+    }
+
+    pub fn start_all(&mut self) {
+        self.entries.iter_mut().for_each(|entry| entry.clock.start());
+    }
+
+    pub fn stop_all(&mut self) {
+        self.entries.iter_mut().for_each(|entry| entry.clock.stop());
+    }
+
+    /// Returns whether every clock in the group has counted down to zero
+    pub fn all_expired(&self) -> bool {
+        self.entries.iter().all(|entry| entry.clock.read() == Duration::ZERO)
+    }
+
+    /// Updates every clock and fires any alarms that have been crossed
+    ///
+    /// # Returns
+    ///
+    /// * The index of the clock and the threshold of each alarm that fired
+    ///   during this update
+    pub fn update(&mut self) -> Vec<(usize, Duration)> {
+        let mut fired = Vec::new();
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            let time = entry.clock.read_and_update();
+            for alarm in entry.alarms.iter_mut() {
+                if !alarm.fired && time <= alarm.threshold {
+                    alarm.fired = true;
+                    fired.push((index, alarm.threshold));
+                }
+            }
+        }
+        fired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClockState, Sleep};
+
+    #[test]
+    fn test_clock_group_start_stop() {
+        let mut group = ClockGroup::new();
+        let a = group.add_countdown("Section A", Duration::from_secs(60));
+        let b = group.add_countdown("Extra time", Duration::from_secs(90));
+        assert_eq!(group.len(), 2);
+        assert_eq!(group.label(b), Some("Extra time"));
+
+        group.start_all();
+        assert!(matches!(group.clock(a).unwrap().state(), ClockState::Running(_)));
+        assert!(matches!(group.clock(b).unwrap().state(), ClockState::Running(_)));
+
+        group.stop_all();
+        let labels: Vec<&str> = group.iter().map(|(label, _)| label).collect();
+        assert_eq!(labels, ["Section A", "Extra time"]);
+        assert!(group.iter().all(|(_, clock)| clock.state() == ClockState::Stopped));
+    }
+
+    #[test]
+    fn test_clock_group_alarms_fire_once() {
+        let mut group = ClockGroup::new();
+        let a = group.add_countdown("A", Duration::from_millis(200));
+        let b = group.add_countdown("B", Duration::from_millis(400));
+        group.add_alarm(a, Duration::from_millis(100));
+        group.add_alarm(b, Duration::from_millis(100));
+
+        group.start_all();
+        assert!(group.update().is_empty());
+
+        Duration::from_millis(150).sleep();
+        assert_eq!(group.update(), [(a, Duration::from_millis(100))]);
+        assert!(group.update().is_empty());
+
+        Duration::from_millis(300).sleep();
+        assert_eq!(group.update(), [(b, Duration::from_millis(100))]);
+        assert!(group.all_expired());
+    }
+}
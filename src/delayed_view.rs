@@ -0,0 +1,117 @@
+//! # Delayed view
+//! A chess clock as it was a fixed time ago, for overlays on a broadcast
+//! that is shown on a delay.
+//!
+//! Relayed games are often streamed a few minutes behind the board, so that
+//! viewers can not pass moves to the players. An overlay showing the live
+//! clock would give the moves away, so the delayed view is worked out only
+//! from the events up to the delayed instant: nothing that happened since
+//! can show through.
+
+use std::time::{Duration, Instant};
+use crate::{
+    compute_state, ChessClock, ClockSnapshot, EventRecord, Status, ViewModel
+};
+
+/// A chess clock as it was `delay` ago
+#[derive(Debug, Clone, Copy)]
+pub struct DelayedView<'a> {
+    clock: &'a ChessClock,
+    delay: Duration,
+}
+
+impl<'a> DelayedView<'a> {
+    /// Create a view of `clock` as it was `delay` ago
+    pub fn new(clock: &'a ChessClock, delay: Duration) -> Self {
+        Self { clock, delay }
+    }
+
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Get the instant shown at `at`, `delay` before it
+    fn shown_at(&self, at: Instant) -> Option<Instant> {
+        at.checked_sub(self.delay)
+    }
+
+    /// Get the events that had happened by `delay` ago, oldest first
+    pub fn events(&self) -> &'a [EventRecord] {
+        self.events_at(self.clock.now())
+    }
+
+    fn events_at(&self, at: Instant) -> &'a [EventRecord] {
+        let events = self.clock.events();
+        let seen = self.shown_at(at).map_or(0, |shown| {
+            events.partition_point(|record| record.instant() <= shown)
+        });
+        &events[..seen]
+    }
+
+    /// Get what the clock showed `delay` ago
+    pub fn snapshot(&self) -> ClockSnapshot {
+        self.snapshot_at(self.clock.now())
+    }
+
+    /// Get what the clock showed `delay` before `at`
+    pub fn snapshot_at(&self, at: Instant) -> ClockSnapshot {
+        let shown = self.shown_at(at).unwrap_or(at);
+        compute_state(self.clock.rules(), self.events_at(at), shown)
+    }
+
+    /// Get everything needed to draw the clock as it was `delay` ago
+    pub fn view_model(&self) -> ViewModel {
+        self.view_model_at(self.clock.now())
+    }
+
+    /// Get everything needed to draw the clock as it was `delay` before `at`
+    pub fn view_model_at(&self, at: Instant) -> ViewModel {
+        let shown = self.shown_at(at).unwrap_or(at);
+        ChessClock::replay(self.clock.rules().clone(), self.events_at(at))
+            .view_model_at(shown)
+    }
+
+    /// Get whether the view will still change if nothing more happens to
+    /// the clock, such as while the last events have yet to be shown
+    pub fn is_catching_up(&self) -> bool {
+        let at = self.clock.now();
+        self.events_at(at).len() < self.clock.events().len()
+            || self.snapshot_at(at).status == Status::Running
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{times, Player, Sleep};
+
+    #[test]
+    fn test_delayed_view_hides_live_moves() {
+        let mut clock = ChessClock::default();
+        clock.start();
+        Duration::from_millis(30).sleep();
+        let before_press = Instant::now();
+        Duration::from_millis(30).sleep();
+        clock.switch_player();
+        clock.stop();
+
+        let delay = Instant::now().saturating_duration_since(before_press);
+        let view = DelayedView::new(&clock, delay);
+        let at = before_press + delay;
+        let snapshot = view.snapshot_at(at);
+        assert_eq!(snapshot, clock.state_at(before_press));
+        assert_eq!(snapshot.active_player, Player::Player1);
+        assert_eq!(snapshot.status, Status::Running);
+        assert_eq!(view.events_at(at).len(), 1);
+
+        let model = view.view_model_at(at);
+        assert_eq!(model.active().player, Player::Player1);
+        assert_eq!(model.player(Player::Player1).moves, 0);
+        assert_eq!(model.player(Player::Player1).time, snapshot.times.0);
+
+        // Long enough after, the view has caught up with the clock
+        let later = Instant::now() + times::ONE_MINUTE;
+        assert_eq!(view.snapshot_at(later + delay), clock.snapshot(later));
+        assert_eq!(view.snapshot_at(later + delay).status, Status::Stopped);
+    }
+}
@@ -0,0 +1,308 @@
+//! # Discord
+//! Publishes the game on a chess clock as the Discord Rich Presence of the
+//! user running it, so that friends and stream viewers can see what is being
+//! played.
+//!
+//! Discord is reached over its local IPC socket (a named pipe on Windows),
+//! which carries frames of an opcode and a JSON payload:
+//!
+//! ```text
+//! <opcode u32 LE> <payload length u32 LE> <JSON payload>
+//! ```
+//!
+//! Discord only accepts a presence update every fifteen seconds or so, so the
+//! presence is only published when the event log of the clock has grown, and
+//! at most once per interval. The running clock counts down inside Discord
+//! from the time it was published.
+
+use std::{
+    io::{self, Read, Write},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use crate::{ChessClock, DurationDisplay, Player, Status, TimingMethod};
+
+/// The shortest interval between presence updates that Discord accepts
+pub const MIN_INTERVAL: Duration = Duration::from_secs(15);
+
+const OP_HANDSHAKE: u32 = 0;
+const OP_FRAME: u32 = 1;
+const OP_CLOSE: u32 = 2;
+
+/// The number of IPC sockets Discord may be listening on
+const PIPES: u32 = 10;
+
+trait Pipe: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Pipe for T {}
+
+/// A connection to the local Discord client that publishes a chess clock as
+/// the user's Rich Presence
+pub struct DiscordPresence {
+    pipe: Box<dyn Pipe>,
+    interval: Duration,
+    last_update: Option<Instant>,
+    events_seen: usize,
+    nonce: u64,
+}
+
+impl std::fmt::Debug for DiscordPresence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiscordPresence")
+            .field("interval", &self.interval)
+            .field("last_update", &self.last_update)
+            .field("events_seen", &self.events_seen)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DiscordPresence {
+    /// Connects to the running Discord client as the Discord application
+    /// `client_id`
+    ///
+    /// The presence is published at most once per `interval`, which is
+    /// raised to [`MIN_INTERVAL`] if shorter.
+    pub fn connect(client_id: &str, interval: Duration) -> io::Result<Self> {
+        let pipe = (0..PIPES)
+            .find_map(|i| open_pipe(i).ok())
+            .ok_or_else(|| io::Error::new(
+                io::ErrorKind::NotFound, "Discord is not running"
+            ))?;
+
+        let mut presence = Self {
+            pipe,
+            interval: interval.max(MIN_INTERVAL),
+            last_update: None,
+            events_seen: 0,
+            nonce: 0,
+        };
+        presence.send(OP_HANDSHAKE, &format!(
+            r#"{{"v":1,"client_id":"{}"}}"#, escape(client_id)
+        ))?;
+        Ok(presence)
+    }
+
+    /// Publishes the state of the clock if it has recorded events since the
+    /// last update and the interval has passed
+    ///
+    /// Returns whether the presence was published.
+    pub fn update(&mut self, clock: &ChessClock) -> io::Result<bool> {
+        let changed = clock.events().len() != self.events_seen;
+        let due = self.last_update
+            .is_none_or(|last| last.elapsed() >= self.interval);
+        if changed && due {
+            self.update_now(clock)?;
+        }
+        Ok(changed && due)
+    }
+
+    /// Publishes the state of the clock immediately
+    pub fn update_now(&mut self, clock: &ChessClock) -> io::Result<()> {
+        let activity = activity(clock, SystemTime::now());
+        self.set_activity(&activity)?;
+        self.events_seen = clock.events().len();
+        self.last_update = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Removes the presence, for when the game is left
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.set_activity("null")
+    }
+
+    fn set_activity(&mut self, activity: &str) -> io::Result<()> {
+        self.nonce += 1;
+        let payload = format!(
+            concat!(
+                r#"{{"cmd":"SET_ACTIVITY","#,
+                r#""args":{{"pid":{},"activity":{}}},"nonce":"{}"}}"#
+            ),
+            std::process::id(), activity, self.nonce
+        );
+        self.send(OP_FRAME, &payload)
+    }
+
+    /// Sends a frame and reads Discord's reply to it
+    fn send(&mut self, opcode: u32, payload: &str) -> io::Result<()> {
+        self.pipe.write_all(&frame(opcode, payload))?;
+
+        let mut header = [0; 8];
+        self.pipe.read_exact(&mut header)?;
+        let [o0, o1, o2, o3, l0, l1, l2, l3] = header;
+        let reply_opcode = u32::from_le_bytes([o0, o1, o2, o3]);
+        let length = u32::from_le_bytes([l0, l1, l2, l3]);
+        let mut reply = vec![0; length as usize];
+        self.pipe.read_exact(&mut reply)?;
+
+        if reply_opcode == OP_CLOSE {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                String::from_utf8_lossy(&reply).into_owned()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn open_pipe(index: u32) -> io::Result<Box<dyn Pipe>> {
+    use std::{env, os::unix::net::UnixStream, path::PathBuf};
+
+    let dir = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"].into_iter()
+        .find_map(env::var_os)
+        .map_or_else(|| PathBuf::from("/tmp"), PathBuf::from);
+    let path = dir.join(format!("discord-ipc-{}", index));
+    Ok(Box::new(UnixStream::connect(path)?))
+}
+
+#[cfg(windows)]
+fn open_pipe(index: u32) -> io::Result<Box<dyn Pipe>> {
+    let pipe = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(format!(r"\\?\pipe\discord-ipc-{}", index))?;
+    Ok(Box::new(pipe))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn open_pipe(_index: u32) -> io::Result<Box<dyn Pipe>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported, "Discord IPC is not available here"
+    ))
+}
+
+fn frame(opcode: u32, payload: &str) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(8 + payload.len());
+    frame.extend_from_slice(&opcode.to_le_bytes());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(payload.as_bytes());
+    frame
+}
+
+/// Describes the time control of the clock, e.g. `05:00 + 3s Fischer`
+fn time_control(clock: &ChessClock) -> String {
+    let rules = clock.rules();
+    let (t1, t2) = (
+        rules.get_time(Player::Player1), rules.get_time(Player::Player2)
+    );
+    let times = match t1 == t2 {
+        true => DurationDisplay::from(t1).to_string(),
+        false => format!(
+            "{} v {}", DurationDisplay::from(t1), DurationDisplay::from(t2)
+        ),
+    };
+    let increment = rules.get_increment();
+    match (increment.is_zero(), rules.get_timing_method()) {
+        (true, TimingMethod::Fischer) => times,
+        (_, method) => format!(
+            "{} + {}s {}", times, increment.as_secs(), method
+        ),
+    }
+}
+
+/// Builds the Rich Presence activity for the clock as JSON
+///
+/// While the clock runs, the activity carries the time at which the active
+/// player would run out, so that Discord counts their clock down between
+/// updates.
+fn activity(clock: &ChessClock, now: SystemTime) -> String {
+    let details = format!(
+        "{}, move {}", time_control(clock), clock.move_number()
+    );
+    let (t1, t2) = clock.read();
+    let times = format!(
+        "{} {} | {} {}",
+        Player::Player1, DurationDisplay::from(t1),
+        Player::Player2, DurationDisplay::from(t2)
+    );
+    let state = match clock.status() {
+        Status::Running => times,
+        Status::Stopped => format!("Paused, {}", times),
+        Status::Finished => format!("Finished, {}", times),
+    };
+
+    let mut activity = format!(
+        r#"{{"details":"{}","state":"{}""#, escape(&details), escape(&state)
+    );
+    if clock.status() == Status::Running {
+        let end = (now + clock.active_clock_remaining()).duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        activity.push_str(&format!(
+            r#","timestamps":{{"end":{}}}"#, end.as_secs()
+        ));
+    }
+    activity.push('}');
+    activity
+}
+
+/// Escapes a string for use inside a JSON string literal
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => {
+                escaped.push_str(&format!("\\u{:04x}", c as u32))
+            },
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{times, Rules};
+
+    #[test]
+    fn test_frame() {
+        let frame = frame(OP_FRAME, "{}");
+        assert_eq!(frame, [1, 0, 0, 0, 2, 0, 0, 0, b'{', b'}']);
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape(r#"a "b" \c"#), r#"a \"b\" \\c"#);
+        assert_eq!(escape("a\nb"), "a\\u000ab");
+    }
+
+    #[test]
+    fn test_time_control() {
+        let clock = ChessClock::new(Rules::new(
+            times::FIVE_MINUTES, times::FIVE_MINUTES, Duration::ZERO,
+            Player::Player1, TimingMethod::Fischer
+        ));
+        assert_eq!(time_control(&clock), "05:00");
+
+        let clock = ChessClock::new(Rules::new(
+            times::FIVE_MINUTES, times::ONE_MINUTE, times::FIVE_SECONDS,
+            Player::Player1, TimingMethod::Bronstein
+        ));
+        assert_eq!(time_control(&clock), "05:00 v 01:00 + 5s Bronstein");
+    }
+
+    #[test]
+    fn test_activity_stopped() {
+        let clock = ChessClock::new(Rules::new(
+            times::FIVE_MINUTES, times::FIVE_MINUTES, Duration::ZERO,
+            Player::Player1, TimingMethod::Fischer
+        ));
+        assert_eq!(
+            activity(&clock, UNIX_EPOCH),
+            r#"{"details":"05:00, move 1","state":"Paused, Player 1 05:00 | Player 2 05:00"}"#
+        );
+    }
+
+    #[test]
+    fn test_activity_running_counts_down() {
+        let mut clock = ChessClock::new(Rules::new(
+            times::FIVE_MINUTES, times::FIVE_MINUTES, Duration::ZERO,
+            Player::Player1, TimingMethod::Fischer
+        ));
+        clock.start();
+        let activity = activity(&clock, UNIX_EPOCH);
+        assert!(activity.contains(r#""timestamps":{"end":29"#));
+        assert!(!activity.contains("Paused"));
+    }
+}
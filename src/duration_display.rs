@@ -2,7 +2,30 @@
 //! Wrapper class for Duration to implement Display
 
 use std::{fmt::Display, ops::Deref, time::Duration};
-use crate::utils::round;
+use crate::{
+    times, TimeParts, utils::{round_duration, Resolution, RoundingMode}
+};
+
+/// Remaining times below this are shown in hundredths, for time scrambles
+pub const SCRAMBLE_THRESHOLD: Duration = times::TEN_SECONDS;
+
+/// Get the resolution to show a player's remaining time at
+///
+/// Both front ends use this, so that a clock switches to hundredths at the
+/// same moment in each, and only for the player who is short of time.
+///
+/// # Arguments
+/// * `remaining` - The time the player has left.
+///
+/// # Returns
+/// Hundredths below `SCRAMBLE_THRESHOLD`, and whole seconds otherwise.
+pub fn display_precision_for(remaining: Duration) -> Resolution {
+    if remaining < SCRAMBLE_THRESHOLD {
+        Resolution::Hundredth
+    } else {
+        Resolution::Second
+    }
+}
 
 /// Wrapper class for Duration to implement Display, automatically convert
 #[derive(Debug, PartialEq)]
@@ -14,6 +37,45 @@ impl From<Duration> for DurationDisplay {
     }
 }
 
+impl DurationDisplay {
+    /// Split the duration into the fields shown on a clock face.
+    pub fn parts(&self) -> TimeParts {
+        TimeParts::from(self.0)
+    }
+
+    /// Format the duration rounded to `resolution`, as `{}` does for whole
+    /// seconds and `{:#}` for hundredths
+    pub fn to_string_at(&self, resolution: Resolution) -> String {
+        let mut out = String::new();
+        self.write_at(&mut out, resolution)
+            .expect("writing to a string does not fail");
+        out
+    }
+
+    fn write_at(
+        &self, f: &mut impl std::fmt::Write, resolution: Resolution
+    ) -> std::fmt::Result {
+        // Round first so that any carry into the seconds, minutes or hours
+        // happens before the fields are split out.
+        let time = round_duration(self.0, resolution, RoundingMode::Nearest);
+        let total_secs = time.as_secs();
+        let (hours, mins, secs) = (
+            total_secs / 3600, total_secs / 60 % 60, total_secs % 60
+        );
+        if hours > 0 {
+            write!(f, "{:02}:{:02}:{:02}", hours, mins, secs)?;
+        } else {
+            write!(f, "{:02}:{:02}", mins, secs)?;
+        }
+        let places = resolution.places();
+        if places > 0 {
+            let fraction = time.subsec_nanos() / 10u32.pow(9 - places as u32);
+            write!(f, ".{:01$}", fraction, places)?;
+        }
+        Ok(())
+    }
+}
+
 impl Deref for DurationDisplay {
     type Target = Duration;
 
@@ -24,28 +86,12 @@ impl Deref for DurationDisplay {
 
 impl Display for DurationDisplay {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let secs = self.0.as_secs();
-        let millis = self.0.subsec_millis();
-        let secs = secs as f64 + millis as f64 / 1000.0;
-        let (precision, width) = if f.alternate() {
-            (2, 5)
+        let resolution = if f.alternate() {
+            Resolution::Hundredth
         } else {
-            (0, 2)
+            Resolution::Second
         };
-        let time = round(secs, precision);
-        let mins = time as u32 / 60;
-        let secs = time % 60.0;
-        if mins >= 60 {
-            let hours = mins / 60;
-            let mins = mins % 60;
-            return write!(
-                f, "{:02}:{:02}:{:03$.4$}",
-                hours, mins, secs, width, precision as usize
-            );
-        }
-        write!(
-            f, "{:02}:{:02$.3$}", mins, secs, width, precision as usize
-        )
+        self.write_at(f, resolution)
     }
 }
 
@@ -111,6 +157,103 @@ mod tests {
         assert_eq!(format!("{:#}", display), "10:00.10");
     }
 
+    #[test]
+    fn test_display_59_994() {
+        let display = DurationDisplay::from(Duration::from_millis(59_994));
+        assert_eq!(display.to_string(), "01:00");
+        assert_eq!(format!("{:#}", display), "00:59.99");
+    }
+
+    #[test]
+    fn test_display_59_995() {
+        let display = DurationDisplay::from(Duration::from_millis(59_995));
+        assert_eq!(display.to_string(), "01:00");
+        assert_eq!(format!("{:#}", display), "01:00.00");
+    }
+
+    #[test]
+    fn test_display_59_499() {
+        let display = DurationDisplay::from(Duration::from_millis(59_499));
+        assert_eq!(display.to_string(), "00:59");
+        assert_eq!(format!("{:#}", display), "00:59.50");
+    }
+
+    #[test]
+    fn test_display_3599_999() {
+        let display = DurationDisplay::from(Duration::from_millis(3_599_999));
+        assert_eq!(display.to_string(), "01:00:00");
+        assert_eq!(format!("{:#}", display), "01:00:00.00");
+    }
+
+    #[test]
+    fn test_display_3599_994() {
+        let display = DurationDisplay::from(Duration::from_millis(3_599_994));
+        assert_eq!(display.to_string(), "01:00:00");
+        assert_eq!(format!("{:#}", display), "59:59.99");
+    }
+
+    #[test]
+    fn test_display_every_hundredth_carry() {
+        // Every value that rounds up to a new minute must carry cleanly.
+        for mins in 0..120u64 {
+            let time = Duration::from_millis(mins * 60_000 + 59_995);
+            let display = DurationDisplay::from(time);
+            let (h, m) = ((mins + 1) / 60, (mins + 1) % 60);
+            let expected = if h > 0 {
+                format!("{:02}:{:02}:00.00", h, m)
+            } else {
+                format!("{:02}:00.00", m)
+            };
+            assert_eq!(format!("{:#}", display), expected);
+            assert!(!display.to_string().contains(":60"));
+        }
+    }
+
+    #[test]
+    fn test_display_large_hours() {
+        let time = Duration::from_secs(1_000_000 * 3600 + 59)
+            + Duration::from_millis(990);
+        let display = DurationDisplay::from(time);
+        assert_eq!(display.to_string(), "1000000:01:00");
+        assert_eq!(format!("{:#}", display), "1000000:00:59.99");
+        assert_eq!(
+            DurationDisplay::from(Duration::from_secs(u64::MAX)).to_string(),
+            format!("{:02}:{:02}:{:02}", u64::MAX / 3600, u64::MAX / 60 % 60, u64::MAX % 60)
+        );
+    }
+
+    #[test]
+    fn test_display_precision_for() {
+        let below = Duration::from_millis(9_999);
+        assert_eq!(display_precision_for(below), Resolution::Hundredth);
+        assert_eq!(
+            display_precision_for(SCRAMBLE_THRESHOLD), Resolution::Second
+        );
+
+        let display = DurationDisplay::from(below);
+        assert_eq!(
+            display.to_string_at(display_precision_for(below)), "00:10.00"
+        );
+        assert_eq!(
+            display.to_string_at(Resolution::Hundredth),
+            format!("{:#}", display)
+        );
+        assert_eq!(display.to_string_at(Resolution::Tenth), "00:10.0");
+        assert_eq!(
+            DurationDisplay::from(Duration::from_millis(4_321))
+                .to_string_at(Resolution::Hundredth),
+            "00:04.32"
+        );
+    }
+
+    #[test]
+    fn test_parts() {
+        let display = DurationDisplay::from(Duration::from_millis(65_250));
+        assert_eq!(display.parts(), TimeParts {
+            hours: 0, minutes: 1, seconds: 5, millis: 250
+        });
+    }
+
     #[test]
     fn test_display_3600() {
         let duration = Duration::from_secs(3600);
@@ -0,0 +1,428 @@
+//! # Encoding
+//! Plain text encodings of rules and clock state shared by the on-disk
+//! formats, one space separated line per record.
+
+// Each of the features using the encodings needs only some of them
+#![cfg_attr(
+    not(all(feature = "persistence", feature = "network")),
+    allow(dead_code)
+)]
+
+use std::{io, time::{Duration, Instant, UNIX_EPOCH}};
+use crate::{
+    millis::Millis, BackgroundTime, ChessClock, ClockEvent, EventRecord,
+    DefaultTime, IncrementStage, Player, Rules, SessionBudget, Status,
+    StoppedSwitch, TimingMethod
+};
+
+pub(crate) fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+pub(crate) fn format_rules(rules: &Rules) -> String {
+    format!(
+        "rules {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
+        format_millis(rules.get_player1_time()),
+        format_millis(rules.get_player2_time()),
+        format_millis(rules.get_increment()),
+        format_player(rules.get_starter()),
+        format_timing_method(rules.get_timing_method()),
+        rules.get_extensions(),
+        if rules.get_require_reasons() { 1 } else { 0 },
+        rules.get_idle_timeout()
+            .map_or("-".to_string(), |timeout| format_millis(timeout).to_string()),
+        format_stopped_switch(rules.get_stopped_switch()),
+        format_background_time(rules.get_background_time()),
+        rules.get_session_budget()
+            .map_or("-".to_string(), |budget| {
+                format_millis(budget.get_time()).to_string()
+            }),
+        rules.get_session_budget()
+            .map_or(0, |budget| u8::from(budget.get_adjudicate())),
+        rules.get_default_time()
+            .map_or("-".to_string(), |default| {
+                format_millis(default.get_time()).to_string()
+            }),
+        rules.get_default_time()
+            .map_or(0, |default| u8::from(default.get_finish())),
+        format_increment_stages(rules.get_increment_stages()),
+        u8::from(rules.get_strict()),
+        rules.get_pause_allowance()
+            .map_or("-".to_string(), |allowance| format_millis(allowance).to_string())
+    )
+}
+
+/// Formats increment stages as `<move>:<increment ms>` joined by commas, or
+/// `-` for none
+fn format_increment_stages(stages: &[IncrementStage]) -> String {
+    if stages.is_empty() {
+        return "-".to_string();
+    }
+    stages.iter()
+        .map(|stage| format!(
+            "{}:{}", stage.get_from_move(), format_millis(stage.get_increment())
+        ))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn parse_increment_stages(field: &str) -> io::Result<Vec<IncrementStage>> {
+    if field == "-" {
+        return Ok(Vec::new());
+    }
+    field.split(',')
+        .map(|stage| {
+            let (from_move, increment) = stage.split_once(':')
+                .ok_or_else(|| invalid("malformed increment stage"))?;
+            Ok(IncrementStage::new(
+                from_move.parse()
+                    .map_err(|_| invalid("malformed increment stage"))?,
+                parse_millis(increment)?
+            ))
+        })
+        .collect()
+}
+
+pub(crate) fn parse_rules(line: &str) -> io::Result<Rules> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    match fields.as_slice() {
+        ["rules", p1, p2, increment, starter, method, extensions, reasons, rest @ ..] => {
+            let mut rules = Rules::new(
+                parse_millis(p1)?,
+                parse_millis(p2)?,
+                parse_millis(increment)?,
+                parse_player(starter)?,
+                parse_timing_method(method)?
+            );
+            rules.set_extensions(
+                extensions.parse().map_err(|_| invalid("malformed extensions"))?
+            );
+            rules.set_require_reasons(parse_flag(reasons)?);
+            // Fields added after the first version are optional, so that
+            // older files still load with the defaults
+            if rest.len() > 10 {
+                return Err(invalid("malformed rules header"));
+            }
+            match rest.first() {
+                None | Some(&"-") => {},
+                Some(idle) => rules.set_idle_timeout(Some(parse_millis(idle)?)),
+            }
+            if let Some(switch) = rest.get(1) {
+                rules.set_stopped_switch(parse_stopped_switch(switch)?);
+            }
+            if let Some(background) = rest.get(2) {
+                rules.set_background_time(parse_background_time(background)?);
+            }
+            match rest.get(3) {
+                None | Some(&"-") => {},
+                Some(session) => {
+                    let adjudicate = match rest.get(4) {
+                        Some(flag) => parse_flag(flag)?,
+                        None => false,
+                    };
+                    rules.set_session_budget(Some(SessionBudget::new(
+                        parse_millis(session)?, adjudicate
+                    )));
+                },
+            }
+            match rest.get(5) {
+                None | Some(&"-") => {},
+                Some(default) => {
+                    let finish = match rest.get(6) {
+                        Some(flag) => parse_flag(flag)?,
+                        None => false,
+                    };
+                    rules.set_default_time(Some(DefaultTime::new(
+                        parse_millis(default)?, finish
+                    )));
+                },
+            }
+            if let Some(stages) = rest.get(7) {
+                rules.set_increment_stages(&parse_increment_stages(stages)?)
+                    .map_err(|error| invalid(&error.to_string()))?;
+            }
+            if let Some(strict) = rest.get(8) {
+                rules.set_strict(parse_flag(strict)?);
+            }
+            match rest.get(9) {
+                None | Some(&"-") => {},
+                Some(allowance) => {
+                    rules.set_pause_allowance(Some(parse_millis(allowance)?))
+                },
+            }
+            Ok(rules)
+        },
+        _ => Err(invalid("malformed rules header")),
+    }
+}
+
+/// Formats the remaining times, active player and status of a clock
+pub(crate) fn format_state(clock: &ChessClock) -> String {
+    format_state_fields(clock.read(), clock.active_player(), clock.status())
+}
+
+/// Formats remaining times, an active player and a status as `format_state`
+/// does, for a state that is not held in a clock
+pub(crate) fn format_state_fields(
+    (t1, t2): (Duration, Duration), active: Player, status: Status
+) -> String {
+    format!(
+        "{} {} {} {}",
+        format_millis(t1), format_millis(t2),
+        format_player(active), format_status(status)
+    )
+}
+
+pub(crate) fn parse_state(
+    fields: &str
+) -> io::Result<((Duration, Duration), Player, Status)> {
+    let fields: Vec<&str> = fields.split_whitespace().collect();
+    match fields.as_slice() {
+        [p1, p2, active, status] => Ok((
+            (parse_millis(p1)?, parse_millis(p2)?),
+            parse_player(active)?,
+            parse_status(status)?
+        )),
+        _ => Err(invalid("malformed state")),
+    }
+}
+
+pub(crate) fn parse_millis(field: &str) -> io::Result<Duration> {
+    Millis::parse(field)
+        .map(Duration::from)
+        .ok_or_else(|| invalid("malformed duration"))
+}
+
+/// Formats a duration as whole milliseconds, the unit of every encoded time
+pub(crate) fn format_millis(duration: Duration) -> Millis {
+    Millis::from(duration)
+}
+
+pub(crate) fn format_player(player: Player) -> &'static str {
+    match player {
+        Player::Player1 => "1",
+        Player::Player2 => "2",
+    }
+}
+
+pub(crate) fn parse_player(field: &str) -> io::Result<Player> {
+    match field {
+        "1" => Ok(Player::Player1),
+        "2" => Ok(Player::Player2),
+        _ => Err(invalid("malformed player")),
+    }
+}
+
+pub(crate) fn format_timing_method(method: TimingMethod) -> &'static str {
+    match method {
+        TimingMethod::Fischer => "fischer",
+        TimingMethod::Bronstein => "bronstein",
+        TimingMethod::ShotClock => "shot_clock",
+    }
+}
+
+pub(crate) fn parse_timing_method(field: &str) -> io::Result<TimingMethod> {
+    match field {
+        "fischer" => Ok(TimingMethod::Fischer),
+        "bronstein" => Ok(TimingMethod::Bronstein),
+        "shot_clock" => Ok(TimingMethod::ShotClock),
+        _ => Err(invalid("malformed timing method")),
+    }
+}
+
+pub(crate) fn format_stopped_switch(switch: StoppedSwitch) -> &'static str {
+    match switch {
+        StoppedSwitch::Ignore => "ignore",
+        StoppedSwitch::Toggle => "toggle",
+        StoppedSwitch::StartAndToggle => "start",
+    }
+}
+
+pub(crate) fn parse_stopped_switch(field: &str) -> io::Result<StoppedSwitch> {
+    match field {
+        "ignore" => Ok(StoppedSwitch::Ignore),
+        "toggle" => Ok(StoppedSwitch::Toggle),
+        "start" => Ok(StoppedSwitch::StartAndToggle),
+        _ => Err(invalid("malformed stopped switch")),
+    }
+}
+
+pub(crate) fn format_background_time(
+    background_time: BackgroundTime
+) -> &'static str {
+    match background_time {
+        BackgroundTime::Counts => "counts",
+        BackgroundTime::Paused => "paused",
+    }
+}
+
+pub(crate) fn parse_background_time(
+    field: &str
+) -> io::Result<BackgroundTime> {
+    match field {
+        "counts" => Ok(BackgroundTime::Counts),
+        "paused" => Ok(BackgroundTime::Paused),
+        _ => Err(invalid("malformed background time")),
+    }
+}
+
+pub(crate) fn format_status(status: Status) -> &'static str {
+    match status {
+        Status::Stopped => "stopped",
+        Status::Running => "running",
+        Status::Finished => "finished",
+    }
+}
+
+pub(crate) fn parse_status(field: &str) -> io::Result<Status> {
+    match field {
+        "stopped" => Ok(Status::Stopped),
+        "running" => Ok(Status::Running),
+        "finished" => Ok(Status::Finished),
+        _ => Err(invalid("malformed status")),
+    }
+}
+
+fn parse_flag(field: &str) -> io::Result<bool> {
+    match field {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        _ => Err(invalid("malformed flag")),
+    }
+}
+
+/// Formats an event record as its elapsed time, wall-clock time in Unix
+/// milliseconds and event, with any reason last so that it may contain spaces
+pub(crate) fn format_event(record: &EventRecord) -> String {
+    let unix_millis = format_millis(
+        record.timestamp().duration_since(UNIX_EPOCH).unwrap_or_default()
+    );
+    let event = match record.event() {
+        ClockEvent::Started(player) => format!("started {}", format_player(*player)),
+        ClockEvent::Stopped(player) => format!("stopped {}", format_player(*player)),
+        ClockEvent::Pressed(player) => format!("pressed {}", format_player(*player)),
+        ClockEvent::ActivePlayerChanged(player) => {
+            format!("active {}", format_player(*player))
+        }
+        ClockEvent::SwitchIgnored(player) => {
+            format!("ignored {}", format_player(*player))
+        }
+        ClockEvent::PressDebounced(player) => {
+            format!("debounced {}", format_player(*player))
+        }
+        ClockEvent::Extended(player) => format!("extended {}", format_player(*player)),
+        ClockEvent::Flagged(player) => format!("flagged {}", format_player(*player)),
+        ClockEvent::IdlePaused(player) => format!("idle {}", format_player(*player)),
+        ClockEvent::TimeAnomaly(player) => {
+            format!("anomaly {}", format_player(*player))
+        }
+        ClockEvent::Backgrounded(player) => {
+            format!("background {}", format_player(*player))
+        }
+        ClockEvent::Foregrounded { player, away } => {
+            format!("foreground {} {}", format_player(*player), format_millis(*away))
+        }
+        ClockEvent::TimeAdded { player, time, reason } => {
+            format_adjustment("added", *player, *time, reason)
+        }
+        ClockEvent::TimeSubtracted { player, time, reason } => {
+            format_adjustment("subtracted", *player, *time, reason)
+        }
+        ClockEvent::Penalty { offender, time, reason } => {
+            format_adjustment("penalty", *offender, *time, reason)
+        }
+        ClockEvent::AdjustmentRefused(player) => {
+            format!("refused {}", format_player(*player))
+        }
+        ClockEvent::TickDelay { player, gap } => {
+            format!("tick {} {}", format_player(*player), format_millis(*gap))
+        }
+        ClockEvent::SessionExpired => "session".to_string(),
+        ClockEvent::ForfeitDue(player) => {
+            format!("forfeit {}", format_player(*player))
+        }
+        ClockEvent::PausedBy(player) => format!("break {}", format_player(*player)),
+        ClockEvent::PauseAllowanceExceeded(player) => {
+            format!("overbreak {}", format_player(*player))
+        }
+        ClockEvent::Discrepancy { player, expected, actual } => format!(
+            "discrepancy {} {} {}",
+            format_player(*player), format_millis(*expected),
+            format_millis(*actual)
+        ),
+        ClockEvent::Finished => "finished".to_string(),
+    };
+    format!("{} {} {}", format_millis(record.elapsed()), unix_millis, event)
+}
+
+fn format_adjustment(
+    kind: &str, player: Player, time: Duration, reason: &Option<String>
+) -> String {
+    let mut line = format!("{} {} {}", kind, format_player(player), format_millis(time));
+    if let Some(reason) = reason {
+        line.push(' ');
+        line.push_str(reason);
+    }
+    line
+}
+
+/// Parses an event record formatted by `format_event`, for a clock created
+/// at `origin`
+pub(crate) fn parse_event(line: &str, origin: Instant) -> io::Result<EventRecord> {
+    let mut fields = line.splitn(6, ' ');
+    let mut field = || fields.next().ok_or_else(|| invalid("truncated event"));
+
+    let elapsed = parse_millis(field()?)?;
+    let timestamp = UNIX_EPOCH + parse_millis(field()?)?;
+    let kind = field()?;
+    let event = match kind {
+        "finished" => ClockEvent::Finished,
+        "session" => ClockEvent::SessionExpired,
+        _ => {
+            let player = parse_player(field()?)?;
+            match kind {
+                "started" => ClockEvent::Started(player),
+                "stopped" => ClockEvent::Stopped(player),
+                "pressed" => ClockEvent::Pressed(player),
+                "active" => ClockEvent::ActivePlayerChanged(player),
+                "ignored" => ClockEvent::SwitchIgnored(player),
+                "debounced" => ClockEvent::PressDebounced(player),
+                "extended" => ClockEvent::Extended(player),
+                "flagged" => ClockEvent::Flagged(player),
+                "idle" => ClockEvent::IdlePaused(player),
+                "anomaly" => ClockEvent::TimeAnomaly(player),
+                "forfeit" => ClockEvent::ForfeitDue(player),
+                "refused" => ClockEvent::AdjustmentRefused(player),
+                "break" => ClockEvent::PausedBy(player),
+                "overbreak" => ClockEvent::PauseAllowanceExceeded(player),
+                "background" => ClockEvent::Backgrounded(player),
+                "foreground" => ClockEvent::Foregrounded {
+                    player,
+                    away: parse_millis(field()?)?,
+                },
+                "tick" => ClockEvent::TickDelay {
+                    player,
+                    gap: parse_millis(field()?)?,
+                },
+                "discrepancy" => ClockEvent::Discrepancy {
+                    player,
+                    expected: parse_millis(field()?)?,
+                    actual: parse_millis(field()?)?,
+                },
+                "added" | "subtracted" | "penalty" => {
+                    let time = parse_millis(field()?)?;
+                    let reason = field().ok().map(str::to_string);
+                    match kind {
+                        "added" => ClockEvent::TimeAdded { player, time, reason },
+                        "subtracted" => {
+                            ClockEvent::TimeSubtracted { player, time, reason }
+                        }
+                        _ => ClockEvent::Penalty { offender: player, time, reason },
+                    }
+                }
+                _ => return Err(invalid("unknown event")),
+            }
+        }
+    };
+    Ok(EventRecord::at(event, origin + elapsed, elapsed, timestamp))
+}
@@ -0,0 +1,262 @@
+//! # Events
+//! Events recorded by a chess clock, which together form the log of a game.
+
+use std::{fmt::Display, time::{Duration, Instant, SystemTime}};
+use crate::{DurationDisplay, Player, utils::format_rfc3339};
+
+/// Something that happened to a chess clock
+///
+/// More events may be added, so matches outside the crate need a wildcard.
+#[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
+pub enum ClockEvent {
+    /// The clock of the given player was started
+    Started(Player),
+    /// The clock of the given player was stopped
+    Stopped(Player),
+    /// The given player pressed the clock, ending their turn
+    Pressed(Player),
+    /// The active player was changed to the given player while stopped
+    ActivePlayerChanged(Player),
+    /// A switch by the given player was ignored because the clock was stopped
+    SwitchIgnored(Player),
+    /// A press by the given player was ignored because it came within the
+    /// debounce window of the previous press, such as a button bouncing
+    PressDebounced(Player),
+    /// The given player used a shot clock extension
+    Extended(Player),
+    /// The clock of the given player ran out
+    Flagged(Player),
+    /// The clock of the given player paused itself after running for the
+    /// idle timeout without a press
+    IdlePaused(Player),
+    /// Time went backwards on the clock of the given player, and the clock
+    /// carried on from the current time
+    TimeAnomaly(Player),
+    /// The shell went into the background with the given player on move
+    Backgrounded(Player),
+    /// The shell came back to the foreground after `away` of wall-clock time
+    Foregrounded { player: Player, away: Duration },
+    /// Time was added to the clock of a player
+    TimeAdded { player: Player, time: Duration, reason: Option<String> },
+    /// Time was subtracted from the clock of a player
+    TimeSubtracted { player: Player, time: Duration, reason: Option<String> },
+    /// A player was penalised by giving time to their opponent
+    Penalty { offender: Player, time: Duration, reason: Option<String> },
+    /// An adjustment of the clock of the given player was refused, as
+    /// strict rules lock the clocks once the game is under way
+    AdjustmentRefused(Player),
+    /// The clock of the given player was updated `gap` after the previous
+    /// update, longer than the watchdog allows, so the time shown may have
+    /// jumped
+    TickDelay { player: Player, gap: Duration },
+    /// The overall time limit on the session ran out
+    SessionExpired,
+    /// The given player had not pressed their clock by the default time, so
+    /// forfeits the game
+    ForfeitDue(Player),
+    /// The given player paused the game for a break, stopping the running
+    /// clock until it is started again
+    PausedBy(Player),
+    /// The given player's breaks went over the pause allowance of the rules
+    PauseAllowanceExceeded(Player),
+    /// The clock of the given player read `actual` where the time it had run
+    /// and the events since the game started give `expected`, found by an
+    /// audit of the clock
+    Discrepancy { player: Player, expected: Duration, actual: Duration },
+    /// The game was finished
+    Finished,
+}
+
+impl Display for ClockEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Started(player) => write!(f, "{} clock started", player),
+            Self::Stopped(player) => write!(f, "{} clock stopped", player),
+            Self::Pressed(player) => write!(f, "{} pressed", player),
+            Self::ActivePlayerChanged(player) => {
+                write!(f, "{} to move", player)
+            }
+            Self::SwitchIgnored(player) => {
+                write!(f, "{} switched while stopped, ignored", player)
+            }
+            Self::PressDebounced(player) => {
+                write!(f, "{} pressed again too soon, ignored", player)
+            }
+            Self::Extended(player) => write!(f, "{} used an extension", player),
+            Self::Flagged(player) => write!(f, "{} flagged", player),
+            Self::IdlePaused(player) => {
+                write!(f, "{} clock paused while idle", player)
+            }
+            Self::TimeAnomaly(player) => {
+                write!(f, "{} clock went backwards", player)
+            }
+            Self::Backgrounded(player) => {
+                write!(f, "{} to move, app in background", player)
+            }
+            Self::Foregrounded { player, away } => write!(
+                f, "{} to move, app back after {}",
+                player, DurationDisplay::from(*away)
+            ),
+            Self::TimeAdded { player, time, reason } => {
+                write!(f, "{} given +{}", player, DurationDisplay::from(*time))?;
+                write_reason(f, reason)
+            }
+            Self::TimeSubtracted { player, time, reason } => {
+                write!(f, "{} docked -{}", player, DurationDisplay::from(*time))?;
+                write_reason(f, reason)
+            }
+            Self::Penalty { offender, time, reason } => {
+                write!(
+                    f, "{} penalised, {} given +{}",
+                    offender, offender.other(), DurationDisplay::from(*time)
+                )?;
+                write_reason(f, reason)
+            }
+            Self::AdjustmentRefused(player) => {
+                write!(f, "{} adjustment refused, strict rules", player)
+            }
+            Self::TickDelay { player, gap } => write!(
+                f, "{} clock display stalled for {:#}",
+                player, DurationDisplay::from(*gap)
+            ),
+            Self::SessionExpired => write!(f, "Session time ran out"),
+            Self::ForfeitDue(player) => {
+                write!(f, "{} forfeits, not having arrived in time", player)
+            },
+            Self::PausedBy(player) => {
+                write!(f, "{} took a break, clock paused", player)
+            },
+            Self::PauseAllowanceExceeded(player) => {
+                write!(f, "{} went over their pause allowance", player)
+            },
+            Self::Discrepancy { player, expected, actual } => write!(
+                f, "{} clock read {:#}, {:#} expected",
+                player, DurationDisplay::from(*actual),
+                DurationDisplay::from(*expected)
+            ),
+            Self::Finished => write!(f, "Game finished"),
+        }
+    }
+}
+
+impl ClockEvent {
+    /// Returns whether the event is an arbiter's adjustment of the clocks
+    pub fn is_adjustment(&self) -> bool {
+        matches!(
+            self,
+            Self::TimeAdded { .. } | Self::TimeSubtracted { .. }
+                | Self::Penalty { .. }
+        )
+    }
+}
+
+fn write_reason(
+    f: &mut std::fmt::Formatter<'_>, reason: &Option<String>
+) -> std::fmt::Result {
+    match reason {
+        Some(reason) => write!(f, " ({})", reason),
+        None => Ok(()),
+    }
+}
+
+/// An event together with when it happened
+///
+/// Each record carries both the monotonic time elapsed since the clock was
+/// created, which is reliable for timing, and the wall-clock time, which is
+/// needed for audit trails.
+#[derive(Debug, Clone)]
+pub struct EventRecord {
+    event: ClockEvent,
+    instant: Instant,
+    elapsed: Duration,
+    timestamp: SystemTime,
+}
+
+impl EventRecord {
+    /// Rebuilds a record of an event that happened at a known time
+    pub(crate) fn at(
+        event: ClockEvent, instant: Instant, elapsed: Duration,
+        timestamp: SystemTime
+    ) -> Self {
+        Self { event, instant, elapsed, timestamp }
+    }
+
+    pub fn event(&self) -> &ClockEvent {
+        &self.event
+    }
+
+    /// Get the monotonic instant at which the event happened
+    pub fn instant(&self) -> Instant {
+        self.instant
+    }
+
+    /// Get the monotonic time elapsed between creating the clock and the
+    /// event
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Get the wall-clock time at which the event happened
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    /// Get the wall-clock time at which the event happened, formatted as an
+    /// RFC 3339 UTC timestamp
+    pub fn timestamp_rfc3339(&self) -> String {
+        format_rfc3339(self.timestamp)
+    }
+}
+
+impl Display for EventRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f, "{} +{:#} {}",
+            self.timestamp_rfc3339(),
+            DurationDisplay::from(self.elapsed),
+            self.event
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_record_display() {
+        let record = EventRecord::at(
+            ClockEvent::Pressed(Player::Player2), Instant::now(), Duration::ZERO,
+            SystemTime::now()
+        );
+        let display = record.to_string();
+
+        let (timestamp, rest) = display.split_once(' ').unwrap();
+        assert_eq!(timestamp, record.timestamp_rfc3339());
+        assert!(timestamp.ends_with('Z'));
+        assert_eq!(rest, "+00:00.00 Player 2 pressed");
+    }
+
+    #[test]
+    fn test_adjustment_display() {
+        let event = ClockEvent::Penalty {
+            offender: Player::Player1,
+            time: Duration::from_secs(120),
+            reason: Some("Illegal move".to_string()),
+        };
+        assert!(event.is_adjustment());
+        assert_eq!(
+            event.to_string(),
+            "Player 1 penalised, Player 2 given +02:00 (Illegal move)"
+        );
+
+        let event = ClockEvent::TimeSubtracted {
+            player: Player::Player2,
+            time: Duration::from_secs(10),
+            reason: None,
+        };
+        assert_eq!(event.to_string(), "Player 2 docked -00:10");
+        assert!(!ClockEvent::Finished.is_adjustment());
+    }
+}
@@ -0,0 +1,340 @@
+//! # Follower
+//! A mirror of a chess clock run elsewhere, kept up to date by the messages
+//! of the [`protocol`](crate::protocol), for displays that follow the clock
+//! rather than run it.
+//!
+//! The follower watches for silence: if nothing, not even a heartbeat, is
+//! heard from the clock for the timeout, the connection is taken as lost and
+//! the times shown are frozen at the last moment the clock was heard from.
+//! On reconnecting, the follower asks to resume from the first event it has
+//! not seen, and ignores any event it already has, so nothing is applied
+//! twice. The times stay frozen until the clock sends its state again.
+//!
+//! A state takes time to arrive, so the active player's clock has already
+//! run down further by the time it is shown. Setting the lag compensation to
+//! the typical one-way latency counts that time off on arrival.
+
+use std::time::{Duration, Instant};
+use crate::{
+    protocol::Message, DurationDisplay, EventRecord, Player, Rules, Status
+};
+
+/// The state last sent by the clock, and when it arrived
+#[derive(Debug, Clone, Copy)]
+struct Followed {
+    times: (Duration, Duration),
+    active: Player,
+    status: Status,
+    received: Instant,
+}
+
+impl Followed {
+    /// Get the times the clock shows at `now`, counting the active player
+    /// down since the state arrived
+    fn read_at(&self, now: Instant) -> (Duration, Duration) {
+        let mut times = [self.times.0, self.times.1];
+        if self.status == Status::Running {
+            let since = now.saturating_duration_since(self.received);
+            let active = &mut times[self.active.index()];
+            *active = active.saturating_sub(since);
+        }
+        (times[0], times[1])
+    }
+}
+
+/// A mirror of a remote chess clock
+#[derive(Debug, Clone)]
+pub struct Follower {
+    origin: Instant,
+    timeout: Duration,
+    rules: Option<Rules>,
+    state: Option<Followed>,
+    events: Vec<EventRecord>,
+    last_heard: Option<Instant>,
+    lost: Option<(Duration, Duration)>,
+    lag: Duration,
+    behind: bool,
+}
+
+impl Follower {
+    /// Creates a follower that takes the connection as lost after `timeout`
+    /// of silence
+    ///
+    /// The timeout should be a few times the clock's heartbeat interval.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            origin: Instant::now(),
+            timeout,
+            rules: None,
+            state: None,
+            events: Vec::new(),
+            last_heard: None,
+            lost: None,
+            lag: Duration::ZERO,
+            behind: false,
+        }
+    }
+
+    /// Sets the one-way latency assumed for every state from the clock
+    pub fn set_lag_compensation(&mut self, lag: Duration) {
+        self.lag = lag;
+    }
+
+    /// Get the instant to decode the clock's messages against
+    pub fn origin(&self) -> Instant {
+        self.origin
+    }
+
+    /// Get the rules of the followed game, once the clock has sent them
+    pub fn rules(&self) -> Option<&Rules> {
+        self.rules.as_ref()
+    }
+
+    /// Get the events of the clock seen so far, oldest first
+    pub fn events(&self) -> &[EventRecord] {
+        &self.events
+    }
+
+    /// Get the status of the clock, once it has sent its state
+    pub fn status(&self) -> Option<Status> {
+        self.state.map(|state| state.status)
+    }
+
+    /// Get the player on move, once the clock has sent its state
+    pub fn active_player(&self) -> Option<Player> {
+        self.state.map(|state| state.active)
+    }
+
+    /// Takes a message from the clock, received at `now`
+    ///
+    /// Returns whether the message changed what the follower shows. Events
+    /// already seen, or that arrive ahead of a gap, are ignored, and the
+    /// follower is then [behind](Follower::behind).
+    pub fn receive(&mut self, message: Message, now: Instant) -> bool {
+        self.last_heard = Some(now);
+        match message {
+            Message::Rules(rules) => {
+                self.rules = Some(*rules);
+                true
+            },
+            Message::State { times, active, status } => {
+                // The state was true when it was sent, a lag ago
+                let sent = now.checked_sub(self.lag).unwrap_or(now);
+                self.state = Some(Followed {
+                    times, active, status, received: sent
+                });
+                // Fresh state from the clock ends a resync
+                self.lost = None;
+                true
+            },
+            Message::Event { sequence, record } => {
+                let next = sequence == self.events.len() as u64;
+                if next {
+                    self.events.push(record);
+                    self.behind = false;
+                } else if sequence > self.events.len() as u64 {
+                    self.behind = true;
+                }
+                next
+            },
+            Message::Heartbeat { events } => {
+                if events > self.events.len() as u64 {
+                    self.behind = true;
+                }
+                false
+            },
+            _ => false,
+        }
+    }
+
+    /// Get whether an event has been missed, so the follower should ask
+    /// the clock to resume from [`Follower::resume`]
+    pub fn behind(&self) -> bool {
+        self.behind
+    }
+
+    /// Get the message asking the clock for the events the follower has not
+    /// seen
+    pub fn resume(&self) -> Message {
+        Message::Resume { sequence: self.events.len() as u64 }
+    }
+
+    /// Checks whether the clock has been silent for longer than the timeout
+    ///
+    /// Returns whether the connection is lost, freezing the times shown the
+    /// first time it is found to be.
+    pub fn check(&mut self, now: Instant) -> bool {
+        if self.lost.is_none() {
+            if let (Some(heard), Some(state)) = (self.last_heard, self.state) {
+                if now.saturating_duration_since(heard) > self.timeout {
+                    self.lost = Some(state.read_at(heard));
+                }
+            }
+        }
+        self.lost.is_some()
+    }
+
+    /// Get the times to show at `now`, once the clock has sent its state
+    ///
+    /// While the connection is lost, these are the times when the clock was
+    /// last heard from.
+    pub fn read(&self, now: Instant) -> Option<(Duration, Duration)> {
+        self.lost.or_else(|| self.state.map(|state| state.read_at(now)))
+    }
+
+    /// Get the time of the player on move when the connection was lost, if
+    /// it is lost
+    pub fn lost_at(&self) -> Option<Duration> {
+        let (times, state) = (self.lost?, self.state?);
+        Some([times.0, times.1][state.active.index()])
+    }
+
+    /// Get the banner to show over the frozen times while the connection is
+    /// lost, e.g. `Connection lost at 04:12`
+    pub fn banner(&self) -> Option<String> {
+        self.lost_at().map(|time| {
+            format!("Connection lost at {}", DurationDisplay::from(time))
+        })
+    }
+
+    /// Get the messages to open a new connection to the clock with
+    ///
+    /// The times stay frozen until the clock answers with its state.
+    pub fn reconnect(&self) -> [Message; 2] {
+        [Message::hello(), self.resume()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{times, ChessClock, ClockEvent};
+
+    fn running(times: (Duration, Duration)) -> Message {
+        Message::State {
+            times, active: Player::Player1, status: Status::Running
+        }
+    }
+
+    fn event(sequence: u64, event: ClockEvent, origin: Instant) -> Message {
+        let record = EventRecord::at(
+            event, origin, Duration::ZERO, std::time::SystemTime::now()
+        );
+        Message::Event { sequence, record }
+    }
+
+    #[test]
+    fn test_counts_down_between_states() {
+        let mut follower = Follower::new(times::THREE_SECONDS);
+        let start = Instant::now();
+        assert_eq!(follower.read(start), None);
+
+        let minute_each = (times::ONE_MINUTE, times::ONE_MINUTE);
+        follower.receive(running(minute_each), start);
+        let later = start + times::TEN_SECONDS;
+        assert_eq!(
+            follower.read(later),
+            Some((times::ONE_MINUTE - times::TEN_SECONDS, times::ONE_MINUTE))
+        );
+    }
+
+    #[test]
+    fn test_freezes_when_silent() {
+        let mut follower = Follower::new(times::THREE_SECONDS);
+        let start = Instant::now();
+        let minute_each = (times::ONE_MINUTE, times::ONE_MINUTE);
+        follower.receive(running(minute_each), start);
+
+        let heartbeat = start + times::TWO_SECONDS;
+        follower.receive(Message::Heartbeat { events: 0 }, heartbeat);
+        assert!(!follower.check(heartbeat + times::THREE_SECONDS));
+
+        let silent = heartbeat + times::TEN_SECONDS;
+        assert!(follower.check(silent));
+        let frozen = times::ONE_MINUTE - times::TWO_SECONDS;
+        assert_eq!(follower.read(silent), Some((frozen, times::ONE_MINUTE)));
+        assert_eq!(
+            follower.read(silent + times::ONE_MINUTE),
+            Some((frozen, times::ONE_MINUTE))
+        );
+        assert_eq!(follower.lost_at(), Some(frozen));
+        assert_eq!(follower.banner().unwrap(), "Connection lost at 00:58");
+    }
+
+    #[test]
+    fn test_resync_does_not_double_apply() {
+        let mut follower = Follower::new(times::THREE_SECONDS);
+        let origin = follower.origin();
+        let start = Instant::now();
+        let minute_each = (times::ONE_MINUTE, times::ONE_MINUTE);
+        follower.receive(running(minute_each), start);
+        assert!(follower.receive(
+            event(0, ClockEvent::Started(Player::Player1), origin), start
+        ));
+        assert!(follower.receive(
+            event(1, ClockEvent::Pressed(Player::Player1), origin), start
+        ));
+
+        let silent = start + times::TEN_SECONDS;
+        assert!(follower.check(silent));
+        assert!(matches!(
+            follower.reconnect()[1], Message::Resume { sequence: 2 }
+        ));
+
+        // The clock resends an event the follower already has
+        assert!(!follower.receive(
+            event(1, ClockEvent::Pressed(Player::Player1), origin), silent
+        ));
+        assert!(follower.check(silent), "still frozen until fresh state");
+        assert!(follower.receive(
+            event(2, ClockEvent::Pressed(Player::Player2), origin), silent
+        ));
+        let resynced = (times::THIRTY_SECONDS, times::ONE_MINUTE);
+        follower.receive(running(resynced), silent);
+        assert!(!follower.check(silent));
+        assert_eq!(follower.events().len(), 3);
+        assert_eq!(follower.banner(), None);
+    }
+
+    #[test]
+    fn test_ignores_gaps() {
+        let mut follower = Follower::new(times::THREE_SECONDS);
+        let origin = follower.origin();
+        assert!(!follower.receive(
+            event(3, ClockEvent::Finished, origin), Instant::now()
+        ));
+        assert!(follower.events().is_empty());
+        assert!(follower.behind());
+        assert!(matches!(follower.resume(), Message::Resume { sequence: 0 }));
+
+        let started = ClockEvent::Started(Player::Player1);
+        assert!(follower.receive(event(0, started, origin), Instant::now()));
+        assert!(!follower.behind());
+
+        // A heartbeat shows that the last event was lost
+        follower.receive(Message::Heartbeat { events: 2 }, Instant::now());
+        assert!(follower.behind());
+    }
+
+    #[test]
+    fn test_follows_clock_messages() {
+        let mut clock = ChessClock::default();
+        clock.start();
+        clock.switch_player();
+
+        let mut follower = Follower::new(times::THREE_SECONDS);
+        let now = Instant::now();
+        let origin = follower.origin();
+        for (sequence, record) in clock.events().iter().enumerate() {
+            let line = Message::Event {
+                sequence: sequence as u64, record: record.clone()
+            }.encode();
+            let message = Message::decode(&line, origin).unwrap();
+            assert!(follower.receive(message, now));
+        }
+        let rules = Box::new(clock.rules().clone());
+        follower.receive(Message::Rules(rules), now);
+        assert_eq!(follower.events().len(), clock.events().len());
+        assert!(follower.rules().is_some());
+    }
+}
@@ -0,0 +1,333 @@
+//! # Game store
+//! A summary of every finished game each profiled player has played, kept
+//! across sessions in a single plain text file that games are appended to.
+//!
+//! The file has a header line, then a line for each player of each game:
+//!
+//! ```text
+//! rusty_chess_clock games 1
+//! game <unix ms> <player> <flagged> <moves> <think ms> <remaining ms> <name>
+//! ```
+//!
+//! `<player>` is the side the player took, 1 or 2, and `<flagged>` is 1 if
+//! they lost on time and 0 otherwise. A torn line, left by a crash as a
+//! game was being added, is skipped when the file is read.
+
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use crate::{
+    encoding::{
+        format_millis, format_player, invalid, parse_millis, parse_player
+    },
+    ChessClock, Player,
+};
+
+const HEADER: &str = "rusty_chess_clock games 1";
+
+/// How one player's clock stood at the end of a finished game
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameSummary {
+    name: String,
+    finished_at: SystemTime,
+    player: Player,
+    flagged: bool,
+    moves: u32,
+    think_time: Duration,
+    remaining: Duration,
+}
+
+impl GameSummary {
+    /// Summarises the game of `clock` for the player on side `player`,
+    /// whose profile is called `name`
+    pub fn from_clock(clock: &ChessClock, player: Player, name: &str) -> Self {
+        let finished_at = clock.events().last()
+            .map_or_else(SystemTime::now, |record| record.timestamp());
+        let times = clock.read();
+        Self {
+            name: name.trim().to_string(),
+            finished_at,
+            player,
+            flagged: clock.flagged() == Some(player),
+            moves: clock.moves_made(player),
+            think_time: clock.think_time(player),
+            remaining: match player {
+                Player::Player1 => times.0,
+                Player::Player2 => times.1,
+            },
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get when the game finished, from its last event
+    pub fn finished_at(&self) -> SystemTime {
+        self.finished_at
+    }
+
+    /// Get the side the player took
+    pub fn player(&self) -> Player {
+        self.player
+    }
+
+    /// Get whether the player lost on time
+    pub fn flagged(&self) -> bool {
+        self.flagged
+    }
+
+    pub fn moves(&self) -> u32 {
+        self.moves
+    }
+
+    /// Get the total time the player's clock ran
+    pub fn think_time(&self) -> Duration {
+        self.think_time
+    }
+
+    /// Get the time left on the player's clock at the end
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    fn to_line(&self) -> String {
+        let unix = self.finished_at.duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        format!(
+            "game {} {} {} {} {} {} {}",
+            format_millis(unix), format_player(self.player),
+            u8::from(self.flagged), self.moves,
+            format_millis(self.think_time), format_millis(self.remaining),
+            self.name
+        )
+    }
+
+    fn parse(line: &str) -> io::Result<Self> {
+        let malformed = || invalid("malformed game");
+        let fields = line.strip_prefix("game ").ok_or_else(malformed)?;
+        let fields: Vec<&str> = fields.splitn(7, ' ').collect();
+        let [unix, player, flagged, moves, think_time, remaining, name] =
+            fields.as_slice()
+        else {
+            return Err(malformed());
+        };
+        let flagged = match *flagged {
+            "0" => false,
+            "1" => true,
+            _ => return Err(malformed()),
+        };
+        Ok(Self {
+            name: name.to_string(),
+            finished_at: UNIX_EPOCH + parse_millis(unix)?,
+            player: parse_player(player)?,
+            flagged,
+            moves: moves.parse().map_err(|_| malformed())?,
+            think_time: parse_millis(think_time)?,
+            remaining: parse_millis(remaining)?,
+        })
+    }
+}
+
+/// Every finished game of the profiled players on a machine, backed by a
+/// file that new games are appended to
+#[derive(Debug, Clone)]
+pub struct GameStore {
+    path: PathBuf,
+    games: Vec<GameSummary>,
+}
+
+impl GameStore {
+    /// Get where games are kept by default, in the user's state directory
+    ///
+    /// # Returns
+    /// `$XDG_STATE_HOME/rusty_chess_clock/games`, falling back to
+    /// `$HOME/.local/state`, or `None` if neither variable is set.
+    pub fn default_path() -> Option<PathBuf> {
+        let state = env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| {
+                PathBuf::from(home).join(".local").join("state")
+            }))?;
+        Some(state.join("rusty_chess_clock").join("games"))
+    }
+
+    /// Opens the store at `path`, reading the games already in it
+    ///
+    /// A missing file is read as no games; it is only created when the
+    /// first game is recorded.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self { path, games: Vec::new() });
+            },
+            Err(error) => return Err(error),
+        };
+        let mut lines = contents.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(invalid("not a games file"));
+        }
+        let games = lines
+            .filter_map(|line| GameSummary::parse(line).ok())
+            .collect();
+        Ok(Self { path, games })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get every game of every player, oldest first
+    pub fn games(&self) -> &[GameSummary] {
+        &self.games
+    }
+
+    /// Get the games of the player called `name`, oldest first
+    pub fn games_of<'a>(
+        &'a self, name: &'a str
+    ) -> impl Iterator<Item = &'a GameSummary> {
+        self.games.iter().filter(move |game| game.name == name)
+    }
+
+    /// Get the names of every player with a game, in order of their first
+    pub fn players(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for game in &self.games {
+            if !names.contains(&game.name.as_str()) {
+                names.push(&game.name);
+            }
+        }
+        names
+    }
+
+    /// Adds a finished game for the players who played it, appending it to
+    /// the file
+    ///
+    /// # Arguments
+    /// * `clock` - The clock of the game.
+    /// * `names` - The name of each player, in player order, or `None` for
+    ///   a player without a profile, whose side is left out.
+    pub fn record_game(
+        &mut self, clock: &ChessClock, names: [Option<&str>; 2]
+    ) -> io::Result<()> {
+        let games: Vec<GameSummary> = Player::ALL.into_iter()
+            .zip(names)
+            .filter_map(|(player, name)| Some((player, name?.trim())))
+            .filter(|(_, name)| !name.is_empty())
+            .map(|(player, name)| GameSummary::from_clock(clock, player, name))
+            .collect();
+        if games.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(directory) = self.path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut contents = String::new();
+        if file.metadata()?.len() == 0 {
+            contents.push_str(&format!("{}\n", HEADER));
+        } else {
+            // End a torn line first, so that it does not run into this game
+            let mut last = [0];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            if last != *b"\n" {
+                contents.push('\n');
+            }
+        }
+        for game in &games {
+            contents.push_str(&format!("{}\n", game.to_line()));
+        }
+        file.write_all(contents.as_bytes())?;
+        self.games.extend(games);
+        Ok(())
+    }
+
+    /// Get the number of games the player called `name` has played
+    pub fn games_played(&self, name: &str) -> u32 {
+        self.games_of(name).count() as u32
+    }
+
+    /// Get the fraction of their games the player called `name` lost on
+    /// time, from 0.0 to 1.0
+    ///
+    /// # Returns
+    /// The fraction, or `None` if they have played no games.
+    pub fn flag_rate(&self, name: &str) -> Option<f64> {
+        let games = self.games_played(name);
+        let flags = self.games_of(name).filter(|game| game.flagged).count();
+        (games > 0).then(|| flags as f64 / f64::from(games))
+    }
+
+    /// Get the average time the player called `name` had left at the end of
+    /// their games
+    ///
+    /// # Returns
+    /// The average, or `None` if they have played no games.
+    pub fn average_remaining(&self, name: &str) -> Option<Duration> {
+        let games = self.games_played(name);
+        let total: Duration = self.games_of(name)
+            .map(GameSummary::remaining)
+            .sum();
+        (games > 0).then(|| total / games)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{presets, times};
+
+    #[test]
+    fn test_record_and_query_games() {
+        let path = env::temp_dir()
+            .join(format!("rusty_chess_clock_games_{}", std::process::id()))
+            .join("games");
+        let mut store = GameStore::open(&path).unwrap();
+        assert!(store.games().is_empty());
+
+        let mut clock = ChessClock::new(presets::BLITZ_3_2);
+        clock.start();
+        clock.press(Player::Player1);
+        clock.subtract_time(Player::Player2, times::ONE_HOUR, None).unwrap();
+        clock.finish();
+        store.record_game(&clock, [Some("Alice"), Some("Bob")]).unwrap();
+        store.record_game(&clock, [Some("Bob"), None]).unwrap();
+        store.record_game(&clock, [None, Some("  ")]).unwrap();
+
+        let mut loaded = GameStore::open(&path).unwrap();
+        assert_eq!(loaded.games().len(), store.games().len());
+        assert_eq!(loaded.games()[1].player(), Player::Player2);
+        assert!(loaded.games()[1].flagged());
+        assert_eq!(loaded.players(), ["Alice", "Bob"]);
+        assert_eq!(loaded.games_played("Alice"), 1);
+        assert_eq!(loaded.games_played("Bob"), 2);
+        assert_eq!(loaded.flag_rate("Bob"), Some(0.5));
+        assert_eq!(loaded.flag_rate("Carol"), None);
+        let alice = loaded.average_remaining("Alice").unwrap();
+        assert!(alice > times::THREE_MINUTES);
+        assert!(loaded.average_remaining("Bob").unwrap() < alice);
+
+        // A game torn by a crash is skipped, and later games still append
+        let torn = fs::read_to_string(&path).unwrap() + "game 17";
+        fs::write(&path, torn).unwrap();
+        assert_eq!(GameStore::open(&path).unwrap().games().len(), 3);
+        loaded.record_game(&clock, [Some("Carol"), None]).unwrap();
+        let reopened = GameStore::open(&path);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        let reopened = reopened.unwrap();
+        assert_eq!(reopened.games().len(), 4);
+        assert_eq!(reopened.games_played("Carol"), 1);
+    }
+}
@@ -0,0 +1,141 @@
+//! # Guard
+//! An optional arbiter PIN for clocks on shared club hardware.
+//!
+//! While a game is in progress, actions that would throw it away, such as
+//! quitting or returning to the settings, need the PIN. The front ends
+//! decide which of their actions are guarded and ask for the PIN before
+//! taking them; this module only decides when the lock applies and checks
+//! what was typed.
+
+use std::{error::Error, fmt::Display};
+use crate::{ChessClock, Status};
+
+/// The fewest digits a PIN may have
+pub const MIN_PIN_DIGITS: usize = 4;
+/// The most digits a PIN may have
+pub const MAX_PIN_DIGITS: usize = 8;
+
+/// Why a PIN can not be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinError {
+    /// The PIN has characters other than the digits 0 to 9
+    NotDigits,
+    /// The PIN has fewer than `MIN_PIN_DIGITS` or more than
+    /// `MAX_PIN_DIGITS` digits
+    WrongLength,
+}
+
+impl Display for PinError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotDigits => write!(f, "The PIN must only have digits"),
+            Self::WrongLength => write!(
+                f, "The PIN must have {} to {} digits",
+                MIN_PIN_DIGITS, MAX_PIN_DIGITS
+            ),
+        }
+    }
+}
+
+impl Error for PinError {}
+
+/// Locks the actions that would end a game in progress behind a PIN
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Guard {
+    pin: Option<String>,
+}
+
+impl Guard {
+    /// Create a guard that never locks
+    pub fn open() -> Self {
+        Self::default()
+    }
+
+    /// Create a guard locked by `pin`
+    ///
+    /// # Returns
+    /// The guard, or why the PIN can not be used.
+    pub fn with_pin(pin: &str) -> Result<Self, PinError> {
+        if !pin.chars().all(|c| c.is_ascii_digit()) {
+            return Err(PinError::NotDigits);
+        }
+        if !(MIN_PIN_DIGITS..=MAX_PIN_DIGITS).contains(&pin.len()) {
+            return Err(PinError::WrongLength);
+        }
+        Ok(Self { pin: Some(pin.to_string()) })
+    }
+
+    /// Get whether the guard has a PIN
+    pub fn has_pin(&self) -> bool {
+        self.pin.is_some()
+    }
+
+    /// Get whether guarded actions on `clock` need the PIN
+    ///
+    /// A game is locked from its first start until it finishes, including
+    /// while the clock is stopped, as a paused game is still in progress.
+    pub fn is_locked(&self, clock: &ChessClock) -> bool {
+        self.has_pin()
+            && !clock.events().is_empty()
+            && clock.status() != Status::Finished
+    }
+
+    /// Check a PIN typed to unlock a guarded action
+    ///
+    /// Every digit is compared, so the time taken does not give away how
+    /// much of a wrong PIN was right.
+    ///
+    /// # Returns
+    /// Whether `entered` is the PIN, which is always true for an open
+    /// guard.
+    pub fn unlock(&self, entered: &str) -> bool {
+        let Some(pin) = &self.pin else {
+            return true;
+        };
+        let differences = pin.bytes()
+            .zip(entered.bytes())
+            .fold(0, |differences, (a, b)| differences | (a ^ b));
+        pin.len() == entered.len() && differences == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rules;
+
+    #[test]
+    fn test_pin_rules() {
+        assert!(Guard::with_pin("1234").is_ok());
+        assert!(Guard::with_pin("12345678").is_ok());
+        assert_eq!(Guard::with_pin("123"), Err(PinError::WrongLength));
+        assert_eq!(Guard::with_pin("123456789"), Err(PinError::WrongLength));
+        assert_eq!(Guard::with_pin("12a4"), Err(PinError::NotDigits));
+    }
+
+    #[test]
+    fn test_unlock() {
+        let guard = Guard::with_pin("2468").unwrap();
+        assert!(guard.unlock("2468"));
+        assert!(!guard.unlock("2469"));
+        assert!(!guard.unlock("246"));
+        assert!(!guard.unlock("24680"));
+        assert!(Guard::open().unlock(""));
+    }
+
+    #[test]
+    fn test_locked_while_game_in_progress() {
+        let guard = Guard::with_pin("2468").unwrap();
+        let mut clock = ChessClock::new(Rules::default());
+        assert!(!guard.is_locked(&clock));
+
+        clock.start();
+        assert!(guard.is_locked(&clock));
+        assert!(!Guard::open().is_locked(&clock));
+        clock.stop();
+        assert!(guard.is_locked(&clock));
+
+        clock.finish();
+        assert!(!guard.is_locked(&clock));
+    }
+}
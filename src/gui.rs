@@ -0,0 +1,3164 @@
+//! # GUI
+//! A chess clock window built with iced.
+//!
+//! A second, borderless window showing only the two clocks can be opened
+//! during a game, for a monitor facing spectators.
+
+use crate::{
+    branding,
+    burn_in::BurnIn,
+    guard::Guard,
+    i18n::NumberFormat,
+    presets,
+    times::{self, DurationExt},
+    touch::{Gesture, Recognizer, Thresholds},
+    display_precision_for, time_pressure_rating, AutoSave, ChessClock,
+    DelayedView, DurationDisplay, GameStore, IncrementStage, Interval,
+    IntervalTimer, NormalizationPolicy, Player, PlayerProfile, PlayerView,
+    PreGame, Profiles, Recovery, SessionBudget, Status, StoppedSwitch, Rules, TimingMethod,
+    RulesError, ScaledTime, WarningLevel, MAX_INCREMENT_STAGES
+};
+use iced::{
+    advanced::widget::{operation, Id},
+    alignment,
+    event,
+    executor,
+    keyboard,
+    mouse,
+    theme::{self, Theme},
+    time,
+    touch,
+    widget::{
+        self, button, canvas, checkbox, column, container, pick_list,
+        progress_bar, row, text, text_input
+    },
+    multi_window::Application,
+    Color,
+    Command,
+    Element,
+    Length,
+    Padding,
+    Point,
+    Radians,
+    Rectangle,
+    Renderer,
+    Settings,
+    Subscription,
+    window
+};
+use std::{
+    f32::consts::PI,
+    fmt::Display,
+    io::{self, Write},
+    path::PathBuf,
+    process,
+    time::{Duration, Instant, SystemTime}
+};
+
+const HELP: &str = "\
+A chess clock window. The rules are chosen on the settings page.
+
+Options:
+      --kiosk          Open borderless and fullscreen, for dedicated
+                       club machines
+      --touch          Use the touchscreen layout: tap your half of the
+                       screen to end your turn, swipe down to pause and
+                       hold to return to the settings page
+      --long-press MS  How long a hold must last, in milliseconds
+                       (default 1000, implies --touch)
+      --swipe PX       How far a swipe must travel, in pixels
+                       (default 100, implies --touch)
+      --tap-slop PX    How far a tap or hold may wander, in pixels
+                       (default 20, implies --touch)
+      --chroma-key KEY Draw the spectator display on plain green or
+                       magenta, with no borders or buttons, to key the
+                       clock into a stream
+      --font-colour HEX
+                       The colour of the chroma key clocks, as RRGGBB
+                       (default ffffff, implies --chroma-key green)
+      --delay SECONDS  Show the chroma key clocks SECONDS behind the game,
+                       to match a stream on a broadcast delay (implies
+                       --chroma-key green)
+      --burn-in        Protect displays from burn-in: move the clocks a
+                       few pixels at a time, and dim them when stopped
+      --dim-after MIN  How long the clock must be stopped to dim, in
+                       minutes (default 5, implies --burn-in)
+      --pin PIN        Lock returning to the settings page and closing the
+                       window during a game behind an arbiter PIN of 4 to
+                       8 digits
+      --round-times SECONDS
+                       Round the players' times to the nearest multiple of
+                       SECONDS, such as 30 for a club's half minutes
+      --round-increments SECONDS
+                       Round increments to the nearest multiple of SECONDS
+      --speed RATE     Run the clocks RATE times as fast as real time, up
+                       to 1000, such as 10 to demo a game in minutes
+  -h, --help           Print this help
+  -V, --version        Print the version
+
+Keys:
+  Enter                Start the clock from the settings page, then
+                       start / stop it
+  Space                End the active player's turn
+  e                    Use a shot clock extension
+  d                    Open / close the spectator display window
+  Backspace            Finish the game
+  r                    Start a rematch with the sides swapped, once the
+                       game is finished
+  q                    Return to the settings page, asking for the PIN if
+                       one is set
+";
+
+/// Run the chess clock window
+///
+/// # Arguments
+/// * `name` - The command name to show in help and version output.
+/// * `args` - The command line arguments, without the command name.
+pub fn run(name: &str, args: impl IntoIterator<Item = String>) -> iced::Result {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--kiosk" => options.kiosk = true,
+            "--touch" => {
+                options.touch.get_or_insert_with(Thresholds::default);
+            },
+            "--long-press" | "--swipe" | "--tap-slop" => {
+                let value = args.next()
+                    .and_then(|value| value.parse::<u32>().ok())
+                    .unwrap_or_else(|| usage_error(&format!(
+                        "{} needs a whole number", flag
+                    )));
+                let thresholds = options.touch
+                    .get_or_insert_with(Thresholds::default);
+                match flag.as_str() {
+                    "--long-press" => thresholds.set_long_press(
+                        Duration::from_millis(value.into())
+                    ),
+                    "--swipe" => thresholds.set_swipe_distance(value as f32),
+                    _ => thresholds.set_tap_slop(value as f32),
+                }
+            },
+            "--chroma-key" => {
+                let key = args.next()
+                    .and_then(|key| KeyColour::from_name(&key))
+                    .unwrap_or_else(|| usage_error(
+                        "--chroma-key needs green or magenta"
+                    ));
+                options.chroma_key.get_or_insert_with(ChromaKey::default)
+                    .key = key;
+            },
+            "--font-colour" => {
+                let font = args.next()
+                    .and_then(|font| parse_hex_colour(&font))
+                    .unwrap_or_else(|| usage_error(
+                        "--font-colour needs a colour as RRGGBB"
+                    ));
+                options.chroma_key.get_or_insert_with(ChromaKey::default)
+                    .font = font;
+            },
+            "--delay" => {
+                let seconds = args.next()
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .unwrap_or_else(|| usage_error(
+                        "--delay needs a whole number"
+                    ));
+                options.chroma_key.get_or_insert_with(ChromaKey::default)
+                    .delay = Some(seconds.seconds());
+            },
+            "--pin" => {
+                let pin = args.next()
+                    .unwrap_or_else(|| usage_error("--pin needs a PIN"));
+                options.guard = Guard::with_pin(&pin)
+                    .unwrap_or_else(|error| usage_error(&error.to_string()));
+            },
+            "--burn-in" => {
+                options.burn_in.get_or_insert_with(BurnIn::default);
+            },
+            "--round-times" | "--round-increments" => {
+                let step = args.next()
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .unwrap_or_else(|| usage_error(&format!(
+                        "{} needs a whole number", flag
+                    )))
+                    .seconds();
+                let policy = options.normalization;
+                options.normalization = if flag == "--round-times" {
+                    NormalizationPolicy::new(step, policy.get_increment_step())
+                } else {
+                    NormalizationPolicy::new(policy.get_time_step(), step)
+                };
+            },
+            "--speed" => {
+                let rate = args.next()
+                    .and_then(|value| value.parse::<f64>().ok())
+                    .and_then(ScaledTime::try_new)
+                    .unwrap_or_else(|| usage_error(&format!(
+                        "--speed needs a rate above 0 and up to {}",
+                        ScaledTime::MAX_RATE
+                    )));
+                options.speed = Some(rate);
+            },
+            "--dim-after" => {
+                let minutes = args.next()
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .unwrap_or_else(|| usage_error(
+                        "--dim-after needs a whole number"
+                    ));
+                options.burn_in.get_or_insert_with(BurnIn::default)
+                    .set_dim_after(minutes.minutes());
+            },
+            "-h" | "--help" => {
+                print!("Usage: {} [OPTIONS]\n\n{}", name, HELP);
+                return Ok(());
+            },
+            "-V" | "--version" => {
+                println!("{} {}", name, crate::VERSION);
+                return Ok(());
+            },
+            _ => usage_error(&format!("Unknown argument {}, see --help", flag)),
+        }
+    }
+
+    Pages::run(settings(options))
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+/// How the window was asked to run on the command line
+#[derive(Debug, Clone, Default)]
+struct Options {
+    kiosk: bool,
+    /// The gesture thresholds, if the touchscreen layout is used
+    touch: Option<Thresholds>,
+    /// The colours of the spectator display, if it is drawn for keying
+    chroma_key: Option<ChromaKey>,
+    /// How the clocks are kept from burning in, if they are
+    burn_in: Option<BurnIn>,
+    guard: Guard,
+    /// How the times entered on the settings page are rounded
+    normalization: NormalizationPolicy,
+    /// How fast new games run, if not in real time
+    speed: Option<ScaledTime>,
+}
+
+/// The background colours that are easy to key out of a video
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum KeyColour {
+    #[default]
+    Green,
+    Magenta,
+}
+
+impl KeyColour {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "green" => Some(Self::Green),
+            "magenta" => Some(Self::Magenta),
+            _ => None,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::Green => Color::from_rgb(0.0, 1.0, 0.0),
+            Self::Magenta => Color::from_rgb(1.0, 0.0, 1.0),
+        }
+    }
+}
+
+/// How the spectator display is drawn for streamers to key out
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ChromaKey {
+    key: KeyColour,
+    font: Color,
+    /// How far behind the game the clocks are shown, if they are delayed
+    /// to match the stream
+    delay: Option<Duration>,
+}
+
+impl Default for ChromaKey {
+    /// White clocks on green, shown live
+    fn default() -> Self {
+        Self { key: KeyColour::default(), font: Color::WHITE, delay: None }
+    }
+}
+
+/// The dark background of clocks dimmed to save the display
+#[derive(Debug, Clone, Copy)]
+struct Dimmed;
+
+impl container::StyleSheet for Dimmed {
+    type Style = Theme;
+
+    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+        let grey = constants::burn_in::DIMMED_GREY;
+        container::Appearance {
+            text_color: Some(Color::from_rgb(grey, grey, grey)),
+            background: Some(Color::BLACK.into()),
+            ..container::Appearance::default()
+        }
+    }
+}
+
+impl container::StyleSheet for ChromaKey {
+    type Style = Theme;
+
+    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            text_color: Some(self.font),
+            background: Some(self.key.color().into()),
+            ..container::Appearance::default()
+        }
+    }
+}
+
+/// Parse a colour written as `RRGGBB` hex, with or without a leading `#`
+fn parse_hex_colour(hex: &str) -> Option<Color> {
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |index: usize| {
+        u8::from_str_radix(hex.get(index..index + 2)?, 16).ok()
+    };
+    Some(Color::from_rgb8(channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Build the iced settings with the application's branding
+///
+/// # Arguments
+/// * `options` - The command line options. In kiosk mode the window opens
+///   borderless, and is made fullscreen once it exists. With a PIN, closing
+///   the window is left to the application, to ask for the PIN first.
+fn settings(options: Options) -> Settings<Options> {
+    let window = window::Settings {
+        exit_on_close_request: !options.guard.has_pin(),
+        ..window_settings(!options.kiosk)
+    };
+    Settings {
+        id: Some(branding::APP_ID.to_string()),
+        window,
+        flags: options,
+        ..Settings::default()
+    }
+}
+
+/// Build the settings of one window, with the application icon and id
+///
+/// # Arguments
+/// * `decorations` - Whether the window has a border and title bar.
+fn window_settings(decorations: bool) -> window::Settings {
+    let icon = window::icon::from_rgba(
+        branding::icon_rgba(), branding::ICON_SIZE, branding::ICON_SIZE
+    ).ok();
+    window::Settings {
+        icon,
+        decorations,
+        platform_specific: platform_specific(),
+        ..window::Settings::default()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn platform_specific() -> window::settings::PlatformSpecific {
+    window::settings::PlatformSpecific {
+        application_id: branding::APP_ID.to_string(),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn platform_specific() -> window::settings::PlatformSpecific {
+    window::settings::PlatformSpecific::default()
+}
+
+mod constants {
+    pub const WIDTH: u16 = 400;
+    pub const HEIGHT: u16 = 400;
+    pub const SPACING: u16 = 20;
+    pub const CLOCK_TEXT_SIZE: u16 = 50;
+    pub const TEXT_SIZE: u16 = 30;
+    pub const HEADER_SIZE: u16 = 50;
+
+    pub mod settings {
+        pub const TEXTBOX_WIDTH: u16 = 290;
+        pub const RATING_WIDTH: u16 = 140;
+        pub const PLAYER_WIDTH: u16 = 160;
+        pub const STAGE_WIDTH: u16 = 100;
+        pub const STEPPER_WIDTH: u16 = 32;
+        pub const TIME_STEP_MINUTES: u64 = 1;
+        pub const INCREMENT_STEP_SECONDS: u64 = 5;
+        pub const MINOR_ROW_SPACING: u16 = 4;
+        pub const INPUT_ELEMENT_SPACING: u16 = 10;
+        pub const BETWEEN_ELEMENT_SPACING: u16 = INPUT_ELEMENT_SPACING * 2;
+        pub const MAJOR_ROW_SPACING: u16 = BETWEEN_ELEMENT_SPACING * 2;
+    }
+
+    pub mod dial {
+        pub const RADIUS_FRACTION: f32 = 0.85;
+        pub const ARC_WIDTH: f32 = 12.0;
+        pub const HAND_WIDTH: f32 = 4.0;
+        pub const TICK_LENGTH: f32 = 12.0;
+        pub const TEXT_SIZE: f32 = 30.0;
+    }
+
+    pub mod countdown {
+        pub const COUNTS: [&str; 3] = ["3", "2", "1"];
+        pub const TEXT_SIZE: u16 = 200;
+    }
+
+    pub mod warm_up {
+        pub const TEXT_SIZE: u16 = 120;
+        pub const INPUT_WIDTH: u16 = 150;
+    }
+
+    pub mod footer {
+        pub const TEXT_SIZE: u16 = 16;
+        pub const GREY: f32 = 0.5;
+        pub const SESSION_BAR_HEIGHT: u16 = 4;
+    }
+
+    pub mod spectator {
+        pub const CLOCK_TEXT_SIZE: u16 = 160;
+        pub const TEXT_SIZE: u16 = 60;
+    }
+
+    pub mod warning {
+        pub const RED: f32 = 0.8;
+    }
+
+    pub mod watchdog {
+        use std::time::Duration;
+
+        /// Five missed ticks at the usual rate
+        pub const THRESHOLD: Duration = Duration::from_millis(500);
+        /// How long the warning stays up after a late tick
+        pub const WARNING: Duration = Duration::from_secs(3);
+    }
+
+    pub mod burn_in {
+        pub const DIMMED_GREY: f32 = 0.25;
+        pub const DIMMED_TEXT_SIZE: u16 = 40;
+    }
+
+    pub mod pin {
+        pub const INPUT_ID: &str = "pin";
+        pub const INPUT_WIDTH: u16 = 200;
+    }
+
+    pub mod adjust {
+        pub const REASON: &str = "Arbiter correction";
+        pub const BUTTON_PADDING: u16 = 5;
+    }
+}
+
+/// How the clocks are drawn while a game is in progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ClockFace {
+    #[default]
+    Digital,
+    Analog,
+}
+
+impl ClockFace {
+    const ALL: [Self; 2] = [Self::Digital, Self::Analog];
+}
+
+impl Display for ClockFace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Digital => write!(f, "Digital"),
+            Self::Analog => write!(f, "Analog"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Pages {
+    settings: ChessClockSettings,
+    clock: Option<ChessClockView>,
+    touch: Option<Thresholds>,
+    /// The spectator display window, if it is open
+    spectator: Option<window::Id>,
+    /// How the spectator display is drawn for keying, if it is
+    chroma_key: Option<ChromaKey>,
+    burn_in: Option<BurnIn>,
+    guard: Guard,
+    normalization: NormalizationPolicy,
+    speed: Option<ScaledTime>,
+    /// The locked action waiting for the PIN, if one is
+    unlock: Option<Unlock>,
+    profiles: Profiles,
+    /// Where the profiles are saved, if there is anywhere to save them
+    profiles_path: Option<PathBuf>,
+    /// Every finished game of the profiled players, if there is anywhere to
+    /// keep them
+    games: Option<GameStore>,
+    /// A game left unfinished last time, until it is resumed or discarded
+    recovery: Option<Recovery>,
+    /// Where the game is saved as it is played, if there is anywhere
+    autosave: Option<AutoSave>,
+}
+
+#[derive(Debug, Clone)]
+enum PagesMessage {
+    SettingsMessage(SettingsMessage),
+    ClockMessage(ChessClockViewMessage),
+    WindowClosed(window::Id),
+    CloseRequested(window::Id),
+    ChangePin(String),
+    SubmitPin,
+    CancelPin,
+    ResumeGame,
+    DiscardRecovery,
+}
+
+/// An action locked behind the arbiter PIN during a game
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Locked {
+    /// Return to the settings page
+    Reset,
+    /// Close the main window
+    Quit,
+    /// Add time to a player's clock in a strict game under way
+    AddTime(Player, Duration),
+    /// Subtract time from a player's clock in a strict game under way
+    SubtractTime(Player, Duration),
+}
+
+/// A locked action waiting for the PIN to be typed
+#[derive(Debug)]
+struct Unlock {
+    action: Locked,
+    entered: String,
+    /// Whether a wrong PIN has been typed
+    wrong: bool,
+}
+
+impl Unlock {
+    fn new(action: Locked) -> Self {
+        Self { action, entered: String::new(), wrong: false }
+    }
+}
+
+impl Application for Pages {
+    type Message = PagesMessage;
+    type Executor = executor::Default;
+    type Theme = Theme;
+    type Flags = Options;
+
+    fn new(options: Self::Flags) -> (Self, Command<Self::Message>) {
+        let command = if options.kiosk {
+            window::change_mode(window::Id::MAIN, window::Mode::Fullscreen)
+        } else {
+            Command::none()
+        };
+        let profiles_path = Profiles::default_path();
+        let profiles = profiles_path.as_ref()
+            .map_or(Ok(Profiles::new()), Profiles::load)
+            .unwrap_or_else(|error| {
+                eprintln!("Could not load player profiles: {}", error);
+                Profiles::new()
+            });
+        let games = GameStore::default_path().and_then(|path| {
+            GameStore::open(path)
+                .map_err(|error| {
+                    eprintln!("Could not load past games: {}", error);
+                })
+                .ok()
+        });
+        (
+            Self {
+                settings: ChessClockSettings::new(options.normalization),
+                clock: None,
+                touch: options.touch,
+                spectator: None,
+                chroma_key: options.chroma_key,
+                burn_in: options.burn_in,
+                guard: options.guard,
+                normalization: options.normalization,
+                speed: options.speed,
+                unlock: None,
+                profiles,
+                profiles_path,
+                games,
+                recovery: Recovery::find_default(),
+                autosave: AutoSave::to_default_path(),
+            },
+            command
+        )
+    }
+
+    fn title(&self, window: window::Id) -> String {
+        match self.clock {
+            _ if Some(window) == self.spectator => {
+                branding::window_title("Chess Clock - Spectators")
+            },
+            Some(_) => branding::window_title("Chess Clock"),
+            None => branding::window_title("Chess Clock - Select Settings")
+        }
+    }
+
+    fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
+        match message {
+            Self::Message::ClockMessage(ChessClockViewMessage::ResetClock) => {
+                return self.guarded(Locked::Reset);
+            },
+            Self::Message::ClockMessage(
+                ChessClockViewMessage::AddTime(player, time)
+            ) if self.adjustments_locked() => {
+                return self.ask_pin(Locked::AddTime(player, time));
+            },
+            Self::Message::ClockMessage(
+                ChessClockViewMessage::SubtractTime(player, time)
+            ) if self.adjustments_locked() => {
+                return self.ask_pin(Locked::SubtractTime(player, time));
+            },
+            Self::Message::CloseRequested(window) => {
+                if window == window::Id::MAIN {
+                    return self.guarded(Locked::Quit);
+                }
+                return window::close(window);
+            },
+            Self::Message::ChangePin(pin) => {
+                if let Some(unlock) = &mut self.unlock {
+                    unlock.entered = pin;
+                }
+            },
+            Self::Message::SubmitPin => {
+                let Some(unlock) = &mut self.unlock else {
+                    return Command::none();
+                };
+                if self.guard.unlock(&unlock.entered) {
+                    let action = unlock.action;
+                    let adjusting = matches!(
+                        action, Locked::AddTime(..) | Locked::SubtractTime(..)
+                    );
+                    if let Some(view) = self.clock.as_mut().filter(|_| adjusting) {
+                        view.clock.authorise(&unlock.entered);
+                    }
+                    self.unlock = None;
+                    return self.take(action);
+                }
+                unlock.entered.clear();
+                unlock.wrong = true;
+            },
+            Self::Message::CancelPin => {
+                self.unlock = None;
+            },
+            Self::Message::ResumeGame => {
+                if let Some(recovery) = self.recovery.take() {
+                    self.open_clock(recovery.resume(), None);
+                }
+            },
+            Self::Message::DiscardRecovery => {
+                let discarded = self.recovery.take().map(Recovery::discard);
+                if let Some(Err(error)) = discarded {
+                    eprintln!(
+                        "Could not discard the interrupted game: {}", error
+                    );
+                }
+            },
+            Self::Message::ClockMessage(
+                ChessClockViewMessage::ToggleSpectator
+            ) => {
+                return match self.spectator.take() {
+                    Some(spectator) => window::close(spectator),
+                    None => {
+                        let (spectator, command) =
+                            window::spawn(window_settings(false));
+                        self.spectator = Some(spectator);
+                        command
+                    },
+                };
+            },
+            Self::Message::WindowClosed(window) => {
+                // The spectator display can not outlive the controls
+                if window == window::Id::MAIN {
+                    if let Some(spectator) = self.spectator.take() {
+                        return window::close(spectator);
+                    }
+                } else if Some(window) == self.spectator {
+                    self.spectator = None;
+                }
+            },
+            Self::Message::SettingsMessage(
+                SettingsMessage::InitialiseClock
+            ) => {
+                // The settings page explains why invalid rules can not start
+                if self.settings.stage_error.is_some() {
+                    return Command::none();
+                }
+                let Ok(clock) = self.new_clock(self.settings.normalized())
+                else {
+                    return Command::none();
+                };
+                // A warm-up starts counting down as soon as the clock shows
+                let warm_up = self.settings.warm_up.map(|time| {
+                    let mut warm_up = PreGame::new(time);
+                    warm_up.start();
+                    warm_up
+                });
+                // A new game takes the place of any left unfinished
+                self.recovery = None;
+                self.open_clock(clock, warm_up);
+            }
+            Self::Message::SettingsMessage(
+                SettingsMessage::ChoosePlayer(player, name)
+            ) => {
+                let favourite = self.profiles.get(&name)
+                    .and_then(PlayerProfile::favourite_preset)
+                    .and_then(presets::by_name);
+                if let Some(rules) = favourite {
+                    self.settings.apply_preset(rules);
+                }
+                self.settings.players[player.index()] = Some(name);
+            }
+            Self::Message::SettingsMessage(
+                message @ (SettingsMessage::FocusNext
+                    | SettingsMessage::FocusPrevious
+                    | SettingsMessage::FindFocus)
+            ) => {
+                return self.settings.change_focus(message)
+                    .map(Self::Message::SettingsMessage);
+            }
+            Self::Message::SettingsMessage(SettingsMessage::AddPlayer) => {
+                if self.profiles.add(&self.settings.new_player) {
+                    self.settings.new_player.clear();
+                    self.save_profiles();
+                }
+            }
+            Self::Message::SettingsMessage(message) => {
+                self.settings.update(message);
+            }
+            Self::Message::ClockMessage(message) => {
+                let view = self.clock.as_mut().unwrap();
+                view.update(message);
+                // Each game counts towards the players' stats once, when it
+                // finishes
+                if view.clock.status() == Status::Finished && !view.recorded {
+                    view.recorded = true;
+                    let names = view.players.each_ref().map(Option::as_deref);
+                    self.profiles.record_game(&view.clock, names);
+                    let recorded = self.games.as_mut()
+                        .map_or(Ok(()), |games| {
+                            games.record_game(&view.clock, names)
+                        });
+                    if let Err(error) = recorded {
+                        eprintln!("Could not save the game: {}", error);
+                    }
+                    self.save_profiles();
+                }
+                self.save_game(false);
+            }
+        }
+        Command::none()
+    }
+
+    fn view(
+        &self, window: window::Id
+    ) -> Element<'_, Self::Message, Self::Theme, iced::Renderer> {
+        if Some(window) == self.spectator {
+            if let Some(key) = self.chroma_key {
+                let clocks = match &self.clock {
+                    Some(clock) => clock.chroma_key_view(),
+                    None => column![].into(),
+                };
+                return container(clocks)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .center_x()
+                    .center_y()
+                    .style(theme::Container::Custom(Box::new(key)))
+                    .into();
+            }
+            return match &self.clock {
+                Some(clock) => {
+                    clock.spectator_view().map(Self::Message::ClockMessage)
+                },
+                None => container(
+                    text(branding::APP_NAME)
+                        .size(constants::spectator::TEXT_SIZE)
+                )
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .center_x()
+                    .center_y()
+                    .into(),
+            };
+        }
+        if let Some(unlock) = &self.unlock {
+            return pin_view(unlock);
+        }
+        if let (None, Some(recovery)) = (&self.clock, &self.recovery) {
+            return resume_view(recovery);
+        }
+        match &self.clock {
+            Some(clock) => clock.view().map(Self::Message::ClockMessage),
+            None => {
+                self.settings.view(&self.profiles, self.games.as_ref())
+                    .map(Self::Message::SettingsMessage)
+            }
+        }
+    }
+
+    fn subscription(&self) -> Subscription<Self::Message> {
+        let page = match &self.clock {
+            Some(clock) => {
+                clock.subscription().map(Self::Message::ClockMessage)
+            },
+            None => {
+                self.settings.subscription().map(Self::Message::SettingsMessage)
+            }
+        };
+        let closed = event::listen_with(|event, _status| match event {
+            iced::Event::Window(window, window::Event::Closed) => {
+                Some(Self::Message::WindowClosed(window))
+            },
+            iced::Event::Window(window, window::Event::CloseRequested) => {
+                Some(Self::Message::CloseRequested(window))
+            },
+            _ => None,
+        });
+        Subscription::batch(vec![page, closed])
+    }
+
+}
+
+impl Pages {
+    /// Get whether adjustments of the clocks need the arbiter's override,
+    /// which they only can have with a PIN
+    fn adjustments_locked(&self) -> bool {
+        self.guard.has_pin() && self.clock.as_ref()
+            .is_some_and(|view| view.clock.adjustments_locked())
+    }
+
+    /// Take a locked action, or ask for the PIN first if the game is locked
+    fn guarded(&mut self, action: Locked) -> Command<PagesMessage> {
+        let locked = self.clock.as_ref()
+            .is_some_and(|view| self.guard.is_locked(&view.clock));
+        if !locked {
+            return self.take(action);
+        }
+        self.ask_pin(action)
+    }
+
+    /// Ask for the PIN to take a locked action
+    fn ask_pin(&mut self, action: Locked) -> Command<PagesMessage> {
+        self.unlock = Some(Unlock::new(action));
+        text_input::focus(text_input::Id::new(constants::pin::INPUT_ID))
+    }
+
+    fn take(&mut self, action: Locked) -> Command<PagesMessage> {
+        match action {
+            Locked::Reset => {
+                // The game is abandoned, so there is nothing to resume
+                let cleared = self.autosave.as_ref().map(AutoSave::clear);
+                if let Some(Err(error)) = cleared {
+                    eprintln!("Could not remove the recovery file: {}", error);
+                }
+                self.clock = None;
+                self.settings = ChessClockSettings::new(self.normalization);
+                Command::none()
+            },
+            Locked::Quit => {
+                self.save_game(true);
+                window::close(window::Id::MAIN)
+            },
+            Locked::AddTime(player, time) => self.update(
+                PagesMessage::ClockMessage(
+                    ChessClockViewMessage::AddTime(player, time)
+                )
+            ),
+            Locked::SubtractTime(player, time) => self.update(
+                PagesMessage::ClockMessage(
+                    ChessClockViewMessage::SubtractTime(player, time)
+                )
+            ),
+        }
+    }
+
+    /// Create a stopped clock for a new game, at the speed asked for
+    fn new_clock(&self, rules: Rules) -> Result<ChessClock, RulesError> {
+        match &self.speed {
+            Some(speed) => ChessClock::try_with_source(rules, speed.clone()),
+            None => ChessClock::try_new(rules),
+        }
+    }
+
+    /// Show the clock for a game, counting down first if `warm_up` is given
+    fn open_clock(&mut self, mut clock: ChessClock, warm_up: Option<PreGame>) {
+        let clock_now = clock.now();
+        clock.set_tick_watchdog(Some(constants::watchdog::THRESHOLD));
+        clock.set_arbiter(self.guard.clone());
+        self.clock = Some(ChessClockView {
+            clock,
+            face: self.settings.face,
+            touch: self.touch,
+            countdown: (self.settings.countdown && warm_up.is_none())
+                .then(StartCountdown::new),
+            warm_up,
+            players: self.settings.players.clone(),
+            recorded: false,
+            numbers: self.settings.numbers,
+            burn_in: self.burn_in,
+            delay: self.chroma_key.and_then(|key| key.delay),
+            opened: clock_now,
+        });
+    }
+
+    /// Save the game for recovery, now or only once due, reporting rather
+    /// than failing if it can not be written
+    fn save_game(&mut self, now: bool) {
+        let (Some(autosave), Some(view)) = (&mut self.autosave, &self.clock)
+        else {
+            return;
+        };
+        let saved = match now {
+            true => autosave.save(&view.clock),
+            false => autosave.save_if_due(&view.clock).map(|_| ()),
+        };
+        if let Err(error) = saved {
+            eprintln!("Could not save the game for recovery: {}", error);
+        }
+    }
+
+    /// Save the player profiles, reporting rather than failing if they can
+    /// not be written, as the game itself is unaffected
+    fn save_profiles(&self) {
+        let Some(path) = &self.profiles_path else {
+            return;
+        };
+        if let Err(error) = self.profiles.save(path) {
+            eprintln!("Could not save player profiles: {}", error);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ChessClockSettings {
+    rules: Rules,
+    time_strings: [String; 2],
+    increment_string: String,
+    session_string: String,
+    pause_allowance_string: String,
+    warm_up_string: String,
+    /// How long to count down to the start of the game, if at all
+    warm_up: Option<Duration>,
+    rating_strings: [String; 2],
+    face: ClockFace,
+    countdown: bool,
+    /// Whether both players are locked to the same time
+    same_time: bool,
+    /// The profile chosen for each player, if any
+    players: [Option<String>; 2],
+    new_player: String,
+    /// How the locale writes the numbers in stats
+    numbers: NumberFormat,
+    /// The text input with keyboard focus, if any
+    focused: Option<Field>,
+    /// Whether the time control designer is shown instead of the settings
+    designer: bool,
+    /// The move and increment typed for each stage on the designer
+    stage_strings: Vec<[String; 2]>,
+    /// Why the stages typed on the designer can not be used, if they can't
+    stage_error: Option<String>,
+    /// How the times entered are rounded before the game
+    normalization: NormalizationPolicy,
+}
+
+/// The text inputs of the settings page, in the order Tab moves through them
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    NewPlayer,
+    Time(Player),
+    Rating(Player),
+    Increment,
+    Session,
+    WarmUp,
+    PauseAllowance,
+}
+
+impl Field {
+    const ALL: [Self; 9] = [
+        Self::NewPlayer,
+        Self::Time(Player::Player1),
+        Self::Time(Player::Player2),
+        Self::Rating(Player::Player1),
+        Self::Rating(Player::Player2),
+        Self::Increment,
+        Self::Session,
+        Self::WarmUp,
+        Self::PauseAllowance,
+    ];
+
+    fn id(self) -> text_input::Id {
+        text_input::Id::new(match self {
+            Self::NewPlayer => "new-player",
+            Self::Time(Player::Player1) => "time-1",
+            Self::Time(Player::Player2) => "time-2",
+            Self::Rating(Player::Player1) => "rating-1",
+            Self::Rating(Player::Player2) => "rating-2",
+            Self::Increment => "increment",
+            Self::Session => "session",
+            Self::WarmUp => "warm-up",
+            Self::PauseAllowance => "pause-allowance",
+        })
+    }
+
+    fn from_id(id: &Id) -> Option<Self> {
+        Self::ALL.into_iter().find(|field| Id::from(field.id()) == *id)
+    }
+
+    /// Get the message that sets the field to the given text
+    fn changed(self, value: String) -> SettingsMessage {
+        match self {
+            Self::NewPlayer => SettingsMessage::ChangeNewPlayer(value),
+            Self::Time(player) => SettingsMessage::ChangeTime(player, value),
+            Self::Rating(player) => {
+                SettingsMessage::ChangeRating(player, value)
+            },
+            Self::Increment => SettingsMessage::ChangeIncrement(value),
+            Self::Session => SettingsMessage::ChangeSession(value),
+            Self::WarmUp => SettingsMessage::ChangeWarmUp(value),
+            Self::PauseAllowance => {
+                SettingsMessage::ChangePauseAllowance(value)
+            },
+        }
+    }
+
+    /// Get how far a stepper moves the field, for the fields that have one
+    fn step_size(self) -> Option<u64> {
+        match self {
+            Self::Time(_) => Some(constants::settings::TIME_STEP_MINUTES),
+            Self::Increment => {
+                Some(constants::settings::INCREMENT_STEP_SECONDS)
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Which way a stepper moves a number
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone)]
+enum SettingsMessage {
+    ChangeTime(Player, String),
+    SwapTimes,
+    ToggleSameTime(bool),
+    ChangeIncrement(String),
+    ChangeSession(String),
+    ChangeWarmUp(String),
+    ChangePauseAllowance(String),
+    ChangeRating(Player, String),
+    ToggleAdjudicate(bool),
+    ChangeTimingMethod(TimingMethod),
+    ChangeActivePlayer(Player),
+    ChangeClockFace(ClockFace),
+    ChangeStoppedSwitch(StoppedSwitch),
+    ToggleCountdown(bool),
+    ToggleStrict(bool),
+    ChoosePlayer(Player, String),
+    ChangeNewPlayer(String),
+    AddPlayer,
+    FocusNext,
+    FocusPrevious,
+    FindFocus,
+    Focused(Option<Field>),
+    ClearFocused,
+    Step(Field, Step),
+    StepFocused(Step),
+    OpenDesigner,
+    CloseDesigner,
+    AddStage,
+    RemoveStage(usize),
+    ChangeStageMove(usize, String),
+    ChangeStageIncrement(usize, String),
+    InitialiseClock,
+}
+
+impl ChessClockSettings {
+    /// Produce the time selector elements for a player
+    ///
+    /// # Arguments
+    ///
+    /// * `player` - The player to select the time for
+    ///
+    /// # Returns
+    ///
+    /// * A tuple of three elements:
+    ///     * A label for the time input element
+    ///     * A text input for the time input element
+    ///     * A printout of the time
+    fn time_selector(&self, player: Player) -> (
+        Element<'_, SettingsMessage>,
+        Element<'_, SettingsMessage>,
+        Element<'_, SettingsMessage>
+    ) {
+        let index = player.index();
+        let time_str = &self.time_strings[index];
+        let placeholder = format!("Enter {} Time (minutes)", player);
+
+        let mut time_input = text_input(&placeholder, time_str)
+            .id(Field::Time(player).id())
+            .width(constants::settings::TEXTBOX_WIDTH);
+        // While the times are locked, player 2's field follows player 1's
+        let editable = !self.same_time || player == Player::Player1;
+        if editable {
+            time_input = time_input.on_input(
+                move |input| SettingsMessage::ChangeTime(player, input)
+            );
+        }
+        let time_input = Self::stepper(
+            time_input, Field::Time(player), editable
+        );
+
+        let time_label = text(format!("{} Time", player).as_str());
+
+        let time_printout = text(
+            DurationDisplay::from(self.rules.get_time(player)).to_string()
+        );
+
+        (time_label.into(), time_input, time_printout.into())
+    }
+
+    /// Put - and + buttons beside a number input, for setting it without
+    /// typing
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The text input to step
+    /// * `field` - The field the input edits
+    /// * `enabled` - Whether the buttons can be pressed
+    ///
+    /// # Returns
+    ///
+    /// * The input with its stepper buttons
+    fn stepper<'a>(
+        input: text_input::TextInput<'a, SettingsMessage>,
+        field: Field,
+        enabled: bool
+    ) -> Element<'a, SettingsMessage> {
+        let step_button = |label, step| {
+            button(
+                text(label).horizontal_alignment(alignment::Horizontal::Center)
+            )
+                .on_press_maybe(
+                    enabled.then_some(SettingsMessage::Step(field, step))
+                )
+                .width(constants::settings::STEPPER_WIDTH)
+                .style(theme::Button::Secondary)
+        };
+
+        row![input, step_button("-", Step::Down), step_button("+", Step::Up)]
+            .format(
+                alignment::Alignment::Center,
+                constants::settings::MINOR_ROW_SPACING
+            )
+            .into()
+    }
+
+    /// Produce the increment selector element
+    ///
+    /// # Returns
+    ///
+    /// * A tuple of three elements:
+    ///     * A label for the text input element
+    ///     * A text input element
+    ///     * A printout of current value of the increment
+    fn increment_selector(&self) -> (
+        Element<'_, SettingsMessage>,
+        Element<'_, SettingsMessage>,
+        Element<'_, SettingsMessage>
+    ) {
+        let increment = &self.increment_string;
+        let placeholder = "Enter Increment (seconds)";
+
+        let increment_input = text_input(placeholder, increment)
+            .id(Field::Increment.id())
+            .on_input(SettingsMessage::ChangeIncrement)
+            .width(constants::settings::TEXTBOX_WIDTH);
+        let increment_input = Self::stepper(
+            increment_input, Field::Increment, true
+        );
+
+        let increment_label = text("Increment");
+
+        let increment = self.rules.get_increment();
+        let printout_text = DurationDisplay::from(increment).to_string();
+        let increment_printout = text(printout_text);
+
+        (
+            increment_label.into(),
+            increment_input,
+            increment_printout.into()
+        )
+    }
+
+    /// Produce the rating inputs, which set the times to give odds to the
+    /// weaker player
+    ///
+    /// # Returns
+    ///
+    /// * A tuple of two elements:
+    ///     * A label for the rating inputs
+    ///     * A text input for each player's rating
+    fn rating_selector(&self) -> (
+        Element<'_, SettingsMessage>,
+        Element<'_, SettingsMessage>
+    ) {
+        let rating_input = |player: Player| {
+            text_input(
+                &format!("{} rating", player),
+                &self.rating_strings[player.index()]
+            )
+                .id(Field::Rating(player).id())
+                .on_input(move |input| {
+                    SettingsMessage::ChangeRating(player, input)
+                })
+                .width(constants::settings::RATING_WIDTH)
+        };
+        let inputs = row![
+            rating_input(Player::Player1), rating_input(Player::Player2)
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::INPUT_ELEMENT_SPACING
+        );
+        (text("Handicap by Rating").into(), inputs.into())
+    }
+
+    /// Produce the session budget selector element
+    ///
+    /// # Returns
+    ///
+    /// * A tuple of three elements:
+    ///     * A label for the text input element
+    ///     * A text input element, with a checkbox for whether the game is
+    ///       adjudicated when the session ends
+    ///     * A printout of the current session budget
+    fn session_selector(&self) -> (
+        Element<'_, SettingsMessage>,
+        Element<'_, SettingsMessage>,
+        Element<'_, SettingsMessage>
+    ) {
+        let budget = self.rules.get_session_budget();
+        let session_input = text_input(
+            "No limit (minutes)", &self.session_string
+        )
+            .id(Field::Session.id())
+            .on_input(SettingsMessage::ChangeSession)
+            .width(constants::settings::TEXTBOX_WIDTH);
+        let adjudicate = budget.is_some_and(|budget| budget.get_adjudicate());
+        let mut adjudicate_checkbox = checkbox(
+            "Adjudicate at the end", adjudicate
+        );
+        // Adjudication only means something when there is a session
+        if budget.is_some() {
+            adjudicate_checkbox = adjudicate_checkbox
+                .on_toggle(SettingsMessage::ToggleAdjudicate);
+        }
+
+        let label = text("Session");
+        let printout = text(budget.map_or("No limit".to_string(), |budget| {
+            DurationDisplay::from(budget.get_time()).to_string()
+        }));
+
+        (
+            label.into(),
+            row![session_input, adjudicate_checkbox].format(
+                alignment::Alignment::Center,
+                constants::settings::INPUT_ELEMENT_SPACING
+            ).into(),
+            printout.into()
+        )
+    }
+
+    /// Produce the player profile selector elements
+    ///
+    /// # Arguments
+    ///
+    /// * `profiles` - The profiles that can be chosen
+    /// * `games` - The past games of the players, if they are kept
+    ///
+    /// # Returns
+    ///
+    /// * A tuple of three elements:
+    ///     * A label for the player pick lists
+    ///     * A pick list for each player, then a text input and button to
+    ///       add a new player
+    ///     * A printout of the chosen players' lifetime stats
+    fn player_selector<'a>(
+        &'a self, profiles: &'a Profiles, games: Option<&GameStore>
+    ) -> (
+        Element<'a, SettingsMessage>,
+        Element<'a, SettingsMessage>,
+        Element<'a, SettingsMessage>
+    ) {
+        let names: Vec<String> = profiles.players().iter()
+            .map(|profile| profile.name().to_string())
+            .collect();
+        let player_list = |player: Player| {
+            pick_list(
+                names.clone(),
+                self.players[player.index()].clone(),
+                move |name| SettingsMessage::ChoosePlayer(player, name)
+            )
+                .placeholder(player.to_string())
+                .width(constants::settings::PLAYER_WIDTH)
+        };
+        let new_player = text_input("New player", &self.new_player)
+            .id(Field::NewPlayer.id())
+            .on_input(SettingsMessage::ChangeNewPlayer)
+            .on_submit(SettingsMessage::AddPlayer)
+            .width(constants::settings::PLAYER_WIDTH);
+        let add_button = button(text("Add"))
+            .on_press(SettingsMessage::AddPlayer)
+            .style(theme::Button::Secondary);
+
+        let stats = self.players.iter()
+            .flatten()
+            .filter_map(|name| profiles.get(name))
+            .map(|profile| profile_summary(profile, games, &self.numbers))
+            .collect::<Vec<_>>()
+            .join("  |  ");
+
+        (
+            text("Players").into(),
+            row![
+                player_list(Player::Player1), player_list(Player::Player2),
+                new_player, add_button
+            ].format(
+                alignment::Alignment::Center,
+                constants::settings::INPUT_ELEMENT_SPACING
+            ).into(),
+            text(stats).into()
+        )
+    }
+
+    /// Produce the timing method selector element
+    ///
+    /// # Returns
+    ///
+    /// * An array of three elements:
+    ///     * A label for the timing method selector element
+    ///     * A pick list for the timing method selector element
+    ///     * A printout of the current timing method
+    fn timing_method_selector(&self) -> (
+        Element<'_, SettingsMessage>,
+        Element<'_, SettingsMessage>,
+        Element<'_, SettingsMessage>
+    ) {
+        let pick_list = pick_list(
+            &TimingMethod::ALL[..],
+            Some(self.rules.get_timing_method()),
+            SettingsMessage::ChangeTimingMethod
+        );
+
+        let label = text("Timing Method");
+        let printout = text(self.rules.get_timing_method().to_string());
+
+        (label.into(), pick_list.into(), printout.into())
+    }
+
+    /// Produce the active player selector element
+    ///
+    /// # Returns
+    ///
+    /// * A tuple of three elements:
+    ///     * A label for the active player selector element
+    ///     * A pick list for the active player selector element
+    ///     * A printout of the current active player
+    fn active_player_selector(&self) -> (
+        Element<'_, SettingsMessage>,
+        Element<'_, SettingsMessage>,
+        Element<'_, SettingsMessage>
+    ) {
+        let pick_list = pick_list(
+            &Player::ALL[..],
+            Some(self.rules.get_starter()),
+            SettingsMessage::ChangeActivePlayer
+        );
+
+        let label = text("Active Player");
+        let printout = text(self.rules.get_starter().to_string());
+        (label.into(), pick_list.into(), printout.into())
+    }
+
+    /// Produce the clock face selector element
+    ///
+    /// # Returns
+    ///
+    /// * A tuple of three elements:
+    ///     * A label for the clock face selector element
+    ///     * A pick list for the clock face selector element
+    ///     * A printout of the current clock face
+    fn clock_face_selector(&self) -> (
+        Element<'_, SettingsMessage>,
+        Element<'_, SettingsMessage>,
+        Element<'_, SettingsMessage>
+    ) {
+        let pick_list = pick_list(
+            &ClockFace::ALL[..],
+            Some(self.face),
+            SettingsMessage::ChangeClockFace
+        );
+
+        let label = text("Clock Face");
+        let printout = text(self.face.to_string());
+        (label.into(), pick_list.into(), printout.into())
+    }
+
+    /// Produce the selector for what a switch does while stopped
+    ///
+    /// # Returns
+    ///
+    /// * A tuple of three elements:
+    ///     * A label for the selector element
+    ///     * A pick list for the selector element
+    ///     * A printout of the current behaviour
+    fn stopped_switch_selector(&self) -> (
+        Element<'_, SettingsMessage>,
+        Element<'_, SettingsMessage>,
+        Element<'_, SettingsMessage>
+    ) {
+        let pick_list = pick_list(
+            &StoppedSwitch::ALL[..],
+            Some(self.rules.get_stopped_switch()),
+            SettingsMessage::ChangeStoppedSwitch
+        );
+
+        let label = text("Switch While Stopped");
+        let printout = text(self.rules.get_stopped_switch().to_string());
+        (label.into(), pick_list.into(), printout.into())
+    }
+
+    /// Produce the start button element
+    ///
+    /// # Returns
+    ///
+    /// * A button that produces a message to start the clock and move to the
+    ///   chess clock view page. If the rules are invalid the button is
+    ///   disabled, and the reason is shown above it. If the times entered
+    ///   are to be rounded, what they are rounded to is shown instead.
+    fn start_button(&self) -> Element<'_, SettingsMessage> {
+        let normalized = self.normalized();
+        let error = self.stage_error.clone()
+            .or_else(|| normalized.validate().err().map(|e| e.to_string()));
+        let button = button(text("Start clock"))
+            .on_press_maybe(
+                error.is_none().then_some(SettingsMessage::InitialiseClock)
+            )
+            .style(theme::Button::Primary)
+            .padding(constants::SPACING);
+
+        let note = match error {
+            None if normalized == self.rules => return button.into(),
+            None => text(format!(
+                "Times rounded to {} / {}, increment {}",
+                DurationDisplay::from(normalized.get_player1_time()),
+                DurationDisplay::from(normalized.get_player2_time()),
+                DurationDisplay::from(normalized.get_increment())
+            )),
+            Some(error) => text(error).style(theme::Text::Color(
+                Color::from_rgb(constants::warning::RED, 0.0, 0.0)
+            )),
+        };
+        column![note, button].format(
+            alignment::Alignment::Center,
+            constants::settings::MINOR_ROW_SPACING
+        ).into()
+    }
+
+    /// Use the time control of a preset, keeping the other settings
+    ///
+    /// # Arguments
+    ///
+    /// * `preset` - The rules of the preset
+    fn apply_preset(&mut self, preset: Rules) {
+        let starter = self.rules.get_starter();
+        let stopped_switch = self.rules.get_stopped_switch();
+        let session_budget = self.rules.get_session_budget();
+        let pause_allowance = self.rules.get_pause_allowance();
+        self.rules = preset;
+        self.rules.set_starter(starter);
+        self.rules.set_stopped_switch(stopped_switch);
+        self.rules.set_session_budget(session_budget);
+        self.rules.set_pause_allowance(pause_allowance);
+        self.same_time = false;
+        for player in Player::ALL {
+            self.time_strings[player.index()] =
+                whole_minutes(self.rules.get_time(player));
+        }
+        self.increment_string = self.rules.get_increment().as_secs()
+            .to_string();
+        self.clear_stages();
+    }
+
+    /// Forget the stages typed on the designer, for rules that have none
+    fn clear_stages(&mut self) {
+        self.stage_strings.clear();
+        self.stage_error = None;
+    }
+
+    /// Use the stages typed on the designer in the rules
+    ///
+    /// A stage left blank is skipped. If any stage can't be used, the rules
+    /// are left with no stages and the first problem is kept to show.
+    fn apply_stages(&mut self) {
+        let stages = self.stage_strings.iter()
+            .filter(|strings| strings.iter().any(|s| !s.trim().is_empty()))
+            .map(|[from_move, increment]| {
+                let from_move = from_move.trim().parse::<u32>().ok()
+                    .filter(|from_move| *from_move > 0)
+                    .ok_or_else(|| {
+                        format!("\"{}\" is not a move number", from_move)
+                    })?;
+                let increment = increment.trim().parse::<u64>()
+                    .map_err(|_| format!(
+                        "\"{}\" is not a whole number of seconds", increment
+                    ))?;
+                Ok(IncrementStage::new(from_move, increment.seconds()))
+            })
+            .collect::<Result<Vec<_>, String>>();
+        let applied = stages.and_then(|stages| {
+            self.rules.set_increment_stages(&stages)
+                .map_err(|error| error.to_string())
+        });
+        self.stage_error = applied.err();
+        if self.stage_error.is_some() {
+            // Nothing is left over from stages that have since changed
+            let _ = self.rules.set_increment_stages(&[]);
+        }
+    }
+
+    /// Get the rules with the times entered rounded, to start the game with
+    fn normalized(&self) -> Rules {
+        self.rules.normalized(self.normalization)
+    }
+
+    /// Create a new instance of the chess clock settings
+    ///
+    /// # Arguments
+    ///
+    /// * `normalization` - How the times entered are rounded
+    fn new(normalization: NormalizationPolicy) -> Self {
+        let rules = Rules::default();
+        Self {
+            rules,
+            time_strings: ["".to_string(), "".to_string()],
+            increment_string: "".to_string(),
+            session_string: "".to_string(),
+            pause_allowance_string: "".to_string(),
+            warm_up_string: "".to_string(),
+            warm_up: None,
+            rating_strings: ["".to_string(), "".to_string()],
+            face: ClockFace::default(),
+            countdown: false,
+            same_time: false,
+            players: [None, None],
+            new_player: "".to_string(),
+            numbers: NumberFormat::detect(),
+            focused: None,
+            designer: false,
+            stage_strings: Vec::new(),
+            stage_error: None,
+            normalization,
+        }
+    }
+
+    /// Step a number field up or down by its step size
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The field to step, which is left alone if it has no
+    ///   stepper or can't be edited
+    /// * `step` - Which way to step
+    fn step(&mut self, field: Field, step: Step) {
+        let Some(size) = field.step_size() else { return };
+        let current = match field {
+            Field::Time(Player::Player2) if self.same_time => return,
+            Field::Time(player) => &self.time_strings[player.index()],
+            _ => &self.increment_string,
+        };
+        let value = current.trim().parse::<u64>().unwrap_or(0);
+        let value = match step {
+            // Snap to the step size, so 7 steps up to 10 and down to 5
+            Step::Up => (value / size + 1).saturating_mul(size),
+            Step::Down => value.saturating_sub(1) / size * size,
+        };
+        self.update(field.changed(value.to_string()));
+    }
+
+    /// Move the keyboard focus, or find where a click moved it to
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - `FocusNext` or `FocusPrevious` to move the focus, or
+    ///   `FindFocus` to only look for it
+    ///
+    /// # Returns
+    ///
+    /// * A command that moves the focus, then reports the focused field
+    fn change_focus(&mut self, message: SettingsMessage)
+        -> Command<SettingsMessage>
+    {
+        // Until the report, nothing is known to have focus
+        self.focused = None;
+        let moved = match message {
+            SettingsMessage::FocusNext => widget::focus_next(),
+            SettingsMessage::FocusPrevious => widget::focus_previous(),
+            _ => Command::none(),
+        };
+        let find = operation::map(
+            Box::new(operation::focusable::find_focused()),
+            |id| SettingsMessage::Focused(Field::from_id(&id))
+        );
+        Command::batch([moved, Command::widget(find)])
+    }
+
+    fn update(&mut self, message: SettingsMessage) {
+        match message {
+
+            SettingsMessage::ChangeTime(player, time) => {
+                let minutes = match time.parse::<u64>() {
+                    Ok(minutes) => minutes.minutes(),
+                    Err(_) => Duration::ZERO,
+                };
+                if self.same_time {
+                    self.time_strings = [time.clone(), time];
+                    self.rules.set_both_times(minutes);
+                } else {
+                    self.time_strings[player.index()] = time;
+                    self.rules.set_time(player, minutes);
+                }
+            },
+
+            SettingsMessage::SwapTimes => {
+                self.rules = self.rules.swapped();
+                self.time_strings.swap(0, 1);
+            },
+
+            SettingsMessage::ToggleSameTime(same_time) => {
+                self.same_time = same_time;
+                if same_time {
+                    let time = self.rules.get_time(Player::Player1);
+                    self.rules.set_both_times(time);
+                    self.time_strings[1] = self.time_strings[0].clone();
+                }
+            },
+
+            SettingsMessage::ChangeIncrement(increment) => {
+                self.increment_string = increment.clone();
+                match increment.parse::<u64>() {
+                    Ok(seconds) => {
+                        self.rules.set_increment(seconds.seconds());
+                    }
+                    Err(_) => {
+                        self.rules.set_increment(Duration::ZERO);
+                    }
+                }
+            },
+
+            SettingsMessage::ChangeSession(session) => {
+                self.session_string = session.clone();
+                let adjudicate = self.rules.get_session_budget()
+                    .is_some_and(|budget| budget.get_adjudicate());
+                let budget = session.parse::<u64>().ok()
+                    .filter(|minutes| *minutes > 0)
+                    .map(|minutes| {
+                        SessionBudget::new(minutes.minutes(), adjudicate)
+                    });
+                self.rules.set_session_budget(budget);
+            },
+
+            SettingsMessage::ChangeWarmUp(warm_up) => {
+                self.warm_up = warm_up.parse::<u64>().ok()
+                    .filter(|minutes| *minutes > 0)
+                    .map(DurationExt::minutes);
+                self.warm_up_string = warm_up;
+            },
+
+            SettingsMessage::ChangePauseAllowance(allowance) => {
+                let allowance_time = allowance.parse::<u64>().ok()
+                    .filter(|minutes| *minutes > 0)
+                    .map(DurationExt::minutes);
+                self.rules.set_pause_allowance(allowance_time);
+                self.pause_allowance_string = allowance;
+            },
+
+            SettingsMessage::ChangeRating(player, rating) => {
+                self.rating_strings[player.index()] = rating;
+                let [Ok(r1), Ok(r2)] = self.rating_strings.clone()
+                    .map(|rating| rating.parse::<u32>())
+                else {
+                    return;
+                };
+                // The weaker player keeps the longer of the current times,
+                // so typing new ratings does not compound the odds
+                let base = self.rules.get_player1_time()
+                    .max(self.rules.get_player2_time());
+                let handicap = Rules::handicap_from_ratings(r1, r2, base);
+                self.same_time = false;
+                for player in Player::ALL {
+                    let time = handicap.get_time(player);
+                    self.rules.set_time(player, time);
+                    self.time_strings[player.index()] = whole_minutes(time);
+                }
+            },
+
+            SettingsMessage::ToggleAdjudicate(adjudicate) => {
+                let budget = self.rules.get_session_budget().map(|budget| {
+                    SessionBudget::new(budget.get_time(), adjudicate)
+                });
+                self.rules.set_session_budget(budget);
+            },
+
+            SettingsMessage::ChangeTimingMethod(TimingMethod::ShotClock) => {
+                // Shot clocks run in seconds, so offer a preset rather than
+                // the minute-based time fields
+                let starter = self.rules.get_starter();
+                let stopped_switch = self.rules.get_stopped_switch();
+                let session_budget = self.rules.get_session_budget();
+                let pause_allowance = self.rules.get_pause_allowance();
+                self.rules = presets::SHOT_CLOCK_30;
+                self.rules.set_starter(starter);
+                self.rules.set_stopped_switch(stopped_switch);
+                self.rules.set_session_budget(session_budget);
+                self.rules.set_pause_allowance(pause_allowance);
+                self.time_strings = ["".to_string(), "".to_string()];
+                self.increment_string = "".to_string();
+                self.clear_stages();
+            },
+
+            SettingsMessage::ChangeTimingMethod(timing_method) => {
+                self.rules.set_timing_method(timing_method);
+            },
+
+            SettingsMessage::ChangeActivePlayer(starter) => {
+                self.rules.set_starter(starter);
+            },
+
+            SettingsMessage::ChangeClockFace(face) => {
+                self.face = face;
+            },
+
+            SettingsMessage::ChangeStoppedSwitch(stopped_switch) => {
+                self.rules.set_stopped_switch(stopped_switch);
+            },
+
+            SettingsMessage::ToggleCountdown(countdown) => {
+                self.countdown = countdown;
+            },
+
+            SettingsMessage::ToggleStrict(strict) => {
+                self.rules.set_strict(strict);
+            },
+
+            SettingsMessage::ChangeNewPlayer(name) => {
+                self.new_player = name;
+            },
+
+            SettingsMessage::Focused(field) => {
+                self.focused = field;
+            },
+
+            SettingsMessage::ClearFocused => {
+                // Escape also takes the focus away from the field
+                if let Some(field) = self.focused.take() {
+                    self.update(field.changed(String::new()));
+                }
+            },
+
+            SettingsMessage::Step(field, step) => {
+                self.step(field, step);
+            },
+
+            SettingsMessage::StepFocused(step) => {
+                if let Some(field) = self.focused {
+                    self.step(field, step);
+                }
+            },
+
+            SettingsMessage::OpenDesigner => {
+                self.designer = true;
+            },
+
+            SettingsMessage::CloseDesigner => {
+                self.designer = false;
+            },
+
+            SettingsMessage::AddStage
+                if self.stage_strings.len() < MAX_INCREMENT_STAGES =>
+            {
+                self.stage_strings.push(Default::default());
+            },
+
+            SettingsMessage::RemoveStage(index)
+                if index < self.stage_strings.len() =>
+            {
+                self.stage_strings.remove(index);
+                self.apply_stages();
+            },
+
+            SettingsMessage::ChangeStageMove(index, from_move) => {
+                if let Some(strings) = self.stage_strings.get_mut(index) {
+                    strings[0] = from_move;
+                    self.apply_stages();
+                }
+            },
+
+            SettingsMessage::ChangeStageIncrement(index, increment) => {
+                if let Some(strings) = self.stage_strings.get_mut(index) {
+                    strings[1] = increment;
+                    self.apply_stages();
+                }
+            },
+
+            _ => {}
+        }
+    }
+
+    fn subscription(&self) -> Subscription<SettingsMessage> {
+        // Tab and Shift-Tab move between the text inputs, Escape clears the
+        // one in focus, the arrows step it, and Enter starts the clock unless
+        // a field took it
+        event::listen_with(|event, status| match event {
+            iced::Event::Keyboard(keyboard::Event::KeyPressed {
+                key, modifiers, ..
+            }) => match key.as_ref() {
+                keyboard::Key::Named(keyboard::key::Named::Tab) => {
+                    Some(match modifiers.shift() {
+                        true => SettingsMessage::FocusPrevious,
+                        false => SettingsMessage::FocusNext,
+                    })
+                },
+                keyboard::Key::Named(keyboard::key::Named::Escape) => {
+                    Some(SettingsMessage::ClearFocused)
+                },
+                keyboard::Key::Named(keyboard::key::Named::ArrowUp) => {
+                    Some(SettingsMessage::StepFocused(Step::Up))
+                },
+                keyboard::Key::Named(keyboard::key::Named::ArrowDown) => {
+                    Some(SettingsMessage::StepFocused(Step::Down))
+                },
+                keyboard::Key::Named(keyboard::key::Named::Enter)
+                    if status == event::Status::Ignored =>
+                {
+                    Some(SettingsMessage::InitialiseClock)
+                },
+                _ => None,
+            },
+            // A click may focus a field, or take the focus away
+            iced::Event::Mouse(mouse::Event::ButtonPressed(_)) => {
+                Some(SettingsMessage::FindFocus)
+            },
+            _ => None,
+        })
+    }
+
+    fn view<'a>(
+        &'a self, profiles: &'a Profiles, games: Option<&GameStore>
+    ) -> Element<'a, SettingsMessage> {
+        if self.designer {
+            return self.designer_view();
+        }
+        let header_text = text("Chess Clock")
+            .horizontal_alignment(alignment::Horizontal::Center)
+            .vertical_alignment(alignment::Vertical::Center)
+            .size(constants::HEADER_SIZE)
+            .style(theme::Text::Color(Color::BLACK))
+            .width(Length::Fill);
+
+        let p1_time_elements = self.time_selector(Player::Player1);
+        let p2_time_elements = self.time_selector(Player::Player2);
+        let increment_elements = self.increment_selector();
+        let timing_method_elements = self.timing_method_selector();
+        let active_player_elements = self.active_player_selector();
+        let clock_face_elements = self.clock_face_selector();
+        let stopped_switch_elements = self.stopped_switch_selector();
+        let session_elements = self.session_selector();
+        let rating_elements = self.rating_selector();
+        let player_elements = self.player_selector(profiles, games);
+        let start_button = self.start_button();
+
+        let p1_time_row = row![
+            p1_time_elements.0, p1_time_elements.1
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::INPUT_ELEMENT_SPACING
+        );
+        let p2_time_row = row![
+            p2_time_elements.0, p2_time_elements.1
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::INPUT_ELEMENT_SPACING
+        );
+
+        let swap_button = button(text("Swap"))
+            .on_press(SettingsMessage::SwapTimes)
+            .style(theme::Button::Secondary);
+        let same_time_checkbox = checkbox("Same for both", self.same_time)
+            .on_toggle(SettingsMessage::ToggleSameTime);
+
+        let timing_row = row![
+            p1_time_row, swap_button, p2_time_row, same_time_checkbox
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::BETWEEN_ELEMENT_SPACING
+        );
+
+        let player_row = column![
+            row![player_elements.0, player_elements.1].format(
+                alignment::Alignment::Center,
+                constants::settings::INPUT_ELEMENT_SPACING
+            ),
+            player_elements.2
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::MINOR_ROW_SPACING
+        );
+
+        let rating_row = row![
+            rating_elements.0, rating_elements.1
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::INPUT_ELEMENT_SPACING
+        );
+
+        let designer_button = button(text("Stages"))
+            .on_press(SettingsMessage::OpenDesigner)
+            .style(theme::Button::Secondary);
+        let increment_row = row![
+            increment_elements.0, increment_elements.1, designer_button
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::INPUT_ELEMENT_SPACING
+        );
+        let timing_method_row = row![
+            timing_method_elements.0,
+            timing_method_elements.1,
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::INPUT_ELEMENT_SPACING
+        );
+        let active_player_row = row![
+            active_player_elements.0,
+            active_player_elements.1,
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::INPUT_ELEMENT_SPACING
+        );
+
+        let clock_face_row = row![
+            clock_face_elements.0,
+            clock_face_elements.1,
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::INPUT_ELEMENT_SPACING
+        );
+
+        let stopped_switch_row = row![
+            stopped_switch_elements.0,
+            stopped_switch_elements.1,
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::INPUT_ELEMENT_SPACING
+        );
+
+        let settings_row = row![
+            increment_row, timing_method_row, active_player_row,
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::BETWEEN_ELEMENT_SPACING
+        );
+
+        let session_row = row![
+            session_elements.0,
+            session_elements.1,
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::INPUT_ELEMENT_SPACING
+        );
+
+        let display_row = row![
+            clock_face_row, stopped_switch_row, session_row
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::BETWEEN_ELEMENT_SPACING
+        );
+
+        let summary_box = row![
+            column![
+                text("Player 1 Time"), text("Player 2 Time"),
+                text("Increment"), text("Timing Method"), text("Starter"),
+                text("Clock Face"), text("Switch Stopped"), text("Session")
+            ].format(
+                alignment::Alignment::Start,
+                constants::settings::MINOR_ROW_SPACING
+            ).width(125),
+            column![
+                p1_time_elements.2, p2_time_elements.2,
+                increment_elements.2, timing_method_elements.2,
+                active_player_elements.2, clock_face_elements.2,
+                stopped_switch_elements.2, session_elements.2
+            ].format(
+                alignment::Alignment::End,
+                constants::settings::MINOR_ROW_SPACING
+            ).width(200)
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::BETWEEN_ELEMENT_SPACING
+        );
+
+
+        container(column![
+            header_text,
+            column![
+                player_row,
+                timing_row,
+                rating_row,
+                settings_row,
+                display_row,
+            ].format(
+                alignment::Alignment::Center,
+                constants::settings::BETWEEN_ELEMENT_SPACING
+            ),
+            summary_box,
+            row![
+                checkbox(
+                    "3-2-1 countdown before the first move", self.countdown
+                ).on_toggle(SettingsMessage::ToggleCountdown),
+                text("Warm-up"),
+                text_input("None (minutes)", &self.warm_up_string)
+                    .id(Field::WarmUp.id())
+                    .on_input(SettingsMessage::ChangeWarmUp)
+                    .width(constants::warm_up::INPUT_WIDTH),
+                text("Breaks"),
+                text_input("None (minutes)", &self.pause_allowance_string)
+                    .id(Field::PauseAllowance.id())
+                    .on_input(SettingsMessage::ChangePauseAllowance)
+                    .width(constants::warm_up::INPUT_WIDTH),
+            ].format(
+                alignment::Alignment::Center,
+                constants::settings::INPUT_ELEMENT_SPACING
+            ),
+            checkbox(
+                "Strict: no adjustments after the first move without the \
+                 arbiter PIN",
+                self.rules.get_strict()
+            ).on_toggle(SettingsMessage::ToggleStrict),
+            start_button
+        ].align_items(alignment::Alignment::Center)
+            .spacing(constants::settings::MAJOR_ROW_SPACING))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .into()
+    }
+
+    /// Produce the time control designer, where the increment can be set to
+    /// change at later moves
+    fn designer_view(&self) -> Element<'_, SettingsMessage> {
+        let header_text = text("Time Control")
+            .horizontal_alignment(alignment::Horizontal::Center)
+            .size(constants::HEADER_SIZE)
+            .style(theme::Text::Color(Color::BLACK))
+            .width(Length::Fill);
+
+        let timing_method_elements = self.timing_method_selector();
+        let timing_method_row = row![
+            timing_method_elements.0, timing_method_elements.1
+        ].format(
+            alignment::Alignment::Center,
+            constants::settings::INPUT_ELEMENT_SPACING
+        );
+
+        let first = text(format!(
+            "Until the first stage: +{}s",
+            self.rules.get_increment().as_secs()
+        ));
+        let stages = self.stage_strings.iter().enumerate()
+            .map(|(index, [from_move, increment])| {
+                row![
+                    text("From move"),
+                    text_input("Move", from_move)
+                        .on_input(move |input| {
+                            SettingsMessage::ChangeStageMove(index, input)
+                        })
+                        .width(constants::settings::STAGE_WIDTH),
+                    text("increment"),
+                    text_input("Seconds", increment)
+                        .on_input(move |input| {
+                            SettingsMessage::ChangeStageIncrement(index, input)
+                        })
+                        .width(constants::settings::STAGE_WIDTH),
+                    button(text("Remove"))
+                        .on_press(SettingsMessage::RemoveStage(index))
+                        .style(theme::Button::Secondary),
+                ].format(
+                    alignment::Alignment::Center,
+                    constants::settings::INPUT_ELEMENT_SPACING
+                ).into()
+            });
+        let stages = iced::widget::Column::with_children(stages).format(
+            alignment::Alignment::Center,
+            constants::settings::MINOR_ROW_SPACING
+        );
+        let add_button = button(text("Add stage"))
+            .on_press_maybe(
+                (self.stage_strings.len() < MAX_INCREMENT_STAGES)
+                    .then_some(SettingsMessage::AddStage)
+            )
+            .style(theme::Button::Secondary);
+
+        let preview = text(self.rules.to_notation())
+            .size(constants::TEXT_SIZE);
+        let error = text(self.stage_error.as_deref().unwrap_or_default())
+            .style(theme::Text::Color(
+                Color::from_rgb(constants::warning::RED, 0.0, 0.0)
+            ));
+        let done_button = button(text("Done"))
+            .on_press(SettingsMessage::CloseDesigner)
+            .style(theme::Button::Primary)
+            .padding(constants::SPACING);
+
+        container(column![
+            header_text,
+            timing_method_row,
+            column![first, stages, add_button].format(
+                alignment::Alignment::Center,
+                constants::settings::BETWEEN_ELEMENT_SPACING
+            ),
+            preview,
+            error,
+            done_button,
+        ].align_items(alignment::Alignment::Center)
+            .spacing(constants::settings::MAJOR_ROW_SPACING))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .into()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ChessClockViewMessage {
+    Update,
+    SwitchPlayer,
+    Extend,
+    AddTime(Player, Duration),
+    SubtractTime(Player, Duration),
+    Finish,
+    ToggleStartStop,
+    TakeBreak,
+    ToggleSpectator,
+    Rematch,
+    ResetClock,
+}
+
+#[derive(Debug)]
+struct ChessClockView {
+    clock: ChessClock,
+    face: ClockFace,
+    /// The gesture thresholds, if the touchscreen layout is used
+    touch: Option<Thresholds>,
+    countdown: Option<StartCountdown>,
+    /// The countdown to the start of the game, until it has started it
+    warm_up: Option<PreGame>,
+    /// The profile of each player, if any
+    players: [Option<String>; 2],
+    /// Whether the finished game has been added to the players' profiles
+    recorded: bool,
+    numbers: NumberFormat,
+    /// How the clocks are kept from burning in, if they are
+    burn_in: Option<BurnIn>,
+    /// How far behind the game the chroma key clocks are shown, if they are
+    delay: Option<Duration>,
+    /// When the clock was first shown, from which an unstarted clock has
+    /// been stopped
+    opened: Instant,
+}
+
+/// A "3, 2, 1" countdown run before the clock first starts
+///
+/// Each count is marked with the terminal bell, as iced has no audio
+/// support of its own.
+#[derive(Debug)]
+struct StartCountdown {
+    timer: IntervalTimer,
+    last_count: Option<usize>,
+}
+
+impl StartCountdown {
+    fn new() -> Self {
+        let counts = constants::countdown::COUNTS.iter()
+            .map(|count| Interval::new(count, times::ONE_SECOND))
+            .collect();
+        Self { timer: IntervalTimer::new(counts), last_count: None }
+    }
+
+    /// The count currently showing, if the countdown is running
+    fn count(&self) -> Option<&str> {
+        if !self.timer.is_running() {
+            return None;
+        }
+        self.timer.current().map(|(_, interval, _)| interval.label())
+    }
+
+    /// Ring the bell on each new count and at the end
+    ///
+    /// # Returns
+    ///
+    /// * `true` once the countdown has finished
+    fn update(&mut self) -> bool {
+        let index = self.timer.current().map(|(index, _, _)| index);
+        if index != self.last_count {
+            print!("\x07");
+            io::stdout().flush().ok();
+            self.last_count = index;
+        }
+        self.timer.is_finished()
+    }
+}
+
+impl ChessClockView {
+    fn update(&mut self, message: ChessClockViewMessage) {
+        match message {
+            // Nobody is on move until the warm-up has started the game
+            ChessClockViewMessage::SwitchPlayer if self.warm_up.is_none() => {
+                self.clock.switch_player();
+            },
+            ChessClockViewMessage::Extend => {
+                self.clock.extend();
+            },
+            // Adjustments from the GUI always have a reason, so can only be
+            // refused by strict rules, which the clock records
+            ChessClockViewMessage::AddTime(player, time) => {
+                let reason = Some(constants::adjust::REASON);
+                let _ = self.clock.add_time(player, time, reason);
+            },
+            ChessClockViewMessage::SubtractTime(player, time) => {
+                let reason = Some(constants::adjust::REASON);
+                let _ = self.clock.subtract_time(player, time, reason);
+            },
+            ChessClockViewMessage::Finish => {
+                self.clock.finish();
+            },
+            // The break is charged to the player on move, who is the one
+            // most likely to leave the board
+            ChessClockViewMessage::TakeBreak => {
+                self.clock.pause_for(self.clock.active_player());
+            },
+            ChessClockViewMessage::Rematch
+                if self.clock.status() == Status::Finished =>
+            {
+                let Ok(mut clock) = ChessClock::try_with_source(
+                    self.clock.rules().swapped(), self.clock.source().clone()
+                ) else {
+                    return;
+                };
+                clock.set_arbiter(self.clock.arbiter().clone());
+                self.clock = clock;
+                self.clock
+                    .set_tick_watchdog(Some(constants::watchdog::THRESHOLD));
+                self.countdown = self.countdown.take()
+                    .map(|_| StartCountdown::new());
+                // The players swap sides along with the times
+                self.players.swap(0, 1);
+                self.recorded = false;
+            },
+            ChessClockViewMessage::Update => {
+                let started = self.warm_up.as_mut()
+                    .is_some_and(|warm_up| warm_up.hand_off(&mut self.clock));
+                if started {
+                    self.warm_up = None;
+                }
+                if let Some(countdown) = &mut self.countdown {
+                    if countdown.update() {
+                        self.countdown = None;
+                        self.clock.start();
+                    }
+                }
+                self.clock.update();
+            },
+            // The arbiter can hold the warm-up, such as for a late board
+            ChessClockViewMessage::ToggleStartStop
+                if self.warm_up.is_some() =>
+            {
+                if let Some(warm_up) = &mut self.warm_up {
+                    if warm_up.is_running() {
+                        warm_up.stop();
+                    } else {
+                        warm_up.start();
+                    }
+                }
+            },
+            ChessClockViewMessage::ToggleStartStop => {
+                match (&mut self.countdown, self.clock.status()) {
+                    // A second press during the countdown cancels it
+                    (Some(countdown), _) if countdown.timer.is_running() => {
+                        countdown.timer.stop();
+                        countdown.timer.reset();
+                        countdown.last_count = None;
+                    },
+                    (Some(countdown), Status::Stopped) => {
+                        countdown.timer.start();
+                    },
+                    (None, Status::Stopped) => self.clock.start(),
+                    _ => self.clock.stop(),
+                }
+            },
+            _ => {}
+        }
+    }
+
+    // Subscription is used to update the clock every 100 milliseconds, or
+    // every 10 in a time scramble, and to listen for keyboard input
+    fn subscription(&self) -> iced::Subscription<ChessClockViewMessage> {
+        let counting_down = self.countdown.as_ref()
+            .is_some_and(|countdown| countdown.timer.is_running());
+        let in_scramble = self.clock.view_model().players.iter()
+            .any(|side| side.warning >= WarningLevel::Scramble);
+        let warming_up = self.warm_up
+            .is_some_and(|warm_up| warm_up.is_running());
+        let update = match self.clock.status() {
+            _ if counting_down || warming_up => {
+                time::every(100.millis())
+                    .map(|_| ChessClockViewMessage::Update)
+            }
+            // Hundredths need a faster redraw to be readable
+            Status::Running if in_scramble => {
+                time::every(10.millis())
+                    .map(|_| ChessClockViewMessage::Update)
+            }
+            Status::Running => {
+                time::every(100.millis())
+                    .map(|_| ChessClockViewMessage::Update)
+            }
+            // The session runs on while the clock is stopped, as does a
+            // break against the allowance
+            Status::Stopped if self.clock.session_remaining().is_some()
+                || self.clock.on_break().is_some() =>
+            {
+                time::every(100.millis())
+                    .map(|_| ChessClockViewMessage::Update)
+            }
+            // A delayed display carries on until it has caught up
+            _ if self.delay.is_some_and(|delay| {
+                DelayedView::new(&self.clock, delay).is_catching_up()
+            }) => {
+                time::every(100.millis())
+                    .map(|_| ChessClockViewMessage::Update)
+            }
+            // Stopped clocks still move and dim to protect the display
+            _ if self.burn_in.is_some() => {
+                time::every(1.seconds())
+                    .map(|_| ChessClockViewMessage::Update)
+            }
+            _ => Subscription::none(),
+        };
+
+        let keypress = keyboard::on_key_press(
+            move |key: keyboard::Key, _modifiers: keyboard::Modifiers| {
+                match key.as_ref() {
+                    keyboard::Key::Named(keyboard::key::Named::Space) => {
+                        Some(ChessClockViewMessage::SwitchPlayer)
+                    },
+                    keyboard::Key::Named(keyboard::key::Named::Enter) => {
+                        Some(ChessClockViewMessage::ToggleStartStop)
+                    },
+                    keyboard::Key::Named(keyboard::key::Named::Backspace) => {
+                        Some(ChessClockViewMessage::Finish)
+                    }
+                    keyboard::Key::Character("q") => {
+                        Some(ChessClockViewMessage::ResetClock)
+                    }
+                    keyboard::Key::Character("e") => {
+                        Some(ChessClockViewMessage::Extend)
+                    }
+                    keyboard::Key::Character("d") => {
+                        Some(ChessClockViewMessage::ToggleSpectator)
+                    }
+                    keyboard::Key::Character("r") => {
+                        Some(ChessClockViewMessage::Rematch)
+                    }
+                    keyboard::Key::Character("b") => {
+                        Some(ChessClockViewMessage::TakeBreak)
+                    }
+                    _ => None
+                }
+            }
+        );
+
+
+        Subscription::batch(vec![
+            update,
+            keypress
+        ])
+    }
+
+    fn view(&self) -> Element<'_, ChessClockViewMessage, Theme, Renderer> {
+        self.protect(self.page_view())
+    }
+
+    fn page_view(
+        &self
+    ) -> Element<'_, ChessClockViewMessage, Theme, Renderer> {
+        if let Some(count) = self.countdown.as_ref()
+            .and_then(StartCountdown::count)
+        {
+            return container(
+                text(count)
+                    .size(constants::countdown::TEXT_SIZE)
+                    .style(theme::Text::Color(Color::BLACK))
+            )
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .into();
+        }
+
+        if let Some(warm_up) = self.warm_up_view() {
+            return warm_up;
+        }
+
+        if let Some(thresholds) = self.touch {
+            return self.touch_view(thresholds);
+        }
+
+        let clock = &self.clock;
+        let active_player = clock.active_player();
+
+        let header_text = button(
+            text("Chess Clock")
+                .horizontal_alignment(alignment::Horizontal::Center)
+                .vertical_alignment(alignment::Vertical::Center)
+                .size(constants::HEADER_SIZE)
+                .style(theme::Text::Color(Color::BLACK))
+        )
+        .width(2 * constants::WIDTH + constants::SPACING)
+        .style(theme::Button::Text);
+
+        let time_button = |player: Player| {
+            self.clock_button(player, constants::CLOCK_TEXT_SIZE)
+                .width(constants::WIDTH)
+                .height(constants::HEIGHT)
+        };
+
+        let player_text = |name: &str| {
+            text(name)
+                .width(constants::WIDTH)
+                .horizontal_alignment(alignment::Horizontal::Center)
+                .vertical_alignment(alignment::Vertical::Center)
+                .size(constants::TEXT_SIZE)
+        };
+
+        let p1_text = player_text("Player 1");
+        let p2_text = player_text("Player 2");
+
+        let p1_time_button = time_button(Player::Player1);
+        let p2_time_button = time_button(Player::Player2);
+
+        // Corrections can only be made while the game is paused, so the
+        // buttons stay out of the way during play
+        let adjust_buttons = |player: Player| {
+            let adjust_button = |label: &str, message| {
+                button(text(label))
+                    .on_press(message)
+                    .style(theme::Button::Secondary)
+                    .padding(constants::adjust::BUTTON_PADDING)
+            };
+            let buttons = row![
+                adjust_button(
+                    "+1m",
+                    ChessClockViewMessage::AddTime(player, times::ONE_MINUTE)
+                ),
+                adjust_button(
+                    "+10s",
+                    ChessClockViewMessage::AddTime(player, times::TEN_SECONDS)
+                ),
+                adjust_button(
+                    "-10s",
+                    ChessClockViewMessage::SubtractTime(
+                        player, times::TEN_SECONDS
+                    )
+                ),
+            ].spacing(constants::SPACING);
+            container(buttons)
+                .width(constants::WIDTH)
+                .center_x()
+        };
+
+        let mut p1 = column![
+            p1_text, p1_time_button
+        ]
+        .spacing(constants::SPACING);
+
+        let mut p2 = column![
+            p2_text, p2_time_button
+        ]
+        .spacing(constants::SPACING);
+
+        // A strict game under way can only be adjusted with the arbiter PIN
+        let adjustable = !clock.adjustments_locked()
+            || clock.arbiter().has_pin();
+        if clock.status() == Status::Stopped && adjustable {
+            p1 = p1.push(adjust_buttons(Player::Player1));
+            p2 = p2.push(adjust_buttons(Player::Player2));
+        }
+
+        let mut key_hints = row![
+            text("Enter - Toggle Start/Stop"),
+            text("Space - Switch Player"),
+            text("Backspace - Finish"),
+            text("d - Spectator display"),
+            text("q - Return to settings")
+        ].spacing(constants::SPACING)
+            .align_items(alignment::Alignment::Center);
+        if clock.status() == Status::Finished {
+            key_hints = key_hints.push(text("r - Rematch, sides swapped"));
+        }
+        if clock.rules().get_timing_method() == TimingMethod::ShotClock {
+            key_hints = key_hints.push(text(format!(
+                "e - Extension ({} left)",
+                clock.extensions_remaining(active_player)
+            )));
+        }
+        if clock.rules().get_pause_allowance().is_some() {
+            key_hints = key_hints.push(text("b - Break"));
+        }
+
+        let footer = text(game_summary(clock))
+            .size(constants::footer::TEXT_SIZE)
+            .style(theme::Text::Color(Color::from_rgb(
+                constants::footer::GREY,
+                constants::footer::GREY,
+                constants::footer::GREY
+            )));
+
+        let mut content = column![
+            header_text,
+            row![
+                p1, p2
+            ].spacing(constants::SPACING),
+            key_hints,
+            footer
+        ].spacing(constants::SPACING)
+            .align_items(alignment::Alignment::Center);
+        if clock.status() == Status::Finished {
+            content = content.push(
+                text(rating_summary(clock, &self.numbers))
+                    .size(constants::footer::TEXT_SIZE)
+            );
+        }
+        if let Some(bar) = self.session_bar() {
+            content = content.push(bar);
+        }
+        let delay = clock.recent_tick_delay(constants::watchdog::WARNING);
+        if let Some(gap) = delay {
+            content = content.push(
+                text(format!(
+                    "The display stalled for {:#} and may have jumped",
+                    DurationDisplay::from(gap)
+                ))
+                    .size(constants::footer::TEXT_SIZE)
+                    .style(theme::Text::Color(
+                        Color::from_rgb(constants::warning::RED, 0.0, 0.0)
+                    ))
+            );
+        }
+
+        container(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .into()
+    }
+
+    /// Draw a thin bar of how much of the session is left, if the rules
+    /// limit it
+    fn session_bar(
+        &self
+    ) -> Option<Element<'_, ChessClockViewMessage, Theme, Renderer>> {
+        let budget = self.clock.rules().get_session_budget()?;
+        let remaining = self.clock.session_remaining()?;
+        let fraction = remaining.as_secs_f32()
+            / budget.get_time().as_secs_f32().max(f32::EPSILON);
+        Some(
+            progress_bar(0.0..=1.0, fraction)
+                .width(2 * constants::WIDTH + constants::SPACING)
+                .height(constants::footer::SESSION_BAR_HEIGHT)
+                .into()
+        )
+    }
+
+    /// Show the clocks as two tap zones filling the window
+    fn touch_view(
+        &self, thresholds: Thresholds
+    ) -> Element<'_, ChessClockViewMessage, Theme, Renderer> {
+        let (time1, time2) = self.clock.read();
+        let pad = canvas(TouchPad {
+            times: [time1, time2],
+            active: self.clock.active_player(),
+            status: self.clock.status(),
+            thresholds,
+        })
+            .width(Length::Fill)
+            .height(Length::Fill);
+        let hints = text(
+            "Tap your side to end your turn · Swipe down to pause · \
+            Hold for settings"
+        )
+            .size(constants::footer::TEXT_SIZE);
+        column![pad, hints]
+            .spacing(constants::SPACING)
+            .padding(constants::SPACING)
+            .align_items(alignment::Alignment::Center)
+            .into()
+    }
+
+    /// Show the time left before the game starts, while the warm-up is
+    /// counting down to it
+    fn warm_up_view(
+        &self
+    ) -> Option<Element<'_, ChessClockViewMessage, Theme, Renderer>> {
+        let warm_up = self.warm_up.as_ref()?;
+        let held = if warm_up.is_running() { "" } else { " (held)" };
+        Some(
+            container(column![
+                text(format!("Game starts in{}", held))
+                    .size(constants::spectator::TEXT_SIZE),
+                text(DurationDisplay::from(warm_up.remaining()))
+                    .size(constants::warm_up::TEXT_SIZE),
+            ].align_items(alignment::Alignment::Center))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .into()
+        )
+    }
+
+    /// Show only the two clocks, as large as the window allows, or the
+    /// warm-up before the game
+    fn spectator_view(
+        &self
+    ) -> Element<'_, ChessClockViewMessage, Theme, Renderer> {
+        if let Some(warm_up) = self.warm_up_view() {
+            return self.protect(warm_up);
+        }
+        let clock = |player| {
+            self.clock_button(player, constants::spectator::CLOCK_TEXT_SIZE)
+                .width(Length::Fill)
+                .height(Length::Fill)
+        };
+        self.protect(
+            row![clock(Player::Player1), clock(Player::Player2)]
+                .spacing(constants::SPACING)
+                .padding(constants::SPACING)
+                .into()
+        )
+    }
+
+    /// Move a page a few pixels for burn-in protection, or replace it with
+    /// dimmed times if the clock has been stopped for a long time
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to protect, returned as it is without burn-in
+    ///   protection
+    fn protect<'a>(
+        &'a self, page: Element<'a, ChessClockViewMessage, Theme, Renderer>
+    ) -> Element<'a, ChessClockViewMessage, Theme, Renderer> {
+        let Some(burn_in) = self.burn_in else {
+            return page;
+        };
+        let stopped_for = match self.clock.status() {
+            Status::Running => Duration::ZERO,
+            _ => {
+                let since = self.clock.events().last()
+                    .map_or(self.opened, |record| record.instant());
+                self.clock.now().saturating_duration_since(since)
+            },
+        };
+        let dimmed = burn_in.is_dimmed(stopped_for);
+        let page = match dimmed {
+            true => {
+                let [side1, side2] = self.clock.view_model().players;
+                text(format!("{}   {}", side1.text, side2.text))
+                    .size(constants::burn_in::DIMMED_TEXT_SIZE)
+                    .into()
+            },
+            false => page,
+        };
+
+        // Every window shifts together, counting from the Unix epoch
+        let since_epoch = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        let (dx, dy) = burn_in.offset(since_epoch);
+        let max = burn_in.get_max_shift();
+        let mut shifted = container(page)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .padding(Padding {
+                top: max + dy,
+                right: max - dx,
+                bottom: max - dy,
+                left: max + dx,
+            });
+        if dimmed {
+            shifted = shifted
+                .center_x()
+                .center_y()
+                .style(theme::Container::Custom(Box::new(Dimmed)));
+        }
+        shifted.into()
+    }
+
+    /// Draw just the two times, for the spectator display in chroma key mode
+    ///
+    /// The text takes its colour from the chroma key background around it.
+    /// With a delay, the times are those of the game that long ago, so that
+    /// they never run ahead of a delayed stream.
+    fn chroma_key_view<M: 'static>(&self) -> Element<'_, M, Theme, Renderer> {
+        let view = match self.delay {
+            Some(delay) => DelayedView::new(&self.clock, delay).view_model(),
+            None => self.clock.view_model(),
+        };
+        let [side1, side2] = view.players;
+        let time = |side: PlayerView| {
+            text(side.text)
+                .size(constants::spectator::CLOCK_TEXT_SIZE)
+                .horizontal_alignment(alignment::Horizontal::Center)
+                .width(Length::Fill)
+        };
+        row![time(side1), time(side2)]
+            .spacing(constants::SPACING)
+            .padding(constants::SPACING)
+            .align_items(alignment::Alignment::Center)
+            .into()
+    }
+
+    /// Draw a player's clock on a button coloured by whose turn it is
+    ///
+    /// # Arguments
+    /// * `player` - The player whose clock to draw.
+    /// * `text_size` - The size of the digital time.
+    fn clock_button(
+        &self, player: Player, text_size: u16
+    ) -> iced::widget::Button<'_, ChessClockViewMessage> {
+        let view = self.clock.view_model();
+        let side = view.player(player);
+        let content: Element<ChessClockViewMessage> = match self.face {
+            ClockFace::Digital => text(&side.text)
+                .horizontal_alignment(alignment::Horizontal::Center)
+                .vertical_alignment(alignment::Vertical::Center)
+                .size(text_size)
+                .style(theme::Text::Color(Color::BLACK))
+                .into(),
+            ClockFace::Analog => canvas(Dial {
+                time: side.time,
+                fraction: side.fraction_remaining,
+            })
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into(),
+        };
+        button(content)
+            .style(
+                match (side.active, view.status) {
+                    (true, Status::Finished) => theme::Button::Positive,
+                    (false, Status::Finished) => theme::Button::Destructive,
+                    (true, _) => theme::Button::Primary,
+                    (false, _) => theme::Button::Secondary,
+                }
+            )
+    }
+}
+
+/// Ask for the arbiter PIN to take a locked action
+///
+/// The game carries on behind the prompt, as the clock keeps its own
+/// subscription.
+fn pin_view(unlock: &Unlock) -> Element<'_, PagesMessage> {
+    let action = match unlock.action {
+        Locked::Reset => "return to the settings",
+        Locked::Quit => "close the clock",
+        Locked::AddTime(..) | Locked::SubtractTime(..) => "adjust the clocks",
+    };
+    let mut prompt = column![
+        text(format!("Enter the arbiter PIN to {}", action))
+            .size(constants::TEXT_SIZE),
+        text_input("PIN", &unlock.entered)
+            .id(text_input::Id::new(constants::pin::INPUT_ID))
+            .secure(true)
+            .on_input(PagesMessage::ChangePin)
+            .on_submit(PagesMessage::SubmitPin)
+            .width(constants::pin::INPUT_WIDTH),
+        row![
+            button(text("Unlock"))
+                .on_press(PagesMessage::SubmitPin)
+                .style(theme::Button::Primary),
+            button(text("Cancel"))
+                .on_press(PagesMessage::CancelPin)
+                .style(theme::Button::Secondary),
+        ].spacing(constants::SPACING)
+    ].spacing(constants::SPACING)
+        .align_items(alignment::Alignment::Center);
+    if unlock.wrong {
+        prompt = prompt.push(text("Wrong PIN").style(theme::Text::Color(
+            Color::from_rgb(constants::warning::RED, 0.0, 0.0)
+        )));
+    }
+    container(prompt)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .into()
+}
+
+/// Ask whether to carry on a game left unfinished last time
+fn resume_view(recovery: &Recovery) -> Element<'_, PagesMessage> {
+    let prompt = column![
+        text(recovery.question()).size(constants::TEXT_SIZE),
+        row![
+            button(text("Resume"))
+                .on_press(PagesMessage::ResumeGame)
+                .style(theme::Button::Primary),
+            button(text("Discard"))
+                .on_press(PagesMessage::DiscardRecovery)
+                .style(theme::Button::Secondary),
+        ].spacing(constants::SPACING)
+    ].spacing(constants::SPACING)
+        .align_items(alignment::Alignment::Center);
+    container(prompt)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .into()
+}
+
+/// Format a player's remaining time, in hundredths during a time scramble
+fn clock_text(time: Duration) -> String {
+    DurationDisplay::from(time).to_string_at(display_precision_for(time))
+}
+
+/// Show a whole number of minutes as typed in a time field
+///
+/// The fields take whole minutes, so other times are left blank and only
+/// shown in the summary.
+fn whole_minutes(time: Duration) -> String {
+    match time.as_secs() {
+        seconds if seconds % 60 == 0 => (seconds / 60).to_string(),
+        _ => "".to_string(),
+    }
+}
+
+/// Summarise a player's lifetime stats, e.g. "Alice: 12 games, 16.7% lost
+/// on time, 14.2s a move, 1:05 left at the end"
+fn profile_summary(
+    profile: &PlayerProfile, games: Option<&GameStore>, numbers: &NumberFormat
+) -> String {
+    let stats = profile.stats();
+    let mut summary = format!(
+        "{}: {} games", profile.name(), stats.get_games()
+    );
+    if stats.get_games() > 0 {
+        let flagged = stats.get_flags() as f64 / stats.get_games() as f64;
+        summary.push_str(&format!(
+            ", {} lost on time", numbers.percent(flagged, 1)
+        ));
+    }
+    if let Some(average) = stats.average_move_time() {
+        summary.push_str(&format!(
+            ", {}s a move", numbers.decimal(average.as_secs_f64(), 1)
+        ));
+    }
+    if let Some(remaining) = games
+        .and_then(|games| games.average_remaining(profile.name()))
+    {
+        summary.push_str(&format!(
+            ", {} left at the end", DurationDisplay::from(remaining)
+        ));
+    }
+    summary
+}
+
+/// Summarise both players' time pressure ratings, e.g. "Time pressure
+/// rating: Player 1 1630 (42% left), Player 2 1210 (0% left)"
+fn rating_summary(clock: &ChessClock, numbers: &NumberFormat) -> String {
+    let ratings = Player::ALL.map(|player| {
+        let rating = time_pressure_rating(clock.rules(), clock.events(), player)
+            .map_or("-".to_string(), |rating| format!(
+                "{} ({} left)",
+                rating.get_rating(),
+                numbers.percent(f64::from(rating.get_fraction_left()), 0)
+            ));
+        format!("{} {}", player, rating)
+    });
+    format!("Time pressure rating: {}", ratings.join(", "))
+}
+
+/// Summarise the rules and progress of a game, e.g. "Fischer +5s · move 23",
+/// with the time left of the session and of each player's breaks if the
+/// rules limit them
+fn game_summary(clock: &ChessClock) -> String {
+    let rules = clock.rules();
+    let method = match rules.get_timing_method() {
+        TimingMethod::ShotClock => TimingMethod::ShotClock.to_string(),
+        method => format!(
+            "{} +{}s", method, clock.current_increment().as_secs()
+        ),
+    };
+    let mut summary = format!("{} · move {}", method, clock.move_number());
+    match clock.session_remaining() {
+        Some(remaining) if remaining.is_zero() => {
+            summary.push_str(" · session over");
+        },
+        Some(remaining) => summary.push_str(&format!(
+            " · session {} left", DurationDisplay::from(remaining)
+        )),
+        None => {},
+    }
+    if rules.get_pause_allowance().is_some() {
+        let left = |player| DurationDisplay::from(
+            clock.pause_remaining(player).unwrap_or_default()
+        );
+        summary.push_str(&format!(
+            " · breaks {} / {} left",
+            left(Player::Player1), left(Player::Player2)
+        ));
+    }
+    if let Some(player) = clock.on_break() {
+        summary.push_str(&format!(" · {} on a break", player));
+    }
+    summary
+}
+
+/// An analog dial for one player's clock
+///
+/// The arc around the edge shrinks as the player's time runs down, and the
+/// hand sweeps once a minute.
+#[derive(Debug)]
+struct Dial {
+    time: Duration,
+    fraction: f32,
+}
+
+impl<Message> canvas::Program<Message> for Dial {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        use constants::dial;
+
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let center = frame.center();
+        let radius = frame.width().min(frame.height()) / 2.0
+            * dial::RADIUS_FRACTION;
+        // Angles are measured clockwise from the positive x-axis, so twelve
+        // o'clock is a quarter turn back
+        let angle = |fraction: f32| -PI / 2.0 + 2.0 * PI * fraction;
+        let point = |angle: f32, length: f32| Point::new(
+            center.x + length * angle.cos(), center.y + length * angle.sin()
+        );
+
+        frame.fill(&canvas::Path::circle(center, radius), Color::WHITE);
+
+        let ticks = canvas::Path::new(|builder| {
+            for hour in 0..12 {
+                let angle = angle(hour as f32 / 12.0);
+                builder.move_to(point(angle, radius - dial::TICK_LENGTH));
+                builder.line_to(point(angle, radius));
+            }
+        });
+        frame.stroke(&ticks, canvas::Stroke::default().with_width(2.0));
+
+        if self.fraction > 0.0 {
+            let arc = canvas::Path::new(|builder| {
+                builder.arc(canvas::path::Arc {
+                    center,
+                    radius,
+                    start_angle: Radians(angle(0.0)),
+                    end_angle: Radians(angle(self.fraction)),
+                });
+            });
+            frame.stroke(&arc, canvas::Stroke::default()
+                .with_width(dial::ARC_WIDTH)
+                .with_color(Color::from_rgb(0.2, 0.6, 0.3)));
+        }
+
+        let seconds = self.time.as_secs_f32() % 60.0;
+        let hand = canvas::Path::line(
+            center, point(angle(seconds / 60.0), radius * 0.8)
+        );
+        frame.stroke(&hand, canvas::Stroke::default()
+            .with_width(dial::HAND_WIDTH)
+            .with_color(Color::BLACK));
+
+        frame.fill_text(canvas::Text {
+            content: clock_text(self.time),
+            position: Point::new(center.x, center.y + radius / 2.0),
+            size: dial::TEXT_SIZE.into(),
+            horizontal_alignment: alignment::Horizontal::Center,
+            vertical_alignment: alignment::Vertical::Center,
+            ..canvas::Text::default()
+        });
+
+        vec![frame.into_geometry()]
+    }
+}
+
+/// Both clocks drawn as the halves of a touchscreen, which turns gestures
+/// into clock messages
+#[derive(Debug)]
+struct TouchPad {
+    times: [Duration; 2],
+    active: Player,
+    status: Status,
+    thresholds: Thresholds,
+}
+
+impl TouchPad {
+    /// The message a gesture sends. Only the player on move can end their
+    /// turn, so a tap on the other half is ignored.
+    fn message(&self, gesture: Gesture) -> Option<ChessClockViewMessage> {
+        match gesture {
+            Gesture::Tap(player) => (player == self.active)
+                .then_some(ChessClockViewMessage::SwitchPlayer),
+            Gesture::SwipeDown => Some(ChessClockViewMessage::ToggleStartStop),
+            Gesture::LongPress => Some(ChessClockViewMessage::ResetClock),
+        }
+    }
+}
+
+impl canvas::Program<ChessClockViewMessage> for TouchPad {
+    type State = Recognizer;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<ChessClockViewMessage>) {
+        let relative = |point: Point| (point.x - bounds.x, point.y - bounds.y);
+        // A mouse drives the pad like a finger, for trying it on a desktop
+        let (pressed, lifted) = match event {
+            canvas::Event::Touch(touch::Event::FingerPressed {
+                position, ..
+            }) => (Some(position), None),
+            canvas::Event::Touch(touch::Event::FingerLifted {
+                position, ..
+            }) => (None, Some(position)),
+            canvas::Event::Touch(touch::Event::FingerLost { .. }) => {
+                state.cancel();
+                return (canvas::event::Status::Captured, None);
+            },
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(
+                mouse::Button::Left
+            )) => (cursor.position_over(bounds), None),
+            canvas::Event::Mouse(mouse::Event::ButtonReleased(
+                mouse::Button::Left
+            )) => (None, cursor.position()),
+            _ => return (canvas::event::Status::Ignored, None),
+        };
+
+        if let Some(position) = pressed {
+            state.press(relative(position), Instant::now());
+            return (canvas::event::Status::Captured, None);
+        }
+        let message = lifted
+            .and_then(|position| state.release(
+                relative(position),
+                Instant::now(),
+                bounds.width,
+                &self.thresholds
+            ))
+            .and_then(|gesture| self.message(gesture));
+        (canvas::event::Status::Captured, message)
+    }
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let palette = theme.extended_palette();
+        let half = iced::Size::new(
+            (frame.width() - constants::SPACING as f32) / 2.0, frame.height()
+        );
+        let text_size = (half.width / 5.0).min(half.height / 3.0);
+
+        for (index, player) in [Player::Player1, Player::Player2]
+            .into_iter()
+            .enumerate()
+        {
+            let pair = match (player == self.active, self.status) {
+                (true, Status::Finished) => palette.success.base,
+                (false, Status::Finished) => palette.danger.base,
+                (true, _) => palette.primary.base,
+                (false, _) => palette.secondary.base,
+            };
+            let left = index as f32 * (half.width + constants::SPACING as f32);
+            frame.fill_rectangle(Point::new(left, 0.0), half, pair.color);
+            frame.fill_text(canvas::Text {
+                content: clock_text(self.times[index]),
+                position: Point::new(
+                    left + half.width / 2.0, half.height / 2.0
+                ),
+                color: pair.text,
+                size: text_size.into(),
+                horizontal_alignment: alignment::Horizontal::Center,
+                vertical_alignment: alignment::Vertical::Center,
+                ..canvas::Text::default()
+            });
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+// Define a trait for formatting elements
+trait Format {
+    fn format(self, alignment: alignment::Alignment, spacing: u16) -> Self;
+}
+
+impl<'a> Format for iced::widget::Row<'a, SettingsMessage> {
+    /// Format the row to have a center alignment and a certain spacing
+    /// between the elements
+    fn format(self, alignment: alignment::Alignment, spacing: u16) -> Self {
+        self.align_items(alignment).spacing(spacing)
+    }
+}
+
+impl<'a> Format for iced::widget::Column<'a, SettingsMessage> {
+    fn format(self, alignment: alignment::Alignment, spacing: u16) -> Self {
+        self.align_items(alignment).spacing(spacing)
+    }
+}
@@ -0,0 +1,283 @@
+//! # History
+//! The moves of a game, with the time each took, kept in a store chosen by
+//! the front end.
+//!
+//! A `Vec` keeps every move of the game. A `RingHistory` keeps only the
+//! latest `N` in a fixed array, so a long game on a small device never
+//! allocates for its history.
+
+use std::time::Duration;
+use crate::{ChessClock, ClockEvent, Player};
+
+/// A move made at the clock
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveRecord {
+    /// The player who made the move
+    pub player: Player,
+    /// The number of the move among the player's own, counting from 1
+    pub number: u32,
+    /// How long the player thought about the move
+    pub used: Duration,
+    /// The time on the player's clock as they pressed it, before any
+    /// increment for the move
+    pub left: Duration,
+}
+
+impl MoveRecord {
+    const BLANK: Self = Self {
+        player: Player::Player1,
+        number: 0,
+        used: Duration::ZERO,
+        left: Duration::ZERO,
+    };
+}
+
+/// Somewhere to keep the moves of a game, oldest first
+pub trait HistoryStore {
+    /// Add a move after the others, which may push out the oldest if the
+    /// store is full
+    fn push(&mut self, record: MoveRecord);
+
+    /// Get the number of moves kept
+    fn len(&self) -> usize;
+
+    /// Get the move at `index` of those kept, oldest first
+    fn get(&self, index: usize) -> Option<&MoveRecord>;
+
+    /// Forget every move
+    fn clear(&mut self);
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the latest move kept
+    fn last(&self) -> Option<&MoveRecord> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
+    }
+}
+
+impl HistoryStore for Vec<MoveRecord> {
+    fn push(&mut self, record: MoveRecord) {
+        Vec::push(self, record);
+    }
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn get(&self, index: usize) -> Option<&MoveRecord> {
+        self.as_slice().get(index)
+    }
+
+    fn clear(&mut self) {
+        Vec::clear(self);
+    }
+}
+
+/// A store keeping the latest `N` moves in a fixed array
+#[derive(Debug, Clone)]
+pub struct RingHistory<const N: usize> {
+    records: [MoveRecord; N],
+    /// The index of the oldest move kept
+    start: usize,
+    len: usize,
+}
+
+impl<const N: usize> RingHistory<N> {
+    pub const fn new() -> Self {
+        Self { records: [MoveRecord::BLANK; N], start: 0, len: 0 }
+    }
+}
+
+impl<const N: usize> Default for RingHistory<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> HistoryStore for RingHistory<N> {
+    fn push(&mut self, record: MoveRecord) {
+        if N == 0 {
+            return;
+        }
+        if self.len < N {
+            self.records[(self.start + self.len) % N] = record;
+            self.len += 1;
+        } else {
+            self.records[self.start] = record;
+            self.start = (self.start + 1) % N;
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> Option<&MoveRecord> {
+        (index < self.len).then(|| &self.records[(self.start + index) % N])
+    }
+
+    fn clear(&mut self) {
+        self.start = 0;
+        self.len = 0;
+    }
+}
+
+/// The moves of a game followed from its clock
+///
+/// The history is brought up to date from the clock's event log with
+/// `update`, which can be called as often or as rarely as suits the front
+/// end: every move since the last update is recorded, with the time it took.
+#[derive(Debug, Clone, Default)]
+pub struct MoveHistory<S: HistoryStore = Vec<MoveRecord>> {
+    store: S,
+    /// The number of events of the clock already looked at
+    seen: usize,
+}
+
+impl<S: HistoryStore> MoveHistory<S> {
+    pub fn new(store: S) -> Self {
+        Self { store, seen: 0 }
+    }
+
+    /// Get the moves kept, in the store they are kept in
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Get the moves kept, oldest first
+    pub fn moves(&self) -> impl Iterator<Item = &MoveRecord> {
+        (0..self.store.len()).filter_map(|index| self.store.get(index))
+    }
+
+    /// Record the moves made on `clock` since the last update
+    ///
+    /// A clock with fewer events than have been seen is taken to be a new
+    /// game, and the history starts again.
+    ///
+    /// # Returns
+    /// The number of moves recorded.
+    pub fn update(&mut self, clock: &ChessClock) -> usize {
+        let events = clock.events();
+        if events.len() < self.seen {
+            self.store.clear();
+            self.seen = 0;
+        }
+        let pressed = events[self.seen..].iter()
+            .any(|record| matches!(record.event(), ClockEvent::Pressed(_)));
+        if !pressed {
+            self.seen = events.len();
+            return 0;
+        }
+
+        // The time each move took comes from the clock as it was just before
+        // the press, which only a replay of the game gives
+        let mut recorded = 0;
+        let mut thought = [Duration::ZERO; 2];
+        let mut numbers = [0; 2];
+        let mut index = 0;
+        ChessClock::replay_with(
+            clock.rules().clone(), events, |replayed, record| {
+                if let ClockEvent::Pressed(player) = record.event() {
+                    let at = record.instant();
+                    let total = replayed.think_time_at(*player, at);
+                    let i = player.index();
+                    numbers[i] += 1;
+                    if index >= self.seen {
+                        let (t1, t2) = replayed.read_at(at);
+                        self.store.push(MoveRecord {
+                            player: *player,
+                            number: numbers[i],
+                            used: total.saturating_sub(thought[i]),
+                            left: [t1, t2][i],
+                        });
+                        recorded += 1;
+                    }
+                    thought[i] = total;
+                }
+                index += 1;
+            }
+        );
+        self.seen = events.len();
+        recorded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{times, Rules, Sleep, TimingMethod};
+
+    fn record(number: u32) -> MoveRecord {
+        MoveRecord { number, ..MoveRecord::BLANK }
+    }
+
+    #[test]
+    fn test_ring_keeps_latest() {
+        let mut ring = RingHistory::<3>::new();
+        assert!(ring.is_empty());
+        for number in 1..=5 {
+            ring.push(record(number));
+        }
+        assert_eq!(ring.len(), 3);
+        let numbers: Vec<_> = (0..ring.len())
+            .map(|index| ring.get(index).unwrap().number)
+            .collect();
+        assert_eq!(numbers, [3, 4, 5]);
+        assert_eq!(ring.last().unwrap().number, 5);
+        assert_eq!(ring.get(3), None);
+
+        ring.clear();
+        assert!(ring.is_empty());
+        assert_eq!(ring.last(), None);
+    }
+
+    #[test]
+    fn test_empty_ring() {
+        let mut ring = RingHistory::<0>::new();
+        ring.push(record(1));
+        assert!(ring.is_empty());
+    }
+
+    #[test]
+    fn test_follows_clock() {
+        let mut clock = ChessClock::new(Rules::new(
+            times::ONE_MINUTE, times::ONE_MINUTE, Duration::ZERO,
+            Player::Player1, TimingMethod::Fischer
+        ));
+        let mut history = MoveHistory::<Vec<MoveRecord>>::default();
+        let mut ring = MoveHistory::new(RingHistory::<2>::new());
+        assert_eq!(history.update(&clock), 0);
+
+        clock.start();
+        Duration::from_millis(20).sleep();
+        clock.switch_player();
+        assert_eq!(history.update(&clock), 1);
+        clock.switch_player();
+        clock.switch_player();
+        assert_eq!(history.update(&clock), 2);
+        assert_eq!(ring.update(&clock), 3);
+        assert_eq!(history.update(&clock), 0);
+
+        let moves: Vec<_> = history.moves().copied().collect();
+        assert_eq!(moves.len(), 3);
+        assert_eq!(moves[0].player, Player::Player1);
+        assert!(moves[0].used >= Duration::from_millis(20));
+        assert_eq!(
+            moves.iter().map(|record| record.number).collect::<Vec<_>>(),
+            [1, 1, 2]
+        );
+        // Player 1 is not on move, so their think time is all in their moves
+        assert_eq!(
+            moves[0].used + moves[2].used, clock.think_time(Player::Player1)
+        );
+
+        let kept: Vec<_> = ring.moves().copied().collect();
+        assert_eq!(kept, moves[1..]);
+
+        // A new game starts the history again
+        history.update(&ChessClock::default());
+        assert!(history.store().is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+//! # i18n
+//! Locale-aware formatting of the numbers in summaries and reports.
+//!
+//! Only number formatting is localised: the decimal separator and the
+//! separator between groups of thousands. The locale is read from the
+//! environment in the usual order of `LC_ALL`, `LC_NUMERIC` and `LANG`.
+
+use std::env;
+
+/// How a locale writes numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    decimal: char,
+    group: char,
+}
+
+impl NumberFormat {
+    /// Numbers as written in English, e.g. "1,234.5"
+    pub const ENGLISH: Self = Self::new('.', ',');
+
+    pub const fn new(decimal: char, group: char) -> Self {
+        Self { decimal, group }
+    }
+
+    pub const fn get_decimal(&self) -> char {
+        self.decimal
+    }
+
+    pub const fn get_group(&self) -> char {
+        self.group
+    }
+
+    /// Detect the number format of the user's locale from the environment
+    pub fn detect() -> Self {
+        let locale = ["LC_ALL", "LC_NUMERIC", "LANG"].iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty());
+        locale.map_or(Self::ENGLISH, |locale| Self::from_locale(&locale))
+    }
+
+    /// Work out the number format of a POSIX locale name, such as
+    /// `de_DE.UTF-8`
+    ///
+    /// Only the language is used. Languages not known to write numbers
+    /// differently, and the `C` and `POSIX` locales, are written in English.
+    pub fn from_locale(locale: &str) -> Self {
+        let language = locale.split(['_', '.', '@', '-'])
+            .next()
+            .unwrap_or("");
+        match language {
+            "de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el" => {
+                Self::new(',', '.')
+            },
+            "fr" | "ru" | "pl" | "sv" | "nb" | "nn" | "fi" | "cs" | "sk"
+            | "uk" | "hu" => Self::new(',', '\u{a0}'),
+            _ => Self::ENGLISH,
+        }
+    }
+
+    /// Format a number with `places` decimal places, grouping thousands
+    ///
+    /// # Arguments
+    /// * `value` - The number to format.
+    /// * `places` - The number of decimal places, to which it is rounded.
+    ///
+    /// # Returns
+    /// The formatted number, e.g. "1.234,5" in German.
+    pub fn decimal(&self, value: f64, places: usize) -> String {
+        let plain = format!("{:.*}", places, value.abs());
+        let (whole, fraction) = match plain.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (plain.as_str(), None),
+        };
+
+        let mut formatted = String::new();
+        // Values that round to zero are written without a sign
+        let nonzero = plain.bytes().any(|byte| (b'1'..=b'9').contains(&byte));
+        if value < 0.0 && nonzero {
+            formatted.push('-');
+        }
+        for (index, digit) in whole.chars().enumerate() {
+            if index > 0 && (whole.len() - index) % 3 == 0 {
+                formatted.push(self.group);
+            }
+            formatted.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            formatted.push(self.decimal);
+            formatted.push_str(fraction);
+        }
+        formatted
+    }
+
+    /// Format a fraction as a percentage with `places` decimal places, e.g.
+    /// "12.5%" for 0.125
+    pub fn percent(&self, fraction: f64, places: usize) -> String {
+        format!("{}%", self.decimal(fraction * 100.0, places))
+    }
+}
+
+impl Default for NumberFormat {
+    /// The format of the user's locale
+    fn default() -> Self {
+        Self::detect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_locale() {
+        let format = NumberFormat::from_locale;
+        assert_eq!(format("de_DE.UTF-8"), NumberFormat::new(',', '.'));
+        assert_eq!(format("fr_FR"), NumberFormat::new(',', '\u{a0}'));
+        assert_eq!(format("en_GB.UTF-8"), NumberFormat::ENGLISH);
+        assert_eq!(format("C"), NumberFormat::ENGLISH);
+    }
+
+    #[test]
+    fn test_decimal() {
+        let english = NumberFormat::ENGLISH;
+        let german = NumberFormat::from_locale("de_DE");
+        assert_eq!(english.decimal(1234.56, 1), "1,234.6");
+        assert_eq!(german.decimal(1234.56, 1), "1.234,6");
+        assert_eq!(german.decimal(1234567.0, 0), "1.234.567");
+        assert_eq!(english.decimal(999.0, 2), "999.00");
+        assert_eq!(english.decimal(-1500.0, 0), "-1,500");
+        assert_eq!(english.decimal(-0.01, 1), "0.0");
+    }
+
+    #[test]
+    fn test_percent() {
+        let format = NumberFormat::from_locale;
+        assert_eq!(NumberFormat::ENGLISH.percent(0.125, 1), "12.5%");
+        assert_eq!(format("it_IT").percent(0.5, 0), "50%");
+        assert_eq!(format("pt_BR").percent(0.333, 1), "33,3%");
+    }
+}
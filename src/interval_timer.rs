@@ -0,0 +1,211 @@
+//! # IntervalTimer
+//! A timer made of a sequence of labelled intervals, run back to back on a
+//! single counting up clock.
+
+use std::time::Duration;
+use crate::{Clock, ClockMode, ClockState, utils::parse_duration};
+
+/// A labelled interval of an IntervalTimer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interval {
+    label: String,
+    duration: Duration,
+}
+
+impl Interval {
+    pub fn new(label: &str, duration: Duration) -> Self {
+        Self { label: label.to_string(), duration }
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+/// A timer that runs through a sequence of intervals
+///
+/// The timer keeps counting once the last interval has ended, so that the
+/// overrun can be reported.
+#[derive(Debug)]
+pub struct IntervalTimer {
+    intervals: Vec<Interval>,
+    clock: Clock,
+}
+
+impl IntervalTimer {
+    /// Constructs a new stopped timer for the given intervals
+    pub fn new(intervals: Vec<Interval>) -> Self {
+        Self { intervals, clock: Clock::new(ClockMode::CountUp, None) }
+    }
+
+    /// Constructs a timer for `rounds` rounds of `work`, separated by `rest`
+    ///
+    /// Intervals are labelled "Round 1", "Rest", "Round 2" and so on, with no
+    /// rest after the final round.
+    pub fn rounds(rounds: u32, work: Duration, rest: Duration) -> Self {
+        let mut intervals = Vec::new();
+        for round in 1..=rounds {
+            if round > 1 && rest > Duration::ZERO {
+                intervals.push(Interval::new("Rest", rest));
+            }
+            intervals.push(Interval::new(&format!("Round {}", round), work));
+        }
+        Self::new(intervals)
+    }
+
+    /// Constructs a rounds timer from a specification such as `12x3m/1m`
+    ///
+    /// The specification is the number of rounds, then `x` and the length of
+    /// each round, then optionally `/` and the length of the rest between
+    /// rounds. Returns `None` if the specification is invalid.
+    pub fn from_rounds_spec(spec: &str) -> Option<Self> {
+        let (rounds, lengths) = spec.split_once('x')?;
+        let rounds = rounds.trim().parse().ok()?;
+        let (work, rest) = match lengths.split_once('/') {
+            Some((work, rest)) => (parse_duration(work)?, parse_duration(rest)?),
+            None => (parse_duration(lengths)?, Duration::ZERO),
+        };
+        Some(Self::rounds(rounds, work, rest))
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    /// Get the total duration of all intervals
+    pub fn total(&self) -> Duration {
+        self.intervals.iter().map(Interval::duration).sum()
+    }
+
+    /// Get the time elapsed since the timer was started
+    pub fn elapsed(&self) -> Duration {
+        self.clock.read()
+    }
+
+    /// Get the current interval
+    ///
+    /// # Returns
+    ///
+    /// * A tuple of the index of the current interval, the interval and the
+    ///   time remaining in it, or `None` if every interval has ended
+    pub fn current(&self) -> Option<(usize, &Interval, Duration)> {
+        let elapsed = self.elapsed();
+        let mut end = Duration::ZERO;
+        for (index, interval) in self.intervals.iter().enumerate() {
+            end += interval.duration;
+            if elapsed < end {
+                return Some((index, interval, end - elapsed));
+            }
+        }
+        None
+    }
+
+    /// Returns whether every interval has ended
+    pub fn is_finished(&self) -> bool {
+        self.current().is_none()
+    }
+
+    /// Get the time elapsed since the last interval ended
+    pub fn overrun(&self) -> Duration {
+        self.elapsed().saturating_sub(self.total())
+    }
+
+    pub fn start(&mut self) {
+        self.clock.start();
+    }
+
+    pub fn stop(&mut self) {
+        self.clock.stop();
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(self.clock.state(), ClockState::Running(_))
+    }
+
+    /// Stops the timer and returns it to the start of the first interval
+    pub fn reset(&mut self) {
+        self.clock.zero();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sleep;
+
+    fn timer() -> IntervalTimer {
+        IntervalTimer::new(vec![
+            Interval::new("first", Duration::from_millis(100)),
+            Interval::new("second", Duration::from_millis(200)),
+        ])
+    }
+
+    #[test]
+    fn test_interval_timer_progression() {
+        let mut timer = timer();
+        assert_eq!(timer.total(), Duration::from_millis(300));
+
+        let (index, interval, remaining) = timer.current().unwrap();
+        assert_eq!(index, 0);
+        assert_eq!(interval.label(), "first");
+        assert_eq!(remaining, Duration::from_millis(100));
+
+        timer.start();
+        Duration::from_millis(150).sleep();
+        timer.stop();
+        let (index, interval, remaining) = timer.current().unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(interval.label(), "second");
+        assert_eq!(timer.elapsed().as_millis(), 150);
+        assert_eq!(remaining + timer.elapsed(), timer.total());
+
+        timer.start();
+        Duration::from_millis(200).sleep();
+        timer.stop();
+        assert!(timer.is_finished());
+        assert_eq!(timer.overrun().as_millis(), 50);
+
+        timer.reset();
+        assert_eq!(timer.current().unwrap().0, 0);
+    }
+
+    #[test]
+    fn test_rounds() {
+        let timer = IntervalTimer::rounds(
+            3, Duration::from_secs(180), Duration::from_secs(60)
+        );
+        let labels: Vec<&str> = timer.intervals().iter()
+            .map(Interval::label)
+            .collect();
+        assert_eq!(
+            labels, ["Round 1", "Rest", "Round 2", "Rest", "Round 3"]
+        );
+        assert_eq!(timer.total(), Duration::from_secs(3 * 180 + 2 * 60));
+    }
+
+    #[test]
+    fn test_from_rounds_spec() {
+        let timer = IntervalTimer::from_rounds_spec("12x3m/1m").unwrap();
+        assert_eq!(timer.intervals().len(), 23);
+        assert_eq!(timer.intervals()[1].duration(), Duration::from_secs(60));
+
+        let timer = IntervalTimer::from_rounds_spec("2x90s").unwrap();
+        assert_eq!(timer.intervals().len(), 2);
+        assert_eq!(timer.total(), Duration::from_secs(180));
+
+        assert!(IntervalTimer::from_rounds_spec("12").is_none());
+        assert!(IntervalTimer::from_rounds_spec("ax3m").is_none());
+        assert!(IntervalTimer::from_rounds_spec("12x3m/").is_none());
+    }
+
+    #[test]
+    fn test_empty_interval_timer_is_finished() {
+        let timer = IntervalTimer::new(Vec::new());
+        assert!(timer.is_finished());
+        assert_eq!(timer.total(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,221 @@
+//! # Journal
+//! Append-only on-disk journal of chess clock state, so that a game
+//! interrupted by a crash or power loss can be recovered at startup.
+//!
+//! The journal starts with a header line describing the rules, followed by
+//! one line per recorded state:
+//!
+//! ```text
+//! rules <p1 ms> <p2 ms> <increment ms> <starter> <timing method> <extensions>
+//!     <require reasons> <idle timeout ms or -> <stopped switch>
+//!     <background time> <session budget ms or -> <adjudicate session>
+//!     <default time ms or -> <finish on default>
+//!     <increment stages or -> <strict> <pause allowance ms or ->
+//! state <p1 ms> <p2 ms> <active player> <status>
+//! ```
+//!
+//! Increment stages are written as `<move>:<increment ms>`, joined by
+//! commas.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+use crate::{
+    ChessClock, Rules, Status,
+    encoding::{format_rules, format_state, invalid, parse_rules, parse_state},
+};
+
+/// FsyncPolicy controls whether journal entries are forced to disk as they
+/// are written.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FsyncPolicy {
+    /// Call `fsync` after every entry, surviving power loss.
+    EveryEntry,
+    /// Leave flushing to the operating system, surviving process crashes only.
+    Never,
+}
+
+/// An append-only journal of chess clock state
+///
+/// Entries are written at most once per `max_loss`, so at most `max_loss` of
+/// play is lost when recovering from the journal.
+#[derive(Debug)]
+pub struct Journal {
+    file: File,
+    max_loss: Duration,
+    fsync: FsyncPolicy,
+    last_entry: Option<Instant>,
+}
+
+impl Journal {
+    /// Creates a new journal at `path` for a game played under `rules`
+    ///
+    /// Any existing journal at `path` is truncated.
+    pub fn create<P: AsRef<Path>>(
+        path: P, rules: &Rules, max_loss: Duration, fsync: FsyncPolicy
+    ) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)?;
+
+        let mut journal = Self { file, max_loss, fsync, last_entry: None };
+        journal.write_line(&format_rules(rules))?;
+        Ok(journal)
+    }
+
+    /// Records the state of the clock if `max_loss` has passed since the last
+    /// entry
+    ///
+    /// Returns whether an entry was written.
+    pub fn record(&mut self, clock: &ChessClock) -> io::Result<bool> {
+        let due = match self.last_entry {
+            Some(last) => last.elapsed() >= self.max_loss,
+            None => true,
+        };
+        if due {
+            self.record_now(clock)?;
+        }
+        Ok(due)
+    }
+
+    /// Records the state of the clock immediately
+    pub fn record_now(&mut self, clock: &ChessClock) -> io::Result<()> {
+        self.write_line(&format!("state {}", format_state(clock)))?;
+        self.last_entry = Some(Instant::now());
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.file, "{}", line)?;
+        if self.fsync == FsyncPolicy::EveryEntry {
+            self.file.sync_data()?;
+        }
+        Ok(())
+    }
+}
+
+/// Recovers a chess clock from the last complete entry of a journal
+///
+/// The recovered clock is stopped with the journaled remaining times, so
+/// that play only resumes when the players are ready. A game journaled as
+/// finished is recovered finished.
+pub fn recover_from_journal<P: AsRef<Path>>(path: P) -> io::Result<ChessClock> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut lines = reader.lines();
+
+    let header = lines.next().ok_or_else(|| invalid("empty journal"))??;
+    let rules = parse_rules(&header)?;
+
+    // A torn final line from a crash mid-write is skipped rather than fatal
+    let mut last = None;
+    for line in lines {
+        let line = line?;
+        if let Some(Ok(entry)) = line.strip_prefix("state ").map(parse_state) {
+            last = Some(entry);
+        }
+    }
+
+    Ok(match last {
+        Some((times, active, status)) => ChessClock::restore(
+            rules, times, active, status == Status::Finished
+        ),
+        None => ChessClock::new(rules),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs, path::PathBuf};
+    use crate::{times, Player, Sleep, TimingMethod};
+
+    fn journal_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!(
+            "rusty_chess_clock_{}_{}.journal", name, std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_recover_running_game() {
+        let path = journal_path("running");
+        let rules = Rules::new(
+            times::ONE_MINUTE, times::TEN_MINUTES,
+            times::FIVE_SECONDS, Player::Player2, TimingMethod::Bronstein
+        );
+        let mut journal = Journal::create(
+            &path, &rules, Duration::ZERO, FsyncPolicy::EveryEntry
+        ).unwrap();
+
+        let mut clock = ChessClock::new(rules);
+        clock.start();
+        Duration::from_secs(1).sleep();
+        clock.stop();
+        journal.record(&clock).unwrap();
+
+        let recovered = recover_from_journal(&path).unwrap();
+        let (t1, t2) = clock.read();
+        assert_eq!(recovered.read().0.as_millis(), t1.as_millis());
+        assert_eq!(recovered.read().1.as_millis(), t2.as_millis());
+        assert_eq!(recovered.active_player(), Player::Player2);
+        assert_eq!(recovered.status(), Status::Stopped);
+        assert_eq!(
+            recovered.rules().get_timing_method(), TimingMethod::Bronstein
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_record_respects_max_loss() {
+        let path = journal_path("max_loss");
+        let clock = ChessClock::default();
+        let mut journal = Journal::create(
+            &path, clock.rules(), times::ONE_HOUR, FsyncPolicy::Never
+        ).unwrap();
+
+        assert!(journal.record(&clock).unwrap());
+        assert!(!journal.record(&clock).unwrap());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_recover_skips_torn_entry() {
+        let path = journal_path("torn");
+        fs::write(
+            &path,
+            "rules 600000 600000 5000 1 fischer 0 0\n\
+             state 1000 2000 2 stopped\n\
+             state 900 20"
+        ).unwrap();
+
+        let recovered = recover_from_journal(&path).unwrap();
+        assert_eq!(
+            recovered.read(),
+            (Duration::from_secs(1), Duration::from_secs(2))
+        );
+        assert_eq!(recovered.active_player(), Player::Player2);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_recover_finished_game() {
+        let path = journal_path("finished");
+        fs::write(
+            &path,
+            "rules 600000 600000 5000 1 fischer 0 0\n\
+             state 0 2000 1 finished\n"
+        ).unwrap();
+
+        let recovered = recover_from_journal(&path).unwrap();
+        assert_eq!(recovered.status(), Status::Finished);
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -1,11 +1,143 @@
+//! # Rusty Chess Clock
+//! Clocks for chess and other timed games, with the front ends built on them.
+//!
+//! Front ends and servers built on the crate should only need a small
+//! surface, all exported from the crate root:
+//!
+//! * [`ChessClock`] runs a game under a set of [`Rules`].
+//! * [`ClockEvent`] is something that happened to the clock, and
+//!   [`EventRecord`] pairs it with when it happened. [`ChessClock::events`]
+//!   is the log of the game, oldest first; new events are the ones past the
+//!   length last seen.
+//! * [`ClockSnapshot`] is everything needed to draw the clock at an instant,
+//!   from [`ChessClock::snapshot`] or [`compute_state`].
+//! * [`ViewModel`] is what a front end draws, with the times formatted as
+//!   the clock shows them and how urgently each should catch the eye, from
+//!   [`ChessClock::view_model`].
+//!
+//! The [`prelude`] brings in these and the other common types at once.
+//!
+//! ```
+//! use std::time::Instant;
+//! use rusty_chess_clock::{ChessClock, ClockEvent, Player, Status};
+//!
+//! let mut clock = ChessClock::default();
+//! clock.start();
+//! clock.switch_player();
+//!
+//! let seen = 0;
+//! let new: Vec<&ClockEvent> = clock.events()[seen..].iter()
+//!     .map(|record| record.event())
+//!     .collect();
+//! assert!(new.contains(&&ClockEvent::Pressed(Player::Player1)));
+//!
+//! let snapshot = clock.snapshot(Instant::now());
+//! assert_eq!(snapshot.active_player, Player::Player2);
+//! assert_eq!(snapshot.status, Status::Running);
+//! ```
+
 mod clock;
 mod chess_clock;
+mod clock_group;
+mod delayed_view;
 mod duration_display;
+#[cfg(any(feature = "persistence", feature = "network", feature = "cli"))]
+mod encoding;
+mod event;
+#[cfg(feature = "persistence")]
+mod game_store;
+#[cfg(feature = "network")]
+mod follower;
+mod history;
+mod interval_timer;
+#[cfg(feature = "persistence")]
+mod journal;
+mod millis;
+mod notation;
+mod pre_game;
+#[cfg(feature = "persistence")]
+mod profile;
+#[cfg(feature = "persistence")]
+mod recovery;
+mod round;
+#[cfg(feature = "persistence")]
+mod session;
+mod snapshot;
 mod sleep;
+mod speech_timer;
+mod stats;
+mod time_parts;
+mod time_source;
+mod view_model;
+pub mod branding;
+pub mod burn_in;
+pub mod guard;
+pub mod i18n;
+pub mod prelude;
+pub mod presets;
+#[cfg(feature = "network")]
+pub mod protocol;
+pub mod render;
 pub mod times;
+pub mod touch;
 pub mod utils;
 
-pub use crate::clock::{Clock, ClockMode, ClockState};
-pub use crate::chess_clock::{ChessClock, Rules, Player, Status, TimingMethod};
-pub use crate::duration_display::DurationDisplay;
+#[cfg(feature = "discord")]
+pub mod discord;
+#[cfg(feature = "gui")]
+pub mod gui;
+#[cfg(feature = "cli")]
+pub mod tui;
+#[cfg(feature = "twitch")]
+pub mod twitch;
+
+/// The crate version and the git revision it was built from, as reported by
+/// the binaries' `--version`
+pub const VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"), " (", env!("RUSTY_CHESS_CLOCK_GIT"), ")"
+);
+
+pub use crate::clock::{Clock, ClockChange, ClockMode, ClockState, Frames};
+pub use crate::chess_clock::{
+    AdjustmentError, BackgroundTime, ChessClock, DefaultTime, IncrementStage,
+    NormalizationPolicy, PressLatency, Rules, RulesError, Player,
+    SaturationPolicy, SessionBudget, Status, StoppedSwitch, TimingMethod,
+    MAX_INCREMENT_STAGES
+};
+pub use crate::clock_group::ClockGroup;
+pub use crate::delayed_view::DelayedView;
+pub use crate::duration_display::{
+    display_precision_for, DurationDisplay, SCRAMBLE_THRESHOLD
+};
+pub use crate::event::{ClockEvent, EventRecord};
+#[cfg(feature = "network")]
+pub use crate::follower::Follower;
+pub use crate::history::{HistoryStore, MoveHistory, MoveRecord, RingHistory};
+pub use crate::interval_timer::{Interval, IntervalTimer};
+pub use crate::notation::NotationError;
+pub use crate::pre_game::{PreGame, PreGameClock};
+#[cfg(feature = "persistence")]
+pub use crate::game_store::{GameStore, GameSummary};
+#[cfg(feature = "persistence")]
+pub use crate::journal::{FsyncPolicy, Journal, recover_from_journal};
+#[cfg(feature = "persistence")]
+pub use crate::profile::{LifetimeStats, PlayerProfile, Profiles};
+#[cfg(feature = "persistence")]
+pub use crate::recovery::{AutoSave, Recovery, AUTO_SAVE_INTERVAL};
+pub use crate::round::Round;
+#[cfg(feature = "persistence")]
+pub use crate::session::Session;
+pub use crate::snapshot::{ClockSnapshot, compute_state};
 pub use crate::sleep::Sleep;
+pub use crate::speech_timer::{Card, SpeechTimer};
+pub use crate::stats::{
+    time_pressure_rating, BucketTime, MoveBuckets, TimeHeatmap,
+    TimePressureRating, MAX_RATING, MIN_RATING
+};
+pub use crate::time_parts::TimeParts;
+pub use crate::time_source::{
+    ManualTime, MonotonicTime, ScaledTime, TimeSource
+};
+pub use crate::view_model::{
+    PlayerView, ViewModel, WarningLevel, LOW_TIME_FRACTION
+};
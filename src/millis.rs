@@ -0,0 +1,107 @@
+//! # Millis
+//! Whole milliseconds, the fixed-point unit of all rule math.
+//!
+//! Rule math is done in integer milliseconds so that increments, delays and
+//! adjustments come out bit-identical on every platform and in every replay,
+//! and so that times round-trip through the text encodings exactly. Times
+//! read from the system clock are truncated to the millisecond when they
+//! enter the math.
+
+use std::{fmt::Display, time::Duration};
+
+/// A whole number of milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub(crate) struct Millis(pub u64);
+
+impl Millis {
+    /// Convert a duration, truncating any fraction of a millisecond and
+    /// saturating durations too long to count
+    pub const fn from_duration(duration: Duration) -> Self {
+        let millis = duration.as_millis();
+        if millis > u64::MAX as u128 {
+            Self(u64::MAX)
+        } else {
+            Self(millis as u64)
+        }
+    }
+
+    pub const fn to_duration(self) -> Duration {
+        Duration::from_millis(self.0)
+    }
+
+    pub const fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn saturating_sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+
+    pub const fn saturating_add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+
+    /// Get `self` as a fraction of `whole`, clamped to 0.0 to 1.0
+    ///
+    /// # Returns
+    /// The fraction, or 0.0 if `whole` is zero.
+    pub fn fraction_of(self, whole: Self) -> f32 {
+        if whole.is_zero() {
+            return 0.0;
+        }
+        (self.0 as f64 / whole.0 as f64).min(1.0) as f32
+    }
+
+    #[cfg_attr(
+        not(any(
+            feature = "persistence", feature = "network", feature = "cli"
+        )),
+        allow(dead_code)
+    )]
+    pub fn parse(field: &str) -> Option<Self> {
+        field.parse().ok().map(Self)
+    }
+}
+
+impl From<Duration> for Millis {
+    fn from(duration: Duration) -> Self {
+        Self::from_duration(duration)
+    }
+}
+
+impl From<Millis> for Duration {
+    fn from(millis: Millis) -> Self {
+        millis.to_duration()
+    }
+}
+
+impl Display for Millis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Millis::from(Duration::from_micros(1_999)), Millis(1));
+        assert_eq!(Millis(1_500).to_duration(), Duration::from_millis(1_500));
+        assert_eq!(Millis::from(Duration::MAX), Millis(u64::MAX));
+        assert_eq!(Millis::parse("250"), Some(Millis(250)));
+        assert_eq!(Millis::parse("-1"), None);
+        assert_eq!(Millis(42).to_string(), "42");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(Millis(5).saturating_sub(Millis(7)), Millis(0));
+        let max = Millis(u64::MAX);
+        assert_eq!(max.saturating_add(Millis(1)), max);
+        assert_eq!(Millis(250).fraction_of(Millis(1_000)), 0.25);
+        assert_eq!(Millis(2_000).fraction_of(Millis(1_000)), 1.0);
+        assert_eq!(Millis(1).fraction_of(Millis(0)), 0.0);
+    }
+}
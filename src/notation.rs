@@ -0,0 +1,400 @@
+//! # Notation
+//! Time controls written out as text, for display, for sharing a time
+//! control with other players, and for the `TimeControl` tag of PGN files.
+//!
+//! The long form follows FIDE's regulations, giving the starting time and
+//! then the increment or delay from each move it applies to:
+//!
+//! ```text
+//! 90min, +30s from move 1
+//! 3min, +2s from move 1, +10s from move 40
+//! 5min v 3min, 2s delay from move 1
+//! 30s per move, 2 extensions of 60s
+//! ```
+//!
+//! The compact form is the one seen on tournament posters, `90'+30` or just
+//! `3+2`, with the time in minutes and the increment in seconds. Time
+//! controls that give more time after a number of moves, such as
+//! `40/90'+30`, are recognised but can't be played on this clock.
+
+use std::{fmt::Display, time::Duration};
+use crate::{
+    times::DurationExt, IncrementStage, Player, Rules, RulesError,
+    TimingMethod
+};
+
+/// The reason a time control could not be read from its notation
+#[derive(Debug, PartialEq, Clone)]
+pub enum NotationError {
+    /// A part of the notation could not be read
+    Malformed(String),
+    /// The notation gives time for a number of moves, as in `40/90'`, which
+    /// the clock does not support
+    MovePeriods,
+    /// The notation gives both Fischer increments and Bronstein delays
+    MixedTimingMethods,
+    /// The time control read is not one a game can be played under
+    Rules(RulesError),
+}
+
+impl Display for NotationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed(part) => write!(f, "can't read \"{}\"", part),
+            Self::MovePeriods => write!(
+                f, "time added after a number of moves is not supported"
+            ),
+            Self::MixedTimingMethods => write!(
+                f, "increments and delays can't be mixed"
+            ),
+            Self::Rules(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+impl From<RulesError> for NotationError {
+    fn from(error: RulesError) -> Self {
+        Self::Rules(error)
+    }
+}
+
+impl Rules {
+    /// Write the time control of the rules in the long form, e.g.
+    /// `3min, +2s from move 1, +10s from move 40`
+    ///
+    /// The starter and the settings beyond the time control are not part of
+    /// the notation.
+    pub fn to_notation(&self) -> String {
+        let method = self.get_timing_method();
+        if method == TimingMethod::ShotClock {
+            let mut notation = format!(
+                "{} per move", format_duration(self.get_player1_time())
+            );
+            if self.get_extensions() > 0 {
+                notation.push_str(&format!(
+                    ", {} extensions of {}",
+                    self.get_extensions(), format_duration(self.get_increment())
+                ));
+            }
+            return notation;
+        }
+
+        let (t1, t2) = (self.get_player1_time(), self.get_player2_time());
+        let mut notation = match t1 == t2 {
+            true => format_duration(t1),
+            false => format!(
+                "{} v {}", format_duration(t1), format_duration(t2)
+            ),
+        };
+        // A Bronstein delay is always written, so that it reads back as one
+        let first = (!self.get_increment().is_zero()
+            || method == TimingMethod::Bronstein)
+            .then(|| IncrementStage::new(1, self.get_increment()));
+        for stage in first.iter().chain(self.get_increment_stages()) {
+            let increment = format_duration(stage.get_increment());
+            let credit = match method {
+                TimingMethod::Bronstein => format!("{} delay", increment),
+                _ => format!("+{}", increment),
+            };
+            notation.push_str(&format!(
+                ", {} from move {}", credit, stage.get_from_move()
+            ));
+        }
+        notation
+    }
+
+    /// Write the time control of the rules in the compact form, e.g.
+    /// `90'+30`
+    ///
+    /// Only a Fischer time control with the same time for both players and
+    /// one increment for the whole game has a compact form.
+    pub fn to_compact_notation(&self) -> Option<String> {
+        let time = self.compact_time()?;
+        let minutes = time.as_secs() / 60;
+        let time = match time.as_secs() % 60 {
+            0 if minutes > 0 => format!("{}'", minutes),
+            _ => format!("{}\"", time.as_secs()),
+        };
+        Some(format!("{}+{}", time, self.get_increment().as_secs()))
+    }
+
+    /// Write the time control of the rules as the `TimeControl` tag of a PGN
+    /// file, e.g. `5400+30`
+    ///
+    /// PGN has no delays or changing increments, so only the time controls
+    /// with a compact form can be written.
+    pub fn to_pgn_time_control(&self) -> Option<String> {
+        let time = self.compact_time()?.as_secs();
+        Some(match self.get_increment().as_secs() {
+            0 => time.to_string(),
+            increment => format!("{}+{}", time, increment),
+        })
+    }
+
+    /// Get the time of both players, for a time control that can be written
+    /// in the compact form
+    fn compact_time(&self) -> Option<Duration> {
+        let whole = |time: Duration| time.subsec_nanos() == 0;
+        let (time, increment) = (self.get_player1_time(), self.get_increment());
+        let compact = self.get_timing_method() == TimingMethod::Fischer
+            && self.get_player2_time() == time
+            && self.get_increment_stages().is_empty()
+            && whole(time)
+            && whole(increment);
+        compact.then_some(time)
+    }
+
+    /// Read a time control written in the long or compact form
+    ///
+    /// The rules have player 1 to start and the defaults for everything
+    /// beyond the time control.
+    pub fn from_notation(notation: &str) -> Result<Self, NotationError> {
+        let notation = notation.trim();
+        if notation.contains('/') {
+            return Err(NotationError::MovePeriods);
+        }
+        let mut parts = notation.split(',').map(str::trim);
+        let head = parts.next().unwrap_or_default();
+
+        if let Some(turn) = head.strip_suffix(" per move") {
+            let turn = parse_duration(turn)?;
+            let (extensions, extension) = match parts.next() {
+                None => (0, Duration::ZERO),
+                Some(part) => parse_extensions(part)?,
+            };
+            if let Some(part) = parts.next() {
+                return Err(malformed(part));
+            }
+            let rules = Self::shot_clock(turn, extension, extensions);
+            rules.validate()?;
+            return Ok(rules);
+        }
+
+        let (t1, t2) = match head.split_once(" v ") {
+            Some((t1, t2)) => (parse_time(t1)?, parse_time(t2)?),
+            None if head.contains('+') => {
+                if let Some(part) = parts.next() {
+                    return Err(malformed(part));
+                }
+                return parse_compact(head);
+            },
+            None => {
+                let time = parse_time(head)?;
+                (time, time)
+            },
+        };
+
+        let mut method = None;
+        let mut stages = Vec::new();
+        for part in parts {
+            let (credit, from_move) = part.split_once(" from move ")
+                .ok_or_else(|| malformed(part))?;
+            let from_move = from_move.parse::<u32>().ok()
+                .filter(|from_move| *from_move > 0)
+                .ok_or_else(|| malformed(part))?;
+            let (stage_method, increment) = match credit.strip_prefix('+') {
+                Some(increment) => (TimingMethod::Fischer, increment),
+                None => match credit.strip_suffix(" delay") {
+                    Some(delay) => (TimingMethod::Bronstein, delay),
+                    None => return Err(malformed(part)),
+                },
+            };
+            if method.is_some_and(|method| method != stage_method) {
+                return Err(NotationError::MixedTimingMethods);
+            }
+            method = Some(stage_method);
+            stages.push(IncrementStage::new(
+                from_move, parse_duration(increment)?
+            ));
+        }
+
+        let increment = match stages.first() {
+            Some(stage) if stage.get_from_move() == 1 => {
+                stages.remove(0).get_increment()
+            },
+            _ => Duration::ZERO,
+        };
+        let mut rules = Self::new(
+            t1, t2, increment, Player::Player1,
+            method.unwrap_or(TimingMethod::Fischer)
+        );
+        rules.set_increment_stages(&stages)?;
+        rules.validate()?;
+        Ok(rules)
+    }
+}
+
+fn malformed(part: &str) -> NotationError {
+    NotationError::Malformed(part.to_string())
+}
+
+/// Write a duration in the largest unit that keeps it whole
+fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis > 0 && millis.is_multiple_of(60_000) {
+        format!("{}min", millis / 60_000)
+    } else if millis.is_multiple_of(1_000) {
+        format!("{}s", millis / 1_000)
+    } else {
+        format!("{}ms", millis)
+    }
+}
+
+/// Read a duration written with its unit, e.g. `90min` or `30s`
+fn parse_duration(text: &str) -> Result<Duration, NotationError> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number = number.parse::<u64>().map_err(|_| malformed(text))?;
+    match unit.trim() {
+        "h" => Ok(number.hours()),
+        "min" | "'" => Ok(number.minutes()),
+        "s" | "\"" => Ok(number.seconds()),
+        "ms" => Ok(Duration::from_millis(number)),
+        _ => Err(malformed(text)),
+    }
+}
+
+/// Read a starting time, which is in minutes if it has no unit
+fn parse_time(text: &str) -> Result<Duration, NotationError> {
+    match text.trim().parse::<u64>() {
+        Ok(minutes) => Ok(minutes.minutes()),
+        Err(_) => parse_duration(text),
+    }
+}
+
+/// Read the extensions of a shot clock, e.g. `2 extensions of 60s`
+fn parse_extensions(part: &str) -> Result<(u32, Duration), NotationError> {
+    let (count, time) = part.split_once(" extensions of ")
+        .or_else(|| part.split_once(" extension of "))
+        .ok_or_else(|| malformed(part))?;
+    let count = count.parse().map_err(|_| malformed(part))?;
+    Ok((count, parse_duration(time)?))
+}
+
+/// Read the compact form, e.g. `90'+30` or `3+2`
+fn parse_compact(text: &str) -> Result<Rules, NotationError> {
+    let (time, increment) = text.split_once('+').ok_or_else(|| malformed(text))?;
+    let time = parse_time(time)?;
+    let increment = increment.trim();
+    let increment = match increment.parse::<u64>() {
+        Ok(seconds) => seconds.seconds(),
+        Err(_) => parse_duration(increment)?,
+    };
+    let rules = Rules::new(
+        time, time, increment, Player::Player1, TimingMethod::Fischer
+    );
+    rules.validate()?;
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{presets, times};
+
+    #[test]
+    fn test_to_notation() {
+        assert_eq!(
+            presets::CLASSICAL_90_30.to_notation(), "90min, +30s from move 1"
+        );
+        assert_eq!(presets::BULLET_1_0.to_notation(), "1min");
+
+        let mut rules = Rules::new(
+            times::FIVE_MINUTES, times::THREE_MINUTES, times::TWO_SECONDS,
+            Player::Player1, TimingMethod::Bronstein
+        );
+        rules.set_increment_stages(&[
+            IncrementStage::new(40, Duration::from_millis(1500))
+        ]).unwrap();
+        assert_eq!(
+            rules.to_notation(),
+            "5min v 3min, 2s delay from move 1, 1500ms delay from move 40"
+        );
+
+        let shot_clock = Rules::shot_clock(
+            times::THIRTY_SECONDS, times::ONE_MINUTE, 2
+        );
+        assert_eq!(shot_clock.to_notation(), "30s per move, 2 extensions of 1min");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut staged = Rules::new(
+            times::THREE_MINUTES, times::THREE_MINUTES, times::TWO_SECONDS,
+            Player::Player1, TimingMethod::Fischer
+        );
+        staged.set_increment_stages(&[
+            IncrementStage::new(40, times::TEN_SECONDS)
+        ]).unwrap();
+        let delayed_stage = Rules::from_notation(
+            "5min, 3s delay from move 20"
+        ).unwrap();
+        for rules in presets::ALL.iter().map(|(_, rules)| rules)
+            .chain([&staged, &delayed_stage])
+        {
+            let notation = rules.to_notation();
+            let read = Rules::from_notation(&notation).unwrap();
+            assert_eq!(read.to_notation(), notation);
+            assert_eq!(read.get_timing_method(), rules.get_timing_method());
+            assert_eq!(read.increment_at(100), rules.increment_at(100));
+        }
+    }
+
+    #[test]
+    fn test_compact() {
+        assert_eq!(
+            presets::CLASSICAL_90_30.to_compact_notation().unwrap(), "90'+30"
+        );
+        assert_eq!(presets::BULLET_1_0.to_pgn_time_control().unwrap(), "60");
+        assert_eq!(
+            presets::CLASSICAL_90_30.to_pgn_time_control().unwrap(), "5400+30"
+        );
+        assert_eq!(presets::SHOT_CLOCK_30.to_compact_notation(), None);
+
+        for notation in ["90'+30", "90+30", "90min + 30s"] {
+            let rules = Rules::from_notation(notation).unwrap();
+            assert_eq!(rules.get_player1_time(), 90.minutes());
+            assert_eq!(rules.get_increment(), times::THIRTY_SECONDS);
+        }
+        let seconds = Rules::from_notation("45\"+1").unwrap();
+        assert_eq!(seconds.get_time(Player::Player2), 45.seconds());
+        assert_eq!(seconds.to_compact_notation().unwrap(), "45\"+1");
+    }
+
+    #[test]
+    fn test_rejects() {
+        assert_eq!(
+            Rules::from_notation("40/90'+30").unwrap_err(),
+            NotationError::MovePeriods
+        );
+        assert_eq!(
+            Rules::from_notation("90min/40+30min, +30s from move 1")
+                .unwrap_err(),
+            NotationError::MovePeriods
+        );
+        assert_eq!(
+            Rules::from_notation("5min, +2s from move 1, 2s delay from move 9")
+                .unwrap_err(),
+            NotationError::MixedTimingMethods
+        );
+        assert_eq!(
+            Rules::from_notation("0min").unwrap_err(),
+            NotationError::Rules(RulesError::ZeroTime(Player::Player1))
+        );
+        for notation in [
+            "", "ninety", "5min, +2s", "5min, +2s from move 0", "5 fortnights",
+            "30s per move, 2 extras",
+        ] {
+            assert!(
+                matches!(
+                    Rules::from_notation(notation),
+                    Err(NotationError::Malformed(_))
+                ),
+                "{}", notation
+            );
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! # Pre-game
+//! A countdown to the start of a game, such as the five minutes to the start
+//! of a round, which starts the game clock when it runs out.
+//!
+//! As for the scheduled start of a `Round`, the game is recorded as starting
+//! the instant the countdown ran out, however late the update that notices it
+//! comes, so the players lose nothing to a slow display.
+
+use std::time::{Duration, Instant};
+use crate::ChessClock;
+
+/// A countdown to the start of a game
+#[derive(Debug, Clone, Copy)]
+pub struct PreGame {
+    /// The time left when the countdown was last started or stopped
+    left: Duration,
+    /// When the countdown was last started, if it is running
+    since: Option<Instant>,
+}
+
+impl PreGame {
+    /// Create a stopped countdown of `time`
+    pub fn new(time: Duration) -> Self {
+        Self { left: time, since: None }
+    }
+
+    /// Starts the countdown, if it is not already running
+    pub fn start(&mut self) {
+        if self.since.is_none() {
+            self.since = Some(Instant::now());
+        }
+    }
+
+    /// Stops the countdown, such as while the arbiter waits for a late
+    /// board, keeping the time left
+    pub fn stop(&mut self) {
+        self.left = self.remaining();
+        self.since = None;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.since.is_some()
+    }
+
+    /// Get the time left before the game starts
+    pub fn remaining(&self) -> Duration {
+        self.remaining_at(Instant::now())
+    }
+
+    /// Get the time left before the game starts at `now`
+    pub fn remaining_at(&self, now: Instant) -> Duration {
+        match self.since {
+            Some(since) => {
+                self.left.saturating_sub(now.saturating_duration_since(since))
+            },
+            None => self.left,
+        }
+    }
+
+    /// Get when the countdown runs out, if it is running
+    pub fn deadline(&self) -> Option<Instant> {
+        self.since.map(|since| since + self.left)
+    }
+
+    /// Starts `clock` if the countdown has run out, as at the instant it did
+    ///
+    /// The countdown stops once it has started the game, so the clock is
+    /// only ever started once.
+    ///
+    /// # Returns
+    /// Whether the game was started.
+    pub fn hand_off(&mut self, clock: &mut ChessClock) -> bool {
+        let Some(deadline) = self.deadline() else {
+            return false;
+        };
+        if Instant::now() < deadline {
+            return false;
+        }
+        self.left = Duration::ZERO;
+        self.since = None;
+        clock.start_at(deadline);
+        true
+    }
+
+    /// Joins the countdown to the clock it starts
+    pub fn then(self, clock: ChessClock) -> PreGameClock {
+        PreGameClock { pre_game: Some(self), clock }
+    }
+}
+
+/// A countdown to the start of a game, followed by the game itself
+///
+/// Until the countdown runs out, the clock stays stopped; `update` then
+/// starts it for the starter of its rules.
+#[derive(Debug)]
+pub struct PreGameClock {
+    /// The countdown, until it has started the game
+    pre_game: Option<PreGame>,
+    clock: ChessClock,
+}
+
+impl PreGameClock {
+    /// Get the countdown, until it has started the game
+    pub fn pre_game(&self) -> Option<&PreGame> {
+        self.pre_game.as_ref()
+    }
+
+    /// Get the countdown to start or stop it, until it has started the game
+    pub fn pre_game_mut(&mut self) -> Option<&mut PreGame> {
+        self.pre_game.as_mut()
+    }
+
+    /// Get the time left before the game starts, or `None` once it has
+    pub fn remaining(&self) -> Option<Duration> {
+        self.pre_game.as_ref().map(PreGame::remaining)
+    }
+
+    pub fn clock(&self) -> &ChessClock {
+        &self.clock
+    }
+
+    /// Get the game clock to play on, such as to press it
+    pub fn clock_mut(&mut self) -> &mut ChessClock {
+        &mut self.clock
+    }
+
+    pub fn into_clock(self) -> ChessClock {
+        self.clock
+    }
+
+    /// Starts the game now, without waiting for the countdown
+    pub fn start_now(&mut self) {
+        self.pre_game = None;
+        self.clock.start();
+    }
+
+    /// Updates the clock, starting the game if the countdown has run out
+    pub fn update(&mut self) {
+        let started = self.pre_game.as_mut()
+            .is_some_and(|pre_game| pre_game.hand_off(&mut self.clock));
+        if started {
+            self.pre_game = None;
+        }
+        self.clock.update();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{times, ClockEvent, Player, Rules, Sleep, Status};
+
+    #[test]
+    fn test_stopped_countdown_keeps_time() {
+        let mut pre_game = PreGame::new(times::FIVE_MINUTES);
+        assert_eq!(pre_game.remaining(), times::FIVE_MINUTES);
+        assert_eq!(pre_game.deadline(), None);
+
+        pre_game.start();
+        Duration::from_millis(20).sleep();
+        pre_game.stop();
+        let left = pre_game.remaining();
+        assert!(left <= times::FIVE_MINUTES - Duration::from_millis(20));
+        Duration::from_millis(20).sleep();
+        assert_eq!(pre_game.remaining(), left);
+        assert!(!pre_game.hand_off(&mut ChessClock::default()));
+    }
+
+    #[test]
+    fn test_hands_off_at_zero() {
+        let mut rules = Rules::default();
+        rules.set_starter(Player::Player2);
+        let mut pre_game = PreGame::new(Duration::from_millis(30));
+        pre_game.start();
+        let deadline = pre_game.deadline().unwrap();
+        let mut game = pre_game.then(ChessClock::new(rules));
+
+        game.update();
+        assert!(game.remaining().is_some());
+        assert_eq!(game.clock().status(), Status::Stopped);
+
+        Duration::from_millis(50).sleep();
+        game.update();
+        assert_eq!(game.remaining(), None);
+        assert_eq!(game.clock().status(), Status::Running);
+        let started = &game.clock().events()[0];
+        assert_eq!(started.event(), &ClockEvent::Started(Player::Player2));
+        assert_eq!(started.instant(), deadline);
+
+        // The countdown only starts the game once
+        game.clock_mut().stop();
+        game.update();
+        assert_eq!(game.into_clock().status(), Status::Stopped);
+    }
+}
@@ -0,0 +1,22 @@
+//! # Prelude
+//! The types most applications need, to bring in with one import.
+//!
+//! ```
+//! use rusty_chess_clock::prelude::*;
+//!
+//! let mut clock = ChessClock::new(presets::BLITZ_3_2);
+//! clock.start();
+//! clock.switch_player();
+//! assert_eq!(clock.active_player(), Player::Player2);
+//! assert_eq!(clock.status(), Status::Running);
+//! assert_eq!(clock.rules().get_timing_method(), TimingMethod::Fischer);
+//! assert_eq!(DurationDisplay::from(5.minutes()).to_string(), "05:00");
+//! ```
+
+pub use crate::{
+    presets,
+    times::{self, DurationExt},
+    ChessClock, Clock, ClockEvent, ClockMode, ClockSnapshot, DurationDisplay,
+    EventRecord, IncrementStage, Player, Rules, Status, TimingMethod,
+    ViewModel, WarningLevel,
+};
@@ -0,0 +1,128 @@
+//! # Presets
+//! Common time controls as constant rules, usable in const contexts such as
+//! firmware images, and shared by the front ends and tests.
+//!
+//! Every preset is checked with `Rules::is_valid` at compile time.
+
+use std::time::Duration;
+use crate::{Player, Rules, TimingMethod, times};
+
+/// 1 minute per player, no increment
+pub const BULLET_1_0: Rules = Rules::new(
+    times::ONE_MINUTE, times::ONE_MINUTE,
+    Duration::ZERO, Player::Player1, TimingMethod::Fischer
+);
+
+/// 3 minutes per player, 2 second Fischer increment
+pub const BLITZ_3_2: Rules = Rules::new(
+    times::THREE_MINUTES, times::THREE_MINUTES,
+    times::TWO_SECONDS, Player::Player1, TimingMethod::Fischer
+);
+
+/// 5 minutes per player, 3 second Bronstein delay
+pub const BLITZ_5_3_BRONSTEIN: Rules = Rules::new(
+    times::FIVE_MINUTES, times::FIVE_MINUTES,
+    times::THREE_SECONDS, Player::Player1, TimingMethod::Bronstein
+);
+
+/// 10 minutes per player, 5 second Fischer increment, the default rules
+pub const RAPID_10_5: Rules = Rules::new(
+    times::TEN_MINUTES, times::TEN_MINUTES,
+    times::FIVE_SECONDS, Player::Player1, TimingMethod::Fischer
+);
+
+/// 15 minutes per player, 10 second Fischer increment
+pub const RAPID_15_10: Rules = Rules::new(
+    times::FIFTEEN_MINUTES, times::FIFTEEN_MINUTES,
+    times::TEN_SECONDS, Player::Player1, TimingMethod::Fischer
+);
+
+/// 90 minutes per player, 30 second Fischer increment
+pub const CLASSICAL_90_30: Rules = Rules::new(
+    times::NINETY_MINUTES, times::NINETY_MINUTES,
+    times::THIRTY_SECONDS, Player::Player1, TimingMethod::Fischer
+);
+
+/// 30 second shot clock with one 30 second extension per player
+pub const SHOT_CLOCK_30: Rules = Rules::shot_clock(
+    times::THIRTY_SECONDS, times::THIRTY_SECONDS, 1
+);
+
+/// Every preset with its name
+pub const ALL: [(&str, Rules); 7] = [
+    ("bullet-1+0", BULLET_1_0),
+    ("blitz-3+2", BLITZ_3_2),
+    ("blitz-5+3-bronstein", BLITZ_5_3_BRONSTEIN),
+    ("rapid-10+5", RAPID_10_5),
+    ("rapid-15+10", RAPID_15_10),
+    ("classical-90+30", CLASSICAL_90_30),
+    ("shot-clock-30", SHOT_CLOCK_30),
+];
+
+/// Get the preset with the given name
+pub fn by_name(name: &str) -> Option<Rules> {
+    ALL.iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, rules)| rules.clone())
+}
+
+/// Get the name of the preset a set of rules was made from, if any
+///
+/// Only the time control is compared, so rules from a preset with a
+/// different starter or other settings still match it.
+pub fn name_of(rules: &Rules) -> Option<&'static str> {
+    ALL.iter()
+        .find(|(_, preset)| {
+            preset.get_player1_time() == rules.get_player1_time()
+                && preset.get_player2_time() == rules.get_player2_time()
+                && preset.get_increment() == rules.get_increment()
+                && preset.get_increment_stages()
+                    == rules.get_increment_stages()
+                && preset.get_timing_method() == rules.get_timing_method()
+                && preset.get_extensions() == rules.get_extensions()
+        })
+        .map(|(name, _)| *name)
+}
+
+const _: () = {
+    let mut i = 0;
+    while i < ALL.len() {
+        assert!(ALL[i].1.is_valid(), "preset gives a player no time");
+        i += 1;
+    }
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_by_name() {
+        let rules = by_name("blitz-3+2").unwrap();
+        assert_eq!(rules.get_player1_time(), times::THREE_MINUTES);
+        assert_eq!(rules.get_increment(), times::TWO_SECONDS);
+        assert!(by_name("blitz").is_none());
+    }
+
+    #[test]
+    fn test_name_of() {
+        let mut rules = BLITZ_3_2;
+        rules.set_starter(Player::Player2);
+        assert_eq!(name_of(&rules), Some("blitz-3+2"));
+        rules.set_increment(times::FIVE_SECONDS);
+        assert_eq!(name_of(&rules), None);
+    }
+
+    #[test]
+    fn test_shot_clock_preset() {
+        assert_eq!(SHOT_CLOCK_30.get_timing_method(), TimingMethod::ShotClock);
+        assert_eq!(SHOT_CLOCK_30.get_extensions(), 1);
+    }
+
+    #[test]
+    fn test_default_rules() {
+        let rules = Rules::default();
+        assert_eq!(rules.get_player2_time(), RAPID_10_5.get_player2_time());
+        assert_eq!(rules.get_increment(), RAPID_10_5.get_increment());
+    }
+}
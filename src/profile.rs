@@ -0,0 +1,341 @@
+//! # Profile
+//! Named players with their preferred time controls and lifetime clock
+//! stats, kept across games in a single plain text file.
+//!
+//! The file has a header line, then a block of lines for each player:
+//!
+//! ```text
+//! rusty_chess_clock profiles 1
+//! player <name>
+//! stats <games> <flags> <moves> <think ms>
+//! preset <games> <preset name>
+//! ```
+//!
+//! A player has one `preset` line for each preset they have played, and
+//! their favourite is the one they have played most.
+
+use std::{
+    env,
+    fs,
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use crate::{
+    encoding::{format_millis, invalid, parse_millis},
+    presets, ChessClock, Player,
+};
+
+const HEADER: &str = "rusty_chess_clock profiles 1";
+
+/// A player's clock stats over every recorded game
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LifetimeStats {
+    games: u32,
+    flags: u32,
+    moves: u32,
+    think_time: Duration,
+}
+
+impl LifetimeStats {
+    pub const fn get_games(&self) -> u32 {
+        self.games
+    }
+
+    /// Get the number of games the player lost on time
+    pub const fn get_flags(&self) -> u32 {
+        self.flags
+    }
+
+    pub const fn get_moves(&self) -> u32 {
+        self.moves
+    }
+
+    /// Get the total time the player's clock has run
+    pub const fn get_think_time(&self) -> Duration {
+        self.think_time
+    }
+
+    /// Get the average time the player takes per move
+    ///
+    /// # Returns
+    /// The average, or `None` if no moves have been recorded.
+    pub fn average_move_time(&self) -> Option<Duration> {
+        (self.moves > 0).then(|| self.think_time / self.moves)
+    }
+}
+
+/// A named player and their history at the clock
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerProfile {
+    name: String,
+    stats: LifetimeStats,
+    /// The number of games played with each preset
+    presets: Vec<(String, u32)>,
+}
+
+impl PlayerProfile {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            stats: LifetimeStats::default(),
+            presets: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn stats(&self) -> &LifetimeStats {
+        &self.stats
+    }
+
+    /// Get the preset the player has played most, the first played among
+    /// equals
+    pub fn favourite_preset(&self) -> Option<&str> {
+        self.presets.iter()
+            .rev()
+            .max_by_key(|(_, games)| *games)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Adds a finished game played as `player` to the stats
+    fn record(&mut self, clock: &ChessClock, player: Player) {
+        self.stats.games += 1;
+        self.stats.flags += u32::from(clock.flagged() == Some(player));
+        self.stats.moves += clock.moves_made(player);
+        self.stats.think_time += clock.think_time(player);
+        if let Some(preset) = presets::name_of(clock.rules()) {
+            match self.presets.iter_mut().find(|(name, _)| name == preset) {
+                Some((_, games)) => *games += 1,
+                None => self.presets.push((preset.to_string(), 1)),
+            }
+        }
+    }
+}
+
+/// Every player profile on a machine
+#[derive(Debug, Clone, Default)]
+pub struct Profiles {
+    players: Vec<PlayerProfile>,
+}
+
+impl Profiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get where profiles are kept by default, in the user's configuration
+    /// directory
+    ///
+    /// # Returns
+    /// `$XDG_CONFIG_HOME/rusty_chess_clock/profiles`, falling back to
+    /// `$HOME/.config`, or `None` if neither variable is set.
+    pub fn default_path() -> Option<PathBuf> {
+        let config = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".config"))
+            })?;
+        Some(config.join("rusty_chess_clock").join("profiles"))
+    }
+
+    pub fn players(&self) -> &[PlayerProfile] {
+        &self.players
+    }
+
+    /// Get the profile of the player called `name`
+    pub fn get(&self, name: &str) -> Option<&PlayerProfile> {
+        self.players.iter().find(|player| player.name == name)
+    }
+
+    /// Adds a player called `name`, if there is not one already
+    ///
+    /// Names are trimmed, and a blank name is not added.
+    ///
+    /// # Returns
+    /// Whether a player was added.
+    pub fn add(&mut self, name: &str) -> bool {
+        let name = name.trim();
+        if name.is_empty() || self.get(name).is_some() {
+            return false;
+        }
+        self.players.push(PlayerProfile::new(name));
+        true
+    }
+
+    /// Adds a finished game to the stats of the players who played it
+    ///
+    /// # Arguments
+    /// * `clock` - The clock of the game.
+    /// * `names` - The name of each player, in player order, or `None` for
+    ///   a player without a profile. Players without a profile yet are
+    ///   added.
+    pub fn record_game(&mut self, clock: &ChessClock, names: [Option<&str>; 2]) {
+        for (player, name) in Player::ALL.into_iter().zip(names) {
+            let Some(name) = name else {
+                continue;
+            };
+            self.add(name);
+            if let Some(profile) = self.players.iter_mut()
+                .find(|profile| profile.name == name.trim())
+            {
+                profile.record(clock, player);
+            }
+        }
+    }
+
+    /// Reads profiles saved by `save`
+    ///
+    /// A missing file is read as no profiles, so that the first run of a
+    /// front end starts empty.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self::new());
+            },
+            Err(error) => return Err(error),
+        };
+        let mut lines = contents.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(invalid("not a profiles file"));
+        }
+
+        let mut players: Vec<PlayerProfile> = Vec::new();
+        for line in lines {
+            let (kind, rest) = line.split_once(' ')
+                .ok_or_else(|| invalid("malformed profile line"))?;
+            if kind == "player" {
+                players.push(PlayerProfile::new(rest));
+                continue;
+            }
+            let player = players.last_mut()
+                .ok_or_else(|| invalid("profile line before any player"))?;
+            match kind {
+                "stats" => player.stats = parse_stats(rest)?,
+                "preset" => {
+                    let (games, name) = rest.split_once(' ')
+                        .ok_or_else(|| invalid("malformed preset"))?;
+                    let games = games.parse()
+                        .map_err(|_| invalid("malformed preset"))?;
+                    player.presets.push((name.to_string(), games));
+                },
+                _ => return Err(invalid("unknown profile line")),
+            }
+        }
+        Ok(Self { players })
+    }
+
+    /// Writes the profiles to a file at `path`, creating its directory and
+    /// replacing any existing file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let mut contents = format!("{}\n", HEADER);
+        for player in &self.players {
+            let stats = player.stats;
+            contents.push_str(&format!(
+                "player {}\nstats {} {} {} {}\n",
+                player.name, stats.games, stats.flags, stats.moves,
+                format_millis(stats.think_time)
+            ));
+            for (preset, games) in &player.presets {
+                contents.push_str(&format!("preset {} {}\n", games, preset));
+            }
+        }
+        fs::write(path, contents)
+    }
+}
+
+fn parse_stats(fields: &str) -> io::Result<LifetimeStats> {
+    let fields: Vec<&str> = fields.split_whitespace().collect();
+    let count = |field: &str| field.parse::<u32>()
+        .map_err(|_| invalid("malformed stats"));
+    match fields.as_slice() {
+        [games, flags, moves, think_time] => Ok(LifetimeStats {
+            games: count(games)?,
+            flags: count(flags)?,
+            moves: count(moves)?,
+            think_time: parse_millis(think_time)?,
+        }),
+        _ => Err(invalid("malformed stats")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{times, Rules, Sleep};
+
+    fn finished_game() -> ChessClock {
+        let mut clock = ChessClock::new(presets::BLITZ_3_2);
+        clock.start();
+        Duration::from_millis(20).sleep();
+        clock.press(Player::Player1);
+        clock.subtract_time(Player::Player2, times::ONE_HOUR, None).unwrap();
+        clock.finish();
+        clock
+    }
+
+    #[test]
+    fn test_record_game() {
+        let mut profiles = Profiles::new();
+        assert!(profiles.add(" Alice "));
+        assert!(!profiles.add("Alice"));
+        assert!(!profiles.add("  "));
+
+        let clock = finished_game();
+        profiles.record_game(&clock, [Some("Alice"), Some("Bob")]);
+        profiles.record_game(&clock, [Some("Alice"), None]);
+
+        let alice = profiles.get("Alice").unwrap();
+        assert_eq!(alice.stats().get_games(), 2);
+        assert_eq!(alice.stats().get_flags(), 0);
+        assert_eq!(alice.stats().get_moves(), 2);
+        assert!(alice.stats().average_move_time().unwrap() >= Duration::from_millis(20));
+        assert_eq!(alice.favourite_preset(), Some("blitz-3+2"));
+
+        let bob = profiles.get("Bob").unwrap();
+        assert_eq!(bob.stats().get_games(), 1);
+        assert_eq!(bob.stats().get_flags(), 1);
+        assert_eq!(bob.stats().average_move_time(), None);
+
+        // Games outside the presets count, but are no one's favourite
+        let mut clock = ChessClock::new(Rules::handicap_from_ratings(
+            1900, 2100, times::TEN_MINUTES
+        ));
+        clock.finish();
+        profiles.record_game(&clock, [None, Some("Bob")]);
+        let bob = profiles.get("Bob").unwrap();
+        assert_eq!(bob.stats().get_games(), 2);
+        assert_eq!(bob.favourite_preset(), Some("blitz-3+2"));
+    }
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let path = env::temp_dir()
+            .join(format!("rusty_chess_clock_profiles_{}", std::process::id()))
+            .join("profiles");
+        assert!(Profiles::load(&path).unwrap().players().is_empty());
+
+        let mut profiles = Profiles::new();
+        profiles.add("Magnus C");
+        profiles.record_game(&finished_game(), [Some("Magnus C"), None]);
+        profiles.save(&path).unwrap();
+        let loaded = Profiles::load(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        let original = profiles.get("Magnus C").unwrap();
+        let loaded = loaded.get("Magnus C").unwrap();
+        assert_eq!(loaded.stats().get_games(), 1);
+        assert_eq!(
+            loaded.stats().get_think_time().as_millis(),
+            original.stats().get_think_time().as_millis()
+        );
+        assert_eq!(loaded.favourite_preset(), Some("blitz-3+2"));
+    }
+}
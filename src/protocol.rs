@@ -0,0 +1,394 @@
+//! # Protocol
+//! The wire protocol spoken by every remote interface to a chess clock, so
+//! that third-party clients can be built against one stable contract.
+//!
+//! A connection carries one message per line, each starting with its kind.
+//! Times are whole milliseconds and the other fields are encoded as in the
+//! journal and session files:
+//!
+//! ```text
+//! hello <min version> <max version>
+//! welcome <version>
+//! rules <p1 ms> <p2 ms> <increment ms> <starter> <timing method> ...
+//! state <p1 ms> <p2 ms> <active player> <status>
+//! event <sequence> <elapsed ms> <unix ms> <event> [<player> [...]]
+//! press <player>
+//! start
+//! stop
+//! resume <sequence>
+//! heartbeat <events>
+//! error <message>
+//! ```
+//!
+//! A client opens with `hello`, offering the range of versions it speaks, and
+//! the clock answers with `welcome` at the highest version both speak, or
+//! `error` if there is none. Events are numbered by their position in the
+//! clock's event log, so that a client can tell which it has already seen.
+//! A client reconnecting sends `resume` with the first sequence it has not
+//! seen. The clock sends `heartbeat` every [`HEARTBEAT_INTERVAL`] when it has
+//! nothing else to say, so that silence means the connection is gone, with
+//! the length of its log so that a lost last event is noticed.
+
+use std::{io, time::{Duration, Instant}};
+use crate::{
+    EventRecord, Player, Rules, Status,
+    encoding::{
+        format_event, format_player, format_rules, format_state_fields,
+        invalid, parse_event, parse_player, parse_rules, parse_state
+    },
+};
+
+/// The newest version of the protocol
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The oldest version of the protocol still spoken
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// The longest the clock stays silent before sending a heartbeat
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A message between a chess clock and a remote client
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Opens a connection, offering the versions the client speaks
+    Hello { min_version: u32, max_version: u32 },
+    /// Accepts a connection at the given version
+    Welcome { version: u32 },
+    /// The rules of the game
+    Rules(Box<Rules>),
+    /// The remaining times, active player and status of the clock
+    State { times: (Duration, Duration), active: Player, status: Status },
+    /// The event at position `sequence` of the clock's event log
+    Event { sequence: u64, record: EventRecord },
+    /// Asks the clock to take a press by the given player
+    Press(Player),
+    /// Asks the clock to start
+    Start,
+    /// Asks the clock to stop
+    Stop,
+    /// Asks the clock for its rules, state and the events from position
+    /// `sequence` of its log, after reconnecting
+    Resume { sequence: u64 },
+    /// Shows that the clock is still connected, with the number of events
+    /// in its log
+    Heartbeat { events: u64 },
+    /// Refuses a message or connection, with the reason
+    Error(String),
+}
+
+impl Message {
+    /// Get the `hello` message offering every version this crate speaks
+    pub fn hello() -> Self {
+        Self::Hello {
+            min_version: MIN_PROTOCOL_VERSION,
+            max_version: PROTOCOL_VERSION,
+        }
+    }
+
+    /// Encodes the message as a line, without its newline
+    pub fn encode(&self) -> String {
+        match self {
+            Self::Hello { min_version, max_version } => {
+                format!("hello {} {}", min_version, max_version)
+            },
+            Self::Welcome { version } => format!("welcome {}", version),
+            Self::Rules(rules) => format_rules(rules),
+            Self::State { times, active, status } => {
+                format!("state {}", format_state_fields(*times, *active, *status))
+            },
+            Self::Event { sequence, record } => {
+                format!("event {} {}", sequence, format_event(record))
+            },
+            Self::Press(player) => format!("press {}", format_player(*player)),
+            Self::Start => "start".to_string(),
+            Self::Stop => "stop".to_string(),
+            Self::Resume { sequence } => format!("resume {}", sequence),
+            Self::Heartbeat { events } => format!("heartbeat {}", events),
+            Self::Error(message) => format!("error {}", message),
+        }
+    }
+
+    /// Decodes a line encoded by [`Message::encode`]
+    ///
+    /// The instants of decoded events are taken relative to `origin`, the
+    /// local instant at which the remote clock was created.
+    pub fn decode(line: &str, origin: Instant) -> io::Result<Self> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let message = match kind {
+            "hello" => match parse_versions(rest)?.as_slice() {
+                [min_version, max_version] => Self::Hello {
+                    min_version: *min_version,
+                    max_version: *max_version,
+                },
+                _ => return Err(invalid("malformed hello")),
+            },
+            "welcome" => match parse_versions(rest)?.as_slice() {
+                [version] => Self::Welcome { version: *version },
+                _ => return Err(invalid("malformed welcome")),
+            },
+            "rules" => Self::Rules(Box::new(parse_rules(line)?)),
+            "state" => {
+                let (times, active, status) = parse_state(rest)?;
+                Self::State { times, active, status }
+            },
+            "event" => {
+                let (sequence, record) = rest.split_once(' ')
+                    .ok_or_else(|| invalid("truncated event"))?;
+                Self::Event {
+                    sequence: sequence.parse()
+                        .map_err(|_| invalid("malformed sequence"))?,
+                    record: parse_event(record, origin)?,
+                }
+            },
+            "press" => Self::Press(parse_player(rest)?),
+            "start" if rest.is_empty() => Self::Start,
+            "stop" if rest.is_empty() => Self::Stop,
+            "resume" => Self::Resume {
+                sequence: rest.parse()
+                    .map_err(|_| invalid("malformed sequence"))?,
+            },
+            "heartbeat" => Self::Heartbeat {
+                events: rest.parse()
+                    .map_err(|_| invalid("malformed event count"))?,
+            },
+            "error" => Self::Error(rest.to_string()),
+            _ => return Err(invalid("unknown message")),
+        };
+        Ok(message)
+    }
+}
+
+fn parse_versions(fields: &str) -> io::Result<Vec<u32>> {
+    fields.split_whitespace()
+        .map(|field| field.parse().map_err(|_| invalid("malformed version")))
+        .collect()
+}
+
+/// Picks the version to speak with a client offering versions `min_version`
+/// to `max_version`
+///
+/// Returns the highest version both sides speak, or `None` if there is none.
+pub fn negotiate(min_version: u32, max_version: u32) -> Option<u32> {
+    let version = max_version.min(PROTOCOL_VERSION);
+    (version >= min_version.max(MIN_PROTOCOL_VERSION)).then_some(version)
+}
+
+/// Answers the `hello` that opens a connection
+///
+/// Returns `welcome` at the negotiated version, or `error` if the message is
+/// not a `hello` or no version is spoken by both sides.
+pub fn answer_hello(message: &Message) -> Message {
+    match message {
+        Message::Hello { min_version, max_version } => {
+            match negotiate(*min_version, *max_version) {
+                Some(version) => Message::Welcome { version },
+                None => Message::Error(format!(
+                    "no common version, this clock speaks {} to {}",
+                    MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+                )),
+            }
+        },
+        _ => Message::Error("expected hello".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        times, ChessClock, ClockEvent, DefaultTime, IncrementStage,
+        SessionBudget, TimingMethod
+    };
+
+    fn round_trip(message: &Message) {
+        let line = message.encode();
+        let decoded = Message::decode(&line, Instant::now()).unwrap();
+        assert_eq!(decoded.encode(), line);
+    }
+
+    #[test]
+    fn test_round_trip_control_messages() {
+        for message in [
+            Message::hello(),
+            Message::Welcome { version: 1 },
+            Message::Press(Player::Player1),
+            Message::Press(Player::Player2),
+            Message::Start,
+            Message::Stop,
+            Message::Resume { sequence: 17 },
+            Message::Heartbeat { events: 3 },
+            Message::Error("clock is finished".to_string()),
+        ] {
+            round_trip(&message);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_rules_and_state() {
+        let mut rules = Rules::new(
+            times::FIVE_MINUTES, times::THREE_MINUTES, times::TWO_SECONDS,
+            Player::Player2, TimingMethod::Bronstein
+        );
+        rules.set_session_budget(Some(
+            SessionBudget::new(times::ONE_HOUR, true)
+        ));
+        rules.set_default_time(Some(
+            DefaultTime::new(times::THIRTY_MINUTES, false)
+        ));
+        round_trip(&Message::Rules(Box::new(rules.clone())));
+        rules.set_increment_stages(&[
+            IncrementStage::new(40, times::TEN_SECONDS),
+            IncrementStage::new(60, Duration::ZERO),
+        ]).unwrap();
+        rules.set_strict(true);
+        rules.set_pause_allowance(Some(times::FIVE_MINUTES));
+        let line = Message::Rules(Box::new(rules)).encode();
+        assert!(line.ends_with(" 40:10000,60:0 1 300000"));
+        round_trip(&Message::decode(&line, Instant::now()).unwrap());
+
+        for status in [Status::Stopped, Status::Running, Status::Finished] {
+            round_trip(&Message::State {
+                times: (times::ONE_MINUTE, Duration::from_millis(1234)),
+                active: Player::Player2,
+                status,
+            });
+        }
+    }
+
+    #[test]
+    fn test_round_trip_every_event() {
+        let reason = Some("illegal move".to_string());
+        let events = [
+            ClockEvent::Started(Player::Player1),
+            ClockEvent::Stopped(Player::Player1),
+            ClockEvent::Pressed(Player::Player2),
+            ClockEvent::ActivePlayerChanged(Player::Player2),
+            ClockEvent::SwitchIgnored(Player::Player1),
+            ClockEvent::PressDebounced(Player::Player2),
+            ClockEvent::Extended(Player::Player1),
+            ClockEvent::Flagged(Player::Player2),
+            ClockEvent::IdlePaused(Player::Player1),
+            ClockEvent::TimeAnomaly(Player::Player1),
+            ClockEvent::Backgrounded(Player::Player2),
+            ClockEvent::Foregrounded {
+                player: Player::Player2, away: times::TEN_SECONDS
+            },
+            ClockEvent::TimeAdded {
+                player: Player::Player1, time: times::ONE_MINUTE,
+                reason: reason.clone()
+            },
+            ClockEvent::TimeSubtracted {
+                player: Player::Player2, time: times::ONE_SECOND, reason: None
+            },
+            ClockEvent::Penalty {
+                offender: Player::Player1, time: times::TWO_SECONDS, reason
+            },
+            ClockEvent::AdjustmentRefused(Player::Player2),
+            ClockEvent::TickDelay {
+                player: Player::Player1, gap: Duration::from_millis(250)
+            },
+            ClockEvent::SessionExpired,
+            ClockEvent::ForfeitDue(Player::Player2),
+            ClockEvent::PausedBy(Player::Player1),
+            ClockEvent::PauseAllowanceExceeded(Player::Player1),
+            ClockEvent::Discrepancy {
+                player: Player::Player2, expected: times::ONE_MINUTE,
+                actual: Duration::from_millis(59_990)
+            },
+            ClockEvent::Finished,
+        ];
+        let origin = Instant::now();
+        for (sequence, event) in events.into_iter().enumerate() {
+            let record = EventRecord::at(
+                event.clone(), origin, Duration::from_millis(42),
+                std::time::SystemTime::now()
+            );
+            let message = Message::Event { sequence: sequence as u64, record };
+            let decoded = Message::decode(&message.encode(), origin).unwrap();
+            match decoded {
+                Message::Event { sequence: decoded_sequence, record } => {
+                    assert_eq!(decoded_sequence, sequence as u64);
+                    assert_eq!(record.event(), &event);
+                    assert_eq!(record.elapsed(), Duration::from_millis(42));
+                },
+                other => panic!("decoded {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_clock_log() {
+        let mut clock = ChessClock::default();
+        clock.start();
+        clock.switch_player();
+        clock.stop();
+        for (sequence, record) in clock.events().iter().enumerate() {
+            round_trip(&Message::Event {
+                sequence: sequence as u64, record: record.clone()
+            });
+        }
+    }
+
+    #[test]
+    fn test_round_trip_adjustment_reasons() {
+        let mut clock = ChessClock::default();
+        let reason = "  Knocked over the clock, née café  \r\n";
+        let trimmed = Some(reason.trim().to_string());
+        clock.apply_penalty(Player::Player1, times::ONE_MINUTE, Some(reason))
+            .unwrap();
+        assert!(clock.add_time(
+            Player::Player2, times::ONE_MINUTE, Some("first\nsecond")
+        ).is_err());
+
+        assert_eq!(clock.events().len(), 1);
+        let message = Message::Event {
+            sequence: 0, record: clock.events()[0].clone()
+        };
+        let line = message.encode();
+        assert_eq!(line.lines().count(), 1);
+        match Message::decode(&line, Instant::now()).unwrap() {
+            Message::Event { record, .. } => assert_eq!(
+                record.event(), &ClockEvent::Penalty {
+                    offender: Player::Player1,
+                    time: times::ONE_MINUTE,
+                    reason: trimmed,
+                }
+            ),
+            other => panic!("decoded {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed() {
+        let origin = Instant::now();
+        for line in [
+            "", "hello", "hello 1", "hello one two", "welcome", "state 1 2 3",
+            "event x 0 0 finished", "press 3", "start now", "launch",
+            "resume", "resume -1", "heartbeat",
+        ] {
+            assert!(Message::decode(line, origin).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate(1, 1), Some(1));
+        assert_eq!(negotiate(1, 5), Some(PROTOCOL_VERSION));
+        assert_eq!(negotiate(PROTOCOL_VERSION + 1, PROTOCOL_VERSION + 3), None);
+        assert_eq!(negotiate(0, 0), None);
+    }
+
+    #[test]
+    fn test_answer_hello() {
+        assert!(matches!(
+            answer_hello(&Message::hello()),
+            Message::Welcome { version: PROTOCOL_VERSION }
+        ));
+        assert!(matches!(
+            answer_hello(&Message::Hello { min_version: 9, max_version: 9 }),
+            Message::Error(_)
+        ));
+        assert!(matches!(answer_hello(&Message::Start), Message::Error(_)));
+    }
+}
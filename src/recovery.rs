@@ -0,0 +1,198 @@
+//! # Recovery
+//! A game saved as it is played, so that a game cut short by a crash, a
+//! power cut or a closed window can be carried on at the next start.
+//!
+//! The game is saved as a `Session`, by default at `Session::recovery_path`,
+//! at most once every `AUTO_SAVE_INTERVAL` and again as the front end exits.
+//! A finished game leaves nothing to recover, so its file is removed.
+
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use crate::{ChessClock, DurationDisplay, Session, Status};
+
+/// How often a game in progress is saved by default
+pub const AUTO_SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Saves a game in progress to a recovery file as it is played
+#[derive(Debug)]
+pub struct AutoSave {
+    path: PathBuf,
+    interval: Duration,
+    last_save: Option<Instant>,
+}
+
+impl AutoSave {
+    /// Creates an auto-save to `path`, at most once every `interval`
+    pub fn new<P: Into<PathBuf>>(path: P, interval: Duration) -> Self {
+        Self { path: path.into(), interval, last_save: None }
+    }
+
+    /// Creates an auto-save to `Session::recovery_path` every
+    /// `AUTO_SAVE_INTERVAL`, or `None` if there is nowhere to save
+    pub fn to_default_path() -> Option<Self> {
+        Session::recovery_path()
+            .map(|path| Self::new(path, AUTO_SAVE_INTERVAL))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Saves the game if `interval` has passed since the last save
+    ///
+    /// Returns whether it was saved.
+    pub fn save_if_due(&mut self, clock: &ChessClock) -> io::Result<bool> {
+        let due = match self.last_save {
+            Some(last) => last.elapsed() >= self.interval,
+            None => true,
+        };
+        if due {
+            self.save(clock)?;
+        }
+        Ok(due)
+    }
+
+    /// Saves the game now, such as as the front end exits
+    ///
+    /// A game that has not started or has finished has nothing to carry on,
+    /// so any recovery file is removed instead.
+    pub fn save(&mut self, clock: &ChessClock) -> io::Result<()> {
+        self.last_save = Some(Instant::now());
+        if clock.events().is_empty() || clock.status() == Status::Finished {
+            return self.clear();
+        }
+        Session::from_clock(clock).export(&self.path)
+    }
+
+    /// Removes the recovery file, if there is one
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+}
+
+/// A game left unfinished, found at the next start
+#[derive(Debug, Clone)]
+pub struct Recovery {
+    path: PathBuf,
+    session: Session,
+}
+
+impl Recovery {
+    /// Looks for a game left unfinished at `path`
+    ///
+    /// # Returns
+    /// The game, or `None` if there is none, it finished, or the file can
+    /// not be read.
+    pub fn find<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let path = path.as_ref();
+        let session = Session::import(path).ok()?;
+        if session.events().is_empty() || session.status() == Status::Finished {
+            return None;
+        }
+        Some(Self { path: path.to_path_buf(), session })
+    }
+
+    /// Looks for a game left unfinished at `Session::recovery_path`
+    pub fn find_default() -> Option<Self> {
+        Session::recovery_path().and_then(Self::find)
+    }
+
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Get the question to ask before carrying on the game, e.g.
+    /// `Resume interrupted game from 04:12 / 03:58?`
+    pub fn question(&self) -> String {
+        let (p1, p2) = self.session.times();
+        format!(
+            "Resume interrupted game from {} / {}?",
+            DurationDisplay::from(p1), DurationDisplay::from(p2)
+        )
+    }
+
+    /// Rebuilds the game to carry on, stopped until the players are ready
+    ///
+    /// The file is left for the next auto-save to replace, so the game can
+    /// still be recovered if it is cut short again before then.
+    pub fn resume(self) -> ChessClock {
+        self.session.to_clock()
+    }
+
+    /// Forgets the game, removing its file
+    pub fn discard(self) -> io::Result<()> {
+        fs::remove_file(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use crate::{times, Player, Rules, Sleep, TimingMethod};
+
+    fn recovery_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!(
+            "rusty_chess_clock_{}_{}.recovery", name, std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_resumes_saved_game() {
+        let path = recovery_path("resume");
+        let mut clock = ChessClock::new(Rules::new(
+            times::FIVE_MINUTES, times::FIVE_MINUTES, Duration::ZERO,
+            Player::Player1, TimingMethod::Fischer
+        ));
+        let mut autosave = AutoSave::new(&path, times::ONE_MINUTE);
+
+        // Nothing is saved before the game starts
+        assert!(autosave.save_if_due(&clock).unwrap());
+        assert!(Recovery::find(&path).is_none());
+
+        clock.start();
+        Duration::from_millis(20).sleep();
+        clock.switch_player();
+        autosave.save(&clock).unwrap();
+        assert!(!autosave.save_if_due(&clock).unwrap());
+
+        let recovery = Recovery::find(&path).unwrap();
+        assert_eq!(
+            recovery.question(), "Resume interrupted game from 05:00 / 05:00?"
+        );
+        assert_eq!(
+            recovery.session().times().0.as_millis(),
+            clock.read().0.as_millis()
+        );
+        let resumed = recovery.resume();
+        assert_eq!(resumed.status(), Status::Stopped);
+        assert_eq!(resumed.active_player(), Player::Player2);
+        assert_eq!(resumed.events().len(), clock.events().len());
+
+        Recovery::find(&path).unwrap().discard().unwrap();
+        assert!(Recovery::find(&path).is_none());
+    }
+
+    #[test]
+    fn test_finished_game_is_cleared() {
+        let path = recovery_path("finished");
+        let mut clock = ChessClock::default();
+        let mut autosave = AutoSave::new(&path, times::ONE_MINUTE);
+        clock.start();
+        autosave.save(&clock).unwrap();
+        assert!(Recovery::find(&path).is_some());
+
+        clock.finish();
+        autosave.save(&clock).unwrap();
+        assert!(Recovery::find(&path).is_none());
+        assert!(!path.exists());
+        autosave.clear().unwrap();
+    }
+}
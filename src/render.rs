@@ -0,0 +1,178 @@
+//! # Render
+//! Seven-segment rendering of clock times
+//!
+//! Each digit is a bitmask of the segments that are lit, with bit 0 for
+//! segment `a` (top) through bit 6 for segment `g` (middle), in the usual
+//! clockwise order:
+//!
+//! ```text
+//!  aaa
+//! f   b
+//!  ggg
+//! e   c
+//!  ddd
+//! ```
+//!
+//! The masks can drive an LED display directly, or be drawn as block art
+//! with [`block_art`].
+
+use crate::TimeParts;
+
+pub const SEGMENT_A: u8 = 1 << 0;
+pub const SEGMENT_B: u8 = 1 << 1;
+pub const SEGMENT_C: u8 = 1 << 2;
+pub const SEGMENT_D: u8 = 1 << 3;
+pub const SEGMENT_E: u8 = 1 << 4;
+pub const SEGMENT_F: u8 = 1 << 5;
+pub const SEGMENT_G: u8 = 1 << 6;
+
+/// The segments lit for each decimal digit.
+pub const DIGITS: [u8; 10] = [
+    0b0111111, // 0
+    0b0000110, // 1
+    0b1011011, // 2
+    0b1001111, // 3
+    0b1100110, // 4
+    0b1101101, // 5
+    0b1111101, // 6
+    0b0000111, // 7
+    0b1111111, // 8
+    0b1101111, // 9
+];
+
+/// The height of a digit drawn by [`block_art`], in lines.
+pub const ART_HEIGHT: usize = 5;
+
+/// A single position on a seven-segment display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Glyph {
+    /// A digit, as a segment bitmask.
+    Digit(u8),
+    /// The colon between fields.
+    Colon,
+}
+
+/// Get the segment bitmask for a decimal digit.
+///
+/// # Arguments
+/// * `digit` - The digit, from 0 to 9.
+///
+/// # Returns
+/// The bitmask, or `None` if `digit` is not a decimal digit.
+pub const fn segments(digit: u8) -> Option<u8> {
+    if digit < 10 {
+        Some(DIGITS[digit as usize])
+    } else {
+        None
+    }
+}
+
+/// Lay out a time as seven-segment glyphs.
+///
+/// Times under an hour are shown as `MM:SS`, and longer times as `H:MM:SS`
+/// with as many hour digits as needed.
+///
+/// # Arguments
+/// * `parts` - The time to lay out.
+///
+/// # Returns
+/// The glyphs, from left to right.
+pub fn glyphs(parts: TimeParts) -> Vec<Glyph> {
+    let mut text = format!("{:02}:{:02}", parts.minutes, parts.seconds);
+    if parts.hours > 0 {
+        text = format!("{}:{}", parts.hours, text);
+    }
+    text.bytes()
+        .map(|byte| match byte {
+            b':' => Glyph::Colon,
+            digit => Glyph::Digit(DIGITS[(digit - b'0') as usize]),
+        })
+        .collect()
+}
+
+/// Draw a time as Unicode block-art digits.
+///
+/// # Arguments
+/// * `parts` - The time to draw.
+///
+/// # Returns
+/// The [`ART_HEIGHT`] lines of the drawing, from top to bottom.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use rusty_chess_clock::{render::block_art, TimeParts};
+///
+/// let art = block_art(TimeParts::from(Duration::from_secs(61)));
+/// assert_eq!(art[0], "███   █   ███   █");
+/// assert_eq!(art[1], "█ █   █ █ █ █   █");
+/// ```
+pub fn block_art(parts: TimeParts) -> [String; ART_HEIGHT] {
+    let mut lines: [String; ART_HEIGHT] = Default::default();
+    for (index, glyph) in glyphs(parts).into_iter().enumerate() {
+        let cell = |lit: bool| if lit { '█' } else { ' ' };
+        for (row, line) in lines.iter_mut().enumerate() {
+            if index > 0 {
+                line.push(' ');
+            }
+            match glyph {
+                Glyph::Digit(mask) => {
+                    line.extend(digit_rows(mask)[row].map(cell));
+                },
+                Glyph::Colon => line.push(cell(row == 1 || row == 3)),
+            }
+        }
+    }
+    lines
+}
+
+/// The cells lit for a digit, three columns by [`ART_HEIGHT`] rows.
+fn digit_rows(mask: u8) -> [[bool; 3]; ART_HEIGHT] {
+    let lit = |segment| mask & segment != 0;
+    let (a, b, c, d) = (lit(SEGMENT_A), lit(SEGMENT_B), lit(SEGMENT_C), lit(SEGMENT_D));
+    let (e, f, g) = (lit(SEGMENT_E), lit(SEGMENT_F), lit(SEGMENT_G));
+    [
+        [a || f, a, a || b],
+        [f, false, b],
+        [f || e || g, g, b || c || g],
+        [e, false, c],
+        [e || d, d, c || d],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_segments() {
+        assert_eq!(segments(8), Some(0b1111111));
+        assert_eq!(segments(1), Some(SEGMENT_B | SEGMENT_C));
+        assert_eq!(segments(7), Some(SEGMENT_A | SEGMENT_B | SEGMENT_C));
+        assert_eq!(segments(10), None);
+    }
+
+    #[test]
+    fn test_glyphs() {
+        let glyphs = glyphs(TimeParts::from(Duration::from_secs(3_723)));
+        assert_eq!(glyphs, vec![
+            Glyph::Digit(DIGITS[1]), Glyph::Colon,
+            Glyph::Digit(DIGITS[0]), Glyph::Digit(DIGITS[2]), Glyph::Colon,
+            Glyph::Digit(DIGITS[0]), Glyph::Digit(DIGITS[3]),
+        ]);
+    }
+
+    #[test]
+    fn test_block_art() {
+        let art = block_art(TimeParts::from(Duration::from_secs(47)));
+        assert_eq!(art, [
+            "███ ███   █ █ ███",
+            "█ █ █ █ █ █ █   █",
+            "█ █ █ █   ███   █",
+            "█ █ █ █ █   █   █",
+            "███ ███     █   █",
+        ].map(String::from));
+    }
+}
@@ -0,0 +1,203 @@
+//! # Round
+//! The boards of one round of a tournament, each with its own chess clock,
+//! started together when the arbiter announces the round.
+//!
+//! Unlike a `ClockGroup` of plain clocks, every board keeps the event log of
+//! its game, so starts and pauses made for the whole round are recorded on
+//! each board as if its own clock had been pressed.
+
+use std::time::Instant;
+use crate::{ChessClock, Rules, RulesError, Status};
+
+#[derive(Debug)]
+struct Board {
+    label: String,
+    clock: ChessClock,
+}
+
+/// The boards of a round
+#[derive(Debug, Default)]
+pub struct Round {
+    boards: Vec<Board>,
+    /// When the round is to start, until it has
+    scheduled_start: Option<Instant>,
+}
+
+impl Round {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a board to the round, returning its index
+    pub fn add(&mut self, label: &str, clock: ChessClock) -> usize {
+        self.boards.push(Board { label: label.to_string(), clock });
+        self.boards.len() - 1
+    }
+
+    /// Adds a board with a new clock for `rules`, returning its index, or
+    /// why the rules are invalid
+    pub fn add_board(
+        &mut self, label: &str, rules: Rules
+    ) -> Result<usize, RulesError> {
+        Ok(self.add(label, ChessClock::try_new(rules)?))
+    }
+
+    pub fn len(&self) -> usize {
+        self.boards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.boards.is_empty()
+    }
+
+    pub fn label(&self, index: usize) -> Option<&str> {
+        self.boards.get(index).map(|board| board.label.as_str())
+    }
+
+    pub fn clock(&self, index: usize) -> Option<&ChessClock> {
+        self.boards.get(index).map(|board| &board.clock)
+    }
+
+    /// Get the clock of a board to play on, such as to press it
+    pub fn clock_mut(&mut self, index: usize) -> Option<&mut ChessClock> {
+        self.boards.get_mut(index).map(|board| &mut board.clock)
+    }
+
+    /// Iterates over the label and clock of every board in the round
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ChessClock)> {
+        self.boards.iter()
+            .map(|board| (board.label.as_str(), &board.clock))
+    }
+
+    /// Get when the round is to start, if it is scheduled and has not yet
+    /// started
+    pub fn scheduled_start(&self) -> Option<Instant> {
+        self.scheduled_start
+    }
+
+    /// Schedules every board to start at `at`
+    ///
+    /// The boards start on the first `update` at or after `at`, and each
+    /// records its start as happening at `at` exactly, so the clocks of the
+    /// round are in step however late the update comes. Scheduling again
+    /// replaces the earlier start.
+    pub fn start_all_at(&mut self, at: Instant) {
+        self.scheduled_start = Some(at);
+    }
+
+    /// Starts every board now
+    pub fn start_all(&mut self) {
+        self.start_all_at(Instant::now());
+        self.update();
+    }
+
+    /// Stops every running board at the same instant, such as for an
+    /// incident in the hall
+    ///
+    /// A round still waiting for its scheduled start stays scheduled.
+    pub fn pause_all(&mut self) {
+        let at = Instant::now();
+        for board in self.boards.iter_mut() {
+            board.clock.stop_at(at);
+        }
+    }
+
+    /// Updates every board, starting the round if its scheduled start has
+    /// been reached
+    pub fn update(&mut self) {
+        let now = Instant::now();
+        if let Some(at) = self.scheduled_start.filter(|at| *at <= now) {
+            self.scheduled_start = None;
+            for board in self.boards.iter_mut() {
+                board.clock.start_at(at);
+            }
+        }
+        for board in self.boards.iter_mut() {
+            board.clock.update();
+        }
+    }
+
+    /// Returns whether every game in the round has finished
+    pub fn all_finished(&self) -> bool {
+        self.boards.iter()
+            .all(|board| board.clock.status() == Status::Finished)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::{ClockEvent, Player, Sleep};
+
+    fn starts(round: &Round) -> Vec<Instant> {
+        round.iter()
+            .flat_map(|(_, clock)| clock.events())
+            .filter(|record| matches!(record.event(), ClockEvent::Started(_)))
+            .map(|record| record.instant())
+            .collect()
+    }
+
+    fn all_are(round: &Round, status: Status) -> bool {
+        round.iter().all(|(_, clock)| clock.status() == status)
+    }
+
+    #[test]
+    fn test_scheduled_start_is_in_step() {
+        let mut round = Round::new();
+        let first = round.add_board("Board 1", Rules::default()).unwrap();
+        round.add_board("Board 2", Rules::default()).unwrap();
+        assert_eq!(round.label(first), Some("Board 1"));
+
+        let at = Instant::now() + Duration::from_millis(30);
+        round.start_all_at(at);
+        round.update();
+        assert_eq!(round.scheduled_start(), Some(at));
+        assert!(all_are(&round, Status::Stopped));
+
+        Duration::from_millis(50).sleep();
+        round.update();
+        assert_eq!(round.scheduled_start(), None);
+        assert_eq!(starts(&round), [at, at]);
+        assert!(all_are(&round, Status::Running));
+    }
+
+    #[test]
+    fn test_invalid_rules_add_no_board() {
+        let mut round = Round::new();
+        let mut rules = Rules::default();
+        rules.set_both_times(Duration::ZERO);
+        assert_eq!(
+            round.add_board("Board 1", rules),
+            Err(RulesError::ZeroTime(Player::Player1))
+        );
+        assert!(round.is_empty());
+    }
+
+    #[test]
+    fn test_pause_all() {
+        let mut round = Round::new();
+        let first = round.add_board("Board 1", Rules::default()).unwrap();
+        round.add_board("Board 2", Rules::default()).unwrap();
+        round.start_all();
+        round.clock_mut(first).unwrap().press(Player::Player1);
+
+        round.pause_all();
+        let stops: Vec<(Player, Instant)> = round.iter()
+            .flat_map(|(_, clock)| clock.events())
+            .filter_map(|record| match record.event() {
+                ClockEvent::Stopped(player) => Some((*player, record.instant())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(stops.len(), 2);
+        assert_eq!(stops[0].0, Player::Player2);
+        assert_eq!(stops[0].1, stops[1].1);
+        assert!(!round.all_finished());
+
+        for index in 0..round.len() {
+            round.clock_mut(index).unwrap().finish();
+        }
+        assert!(round.all_finished());
+    }
+}
@@ -0,0 +1,271 @@
+//! # Session
+//! A complete record of a chess clock game, exported to and imported from a
+//! single portable file for replay and analysis on another machine.
+//!
+//! The file is plain text, one record per line:
+//!
+//! ```text
+//! rusty_chess_clock session 1
+//! rules <p1 ms> <p2 ms> <increment ms> <starter> <timing method> <extensions>
+//!     <require reasons> <idle timeout ms or -> <stopped switch>
+//!     <background time> <session budget ms or -> <adjudicate session>
+//!     <default time ms or -> <finish on default>
+//!     <increment stages or -> <strict> <pause allowance ms or ->
+//! final <p1 ms> <p2 ms> <active player> <status>
+//! event <elapsed ms> <unix ms> <event> [<player> [<time ms> [<reason>]]]
+//! ```
+//!
+//! Increment stages are written as `<move>:<increment ms>`, joined by
+//! commas.
+
+use std::{
+    env,
+    fs,
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use crate::{
+    ChessClock, EventRecord, Player, Rules, Status,
+    encoding::{
+        format_event, format_rules, format_state, invalid, parse_event,
+        parse_rules, parse_state
+    },
+};
+
+const HEADER: &str = "rusty_chess_clock session 1";
+
+/// A snapshot of a game: its rules, final state and event log
+#[derive(Debug, Clone)]
+pub struct Session {
+    rules: Rules,
+    times: (Duration, Duration),
+    active: Player,
+    status: Status,
+    events: Vec<EventRecord>,
+}
+
+impl Session {
+    /// Captures the current state and event log of a chess clock
+    pub fn from_clock(clock: &ChessClock) -> Self {
+        Self {
+            rules: clock.rules().clone(),
+            times: clock.read(),
+            active: clock.active_player(),
+            status: clock.status(),
+            events: clock.events().to_vec(),
+        }
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Get the remaining time of each player when the session was captured
+    pub fn times(&self) -> (Duration, Duration) {
+        self.times
+    }
+
+    pub fn active_player(&self) -> Player {
+        self.active
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub fn events(&self) -> &[EventRecord] {
+        &self.events
+    }
+
+    /// Rebuilds a stopped chess clock in the captured state, with the
+    /// captured event log
+    pub fn to_clock(&self) -> ChessClock {
+        let mut clock = ChessClock::restore(
+            self.rules.clone(), self.times, self.active,
+            self.status == Status::Finished
+        );
+        clock.restore_events(self.events.clone());
+        clock
+    }
+
+    /// Get where a game cut short is saved, in the user's state directory
+    ///
+    /// # Returns
+    /// `$XDG_STATE_HOME/rusty_chess_clock/recovery`, falling back to
+    /// `$HOME/.local/state`, or `None` if neither variable is set.
+    pub fn recovery_path() -> Option<PathBuf> {
+        let state = env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| {
+                PathBuf::from(home).join(".local").join("state")
+            }))?;
+        Some(state.join("rusty_chess_clock").join("recovery"))
+    }
+
+    /// Writes the session to a file at `path`, creating its directory and
+    /// replacing any existing file
+    pub fn export<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let mut contents = format!("{}\n{}\n", HEADER, format_rules(&self.rules));
+        contents.push_str(&format!(
+            "final {}\n", format_state(&self.to_clock())
+        ));
+        for record in &self.events {
+            contents.push_str(&format!("event {}\n", format_event(record)));
+        }
+        fs::write(path, contents)
+    }
+
+    /// Reads a session exported by `export`
+    ///
+    /// Wall-clock timestamps are restored exactly. Monotonic instants cannot
+    /// be carried between machines, so they are rebuilt from the elapsed
+    /// times as if the game had just been played.
+    pub fn import<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut lines = contents.lines();
+
+        if lines.next() != Some(HEADER) {
+            return Err(invalid("not a session file"));
+        }
+        let rules = parse_rules(lines.next().unwrap_or_default())?;
+        let (times, active, status) = lines.next()
+            .and_then(|line| line.strip_prefix("final "))
+            .ok_or_else(|| invalid("missing final state"))
+            .and_then(parse_state)?;
+
+        let records: Vec<&str> = lines
+            .map(|line| line.strip_prefix("event ")
+                .ok_or_else(|| invalid("malformed event")))
+            .collect::<io::Result<_>>()?;
+        let last_elapsed = records.last()
+            .and_then(|record| record.split(' ').next())
+            .and_then(|field| field.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or_default();
+        let now = Instant::now();
+        let origin = now.checked_sub(last_elapsed).unwrap_or(now);
+        let events = records.iter()
+            .map(|record| parse_event(record, origin))
+            .collect::<io::Result<_>>()?;
+
+        Ok(Self { rules, times, active, status, events })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use crate::{
+        guard::Guard, times, BackgroundTime, ClockEvent, DefaultTime,
+        SessionBudget, Sleep, StoppedSwitch, TimingMethod
+    };
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let path = env::temp_dir().join(format!(
+            "rusty_chess_clock_session_{}.session", std::process::id()
+        ));
+
+        let mut rules = Rules::new(
+            times::ONE_MINUTE, times::TEN_MINUTES, times::FIVE_SECONDS,
+            Player::Player1, TimingMethod::Bronstein
+        );
+        rules.set_require_reasons(true);
+        rules.set_strict(true);
+        rules.set_idle_timeout(Some(times::THIRTY_MINUTES));
+        rules.set_stopped_switch(StoppedSwitch::Ignore);
+        rules.set_background_time(BackgroundTime::Paused);
+        rules.set_session_budget(
+            Some(SessionBudget::new(times::ONE_HOUR, true))
+        );
+        rules.set_default_time(
+            Some(DefaultTime::new(times::THIRTY_MINUTES, true))
+        );
+        rules.set_pause_allowance(Some(times::FIVE_MINUTES));
+        let mut clock = ChessClock::new(rules);
+        clock.start();
+        Duration::from_millis(20).sleep();
+        clock.press(Player::Player1);
+        // Strict rules lock penalties once the game is under way
+        clock.set_arbiter(Guard::with_pin("1234").unwrap());
+        assert!(clock.authorise("1234"));
+        clock.apply_penalty(
+            Player::Player2, times::ONE_MINUTE, Some("Illegal move: Ke1-e3")
+        ).unwrap();
+        clock.stop();
+
+        let session = Session::from_clock(&clock);
+        session.export(&path).unwrap();
+        let imported = Session::import(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(imported.rules().get_timing_method(), TimingMethod::Bronstein);
+        assert!(imported.rules().get_require_reasons());
+        assert!(imported.rules().get_strict());
+        assert_eq!(
+            imported.rules().get_pause_allowance(), Some(times::FIVE_MINUTES)
+        );
+        assert_eq!(
+            imported.rules().get_idle_timeout(), Some(times::THIRTY_MINUTES)
+        );
+        assert_eq!(
+            imported.rules().get_stopped_switch(), StoppedSwitch::Ignore
+        );
+        assert_eq!(
+            imported.rules().get_background_time(), BackgroundTime::Paused
+        );
+        assert_eq!(
+            imported.rules().get_session_budget(),
+            Some(SessionBudget::new(times::ONE_HOUR, true))
+        );
+        assert_eq!(
+            imported.rules().get_default_time(),
+            Some(DefaultTime::new(times::THIRTY_MINUTES, true))
+        );
+        assert_eq!(imported.active_player(), Player::Player2);
+        assert_eq!(imported.status(), Status::Stopped);
+        assert_eq!(
+            imported.times().0.as_millis(), session.times().0.as_millis()
+        );
+
+        let events: Vec<&ClockEvent> = imported.events().iter()
+            .map(EventRecord::event)
+            .collect();
+        let expected: Vec<&ClockEvent> = session.events().iter()
+            .map(EventRecord::event)
+            .collect();
+        assert_eq!(events, expected);
+        for (imported, original) in imported.events().iter()
+            .zip(session.events())
+        {
+            assert_eq!(
+                imported.elapsed().as_millis(), original.elapsed().as_millis()
+            );
+            assert_eq!(
+                imported.timestamp_rfc3339(), original.timestamp_rfc3339()
+            );
+        }
+
+        let restored = imported.to_clock();
+        assert_eq!(restored.events().len(), session.events().len());
+        assert_eq!(restored.active_player(), Player::Player2);
+    }
+
+    #[test]
+    fn test_import_rejects_other_files() {
+        let path = env::temp_dir().join(format!(
+            "rusty_chess_clock_not_session_{}.session", std::process::id()
+        ));
+        fs::write(&path, "rules 1 2 3 1 fischer 0 0\n").unwrap();
+
+        let error = Session::import(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,79 @@
+//! # Snapshot
+//! The state of a chess clock at a point in time, computed purely from its
+//! rules and event log.
+//!
+//! Nothing here reads the system clock: the same rules, events and instant
+//! always give the same snapshot, which makes the result reproducible when
+//! a game is disputed or replayed on another machine.
+
+use std::time::{Duration, Instant};
+use crate::{ChessClock, EventRecord, Player, Rules, Status};
+
+/// The state of a chess clock at a point in time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockSnapshot {
+    /// The time left on each player's clock
+    pub times: (Duration, Duration),
+    /// The player on move
+    pub active_player: Player,
+    /// Whether the clock is running, stopped or finished
+    pub status: Status,
+    /// The number of the move being played, counting from 1
+    pub move_number: u32,
+    /// The player who ran out of time first, if either has
+    pub flagged: Option<Player>,
+}
+
+/// Compute the state of a clock from its rules and event log
+///
+/// # Arguments
+/// * `rules` - The rules the game was played under.
+/// * `events` - The event log of the game, oldest first.
+/// * `now` - The instant to compute the state at, which should not be before
+///   the last event.
+///
+/// # Returns
+/// The state of the clock at `now`, as if nothing happened after the last
+/// event.
+pub fn compute_state(
+    rules: &Rules, events: &[EventRecord], now: Instant
+) -> ClockSnapshot {
+    ChessClock::replay(rules.clone(), events).snapshot(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{times, Sleep};
+
+    #[test]
+    fn test_compute_state_is_deterministic() {
+        let rules = Rules::default();
+        let mut clock = ChessClock::new(rules.clone());
+        clock.start();
+        Duration::from_millis(20).sleep();
+        clock.switch_player();
+
+        let now = Instant::now() + times::ONE_MINUTE;
+        let snapshot = compute_state(&rules, clock.events(), now);
+        assert_eq!(snapshot, compute_state(&rules, clock.events(), now));
+        assert_eq!(snapshot, clock.snapshot(now));
+        assert_eq!(snapshot.active_player, Player::Player2);
+        assert_eq!(snapshot.status, Status::Running);
+        assert_eq!(snapshot.move_number, 1);
+        assert!(snapshot.times.1 <= times::TEN_MINUTES - times::ONE_MINUTE);
+    }
+
+    #[test]
+    fn test_compute_state_sees_flag() {
+        let rules = Rules::default();
+        let mut clock = ChessClock::new(rules.clone());
+        clock.start();
+
+        let later = Instant::now() + times::THIRTY_MINUTES;
+        let snapshot = compute_state(&rules, clock.events(), later);
+        assert_eq!(snapshot.times.0, Duration::ZERO);
+        assert_eq!(snapshot.status, Status::Finished);
+        assert_eq!(snapshot.flagged, Some(Player::Player1));
+    }
+}
@@ -0,0 +1,129 @@
+//! # SpeechTimer
+//! Timer for a single speaker which shows green, yellow and red cards as the
+//! speech nears its end, as used in debating and public speaking.
+
+use std::{fmt::Display, time::Duration};
+use crate::{Interval, IntervalTimer};
+
+/// The card shown to the speaker
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Card {
+    Green,
+    Yellow,
+    Red,
+}
+
+impl Display for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            Self::Green => "Green",
+            Self::Yellow => "Yellow",
+            Self::Red => "Red",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// A speech timer built from green, yellow and red intervals
+///
+/// The red card stays up once the speech has run over its length.
+#[derive(Debug)]
+pub struct SpeechTimer {
+    timer: IntervalTimer,
+}
+
+impl SpeechTimer {
+    /// Constructs a new stopped speech timer
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The length of the speech
+    /// * `yellow` - The time remaining when the yellow card is shown
+    /// * `red` - The time remaining when the red card is shown
+    pub fn new(length: Duration, yellow: Duration, red: Duration) -> Self {
+        let yellow = yellow.min(length);
+        let red = red.min(yellow);
+        Self {
+            timer: IntervalTimer::new(vec![
+                Interval::new("Green", length - yellow),
+                Interval::new("Yellow", yellow - red),
+                Interval::new("Red", red),
+            ]),
+        }
+    }
+
+    /// Get the card currently shown to the speaker
+    pub fn card(&self) -> Card {
+        match self.timer.current() {
+            Some((0, _, _)) => Card::Green,
+            Some((1, _, _)) => Card::Yellow,
+            _ => Card::Red,
+        }
+    }
+
+    /// Get the time remaining in the speech, zero once it has run over
+    pub fn remaining(&self) -> Duration {
+        self.timer.total().saturating_sub(self.timer.elapsed())
+    }
+
+    /// Get the time the speaker has run over the length of the speech
+    pub fn overrun(&self) -> Duration {
+        self.timer.overrun()
+    }
+
+    pub fn timer(&self) -> &IntervalTimer {
+        &self.timer
+    }
+
+    pub fn start(&mut self) {
+        self.timer.start();
+    }
+
+    pub fn stop(&mut self) {
+        self.timer.stop();
+    }
+
+    pub fn reset(&mut self) {
+        self.timer.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sleep;
+
+    #[test]
+    fn test_speech_timer_cards() {
+        let mut speech = SpeechTimer::new(
+            Duration::from_millis(300),
+            Duration::from_millis(200),
+            Duration::from_millis(100)
+        );
+        assert_eq!(speech.card(), Card::Green);
+
+        speech.start();
+        Duration::from_millis(150).sleep();
+        assert_eq!(speech.card(), Card::Yellow);
+
+        Duration::from_millis(100).sleep();
+        assert_eq!(speech.card(), Card::Red);
+
+        Duration::from_millis(100).sleep();
+        speech.stop();
+        assert_eq!(speech.card(), Card::Red);
+        assert_eq!(speech.remaining(), Duration::ZERO);
+        assert_eq!(speech.overrun().as_millis(), 50);
+    }
+
+    #[test]
+    fn test_speech_timer_clamps_thresholds() {
+        let speech = SpeechTimer::new(
+            Duration::from_secs(60),
+            Duration::from_secs(120),
+            Duration::from_secs(180)
+        );
+        assert_eq!(speech.card(), Card::Red);
+        assert_eq!(speech.remaining(), Duration::from_secs(60));
+    }
+}
@@ -0,0 +1,443 @@
+//! # Stats
+//! Post-game stats worked out purely from a clock's rules and event log.
+//!
+//! The time pressure rating grades how well a player managed their clock,
+//! on an Elo-like scale from 800 to 2400. It is meant for fun: it knows
+//! nothing about the moves on the board, only how the time was spent.
+//!
+//! Three things are graded, each from 0 to 1:
+//!
+//! * Reserve: the time left at the end, with full marks for a fifth of the
+//!   starting time or more and none for losing on time.
+//! * Consistency: how evenly the time was spread over the moves, from the
+//!   variation of the move times.
+//! * Composure: how few moves were made in a time scramble, with less than
+//!   `SCRAMBLE_THRESHOLD` on the clock.
+//!
+//! A `TimeHeatmap` adds up the time a player spent on their moves over one
+//! or many games, in buckets by move number, to show where in a game they
+//! habitually burn their time.
+
+use std::time::Duration;
+use crate::{
+    ChessClock, ClockEvent, EventRecord, Player, Rules, SCRAMBLE_THRESHOLD
+};
+
+/// The lowest time pressure rating
+pub const MIN_RATING: u32 = 800;
+/// The highest time pressure rating
+pub const MAX_RATING: u32 = 2400;
+
+/// The fraction of the starting time left that earns full marks for reserve
+const FULL_RESERVE: f32 = 0.2;
+/// How much each grade counts towards the rating
+const RESERVE_WEIGHT: f32 = 0.4;
+const CONSISTENCY_WEIGHT: f32 = 0.3;
+const COMPOSURE_WEIGHT: f32 = 0.3;
+
+/// How well a player managed their time over a game
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimePressureRating {
+    moves: u32,
+    fraction_left: f32,
+    flagged: bool,
+    variation: f32,
+    scramble_moves: u32,
+}
+
+impl TimePressureRating {
+    /// Get the number of moves the player made
+    pub const fn get_moves(&self) -> u32 {
+        self.moves
+    }
+
+    /// Get the time left at the end as a fraction of the starting time,
+    /// from 0.0 to 1.0
+    pub const fn get_fraction_left(&self) -> f32 {
+        self.fraction_left
+    }
+
+    /// Get whether the player lost on time
+    pub const fn get_flagged(&self) -> bool {
+        self.flagged
+    }
+
+    /// Get the coefficient of variation of the move times, their standard
+    /// deviation over their mean
+    pub const fn get_variation(&self) -> f32 {
+        self.variation
+    }
+
+    /// Get the number of moves made in a time scramble
+    pub const fn get_scramble_moves(&self) -> u32 {
+        self.scramble_moves
+    }
+
+    /// Get the rating, from `MIN_RATING` to `MAX_RATING`
+    pub fn get_rating(&self) -> u32 {
+        let reserve = match self.flagged {
+            true => 0.0,
+            false => (self.fraction_left / FULL_RESERVE).min(1.0),
+        };
+        let consistency = 1.0 / (1.0 + self.variation);
+        let composure = 1.0 - self.scramble_moves as f32 / self.moves as f32;
+        let grade = RESERVE_WEIGHT * reserve
+            + CONSISTENCY_WEIGHT * consistency
+            + COMPOSURE_WEIGHT * composure;
+        let range = (MAX_RATING - MIN_RATING) as f32;
+        MIN_RATING + (grade.clamp(0.0, 1.0) * range).round() as u32
+    }
+}
+
+/// Rate how well `player` managed their time in a game
+///
+/// # Arguments
+/// * `rules` - The rules the game was played under.
+/// * `events` - The event log of the game, oldest first.
+/// * `player` - The player to rate.
+///
+/// # Returns
+/// The rating, or `None` if the player made no moves. The time left is read
+/// at the last event, so a game still running is rated as it stood then.
+pub fn time_pressure_rating(
+    rules: &Rules, events: &[EventRecord], player: Player
+) -> Option<TimePressureRating> {
+    let mut move_times = Vec::new();
+    let mut scramble_moves = 0;
+    let mut thought = Duration::ZERO;
+    let clock = ChessClock::replay_with(
+        rules.clone(), events, |clock, record| {
+            if record.event() != &ClockEvent::Pressed(player) {
+                return;
+            }
+            let at = record.instant();
+            let total = clock.think_time_at(player, at);
+            move_times.push(total.saturating_sub(thought));
+            thought = total;
+            if time_of(clock.read_at(at), player) < SCRAMBLE_THRESHOLD {
+                scramble_moves += 1;
+            }
+        }
+    );
+    if move_times.is_empty() {
+        return None;
+    }
+
+    let end = events.last()?.instant();
+    let start = rules.get_time(player).as_secs_f32();
+    let left = time_of(clock.read_at(end), player).as_secs_f32();
+    let fraction_left = match start {
+        start if start > 0.0 => (left / start).min(1.0),
+        _ => 0.0,
+    };
+
+    Some(TimePressureRating {
+        moves: move_times.len() as u32,
+        fraction_left,
+        flagged: clock.flagged() == Some(player),
+        variation: variation(&move_times),
+        scramble_moves,
+    })
+}
+
+/// How the moves of a game are split into buckets for a `TimeHeatmap`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoveBuckets {
+    /// Split each game into this many parts of as near equal length as
+    /// possible, such as thirds for the opening, middlegame and endgame
+    Equal(u32),
+    /// Split every game before each of these move numbers, in increasing
+    /// order, such as `[15, 40]` for moves 1-14, 15-39 and 40 on
+    Before(Vec<u32>),
+}
+
+impl MoveBuckets {
+    /// Get the number of buckets
+    fn count(&self) -> usize {
+        match self {
+            Self::Equal(parts) => (*parts).max(1) as usize,
+            Self::Before(starts) => starts.len() + 1,
+        }
+    }
+
+    /// Get the bucket of a move
+    ///
+    /// # Arguments
+    /// * `number` - The number of the move among the player's own, counting
+    ///   from 1.
+    /// * `moves` - The number of moves the player made in the game.
+    fn bucket_of(&self, number: u32, moves: u32) -> usize {
+        match self {
+            Self::Equal(parts) => {
+                let parts = u64::from((*parts).max(1));
+                let index = u64::from(number.saturating_sub(1));
+                (index * parts / u64::from(moves.max(1))) as usize
+            },
+            Self::Before(starts) => {
+                starts.iter().filter(|start| **start <= number).count()
+            },
+        }
+    }
+}
+
+impl Default for MoveBuckets {
+    /// Thirds of each game
+    fn default() -> Self {
+        Self::Equal(3)
+    }
+}
+
+/// The moves of a `TimeHeatmap` in one bucket and the time spent on them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BucketTime {
+    moves: u32,
+    time: Duration,
+}
+
+impl BucketTime {
+    pub const fn get_moves(&self) -> u32 {
+        self.moves
+    }
+
+    /// Get the total time spent on the moves
+    pub const fn get_time(&self) -> Duration {
+        self.time
+    }
+
+    /// Get the average time spent per move
+    ///
+    /// # Returns
+    /// The average, or `None` if no moves fell in the bucket.
+    pub fn average(&self) -> Option<Duration> {
+        (self.moves > 0).then(|| self.time / self.moves)
+    }
+}
+
+/// The time spent per move, in buckets by move number, over one or many
+/// games
+///
+/// ```
+/// use rusty_chess_clock::{ChessClock, MoveBuckets, Player, TimeHeatmap};
+///
+/// let mut clock = ChessClock::default();
+/// clock.start();
+/// for _ in 0..3 {
+///     clock.press(Player::Player1);
+///     clock.press(Player::Player2);
+/// }
+///
+/// let mut heatmap = TimeHeatmap::new(MoveBuckets::default());
+/// heatmap.add_game(clock.rules(), clock.events(), Player::Player1);
+/// assert_eq!(heatmap.buckets().len(), 3);
+/// assert_eq!(heatmap.buckets()[0].get_moves(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeHeatmap {
+    split: MoveBuckets,
+    buckets: Vec<BucketTime>,
+    games: u32,
+}
+
+impl TimeHeatmap {
+    /// Create an empty heatmap with moves split into buckets by `split`
+    pub fn new(split: MoveBuckets) -> Self {
+        let buckets = vec![BucketTime::default(); split.count()];
+        Self { split, buckets, games: 0 }
+    }
+
+    pub fn split(&self) -> &MoveBuckets {
+        &self.split
+    }
+
+    /// Get the buckets, in order of move number
+    pub fn buckets(&self) -> &[BucketTime] {
+        &self.buckets
+    }
+
+    /// Get the number of games added in which the player made a move
+    pub const fn get_games(&self) -> u32 {
+        self.games
+    }
+
+    /// Adds the moves of `player` in a game
+    ///
+    /// # Arguments
+    /// * `rules` - The rules the game was played under.
+    /// * `events` - The event log of the game, oldest first.
+    /// * `player` - The player whose moves to add. To follow one person
+    ///   over many games, add whichever side they played in each.
+    pub fn add_game(
+        &mut self, rules: &Rules, events: &[EventRecord], player: Player
+    ) {
+        let times = move_times(rules, events, player);
+        if times.is_empty() {
+            return;
+        }
+        let moves = times.len() as u32;
+        for (number, time) in (1..).zip(times) {
+            let bucket = &mut self.buckets[self.split.bucket_of(number, moves)];
+            bucket.moves += 1;
+            bucket.time += time;
+        }
+        self.games += 1;
+    }
+
+    /// Get the heatmap as comma separated values, with a header line and
+    /// then a line per bucket of its number from 1, its moves and the
+    /// average seconds per move, empty if it has no moves
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("bucket,moves,average_seconds\n");
+        for (number, bucket) in (1..).zip(&self.buckets) {
+            let average = bucket.average()
+                .map(|average| format!("{:.3}", average.as_secs_f64()))
+                .unwrap_or_default();
+            csv.push_str(
+                &format!("{},{},{}\n", number, bucket.moves, average)
+            );
+        }
+        csv
+    }
+}
+
+/// The time `player` spent on each of their moves in a game, in order
+fn move_times(
+    rules: &Rules, events: &[EventRecord], player: Player
+) -> Vec<Duration> {
+    let mut move_times = Vec::new();
+    let mut thought = Duration::ZERO;
+    ChessClock::replay_with(rules.clone(), events, |clock, record| {
+        if record.event() == &ClockEvent::Pressed(player) {
+            let total = clock.think_time_at(player, record.instant());
+            move_times.push(total.saturating_sub(thought));
+            thought = total;
+        }
+    });
+    move_times
+}
+
+fn time_of(times: (Duration, Duration), player: Player) -> Duration {
+    match player {
+        Player::Player1 => times.0,
+        Player::Player2 => times.1,
+    }
+}
+
+/// The coefficient of variation of some durations, or 0.0 if they have no
+/// mean
+fn variation(times: &[Duration]) -> f32 {
+    let count = times.len() as f32;
+    let mean = times.iter().map(Duration::as_secs_f32).sum::<f32>() / count;
+    if mean <= 0.0 {
+        return 0.0;
+    }
+    let variance = times.iter()
+        .map(|time| (time.as_secs_f32() - mean).powi(2))
+        .sum::<f32>() / count;
+    variance.sqrt() / mean
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{presets, times, Sleep};
+
+    #[test]
+    fn test_variation() {
+        let even = [Duration::from_secs(2); 3];
+        assert_eq!(variation(&even), 0.0);
+        let uneven = [Duration::from_secs(1), Duration::from_secs(3)];
+        assert!((variation(&uneven) - 0.5).abs() < 1e-6);
+        assert_eq!(variation(&[Duration::ZERO]), 0.0);
+    }
+
+    #[test]
+    fn test_rating_grades() {
+        let rating = |fraction_left, flagged, variation, scramble_moves| {
+            TimePressureRating {
+                moves: 10, fraction_left, flagged, variation, scramble_moves
+            }.get_rating()
+        };
+        assert_eq!(rating(0.5, false, 0.0, 0), MAX_RATING);
+        assert_eq!(rating(0.0, true, 0.0, 0), 1760);
+        assert_eq!(rating(0.1, false, 1.0, 5), 1600);
+        assert!(rating(0.0, true, 3.0, 10) < 1000);
+        assert!(rating(0.0, true, 3.0, 10) >= MIN_RATING);
+    }
+
+    #[test]
+    fn test_time_pressure_rating() {
+        let mut clock = ChessClock::new(presets::BLITZ_3_2);
+        clock.start();
+        for _ in 0..2 {
+            Duration::from_millis(20).sleep();
+            clock.press(Player::Player1);
+            clock.press(Player::Player2);
+        }
+        clock.subtract_time(Player::Player2, times::ONE_HOUR, None).unwrap();
+        clock.finish();
+
+        let rules = clock.rules();
+        let first = time_pressure_rating(rules, clock.events(), Player::Player1)
+            .unwrap();
+        assert_eq!(first.get_moves(), 2);
+        assert!(!first.get_flagged());
+        assert_eq!(first.get_fraction_left(), 1.0);
+        assert_eq!(first.get_scramble_moves(), 0);
+
+        let second = time_pressure_rating(rules, clock.events(), Player::Player2)
+            .unwrap();
+        assert!(second.get_flagged());
+        assert_eq!(second.get_fraction_left(), 0.0);
+        assert!(second.get_rating() < first.get_rating());
+
+        let unplayed = ChessClock::new(presets::BLITZ_3_2);
+        assert_eq!(
+            time_pressure_rating(rules, unplayed.events(), Player::Player1),
+            None
+        );
+    }
+
+    #[test]
+    fn test_move_buckets() {
+        let thirds = MoveBuckets::default();
+        let buckets: Vec<usize> = (1..=7)
+            .map(|number| thirds.bucket_of(number, 7))
+            .collect();
+        assert_eq!(buckets, [0, 0, 0, 1, 1, 2, 2]);
+        assert_eq!(thirds.bucket_of(1, 1), 0);
+
+        let before = MoveBuckets::Before(vec![15, 40]);
+        assert_eq!(before.count(), 3);
+        assert_eq!(before.bucket_of(14, 60), 0);
+        assert_eq!(before.bucket_of(15, 60), 1);
+        assert_eq!(before.bucket_of(40, 20), 2);
+    }
+
+    #[test]
+    fn test_time_heatmap() {
+        let mut clock = ChessClock::new(presets::BLITZ_3_2);
+        clock.start();
+        for pause in [0, 0, 30] {
+            Duration::from_millis(pause).sleep();
+            clock.press(Player::Player1);
+            clock.press(Player::Player2);
+        }
+
+        let mut heatmap = TimeHeatmap::new(MoveBuckets::default());
+        for _ in 0..2 {
+            heatmap.add_game(clock.rules(), clock.events(), Player::Player1);
+        }
+        let unplayed = ChessClock::new(presets::BLITZ_3_2);
+        heatmap.add_game(unplayed.rules(), unplayed.events(), Player::Player1);
+        assert_eq!(heatmap.get_games(), 2);
+
+        let buckets = heatmap.buckets();
+        assert!(buckets.iter().all(|bucket| bucket.get_moves() == 2));
+        let endgame = buckets[2].average().unwrap();
+        assert!(endgame >= Duration::from_millis(30));
+        assert!(buckets[0].average().unwrap() < endgame);
+        let csv = heatmap.to_csv();
+        assert!(csv.starts_with("bucket,moves,average_seconds\n1,2,"));
+        assert_eq!(TimeHeatmap::new(MoveBuckets::Equal(0)).buckets().len(), 1);
+    }
+}
@@ -0,0 +1,80 @@
+//! # TimeParts
+//! A duration split into the fields shown on a clock face
+
+use std::time::Duration;
+
+/// A duration split into hours, minutes, seconds and milliseconds.
+///
+/// The fields are truncated rather than rounded, so each one is the digit a
+/// clock would show if it displayed that many fields. Renderers that need a
+/// rounded display should round the duration first, for example with
+/// [`crate::utils::round_duration`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TimeParts {
+    pub hours: u64,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub millis: u16,
+}
+
+impl TimeParts {
+    /// Split a duration into its parts.
+    pub const fn from_duration(duration: Duration) -> TimeParts {
+        let secs = duration.as_secs();
+        TimeParts {
+            hours: secs / 3600,
+            minutes: (secs / 60 % 60) as u8,
+            seconds: (secs % 60) as u8,
+            millis: duration.subsec_millis() as u16,
+        }
+    }
+
+    /// Join the parts back into a duration.
+    pub const fn to_duration(self) -> Duration {
+        let secs = self.hours * 3600
+            + self.minutes as u64 * 60
+            + self.seconds as u64;
+        Duration::from_millis(secs * 1000 + self.millis as u64)
+    }
+
+    /// The tenths of a second, as shown on a display with one decimal place.
+    pub const fn tenths(self) -> u8 {
+        (self.millis / 100) as u8
+    }
+
+    /// The hundredths of a second, as shown on a display with two decimal
+    /// places.
+    pub const fn hundredths(self) -> u8 {
+        (self.millis / 10) as u8
+    }
+}
+
+impl From<Duration> for TimeParts {
+    fn from(duration: Duration) -> Self {
+        TimeParts::from_duration(duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_duration() {
+        let parts = TimeParts::from(Duration::from_millis(3_723_456));
+        assert_eq!(parts, TimeParts {
+            hours: 1, minutes: 2, seconds: 3, millis: 456
+        });
+        assert_eq!(parts.tenths(), 4);
+        assert_eq!(parts.hundredths(), 45);
+        assert_eq!(parts.to_duration(), Duration::from_millis(3_723_456));
+    }
+
+    #[test]
+    fn test_truncates() {
+        let parts = TimeParts::from(Duration::from_nanos(59_999_999_999));
+        assert_eq!(parts, TimeParts {
+            hours: 0, minutes: 0, seconds: 59, millis: 999
+        });
+    }
+}
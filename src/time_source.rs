@@ -0,0 +1,296 @@
+//! # Time source
+//! Where a `Clock` or `ChessClock` gets the current time from.
+//!
+//! Clocks read `Instant::now()` by default. A clock can be given another
+//! source instead, such as a `ManualTime` that tests move on by hand rather
+//! than sleeping, or a platform's own monotonic timer wrapped up as an
+//! `Instant` offset. A `ScaledTime` runs faster or slower than another
+//! source, for demos and replays that should not take real minutes.
+
+use std::{
+    fmt::{self, Debug},
+    sync::{atomic::{AtomicU64, Ordering}, Arc},
+    time::{Duration, Instant},
+};
+
+/// A source of the current time for a clock
+///
+/// The times given must never go backwards, as for `Instant`.
+pub trait TimeSource {
+    /// Get the current time
+    fn now(&self) -> Instant;
+
+    /// Block the thread until the time is `due`, returning straight away if
+    /// it already is
+    ///
+    /// By default this sleeps for the difference in real time, which suits
+    /// any source that runs as fast as real time does.
+    fn sleep_until(&self, due: Instant) {
+        if let Some(wait) = due.checked_duration_since(self.now()) {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+/// The monotonic clock of the operating system, read with `Instant::now()`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MonotonicTime;
+
+impl TimeSource for MonotonicTime {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A time that only moves when it is advanced, for tests
+///
+/// Clones share the same time, so a clone kept by a test advances the time
+/// of a clock given the original.
+///
+/// ```
+/// use std::time::Duration;
+/// use rusty_chess_clock::{Clock, ClockMode, ManualTime};
+///
+/// let time = ManualTime::new();
+/// let mut clock = Clock::with_source(
+///     ClockMode::CountDown, Some(Duration::from_secs(60)), time.clone()
+/// );
+/// clock.start();
+/// time.advance(Duration::from_secs(15));
+/// assert_eq!(clock.read(), Duration::from_secs(45));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManualTime {
+    origin: Instant,
+    /// Nanoseconds since `origin`
+    offset: Arc<AtomicU64>,
+}
+
+impl ManualTime {
+    /// Create a time standing at the instant it was created
+    pub fn new() -> Self {
+        Self::starting_at(Instant::now())
+    }
+
+    /// Create a time standing at `origin`
+    pub fn starting_at(origin: Instant) -> Self {
+        Self { origin, offset: Arc::new(AtomicU64::new(0)) }
+    }
+
+    /// Moves the time on by `by`, for every clone
+    pub fn advance(&self, by: Duration) {
+        let nanos = u64::try_from(by.as_nanos()).unwrap_or(u64::MAX);
+        let _ = self.offset.fetch_update(
+            Ordering::SeqCst, Ordering::SeqCst,
+            |offset| Some(offset.saturating_add(nanos))
+        );
+    }
+
+    /// Get how far the time has been advanced since it was created
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.offset.load(Ordering::SeqCst))
+    }
+}
+
+impl Default for ManualTime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn now(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+
+    /// Advances the time to `due` rather than waiting for it, as nothing
+    /// else would
+    fn sleep_until(&self, due: Instant) {
+        if let Some(wait) = due.checked_duration_since(self.now()) {
+            self.advance(wait);
+        }
+    }
+}
+
+/// Another time source sped up or slowed down by a rate, such as 2.0 for
+/// twice as fast or 0.25 for a quarter speed
+///
+/// The time starts from the source's time when it is created, and moves on
+/// by `rate` times as much as the source does from then. The rate is at most
+/// `MAX_RATE`, so that the scaled time can not overflow.
+///
+/// ```
+/// use std::time::Duration;
+/// use rusty_chess_clock::{
+///     Clock, ClockMode, ManualTime, ScaledTime, TimeSource
+/// };
+///
+/// let time = ManualTime::new();
+/// let mut clock = Clock::with_source(
+///     ClockMode::CountUp, None, ScaledTime::with_source(time.clone(), 4.0)
+/// );
+/// clock.start();
+/// time.advance(Duration::from_secs(15));
+/// assert_eq!(clock.read(), Duration::from_secs(60));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScaledTime<T = MonotonicTime> {
+    source: T,
+    origin: Instant,
+    rate: f64,
+}
+
+impl ScaledTime {
+    /// The fastest rate a time can run at
+    pub const MAX_RATE: f64 = 1000.0;
+
+    /// Create the monotonic clock of the operating system running at `rate`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not positive and at most `MAX_RATE`. Use
+    /// `try_new` for a rate that may be invalid, such as one typed in.
+    pub fn new(rate: f64) -> Self {
+        Self::with_source(MonotonicTime, rate)
+    }
+
+    /// Create the monotonic clock of the operating system running at `rate`
+    ///
+    /// # Returns
+    /// The time, or `None` if `rate` is not positive and at most `MAX_RATE`.
+    pub fn try_new(rate: f64) -> Option<Self> {
+        Self::try_with_source(MonotonicTime, rate)
+    }
+}
+
+impl<T: TimeSource> ScaledTime<T> {
+    /// Create `source` running at `rate`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not positive and at most `MAX_RATE`.
+    pub fn with_source(source: T, rate: f64) -> Self {
+        Self::try_with_source(source, rate).unwrap_or_else(|| panic!(
+            "rate must be above 0 and at most {}, not {}",
+            ScaledTime::MAX_RATE, rate
+        ))
+    }
+
+    /// Create `source` running at `rate`
+    ///
+    /// # Returns
+    /// The time, or `None` if `rate` is not positive and at most `MAX_RATE`.
+    pub fn try_with_source(source: T, rate: f64) -> Option<Self> {
+        // NaN fails both comparisons
+        if !(rate > 0.0 && rate <= ScaledTime::MAX_RATE) {
+            return None;
+        }
+        let origin = source.now();
+        Some(Self { source, origin, rate })
+    }
+
+    /// Get how many times faster than its source the time runs
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+}
+
+impl<T: TimeSource> TimeSource for ScaledTime<T> {
+    fn now(&self) -> Instant {
+        let real = self.source.now().saturating_duration_since(self.origin);
+        self.origin + real.mul_f64(self.rate)
+    }
+
+    /// Waits on the source until the scaled time is `due`, such as half as
+    /// long as the difference at twice the speed
+    fn sleep_until(&self, due: Instant) {
+        let scaled = due.saturating_duration_since(self.origin);
+        self.source.sleep_until(self.origin + scaled.div_f64(self.rate));
+    }
+}
+
+/// A time source shared between clocks, such as the two clocks of a
+/// `ChessClock`, so that they always agree on the time
+#[derive(Clone)]
+pub(crate) struct SharedTime(Arc<dyn TimeSource + Send + Sync>);
+
+impl SharedTime {
+    pub(crate) fn new<T>(source: T) -> Self
+    where
+        T: TimeSource + Send + Sync + 'static,
+    {
+        Self(Arc::new(source))
+    }
+}
+
+impl Debug for SharedTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SharedTime")
+    }
+}
+
+impl TimeSource for SharedTime {
+    fn now(&self) -> Instant {
+        self.0.now()
+    }
+
+    fn sleep_until(&self, due: Instant) {
+        self.0.sleep_until(due);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::times;
+
+    #[test]
+    fn test_manual_time_is_shared() {
+        let time = ManualTime::new();
+        let start = time.now();
+        let clone = time.clone();
+        clone.advance(times::ONE_MINUTE);
+        assert_eq!(time.now(), start + times::ONE_MINUTE);
+        assert_eq!(time.elapsed(), times::ONE_MINUTE);
+        assert!(MonotonicTime.now() >= start);
+    }
+
+    #[test]
+    fn test_scaled_time() {
+        let time = ManualTime::new();
+        let start = time.now();
+        let slow = ScaledTime::with_source(time.clone(), 0.25);
+        assert_eq!(slow.now(), start);
+        time.advance(times::ONE_MINUTE);
+        assert_eq!(slow.now(), start + times::FIFTEEN_SECONDS);
+        assert_eq!(slow.rate(), 0.25);
+        for rate in [0.0, f64::NAN, f64::INFINITY, ScaledTime::MAX_RATE * 2.0] {
+            assert!(ScaledTime::try_new(rate).is_none());
+            let scaled = std::panic::catch_unwind(|| ScaledTime::new(rate));
+            assert!(scaled.is_err());
+        }
+        assert!(ScaledTime::try_new(ScaledTime::MAX_RATE).is_some());
+
+        // The fastest rate still reads after a very long time
+        let fast = ScaledTime::with_source(time.clone(), ScaledTime::MAX_RATE);
+        time.advance(times::ONE_HOUR * 24 * 365 * 1000);
+        assert!(fast.now() > time.now());
+    }
+
+    #[test]
+    fn test_sleep_until_waits_on_source() {
+        let time = ManualTime::new();
+        let start = time.now();
+        time.sleep_until(start + times::ONE_MINUTE);
+        assert_eq!(time.elapsed(), times::ONE_MINUTE);
+        // Already past, so no time passes
+        time.sleep_until(start);
+        assert_eq!(time.elapsed(), times::ONE_MINUTE);
+
+        let fast = ScaledTime::with_source(time.clone(), 4.0);
+        let scaled_start = fast.now();
+        fast.sleep_until(scaled_start + times::ONE_MINUTE);
+        assert_eq!(fast.now(), scaled_start + times::ONE_MINUTE);
+        assert_eq!(time.elapsed(), times::ONE_MINUTE + times::FIFTEEN_SECONDS);
+    }
+}
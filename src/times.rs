@@ -1,8 +1,72 @@
+//! # Times
+//! Common durations, and an extension trait for writing durations such as
+//! `5.seconds()` or `10.minutes()`.
+
 use std::time::Duration;
 
 pub const ONE_SECOND: Duration = Duration::from_secs(1);
 pub const ONE_MINUTE: Duration = Duration::from_secs(60);
 pub const ONE_HOUR: Duration = Duration::from_secs(3600);
 
+pub const TWO_SECONDS: Duration = Duration::from_secs(2);
+pub const THREE_SECONDS: Duration = Duration::from_secs(3);
 pub const FIVE_SECONDS: Duration = Duration::from_secs(5);
+pub const TEN_SECONDS: Duration = Duration::from_secs(10);
+pub const FIFTEEN_SECONDS: Duration = Duration::from_secs(15);
+pub const THIRTY_SECONDS: Duration = Duration::from_secs(30);
+
+pub const THREE_MINUTES: Duration = Duration::from_secs(3 * 60);
+pub const FIVE_MINUTES: Duration = Duration::from_secs(5 * 60);
 pub const TEN_MINUTES: Duration = Duration::from_secs(10 * 60);
+pub const FIFTEEN_MINUTES: Duration = Duration::from_secs(15 * 60);
+pub const THIRTY_MINUTES: Duration = Duration::from_secs(30 * 60);
+pub const NINETY_MINUTES: Duration = Duration::from_secs(90 * 60);
+
+/// Extension trait for building durations from whole numbers of units.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_chess_clock::times::{self, DurationExt};
+///
+/// assert_eq!(10.minutes(), times::TEN_MINUTES);
+/// assert_eq!(90.seconds(), 1.minutes() + 30.seconds());
+/// ```
+pub trait DurationExt {
+    fn millis(self) -> Duration;
+    fn seconds(self) -> Duration;
+    fn minutes(self) -> Duration;
+    fn hours(self) -> Duration;
+}
+
+impl DurationExt for u64 {
+    fn millis(self) -> Duration {
+        Duration::from_millis(self)
+    }
+
+    fn seconds(self) -> Duration {
+        Duration::from_secs(self)
+    }
+
+    fn minutes(self) -> Duration {
+        Duration::from_secs(self * 60)
+    }
+
+    fn hours(self) -> Duration {
+        Duration::from_secs(self * 3600)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_ext() {
+        assert_eq!(250.millis(), Duration::from_millis(250));
+        assert_eq!(5.seconds(), FIVE_SECONDS);
+        assert_eq!(3.minutes(), THREE_MINUTES);
+        assert_eq!(1.hours(), ONE_HOUR);
+        assert_eq!(0.minutes(), Duration::ZERO);
+    }
+}
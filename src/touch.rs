@@ -0,0 +1,207 @@
+//! # Touch
+//! Gesture recognition for touchscreen clocks, such as tablets used as club
+//! clocks
+//!
+//! The screen is split into two tap zones, the left half for player 1 and
+//! the right half for player 2. A quick tap in a zone is that player pressing
+//! their clock, a swipe down pauses, and holding a finger still opens the
+//! menu. Gestures are recognised when the finger is lifted, so a long press
+//! can not be mistaken for a tap if the finger is held a little too long.
+
+use std::time::{Duration, Instant};
+use crate::Player;
+
+/// A gesture made on a touchscreen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// A quick tap in a player's half of the screen
+    Tap(Player),
+    /// A drag downwards, anywhere on the screen
+    SwipeDown,
+    /// A finger held still for a while
+    LongPress,
+}
+
+/// How far and how long a touch must go to count as each gesture
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    long_press: Duration,
+    swipe_distance: f32,
+    tap_slop: f32,
+}
+
+impl Thresholds {
+    /// Create new thresholds
+    ///
+    /// # Arguments
+    /// * `long_press` - How long a finger must be held to be a long press.
+    /// * `swipe_distance` - How far a finger must move down to be a swipe.
+    /// * `tap_slop` - How far a finger may wander and still be a tap or a
+    ///   long press.
+    pub const fn new(
+        long_press: Duration, swipe_distance: f32, tap_slop: f32
+    ) -> Self {
+        Self { long_press, swipe_distance, tap_slop }
+    }
+
+    pub const fn get_long_press(&self) -> Duration {
+        self.long_press
+    }
+
+    pub const fn get_swipe_distance(&self) -> f32 {
+        self.swipe_distance
+    }
+
+    pub const fn get_tap_slop(&self) -> f32 {
+        self.tap_slop
+    }
+
+    pub fn set_long_press(&mut self, long_press: Duration) {
+        self.long_press = long_press;
+    }
+
+    pub fn set_swipe_distance(&mut self, swipe_distance: f32) {
+        self.swipe_distance = swipe_distance;
+    }
+
+    pub fn set_tap_slop(&mut self, tap_slop: f32) {
+        self.tap_slop = tap_slop;
+    }
+}
+
+impl Default for Thresholds {
+    /// A one second long press, a 100 pixel swipe and 20 pixels of slop
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1), 100.0, 20.0)
+    }
+}
+
+/// Tracks one touch from the finger going down to it lifting
+#[derive(Debug, Clone, Default)]
+pub struct Recognizer {
+    start: Option<((f32, f32), Instant)>,
+}
+
+impl Recognizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a finger going down
+    ///
+    /// # Arguments
+    /// * `position` - Where the finger went down, relative to the top left
+    ///   of the touch area.
+    /// * `at` - When the finger went down.
+    pub fn press(&mut self, position: (f32, f32), at: Instant) {
+        self.start = Some((position, at));
+    }
+
+    /// Forget the current touch, for when the system cancels it
+    pub fn cancel(&mut self) {
+        self.start = None;
+    }
+
+    /// Record a finger lifting and recognise the gesture it made
+    ///
+    /// # Arguments
+    /// * `position` - Where the finger lifted, relative to the top left of
+    ///   the touch area.
+    /// * `at` - When the finger lifted.
+    /// * `width` - The width of the touch area, to find the tap zones.
+    /// * `thresholds` - The thresholds of each gesture.
+    ///
+    /// # Returns
+    /// The gesture, or `None` if there was no press or the touch was not a
+    /// gesture, such as a sideways drag.
+    pub fn release(
+        &mut self,
+        position: (f32, f32),
+        at: Instant,
+        width: f32,
+        thresholds: &Thresholds,
+    ) -> Option<Gesture> {
+        let ((x, y), pressed) = self.start.take()?;
+        let dx = position.0 - x;
+        let dy = position.1 - y;
+
+        if dy >= thresholds.swipe_distance && dy > dx.abs() {
+            return Some(Gesture::SwipeDown);
+        }
+        if dx.hypot(dy) > thresholds.tap_slop {
+            return None;
+        }
+        if at.saturating_duration_since(pressed) >= thresholds.long_press {
+            return Some(Gesture::LongPress);
+        }
+        let player = if x < width / 2.0 {
+            Player::Player1
+        } else {
+            Player::Player2
+        };
+        Some(Gesture::Tap(player))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: f32 = 800.0;
+
+    fn gesture(
+        from: (f32, f32), to: (f32, f32), held: Duration
+    ) -> Option<Gesture> {
+        let mut recognizer = Recognizer::new();
+        let now = Instant::now();
+        recognizer.press(from, now);
+        recognizer.release(to, now + held, WIDTH, &Thresholds::default())
+    }
+
+    #[test]
+    fn test_taps_find_their_zone() {
+        let quick = Duration::from_millis(100);
+        assert_eq!(
+            gesture((100.0, 300.0), (105.0, 302.0), quick),
+            Some(Gesture::Tap(Player::Player1))
+        );
+        assert_eq!(
+            gesture((700.0, 300.0), (700.0, 300.0), quick),
+            Some(Gesture::Tap(Player::Player2))
+        );
+    }
+
+    #[test]
+    fn test_long_press_and_swipe() {
+        assert_eq!(
+            gesture((100.0, 300.0), (100.0, 300.0), Duration::from_secs(2)),
+            Some(Gesture::LongPress)
+        );
+        assert_eq!(
+            gesture((100.0, 100.0), (120.0, 300.0), Duration::from_millis(200)),
+            Some(Gesture::SwipeDown)
+        );
+        // Sideways drags and upward swipes are not gestures
+        assert_eq!(
+            gesture((100.0, 300.0), (400.0, 320.0), Duration::ZERO), None
+        );
+        assert_eq!(
+            gesture((100.0, 300.0), (100.0, 100.0), Duration::ZERO), None
+        );
+    }
+
+    #[test]
+    fn test_release_needs_press() {
+        let mut recognizer = Recognizer::new();
+        let thresholds = Thresholds::default();
+        let now = Instant::now();
+        let release = |recognizer: &mut Recognizer| {
+            recognizer.release((0.0, 0.0), now, WIDTH, &thresholds)
+        };
+        assert_eq!(release(&mut recognizer), None);
+
+        recognizer.press((0.0, 0.0), now);
+        recognizer.cancel();
+        assert_eq!(release(&mut recognizer), None);
+    }
+}
@@ -0,0 +1,994 @@
+//! # Chess
+//! A chess clock for the terminal.
+
+use std::{
+    io::{self, Write},
+    panic::{self, AssertUnwindSafe},
+    process,
+    thread::sleep,
+    time::Duration
+};
+use crate::{
+    encoding::parse_timing_method,
+    guard::Guard,
+    i18n::NumberFormat,
+    tui::{
+        terminal::{self, TerminalGuard},
+        theme::{ColorDepth, GradientColor}
+    },
+    presets,
+    times::{self, DurationExt},
+    utils::parse_duration,
+    AdjustmentError, NormalizationPolicy, Rules, Player, ChessClock, ClockEvent,
+    time_pressure_rating, AutoSave, IncrementStage, Recovery, SessionBudget,
+    Status,
+    DurationDisplay, TimingMethod
+};
+use termion::{
+    color::{self, Color},
+    clear,
+    event::Key,
+    input::{TermRead, Keys},
+    AsyncReader
+};
+
+const HELP: &str = "\
+A chess clock for the terminal. Unless a preset is given, the rules
+are asked for interactively.
+
+Options:
+  --preset NAME        Use a preset instead of asking for the rules, one of
+                       bullet-1+0, blitz-3+2, blitz-5+3-bronstein, rapid-10+5,
+                       rapid-15+10, classical-90+30 or shot-clock-30
+  --method METHOD      Use this timing method, one of fischer, bronstein or
+                       shot_clock
+  --gradient           Colour the running clock from green through yellow
+                       to red as its time runs down, in as many colours as
+                       the terminal supports
+  --session MINUTES    Limit the whole session, counting from the first
+                       start and running while the clock is stopped, such
+                       as for a club that closes at a set time
+  --adjudicate         Finish the game when the session runs out, for an
+                       arbiter to adjudicate (needs --session)
+  --rating1 RATING     Give time odds by rating: the stronger player loses
+  --rating2 RATING     1% of their time for every 10 points between the
+                       players, up to 90% (needs both)
+  --increment-from MOVE:SECONDS
+                       Change the increment to SECONDS from each player's
+                       MOVEth move on, such as 40:10 for +10s after move
+                       40 (may be given more than once)
+  --pin PIN            Lock quitting a game in progress behind an arbiter
+                       PIN of 4 to 8 digits
+  --strict             Refuse adding or subtracting time, and penalties,
+                       after the first press, as for a rated game, unless
+                       the arbiter types the PIN (see --pin)
+  --round-times SECONDS
+                       Round the players' times to the nearest multiple of
+                       SECONDS, such as 30 for a club's half minutes
+  --round-increments SECONDS
+                       Round increments to the nearest multiple of SECONDS
+  --pause-allowance MINUTES
+                       Let each player pause the game for breaks of up to
+                       MINUTES in all, such as to go to the bathroom
+  --debounce MS        Ignore a press within MS milliseconds of the previous
+                       one, such as a bouncing button or a double press
+  -h, --help           Print this help
+  -V, --version        Print the version
+
+Keys:
+  Enter                Start / stop the clock
+  Space                End the active player's turn
+  e                    Use a shot clock extension
+  + / -                Add / subtract 10 seconds for the active player
+  p                    Penalise the active player, giving 2 minutes to
+                       their opponent
+  b                    Pause for a break, charged to the player on move
+                       (Enter to carry on)
+  q                    Quit, asking for the PIN if one is set
+
+When the game is over, type a move number, or a time into the game such
+as 90s or 5m, to see what the clock showed then.
+";
+
+/// The longest gap between redraws before the display may have jumped
+const TICK_THRESHOLD: Duration = Duration::from_millis(500);
+/// How long the warning stays up after a late redraw
+const TICK_WARNING: Duration = Duration::from_secs(3);
+
+/// An adjustment of the active player's clock made from the keyboard
+#[derive(Debug, Clone, Copy)]
+enum Adjustment {
+    Add,
+    Subtract,
+    Penalty,
+}
+
+impl Adjustment {
+    const TIME: Duration = times::TEN_SECONDS;
+    const PENALTY: Duration = Duration::from_secs(120);
+}
+
+/// An adjustment waiting for the arbiter to type its reason
+#[derive(Debug)]
+struct PendingAdjustment {
+    adjustment: Adjustment,
+    player: Player,
+    reason: String,
+}
+
+/// An action locked behind the arbiter PIN
+#[derive(Debug, Clone, Copy)]
+enum Locked {
+    /// Quit a game in progress
+    Quit,
+    /// Adjust the clocks of a strict game under way
+    Adjust(Adjustment),
+}
+
+/// A line the arbiter is typing, shown in place of the clocks
+#[derive(Debug)]
+enum Prompt {
+    /// The reason for an adjustment
+    Reason(PendingAdjustment),
+    /// The PIN to take a locked action, and whether a wrong one was typed
+    Pin { action: Locked, entered: String, wrong: bool },
+}
+
+/// Run the terminal chess clock
+///
+/// # Arguments
+/// * `name` - The command name to show in help and version output.
+/// * `args` - The command line arguments, without the command name.
+pub fn run(name: &str, args: impl IntoIterator<Item = String>) {
+    terminal::install_panic_hook();
+    let mut preset = None;
+    let mut method = None;
+    let mut gradient = false;
+    let mut session = None;
+    let mut pause_allowance = None;
+    let mut adjudicate = false;
+    let mut ratings = [None, None];
+    let mut stages = Vec::new();
+    let mut guard = Guard::open();
+    let mut strict = false;
+    let mut normalization = NormalizationPolicy::NONE;
+    let mut debounce = None;
+
+    let mut args = args.into_iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "-h" | "--help" => {
+                print!("Usage: {} [OPTIONS]\n\n{}", name, HELP);
+                return;
+            },
+            "-V" | "--version" => {
+                println!("{} {}", name, crate::VERSION);
+                return;
+            },
+            "--gradient" => gradient = true,
+            "--adjudicate" => adjudicate = true,
+            "--strict" => strict = true,
+            "--preset" | "--method" | "--session" | "--rating1"
+                | "--rating2" | "--increment-from" | "--pin"
+                | "--round-times" | "--round-increments"
+                | "--pause-allowance" | "--debounce" => {
+                let Some(value) = args.next() else {
+                    usage_error(&format!("Missing value for {}", flag));
+                };
+                match flag.as_str() {
+                    "--preset" => {
+                        preset = Some(presets::by_name(&value).unwrap_or_else(
+                            || usage_error(&format!("Unknown preset {}", value))
+                        ));
+                    },
+                    "--method" => {
+                        let parsed = parse_timing_method(&value);
+                        method = Some(parsed.unwrap_or_else(|_| usage_error(
+                            &format!("Unknown timing method {}", value)
+                        )));
+                    },
+                    "--session" => {
+                        let minutes = value.parse::<u64>().unwrap_or_else(|_| {
+                            usage_error("--session needs a whole number")
+                        });
+                        session = Some(minutes.minutes());
+                    },
+                    "--pause-allowance" => {
+                        let minutes = value.parse::<u64>().unwrap_or_else(|_| {
+                            usage_error("--pause-allowance needs a whole number")
+                        });
+                        pause_allowance = Some(minutes.minutes());
+                    },
+                    "--debounce" => {
+                        let millis = value.parse::<u64>().unwrap_or_else(|_| {
+                            usage_error("--debounce needs a whole number")
+                        });
+                        debounce = Some(millis.millis());
+                    },
+                    "--increment-from" => {
+                        stages.push(parse_stage(&value).unwrap_or_else(|| {
+                            usage_error("--increment-from needs MOVE:SECONDS")
+                        }));
+                    },
+                    "--pin" => {
+                        guard = Guard::with_pin(&value).unwrap_or_else(
+                            |error| usage_error(&error.to_string())
+                        );
+                    },
+                    "--round-times" | "--round-increments" => {
+                        let step = value.parse::<u64>().unwrap_or_else(|_| {
+                            usage_error(&format!(
+                                "{} needs a whole number", flag
+                            ))
+                        }).seconds();
+                        normalization = if flag == "--round-times" {
+                            NormalizationPolicy::new(
+                                step, normalization.get_increment_step()
+                            )
+                        } else {
+                            NormalizationPolicy::new(
+                                normalization.get_time_step(), step
+                            )
+                        };
+                    },
+                    _ => {
+                        let rating = value.parse::<u32>().unwrap_or_else(|_| {
+                            usage_error(&format!(
+                                "{} needs a whole number", flag
+                            ))
+                        });
+                        let index = usize::from(flag == "--rating2");
+                        ratings[index] = Some(rating);
+                    },
+                }
+            },
+            _ => usage_error(&format!("Unknown argument {}, see --help", flag)),
+        }
+    }
+
+    let resumed = Recovery::find_default().and_then(ask_resume);
+    let mut chess_clock = match resumed {
+        Some(chess_clock) => chess_clock,
+        None => {
+            let mut rules = match preset {
+                Some(rules) => rules,
+                None => get_rules(method.unwrap_or_else(get_timing_method)),
+            };
+            match ratings {
+                [Some(r1), Some(r2)] => {
+                    let base = rules.get_player1_time()
+                        .max(rules.get_player2_time());
+                    let handicap = Rules::handicap_from_ratings(r1, r2, base);
+                    for player in Player::ALL {
+                        rules.set_time(player, handicap.get_time(player));
+                    }
+                },
+                [None, None] => {},
+                _ => usage_error(
+                    "--rating1 and --rating2 must be given together"
+                ),
+            }
+            rules.set_increment_stages(&stages)
+                .unwrap_or_else(|error| usage_error(&error.to_string()));
+            rules.set_require_reasons(get_arbiter_mode());
+            rules.set_strict(strict);
+            rules.set_pause_allowance(pause_allowance);
+            match session {
+                Some(time) => rules.set_session_budget(
+                    Some(SessionBudget::new(time, adjudicate))
+                ),
+                None if adjudicate => {
+                    usage_error("--adjudicate needs --session")
+                },
+                None => {},
+            }
+            let normalized = rules.normalized(normalization);
+            if normalized != rules {
+                print_rounded(&normalized);
+            }
+            ChessClock::try_new(normalized).unwrap_or_else(|error| {
+                usage_error(&format!("Invalid rules: {}", error))
+            })
+        },
+    };
+    print_instructions(chess_clock.rules());
+    chess_clock.set_tick_watchdog(Some(TICK_THRESHOLD));
+    chess_clock.set_debounce(debounce);
+    chess_clock.set_arbiter(guard.clone());
+    let mut autosave = AutoSave::to_default_path();
+    let played = panic::catch_unwind(AssertUnwindSafe(|| run_clock(
+        &mut chess_clock, gradient.then(ColorDepth::detect), &guard,
+        autosave.as_mut()
+    )));
+    if let Err(payload) = played {
+        if let Some(autosave) = &mut autosave {
+            save_recovery(&chess_clock, autosave);
+        }
+        panic::resume_unwind(payload);
+    }
+}
+
+/// Ask whether to carry on a game left unfinished, forgetting it if not
+fn ask_resume(recovery: Recovery) -> Option<ChessClock> {
+    print!("{} (y/N): ", recovery.question());
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).expect("Failed to read line");
+    if input.trim().eq_ignore_ascii_case("y") {
+        return Some(recovery.resume());
+    }
+    if let Err(error) = recovery.discard() {
+        eprintln!("Could not discard the interrupted game: {}", error);
+    }
+    None
+}
+
+/// Save a game cut short by a panic, so that it can be carried on
+fn save_recovery(chess_clock: &ChessClock, autosave: &mut AutoSave) {
+    match autosave.save(chess_clock) {
+        Ok(()) => eprintln!(
+            "The game was saved to {}", autosave.path().display()
+        ),
+        Err(error) => eprintln!("The game could not be saved: {}", error),
+    }
+}
+
+/// Parses an increment stage given as `MOVE:SECONDS`
+fn parse_stage(value: &str) -> Option<IncrementStage> {
+    let (from_move, seconds) = value.split_once(':')?;
+    let from_move = from_move.parse().ok().filter(|from_move| *from_move > 0)?;
+    Some(IncrementStage::new(from_move, seconds.parse::<u64>().ok()?.seconds()))
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+fn get_rules(timing_method: TimingMethod) -> Rules {
+    match timing_method {
+        TimingMethod::ShotClock => get_shot_clock_rules(),
+        _ => {
+            let start_time = get_start_time();
+            let increment = get_increment();
+            Rules::new(
+                start_time, start_time,
+                increment, Player::Player1, timing_method
+            )
+        }
+    }
+}
+
+fn get_start_time() -> Duration {
+    print!("Time per player (default 10 minutes): ");
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).expect("Failed to read line");
+
+    match input.trim().parse::<u64>() {
+        Ok(duration) => duration.minutes(),
+        Err(_) => times::TEN_MINUTES,
+    }
+}
+
+fn get_increment() -> Duration {
+    print!("Increment per move (default 5 seconds): ");
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).expect("Failed to read line");
+
+    match input.trim().parse::<u64>() {
+        Ok(duration) => duration.seconds(),
+        Err(_) => times::FIVE_SECONDS,
+    }
+}
+
+fn get_shot_clock_rules() -> Rules {
+    let turn_time = prompt_seconds(
+        "Time per shot (default 30 seconds): ", times::THIRTY_SECONDS
+    );
+    let extension_time = prompt_seconds(
+        "Extension length (default 30 seconds): ", times::THIRTY_SECONDS
+    );
+
+    print!("Extensions per player (default 1): ");
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).expect("Failed to read line");
+    let extensions = input.trim().parse::<u32>().unwrap_or(1);
+
+    Rules::shot_clock(turn_time, extension_time, extensions)
+}
+
+fn prompt_seconds(prompt: &str, default: Duration) -> Duration {
+    print!("{}", prompt);
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).expect("Failed to read line");
+
+    match input.trim().parse::<u64>() {
+        Ok(duration) => duration.seconds(),
+        Err(_) => default,
+    }
+}
+
+fn get_arbiter_mode() -> bool {
+    print!("Require reasons for adjustments (y/N): ");
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).expect("Failed to read line");
+    input.trim().eq_ignore_ascii_case("y")
+}
+
+fn get_timing_method() -> TimingMethod {
+    loop {
+        print!(
+            "\r{}Timing method (f for Fischer, b for Bronstein, \
+             s for Shot Clock): ",
+            clear::CurrentLine
+        );
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) => {},
+            Err(_) => continue,
+        }
+
+        match input.trim() {
+            "f" => return TimingMethod::Fischer,
+            "b" => return TimingMethod::Bronstein,
+            "s" => return TimingMethod::ShotClock,
+            _ => continue,
+        }
+    }
+}
+
+/// Tell the players the times were rounded, and what to
+fn print_rounded(rules: &Rules) {
+    println!(
+        "Times rounded to the club's steps - Player 1: {}, Player 2: {}, \
+         increment: {}",
+        DurationDisplay::from(rules.get_player1_time()),
+        DurationDisplay::from(rules.get_player2_time()),
+        DurationDisplay::from(rules.get_increment())
+    );
+}
+
+fn print_instructions(rules: &Rules) {
+    println!("===================== Chess Clock ====================");
+    println!("Time control: {}", rules.to_notation());
+    println!(
+        "Player 1 time: {}, Player 2 time: {}",
+        DurationDisplay::from(rules.get_player1_time()),
+        DurationDisplay::from(rules.get_player2_time())
+    );
+    println!("Extra time: {}", DurationDisplay::from(rules.get_increment()));
+    for stage in rules.get_increment_stages() {
+        println!(
+            "Extra time from move {}: {}",
+            stage.get_from_move(), DurationDisplay::from(stage.get_increment())
+        );
+    }
+    println!("Timing Method: {}", rules.get_timing_method());
+    if rules.get_timing_method() == TimingMethod::ShotClock {
+        println!("Extensions per player: {}", rules.get_extensions());
+    }
+    if let Some(budget) = rules.get_session_budget() {
+        let end = if budget.get_adjudicate() {
+            "then adjudicated"
+        } else {
+            "then play on"
+        };
+        println!(
+            "Session: {}, {}", DurationDisplay::from(budget.get_time()), end
+        );
+    }
+    if let Some(allowance) = rules.get_pause_allowance() {
+        println!(
+            "Breaks per player: {}", DurationDisplay::from(allowance)
+        );
+    }
+    println!("Instructions:");
+    println!("- Active player is indicated by highlighted background");
+    println!("- Yellow: Stopped, Green: Running, Red: Finished");
+    println!("- Press enter to start/stop");
+    println!("- Press space to switch player");
+    if rules.get_timing_method() == TimingMethod::ShotClock {
+        println!("- Press e to use an extension");
+    }
+    println!("- Press + or - to add or subtract 10 seconds for the active player");
+    println!("- Press p to penalise the active player, giving 2 minutes to \
+              their opponent");
+    if rules.get_strict() {
+        println!("  (strict: only with the arbiter PIN after the first press)");
+    }
+    if rules.get_pause_allowance().is_some() {
+        println!("- Press b to pause for a break taken by the active player, \
+                  and enter to carry on");
+    }
+    println!("- Press q to quit");
+    println!("======================================================");
+}
+
+fn run_clock(
+    chess_clock: &mut ChessClock,
+    gradient: Option<ColorDepth>,
+    guard: &Guard,
+    mut autosave: Option<&mut AutoSave>
+) {
+    let stdin = termion::async_stdin();
+    let mut stdout = TerminalGuard::new().unwrap();
+    let mut keys = stdin.keys();
+    let mut prompt = None;
+
+    while chess_clock.status() != Status::Finished {
+        async_process_input(chess_clock, &mut keys, &mut prompt, guard);
+        chess_clock.update();
+        // A failed save is tried again at the next, rather than interrupting
+        // the game
+        if let Some(autosave) = autosave.as_deref_mut() {
+            let _ = autosave.save_if_due(chess_clock);
+        }
+        match &prompt {
+            Some(Prompt::Reason(pending)) => {
+                display_reason_prompt(pending, &mut stdout)
+            },
+            Some(Prompt::Pin { action, entered, wrong }) => {
+                display_pin_prompt(*action, entered, *wrong, &mut stdout)
+            },
+            None => display_clock(chess_clock, gradient, &mut stdout),
+        }
+        sleep(10.millis());
+    }
+    let _ = stdout.suspend_raw_mode();
+    println!();
+    println!("Game finished!");
+    if let Some(autosave) = autosave {
+        if let Err(error) = autosave.save(chess_clock) {
+            eprintln!("Could not remove the recovery file: {}", error);
+        }
+    }
+    print_report(chess_clock);
+    inspect_game(chess_clock, &mut keys);
+}
+
+/// Show the clock as it was at moves or times into the game asked for, until
+/// a blank line
+///
+/// The lines are read from `keys`, as its reader keeps the terminal until it
+/// is dropped, with the terminal back in cooked mode to echo and edit them.
+fn inspect_game(chess_clock: &ChessClock, keys: &mut Keys<AsyncReader>) {
+    let Some(first) = chess_clock.events().first() else {
+        return;
+    };
+    let started = first.instant();
+    loop {
+        print!(
+            "Inspect the clock at a move, or a time into the game such as 5m \
+            (Enter to finish): "
+        );
+        io::stdout().flush().unwrap();
+        let input = read_line(keys);
+        let input = input.trim();
+        if input.is_empty() {
+            break;
+        }
+        let (label, snapshot) = if let Ok(n) = input.parse::<u32>() {
+            (format!("Move {}", n), chess_clock.state_at_move(n))
+        } else if let Some(time) = parse_duration(input) {
+            (
+                format!("At {}", DurationDisplay::from(time)),
+                Some(chess_clock.state_at(started + time)),
+            )
+        } else {
+            println!("Enter a move number, or a time such as 90s or 5m");
+            continue;
+        };
+        match snapshot {
+            Some(snapshot) => {
+                let (p1, p2) = snapshot.times;
+                let on_move = match snapshot.status {
+                    Status::Finished => "game over".to_string(),
+                    _ => format!("{} to move", snapshot.active_player),
+                };
+                println!(
+                    "{}: Player 1 {} | Player 2 {}, {}",
+                    label, DurationDisplay::from(p1),
+                    DurationDisplay::from(p2), on_move
+                );
+            },
+            None => println!("{} was not played", label),
+        }
+    }
+}
+
+/// Read a line typed at the terminal from `keys`, without the newline
+fn read_line(keys: &mut Keys<AsyncReader>) -> String {
+    let mut line = String::new();
+    loop {
+        match keys.next() {
+            Some(Ok(Key::Char('\n'))) => return line,
+            Some(Ok(Key::Char(c))) => line.push(c),
+            Some(_) => {},
+            None => sleep(10.millis()),
+        }
+    }
+}
+
+/// Print the final times and every adjustment made during the game
+fn print_report(chess_clock: &ChessClock) {
+    let (p1, p2) = chess_clock.read();
+    println!(
+        "Final times - Player 1: {}, Player 2: {}",
+        DurationDisplay::from(p1), DurationDisplay::from(p2)
+    );
+    println!(
+        "Time used - Player 1: {}, Player 2: {}, game: {}",
+        DurationDisplay::from(chess_clock.think_time(Player::Player1)),
+        DurationDisplay::from(chess_clock.think_time(Player::Player2)),
+        DurationDisplay::from(chess_clock.elapsed_game_time())
+    );
+    let numbers = NumberFormat::detect();
+    let rating = |player| {
+        time_pressure_rating(chess_clock.rules(), chess_clock.events(), player)
+            .map_or("-".to_string(), |rating| format!(
+                "{} ({} left)",
+                rating.get_rating(),
+                numbers.percent(f64::from(rating.get_fraction_left()), 1)
+            ))
+    };
+    println!(
+        "Time pressure rating - Player 1: {}, Player 2: {}",
+        rating(Player::Player1), rating(Player::Player2)
+    );
+
+    let expired = chess_clock.events().iter()
+        .any(|record| record.event() == &ClockEvent::SessionExpired);
+    if expired {
+        println!("The session ran out during the game");
+    }
+
+    if chess_clock.rules().get_pause_allowance().is_some() {
+        let used = |player| {
+            DurationDisplay::from(chess_clock.pause_used(player))
+        };
+        println!(
+            "Breaks taken - Player 1: {}, Player 2: {}",
+            used(Player::Player1), used(Player::Player2)
+        );
+        chess_clock.events().iter()
+            .filter(|record| matches!(
+                record.event(), ClockEvent::PauseAllowanceExceeded(_)
+            ))
+            .for_each(|record| println!("- {}", record));
+    }
+
+    let discrepancies: Vec<_> = chess_clock.events().iter()
+        .filter(|record| {
+            matches!(record.event(), ClockEvent::Discrepancy { .. })
+        })
+        .collect();
+    if !discrepancies.is_empty() {
+        println!("The clock audit found time lost or gained:");
+        discrepancies.iter().for_each(|record| println!("- {}", record));
+    }
+
+    let adjustments: Vec<_> = chess_clock.events().iter()
+        .filter(|record| {
+            let event = record.event();
+            event.is_adjustment()
+                || matches!(event, ClockEvent::AdjustmentRefused(_))
+        })
+        .collect();
+    if adjustments.is_empty() {
+        println!("No adjustments were made");
+    } else {
+        println!("Adjustments:");
+        adjustments.iter().for_each(|record| println!("- {}", record));
+    }
+}
+
+fn display_reason_prompt(
+    pending: &PendingAdjustment,
+    stdout: &mut TerminalGuard
+) {
+    let action = match pending.adjustment {
+        Adjustment::Add => "Add 10 seconds for",
+        Adjustment::Subtract => "Subtract 10 seconds for",
+        Adjustment::Penalty => "Penalise",
+    };
+    print!(
+        "\r{}{} {}, reason (Enter to apply, Esc to cancel): {}",
+        clear::CurrentLine, action, pending.player, pending.reason
+    );
+    stdout.flush().unwrap();
+}
+
+/// Ask for the arbiter PIN, showing a star for each digit typed
+fn display_pin_prompt(
+    action: Locked,
+    entered: &str,
+    wrong: bool,
+    stdout: &mut TerminalGuard
+) {
+    let wrong = if wrong { "Wrong PIN. " } else { "" };
+    let action = match action {
+        Locked::Quit => "quit",
+        Locked::Adjust(_) => "adjust the clocks",
+    };
+    print!(
+        "\r{}{}PIN to {} (Enter to confirm, Esc to cancel): {}",
+        clear::CurrentLine, wrong, action, "*".repeat(entered.len())
+    );
+    stdout.flush().unwrap();
+}
+
+fn display_clock(
+    chess_clock: &ChessClock,
+    gradient: Option<ColorDepth>,
+    stdout: &mut TerminalGuard
+) {
+    print!(
+        "\r{}{}",
+        clear::CurrentLine, format_chess_clock(chess_clock, gradient)
+    );
+    stdout.flush().unwrap();
+}
+
+#[derive(Debug, Clone, Copy)]
+struct StatusColor(Status);
+
+impl Color for StatusColor {
+    fn write_fg(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.0 {
+            Status::Running => color::Green.write_fg(f),
+            Status::Stopped => color::Yellow.write_fg(f),
+            Status::Finished => color::Red.write_fg(f),
+        }
+    }
+
+    fn write_bg(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.0 {
+            Status::Running => color::Green.write_bg(f),
+            Status::Stopped => color::Yellow.write_bg(f),
+            Status::Finished => color::Red.write_bg(f),
+        }
+    }
+}
+
+/// Format both clocks on one line, with the active player's highlighted
+///
+/// # Arguments
+/// * `clock` - The clock to format.
+/// * `gradient` - The colour depth of the terminal, to highlight a running
+///   clock by how much of its time is left; otherwise the highlight shows
+///   the status of the game.
+pub fn format_chess_clock(
+    clock: &ChessClock, gradient: Option<ColorDepth>
+) -> String {
+    let view = clock.view_model();
+
+    let highlight = match gradient {
+        Some(depth) if view.status == Status::Running => {
+            let fraction = view.active().fraction_remaining;
+            format!(
+                "{}{}",
+                color::Fg(color::Black),
+                color::Bg(GradientColor::new(depth, fraction))
+            )
+        },
+        _ => format!(
+            "{}{}",
+            color::Fg(color::White),
+            color::Bg(StatusColor(view.status))
+        ),
+    };
+
+    let mut result = String::new();
+    for player in &view.players {
+        if player.active {
+            result.push_str(&highlight);
+        }
+        result.push_str(&format!(" {}: {} ", player.label, player.text));
+        if player.active {
+            result.push_str(&format!(
+                "{}{}",
+                color::Fg(color::Reset),
+                color::Bg(color::Reset)
+            ));
+        }
+    }
+
+    if let Some(remaining) = clock.session_remaining() {
+        result.push_str(&format!(
+            " | Session: {}", DurationDisplay::from(remaining)
+        ));
+    }
+    if clock.rules().get_pause_allowance().is_some() {
+        let left = |player| DurationDisplay::from(
+            clock.pause_remaining(player).unwrap_or_default()
+        );
+        result.push_str(&format!(
+            " | Breaks: {} / {}", left(Player::Player1), left(Player::Player2)
+        ));
+        if let Some(player) = clock.on_break() {
+            result.push_str(&format!(" | {} on a break", player));
+        }
+    }
+    if clock.recent_tick_delay(TICK_WARNING).is_some() {
+        result.push_str(" | Display stalled, times may have jumped");
+    }
+
+    result
+}
+
+/// Process a key typed while entering the reason for an adjustment
+///
+/// The adjustment is applied when Enter is pressed, unless the rules require
+/// a reason and none has been typed. An adjustment refused by strict rules
+/// is recorded by the clock, so the prompt closes.
+fn process_reason_key(
+    chess_clock: &mut ChessClock,
+    key: Key,
+    prompt: &mut Option<Prompt>
+) {
+    let Some(Prompt::Reason(adjustment)) = prompt.as_mut() else {
+        return;
+    };
+    match key {
+        Key::Char('\n') => {
+            let PendingAdjustment { adjustment, player, reason } = adjustment;
+            let reason = Some(reason.as_str());
+            let result = match adjustment {
+                Adjustment::Add => {
+                    chess_clock.add_time(*player, Adjustment::TIME, reason)
+                },
+                Adjustment::Subtract => {
+                    chess_clock.subtract_time(*player, Adjustment::TIME, reason)
+                },
+                Adjustment::Penalty => chess_clock.apply_penalty(
+                    *player, Adjustment::PENALTY, reason
+                ),
+            };
+            if result != Err(AdjustmentError::MissingReason) {
+                *prompt = None;
+            }
+        },
+        Key::Char(c) if !c.is_control() => adjustment.reason.push(c),
+        Key::Backspace => {
+            adjustment.reason.pop();
+        },
+        Key::Esc => *prompt = None,
+        _ => {}
+    }
+}
+
+/// Process a key typed while entering the PIN for a locked action
+///
+/// The action is taken when Enter is pressed after the right PIN, going on
+/// to ask for the reason for an adjustment; a wrong PIN is cleared to be
+/// typed again.
+fn process_pin_key(
+    chess_clock: &mut ChessClock,
+    key: Key,
+    prompt: &mut Option<Prompt>,
+    guard: &Guard
+) {
+    let Some(Prompt::Pin { action, entered, wrong }) = prompt.as_mut() else {
+        return;
+    };
+    match key {
+        Key::Char('\n') if guard.unlock(entered) => {
+            match *action {
+                Locked::Quit => {
+                    *prompt = None;
+                    quit(chess_clock);
+                },
+                Locked::Adjust(adjustment) => {
+                    chess_clock.authorise(entered);
+                    *prompt = Some(Prompt::Reason(PendingAdjustment {
+                        adjustment,
+                        player: chess_clock.active_player(),
+                        reason: String::new(),
+                    }));
+                },
+            }
+        },
+        Key::Char('\n') => {
+            entered.clear();
+            *wrong = true;
+        },
+        Key::Char(c) => entered.push(c),
+        Key::Backspace => {
+            entered.pop();
+        },
+        Key::Esc => *prompt = None,
+        _ => {}
+    }
+}
+
+/// End the game, stopping the clock first if it is running, and audit the
+/// time charged over it for the report
+fn quit(chess_clock: &mut ChessClock) {
+    if chess_clock.status() == Status::Running {
+        chess_clock.stop();
+    }
+    chess_clock.finish();
+    chess_clock.audit();
+}
+
+fn async_process_input(
+    chess_clock: &mut ChessClock,
+    keys: &mut Keys<AsyncReader>,
+    prompt: &mut Option<Prompt>,
+    guard: &Guard
+) {
+    if let Some(Ok(key)) = keys.next() {
+        match prompt {
+            Some(Prompt::Reason(_)) => {
+                process_reason_key(chess_clock, key, prompt);
+                return;
+            },
+            Some(Prompt::Pin { .. }) => {
+                process_pin_key(chess_clock, key, prompt, guard);
+                return;
+            },
+            None => {},
+        }
+
+        let pin = |action| Some(Prompt::Pin {
+            action, entered: String::new(), wrong: false
+        });
+        let adjust = |adjustment| Some(Prompt::Reason(PendingAdjustment {
+            adjustment,
+            player: chess_clock.active_player(),
+            reason: String::new(),
+        }));
+        if let termion::event::Key::Char(c) = key {
+            match c {
+                'q' if guard.is_locked(chess_clock) => {
+                    *prompt = pin(Locked::Quit);
+                },
+                'q' => quit(chess_clock),
+                ' ' => {
+                    chess_clock.switch_player();
+                },
+                'e' => {
+                    chess_clock.extend();
+                },
+                '+' | '-' | 'p' => {
+                    let adjustment = match c {
+                        '+' => Adjustment::Add,
+                        '-' => Adjustment::Subtract,
+                        _ => Adjustment::Penalty,
+                    };
+                    // Without a PIN the adjustment is refused on Enter, and
+                    // the clock records the attempt
+                    *prompt = if chess_clock.adjustments_locked()
+                        && guard.has_pin()
+                    {
+                        pin(Locked::Adjust(adjustment))
+                    } else {
+                        adjust(adjustment)
+                    };
+                },
+                'b' => chess_clock.pause_for(chess_clock.active_player()),
+                '\n' => {
+                    if chess_clock.status() == Status::Running {
+                        chess_clock.stop();
+                    } else {
+                        chess_clock.start();
+                    }
+                },
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! # Completions
+//! Shell completion scripts for the `rusty-clock` binary.
+
+use crate::{encoding::format_timing_method, presets, TimingMethod};
+
+/// A shell that completion scripts can be generated for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub const ALL: [Self; 3] = [Self::Bash, Self::Zsh, Self::Fish];
+
+    /// Get the shell with the given name, as typed on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|shell| shell.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bash => "bash",
+            Self::Zsh => "zsh",
+            Self::Fish => "fish",
+        }
+    }
+}
+
+/// An option of a subcommand, and the values it takes
+struct Flag {
+    name: &'static str,
+    values: Values,
+}
+
+enum Values {
+    /// The option is a switch and takes no value
+    None,
+    /// The option takes a value that can not be completed
+    Any,
+    /// The option takes one of these values
+    OneOf(Vec<&'static str>),
+}
+
+/// The subcommands of `rusty-clock` and their options
+fn commands() -> Vec<(&'static str, Vec<Flag>)> {
+    let flag = |name, values| Flag { name, values };
+    let info = || vec![
+        flag("--help", Values::None), flag("--version", Values::None)
+    ];
+    let presets = presets::ALL.iter().map(|(name, _)| *name).collect();
+    let methods = TimingMethod::ALL.into_iter()
+        .map(format_timing_method)
+        .collect();
+    let shells = Shell::ALL.iter().map(Shell::name).collect();
+
+    vec![
+        ("timer", [
+            flag("--rounds", Values::Any),
+            flag("--exam", Values::Any),
+            flag("--alarm", Values::Any),
+            flag("--big", Values::None),
+            flag("--speed", Values::Any),
+        ].into_iter().chain(info()).collect()),
+        ("chess", [
+            flag("--preset", Values::OneOf(presets)),
+            flag("--method", Values::OneOf(methods)),
+            flag("--gradient", Values::None),
+            flag("--session", Values::Any),
+            flag("--adjudicate", Values::None),
+            flag("--rating1", Values::Any),
+            flag("--rating2", Values::Any),
+            flag("--pin", Values::Any),
+            flag("--round-times", Values::Any),
+            flag("--round-increments", Values::Any),
+            flag("--pause-allowance", Values::Any),
+            flag("--debounce", Values::Any),
+        ].into_iter().chain(info()).collect()),
+        ("gui", [
+            flag("--kiosk", Values::None),
+            flag("--touch", Values::None),
+            flag("--long-press", Values::Any),
+            flag("--swipe", Values::Any),
+            flag("--tap-slop", Values::Any),
+            flag("--chroma-key", Values::OneOf(vec!["green", "magenta"])),
+            flag("--font-colour", Values::Any),
+            flag("--delay", Values::Any),
+            flag("--burn-in", Values::None),
+            flag("--dim-after", Values::Any),
+            flag("--pin", Values::Any),
+            flag("--round-times", Values::Any),
+            flag("--round-increments", Values::Any),
+            flag("--speed", Values::Any),
+        ].into_iter().chain(info()).collect()),
+        ("heatmap", [
+            flag("--buckets", Values::Any),
+            flag("--split", Values::Any),
+            flag("--player", Values::OneOf(vec!["1", "2"])),
+        ].into_iter().chain(info()).collect()),
+        ("stats", [
+            flag("--games", Values::Any),
+        ].into_iter().chain(info()).collect()),
+        ("completions", vec![flag("shell", Values::OneOf(shells))]),
+    ]
+}
+
+/// Generate the completion script for `shell`
+///
+/// # Arguments
+/// * `shell` - The shell to generate the script for.
+///
+/// # Returns
+/// The script, to be sourced by the shell or saved in its completions
+/// directory.
+pub fn generate(shell: Shell) -> String {
+    let commands = commands();
+    match shell {
+        Shell::Bash => bash(&commands),
+        Shell::Zsh => zsh(&commands),
+        Shell::Fish => fish(&commands),
+    }
+}
+
+/// The words completed after the flag or subcommand, as a space separated
+/// list, either the flags of a subcommand or the values of one flag
+fn words<'a>(words: impl IntoIterator<Item = &'a str>) -> String {
+    words.into_iter().collect::<Vec<_>>().join(" ")
+}
+
+fn top_level(commands: &[(&str, Vec<Flag>)]) -> String {
+    words(commands.iter().map(|(name, _)| *name).chain(["--help", "--version"]))
+}
+
+/// The flags of a subcommand, or the positional values of `completions`
+fn arguments(flags: &[Flag]) -> String {
+    words(flags.iter().flat_map(|flag| match (&flag.values, flag.name) {
+        (Values::OneOf(values), name) if !name.starts_with('-') => {
+            values.clone()
+        },
+        (_, name) => vec![name],
+    }))
+}
+
+fn bash(commands: &[(&str, Vec<Flag>)]) -> String {
+    let mut values = String::new();
+    let mut flags = String::new();
+    for (command, command_flags) in commands {
+        for flag in command_flags {
+            if let (Values::OneOf(choices), true) =
+                (&flag.values, flag.name.starts_with('-'))
+            {
+                values += &format!(
+                    "        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); \
+                     return ;;\n",
+                    flag.name, words(choices.iter().copied())
+                );
+            }
+        }
+        flags += &format!(
+            "        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;\n",
+            command, arguments(command_flags)
+        );
+    }
+    format!(
+        "_rusty_clock() {{\n\
+         \x20   local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"\n\
+         \x20   local prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"\n\
+         \x20   if [ \"$COMP_CWORD\" -eq 1 ]; then\n\
+         \x20       COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n\
+         \x20       return\n\
+         \x20   fi\n\
+         \x20   case \"$prev\" in\n{}\x20   esac\n\
+         \x20   case \"${{COMP_WORDS[1]}}\" in\n{}\x20   esac\n\
+         }}\n\
+         complete -F _rusty_clock rusty-clock\n",
+        top_level(commands), values, flags
+    )
+}
+
+fn zsh(commands: &[(&str, Vec<Flag>)]) -> String {
+    let mut values = String::new();
+    let mut flags = String::new();
+    for (command, command_flags) in commands {
+        for flag in command_flags {
+            if let (Values::OneOf(choices), true) =
+                (&flag.values, flag.name.starts_with('-'))
+            {
+                values += &format!(
+                    "        {}) compadd -- {}; return ;;\n",
+                    flag.name, words(choices.iter().copied())
+                );
+            }
+        }
+        flags += &format!(
+            "        {}) compadd -- {} ;;\n", command, arguments(command_flags)
+        );
+    }
+    format!(
+        "#compdef rusty-clock\n\n\
+         _rusty_clock() {{\n\
+         \x20   if (( CURRENT == 2 )); then\n\
+         \x20       compadd -- {}\n\
+         \x20       return\n\
+         \x20   fi\n\
+         \x20   case ${{words[CURRENT-1]}} in\n{}\x20   esac\n\
+         \x20   case ${{words[2]}} in\n{}\x20   esac\n\
+         }}\n\n\
+         _rusty_clock \"$@\"\n",
+        top_level(commands), values, flags
+    )
+}
+
+fn fish(commands: &[(&str, Vec<Flag>)]) -> String {
+    let mut script = String::from("complete -c rusty-clock -f\n");
+    script += &format!(
+        "complete -c rusty-clock -n __fish_use_subcommand -a \"{}\"\n",
+        words(commands.iter().map(|(name, _)| *name))
+    );
+    for (command, command_flags) in commands {
+        let condition = format!("__fish_seen_subcommand_from {}", command);
+        for flag in command_flags {
+            let Some(long) = flag.name.strip_prefix("--") else {
+                if let Values::OneOf(choices) = &flag.values {
+                    script += &format!(
+                        "complete -c rusty-clock -n \"{}\" -a \"{}\"\n",
+                        condition, words(choices.iter().copied())
+                    );
+                }
+                continue;
+            };
+            let values = match &flag.values {
+                Values::None => String::new(),
+                Values::Any => " -r".to_string(),
+                Values::OneOf(choices) => format!(
+                    " -x -a \"{}\"", words(choices.iter().copied())
+                ),
+            };
+            script += &format!(
+                "complete -c rusty-clock -n \"{}\" -l {}{}\n",
+                condition, long, values
+            );
+        }
+    }
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_from_name() {
+        assert_eq!(Shell::from_name("zsh"), Some(Shell::Zsh));
+        assert_eq!(Shell::from_name("powershell"), None);
+    }
+
+    #[test]
+    fn test_scripts_complete_presets_and_methods() {
+        for shell in Shell::ALL {
+            let script = generate(shell);
+            for (preset, _) in presets::ALL {
+                assert!(script.contains(preset), "{:?} lacks {}", shell, preset);
+            }
+            for method in ["fischer", "bronstein", "shot_clock"] {
+                assert!(script.contains(method), "{:?} lacks {}", shell, method);
+            }
+            assert!(script.contains("timer"));
+            assert!(script.contains("rounds"));
+        }
+    }
+
+    #[test]
+    fn test_bash_script() {
+        let script = generate(Shell::Bash);
+        assert!(script.ends_with("complete -F _rusty_clock rusty-clock\n"));
+        assert!(script.contains(
+            "compgen -W \"timer chess gui heatmap stats completions --help --version\""
+        ));
+        assert!(script.contains(
+            "completions) COMPREPLY=($(compgen -W \"bash zsh fish\" -- \"$cur\"))"
+        ));
+    }
+}
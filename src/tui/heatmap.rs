@@ -0,0 +1,124 @@
+//! # Heatmap
+//! The average time per move in buckets by move number, over saved
+//! sessions, printed as comma separated values.
+
+use std::process;
+use crate::{MoveBuckets, Player, Session, TimeHeatmap};
+
+const HELP: &str = "\
+The average time per move in the opening, middlegame and endgame, or other
+buckets by move number, over one or many sessions saved by Session::export.
+Prints a line per bucket of comma separated values.
+
+Options:
+  --buckets N          Split each game into N equal parts (default 3)
+  --split MOVES        Split every game before these move numbers instead,
+                       e.g. 15,40
+  --player N           Only count the moves of player 1 or 2, rather than
+                       both
+  -h, --help           Print this help
+  -V, --version        Print the version
+";
+
+/// Print the heatmap of some saved sessions
+///
+/// # Arguments
+/// * `name` - The command name to show in help and version output.
+/// * `args` - The command line arguments, without the command name.
+pub fn run(name: &str, args: impl IntoIterator<Item = String>) {
+    let mut split = MoveBuckets::default();
+    let mut players = Player::ALL.to_vec();
+    let mut paths = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "-h" | "--help" => {
+                print!("Usage: {} [OPTIONS] SESSION...\n\n{}", name, HELP);
+                return;
+            },
+            "-V" | "--version" => {
+                println!("{} {}", name, crate::VERSION);
+                return;
+            },
+            _ if !flag.starts_with('-') => {
+                paths.push(flag);
+                continue;
+            },
+            _ => {},
+        }
+        let Some(value) = args.next() else {
+            usage_error(&format!("Missing value for {}", flag));
+        };
+        match flag.as_str() {
+            "--buckets" => match value.parse() {
+                Ok(parts) if parts > 0 => split = MoveBuckets::Equal(parts),
+                _ => usage_error(&format!(
+                    "Invalid buckets '{}', expected a whole number above 0",
+                    value
+                )),
+            },
+            "--split" => match parse_split(&value) {
+                Some(starts) => split = MoveBuckets::Before(starts),
+                None => usage_error(&format!(
+                    "Invalid split '{}', expected increasing move numbers \
+                     such as 15,40",
+                    value
+                )),
+            },
+            "--player" => match value.as_str() {
+                "1" => players = vec![Player::Player1],
+                "2" => players = vec![Player::Player2],
+                _ => usage_error(&format!(
+                    "Invalid player '{}', expected 1 or 2", value
+                )),
+            },
+            _ => usage_error(&format!("Unknown argument {}", flag)),
+        }
+    }
+    if paths.is_empty() {
+        usage_error("Expected at least one session file, see --help");
+    }
+
+    let mut heatmap = TimeHeatmap::new(split);
+    for path in paths {
+        let session = match Session::import(&path) {
+            Ok(session) => session,
+            Err(error) => {
+                eprintln!("Could not read {}: {}", path, error);
+                process::exit(1);
+            },
+        };
+        for player in &players {
+            heatmap.add_game(session.rules(), session.events(), *player);
+        }
+    }
+    print!("{}", heatmap.to_csv());
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+/// Parse move numbers to split games before, such as `15,40`
+fn parse_split(value: &str) -> Option<Vec<u32>> {
+    let starts = value.split(',')
+        .map(|number| number.trim().parse().ok().filter(|number| *number > 1))
+        .collect::<Option<Vec<u32>>>()?;
+    starts.windows(2).all(|pair| pair[0] < pair[1]).then_some(starts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_split() {
+        assert_eq!(parse_split("15,40"), Some(vec![15, 40]));
+        assert_eq!(parse_split("20"), Some(vec![20]));
+        assert_eq!(parse_split("40,15"), None);
+        assert_eq!(parse_split("1,15"), None);
+        assert_eq!(parse_split("15,x"), None);
+    }
+}
@@ -0,0 +1,10 @@
+//! # TUI
+//! Terminal frontends, built with termion. Available with the `cli` feature.
+
+pub mod chess;
+pub mod completions;
+pub mod heatmap;
+pub mod stats;
+pub mod terminal;
+pub mod theme;
+pub mod timer;
@@ -0,0 +1,93 @@
+//! # Stats
+//! The record of each player over every game kept in the game store.
+
+use std::{path::PathBuf, process};
+use crate::{i18n::NumberFormat, DurationDisplay, GameStore};
+
+const HELP: &str = "\
+Each player's games, how often they lost on time and the time they had left
+at the end on average, over every finished game of a profiled player in the
+GUI. Lists every player, or only the players named.
+
+Options:
+  --games PATH         Read the games from PATH rather than
+                       $XDG_STATE_HOME/rusty_chess_clock/games
+  -h, --help           Print this help
+  -V, --version        Print the version
+";
+
+/// Print the stats of the players in the game store
+///
+/// # Arguments
+/// * `name` - The command name to show in help and version output.
+/// * `args` - The command line arguments, without the command name.
+pub fn run(name: &str, args: impl IntoIterator<Item = String>) {
+    let mut path = GameStore::default_path();
+    let mut players = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "-h" | "--help" => {
+                print!("Usage: {} [OPTIONS] [PLAYER...]\n\n{}", name, HELP);
+                return;
+            },
+            "-V" | "--version" => {
+                println!("{} {}", name, crate::VERSION);
+                return;
+            },
+            "--games" => match args.next() {
+                Some(value) => path = Some(PathBuf::from(value)),
+                None => usage_error("Missing value for --games"),
+            },
+            _ if !flag.starts_with('-') => players.push(flag),
+            _ => usage_error(&format!("Unknown argument {}", flag)),
+        }
+    }
+    let Some(path) = path else {
+        usage_error("No state directory to find games in, pass --games");
+    };
+
+    let store = match GameStore::open(&path) {
+        Ok(store) => store,
+        Err(error) => {
+            eprintln!("Could not read {}: {}", path.display(), error);
+            process::exit(1);
+        },
+    };
+    if players.is_empty() {
+        players = store.players().into_iter().map(String::from).collect();
+    }
+    if players.is_empty() {
+        println!("No games recorded yet in {}", path.display());
+        return;
+    }
+    let numbers = NumberFormat::detect();
+    for player in &players {
+        println!("{}", player_summary(&store, player, &numbers));
+    }
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+/// Summarise a player's games, e.g. "Alice: 12 games, 16.7% lost on time,
+/// 1:05 left at the end"
+fn player_summary(
+    store: &GameStore, name: &str, numbers: &NumberFormat
+) -> String {
+    let mut summary = format!("{}: {} games", name, store.games_played(name));
+    if let Some(rate) = store.flag_rate(name) {
+        summary.push_str(&format!(
+            ", {} lost on time", numbers.percent(rate, 1)
+        ));
+    }
+    if let Some(remaining) = store.average_remaining(name) {
+        summary.push_str(&format!(
+            ", {} left at the end", DurationDisplay::from(remaining)
+        ));
+    }
+    summary
+}
@@ -0,0 +1,106 @@
+//! # Terminal
+//! Raw mode for the terminal frontends, undone however they exit.
+//!
+//! The raw terminal is kept where the panic hook can reach it, so that a
+//! panic restores the terminal before its message is printed, rather than
+//! leaving the shell without echo or line editing.
+
+use std::{
+    io::{self, Stdout, Write},
+    panic,
+    sync::{Mutex, MutexGuard, Once, PoisonError, TryLockError},
+};
+use termion::{
+    color, cursor, style,
+    raw::{IntoRawMode, RawTerminal},
+};
+
+/// The terminal in raw mode, while a `TerminalGuard` is held
+static RAW: Mutex<Option<RawTerminal<Stdout>>> = Mutex::new(None);
+
+fn raw() -> MutexGuard<'static, Option<RawTerminal<Stdout>>> {
+    RAW.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The terminal in raw mode, written to like standard output
+///
+/// Dropping the guard puts the terminal back in cooked mode and resets the
+/// cursor and colours. Only one guard should be held at a time.
+#[derive(Debug)]
+pub struct TerminalGuard {
+    stdout: Stdout,
+}
+
+impl TerminalGuard {
+    /// Puts the terminal in raw mode until the guard is dropped
+    pub fn new() -> io::Result<Self> {
+        let terminal = io::stdout().into_raw_mode()?;
+        *raw() = Some(terminal);
+        Ok(Self { stdout: io::stdout() })
+    }
+
+    /// Puts the terminal back in cooked mode for a while, such as to read a
+    /// line
+    pub fn suspend_raw_mode(&self) -> io::Result<()> {
+        raw().as_ref().map_or(Ok(()), RawTerminal::suspend_raw_mode)
+    }
+
+    /// Puts the terminal back in raw mode after `suspend_raw_mode`
+    pub fn activate_raw_mode(&self) -> io::Result<()> {
+        raw().as_ref().map_or(Ok(()), RawTerminal::activate_raw_mode)
+    }
+}
+
+impl Write for TerminalGuard {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stdout.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdout.flush()
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore(raw().take());
+    }
+}
+
+/// Resets the cursor and colours, then leaves raw mode by dropping the raw
+/// terminal
+fn restore(terminal: Option<RawTerminal<Stdout>>) {
+    let mut stdout = io::stdout();
+    let _ = write!(
+        stdout, "{}{}{}{}",
+        color::Fg(color::Reset), color::Bg(color::Reset), style::Reset,
+        cursor::Show
+    );
+    let _ = stdout.flush();
+    drop(terminal);
+}
+
+/// Installs a panic hook that restores the terminal before the panic is
+/// reported, once however often it is called
+pub fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let report = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            // The lock is only held briefly, so a panic while it is held is
+            // left alone rather than waited on forever
+            let terminal = match RAW.try_lock() {
+                Ok(terminal) => Some(terminal),
+                Err(TryLockError::Poisoned(error)) => Some(error.into_inner()),
+                Err(TryLockError::WouldBlock) => None,
+            };
+            if let Some(mut terminal) = terminal {
+                if let Some(terminal) = terminal.take() {
+                    restore(Some(terminal));
+                    eprintln!();
+                }
+            }
+            report(info);
+        }));
+    });
+}
@@ -0,0 +1,145 @@
+//! # Theme
+//! Colours for the terminal clocks that fade from green through yellow to
+//! red as a player's time runs down.
+//!
+//! The gradient is drawn in as many colours as the terminal supports, and
+//! falls back to the three basic colours on terminals that do not advertise
+//! anything better.
+
+use std::env;
+use termion::color::{self, Color};
+
+/// How many colours a terminal can show
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorDepth {
+    /// The 8 basic ANSI colours
+    Basic,
+    /// The 256 colour palette
+    Ansi256,
+    /// 24-bit colour
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Detect the colour depth of the terminal from the environment
+    pub fn detect() -> Self {
+        Self::from_env(
+            env::var("COLORTERM").ok().as_deref(),
+            env::var("TERM").ok().as_deref()
+        )
+    }
+
+    /// Work out the colour depth from the values of `COLORTERM` and `TERM`
+    ///
+    /// Terminals with 24-bit colour advertise it with a `COLORTERM` of
+    /// `truecolor` or `24bit`, and those with 256 colours usually have a
+    /// `TERM` ending in `256color`.
+    pub fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        match (colorterm, term) {
+            (Some("truecolor" | "24bit"), _) => Self::TrueColor,
+            (_, Some(term)) if term.contains("256color") => Self::Ansi256,
+            _ => Self::Basic,
+        }
+    }
+}
+
+/// The colour of a clock with `fraction` of its time left, fading from
+/// green when full through yellow at half to red when empty
+///
+/// # Returns
+/// The colour as red, green and blue components.
+pub fn gradient(fraction: f32) -> (u8, u8, u8) {
+    let fraction = fraction.clamp(0.0, 1.0);
+    let (red, green) = if fraction >= 0.5 {
+        ((1.0 - fraction) * 2.0, 1.0)
+    } else {
+        (1.0, fraction * 2.0)
+    };
+    ((red * 255.0).round() as u8, (green * 255.0).round() as u8, 0)
+}
+
+/// The gradient colour of a clock, drawn at a terminal's colour depth
+#[derive(Debug, Clone, Copy)]
+pub struct GradientColor {
+    depth: ColorDepth,
+    fraction: f32,
+}
+
+impl GradientColor {
+    pub fn new(depth: ColorDepth, fraction: f32) -> Self {
+        Self { depth, fraction }
+    }
+
+    fn with<T>(&self, mut write: impl FnMut(&dyn Color) -> T) -> T {
+        let (r, g, b) = gradient(self.fraction);
+        match self.depth {
+            ColorDepth::TrueColor => write(&color::Rgb(r, g, b)),
+            // The 256 colour palette has a 6x6x6 colour cube
+            ColorDepth::Ansi256 => {
+                write(&color::AnsiValue::rgb(r / 51, g / 51, b / 51))
+            },
+            ColorDepth::Basic if self.fraction > 2.0 / 3.0 => {
+                write(&color::Green)
+            },
+            ColorDepth::Basic if self.fraction > 1.0 / 3.0 => {
+                write(&color::Yellow)
+            },
+            ColorDepth::Basic => write(&color::Red),
+        }
+    }
+}
+
+impl Color for GradientColor {
+    fn write_fg(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.with(|color| color.write_fg(f))
+    }
+
+    fn write_bg(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.with(|color| color.write_bg(f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_from_env() {
+        assert_eq!(
+            ColorDepth::from_env(Some("truecolor"), Some("xterm")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::from_env(None, Some("xterm-256color")),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(
+            ColorDepth::from_env(Some("yes"), Some("xterm")),
+            ColorDepth::Basic
+        );
+        assert_eq!(ColorDepth::from_env(None, None), ColorDepth::Basic);
+    }
+
+    #[test]
+    fn test_gradient() {
+        assert_eq!(gradient(1.0), (0, 255, 0));
+        assert_eq!(gradient(0.5), (255, 255, 0));
+        assert_eq!(gradient(0.0), (255, 0, 0));
+        assert_eq!(gradient(2.0), gradient(1.0));
+    }
+
+    #[test]
+    fn test_colour_depths() {
+        let bg = |depth, fraction| {
+            color::Bg(GradientColor::new(depth, fraction)).to_string()
+        };
+        assert_eq!(bg(ColorDepth::TrueColor, 0.0), "\x1b[48;2;255;0;0m");
+        assert_eq!(
+            bg(ColorDepth::Ansi256, 1.0),
+            color::Bg(color::AnsiValue::rgb(0, 5, 0)).to_string()
+        );
+        assert_eq!(
+            bg(ColorDepth::Basic, 0.5), color::Bg(color::Yellow).to_string()
+        );
+    }
+}
@@ -0,0 +1,444 @@
+//! # Timer
+//! Stopwatches, countdowns, speech, interval and exam timers for the
+//! terminal.
+
+use std::{io::{self, Write}, process, thread::sleep, time::Duration};
+use crate::{
+    Card, Clock, ClockChange, ClockGroup, ClockMode, ClockState,
+    DurationDisplay,
+    IntervalTimer, ScaledTime, SpeechTimer, TimeSource, render, times::*,
+    utils::parse_duration,
+    tui::terminal::{self, TerminalGuard}
+};
+use termion::{
+    clear, color, cursor, input::{TermRead, Keys}, AsyncReader
+};
+
+const HELP: &str = "\
+A stopwatch, countdown, speech timer, interval timer or exam clock. With
+no options the kind of timer is asked for interactively.
+
+Options:
+  --rounds SPEC        Run an interval timer, e.g. 12x3m/1m
+  --exam LABEL=DUR     Add an exam countdown, e.g. \"Section A=90m\"
+                       (repeatable)
+  --alarm DUR          Ring at this time remaining on every exam clock or
+                       the countdown, or this time into the stopwatch
+                       (repeatable)
+  --big                Draw the clock in large seven-segment digits
+  --speed RATE         Run the stopwatch or countdown RATE times as fast
+                       as real time, e.g. 2 or 0.25
+  -h, --help           Print this help
+  -V, --version        Print the version
+
+Keys:
+  q                    Stop and quit
+  p                    Pause / resume
+  r                    Reset and restart
+  l                    Record a lap
+  ] / [                Add / subtract one second
+  ' / ;                Add / subtract one minute
+  . / ,                Add / subtract one hour
+";
+
+/// The kinds of timer the CLI can run
+enum Mode {
+    Clock(ClockMode),
+    Speech,
+}
+
+/// Run the terminal timer
+///
+/// # Arguments
+/// * `name` - The command name to show in help and version output.
+/// * `args` - The command line arguments, without the command name.
+pub fn run(name: &str, args: impl IntoIterator<Item = String>) {
+    terminal::install_panic_hook();
+    let mut rounds = None;
+    let mut exam = ClockGroup::new();
+    let mut alarms = Vec::new();
+    let mut big = false;
+    let mut speed = None;
+
+    let mut args = args.into_iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--big" => {
+                big = true;
+                continue;
+            },
+            "-h" | "--help" => {
+                print!("Usage: {} [OPTIONS]\n\n{}", name, HELP);
+                return;
+            },
+            "-V" | "--version" => {
+                println!("{} {}", name, crate::VERSION);
+                return;
+            },
+            _ => {},
+        }
+        let Some(value) = args.next() else {
+            usage_error(&format!("Missing value for {}", flag));
+        };
+        match flag.as_str() {
+            "--rounds" => match IntervalTimer::from_rounds_spec(&value) {
+                Some(timer) => rounds = Some(timer),
+                None => usage_error(&format!(
+                    "Invalid rounds '{}', expected e.g. 12x3m/1m", value
+                )),
+            },
+            "--exam" => match value.split_once('=')
+                .and_then(|(label, time)| Some((label, parse_duration(time)?)))
+            {
+                Some((label, time)) => {
+                    exam.add_countdown(label, time);
+                },
+                _ => usage_error(&format!(
+                    "Invalid exam clock '{}', expected e.g. \"Section A=90m\"",
+                    value
+                )),
+            },
+            "--alarm" => match parse_duration(&value) {
+                Some(threshold) => alarms.push(threshold),
+                None => usage_error(&format!(
+                    "Invalid alarm '{}', expected e.g. 10m", value
+                )),
+            },
+            "--speed" => match value.trim_end_matches('x').parse::<f64>()
+                .ok()
+                .and_then(ScaledTime::try_new)
+            {
+                Some(rate) => speed = Some(rate),
+                None => usage_error(&format!(
+                    "Invalid speed '{}', expected e.g. 2 or 0.25, up to {}",
+                    value, ScaledTime::MAX_RATE
+                )),
+            },
+            _ => usage_error(&format!("Unknown argument {}", flag)),
+        }
+    }
+
+    if let Some(mut timer) = rounds {
+        run_interval_timer(&mut timer);
+        return;
+    }
+    if !exam.is_empty() {
+        for index in 0..exam.len() {
+            alarms.iter().for_each(|&alarm| exam.add_alarm(index, alarm));
+        }
+        run_exam(&mut exam);
+        return;
+    }
+
+    println!("Clock");
+    println!("=====");
+
+    match get_mode() {
+        Mode::Clock(mode) => {
+            let start = get_start_time();
+            println!();
+
+            match speed {
+                Some(rate) => run_clock(
+                    Clock::with_source(mode, start, rate), &alarms, big
+                ),
+                None => run_clock(Clock::new(mode, start), &alarms, big),
+            }
+        },
+        Mode::Speech => {
+            let mut speech = get_speech_timer();
+            println!();
+
+            run_speech_timer(&mut speech);
+        },
+    }
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+/// Run a group of exam countdowns, one per line
+///
+/// The terminal bell is rung and the clock marked when one of its alarms
+/// fires. Runs until every clock has expired or 'q' is pressed.
+fn run_exam(exam: &mut ClockGroup) {
+    let stdin = termion::async_stdin();
+    let mut stdout = TerminalGuard::new().unwrap();
+    let mut keys = stdin.keys();
+    let mut alarmed = vec![false; exam.len()];
+    let lines = exam.len() as u16;
+
+    exam.start_all();
+    loop {
+        if let Some(Ok(termion::event::Key::Char('q'))) = keys.next() {
+            exam.stop_all();
+            break;
+        }
+
+        let fired = exam.update();
+        if !fired.is_empty() {
+            print!("\x07");
+        }
+        for (index, _) in fired {
+            alarmed[index] = true;
+        }
+
+        for (index, (label, clock)) in exam.iter().enumerate() {
+            let marker = if alarmed[index] { "*" } else { " " };
+            print!("\r{}{} {}: {}\r\n", clear::CurrentLine, marker, label, clock);
+        }
+        if exam.all_expired() {
+            break;
+        }
+        print!("{}", cursor::Up(lines));
+        stdout.flush().unwrap();
+        sleep(10.millis());
+    }
+    print!("\rAll clocks stopped");
+    stdout.flush().unwrap();
+}
+
+/// Run the clock
+///
+/// This function starts the clock, and then runs in a loop, updating the clock
+/// every 10 milliseconds. It also processes terminal input asynchronously,
+/// allowing the clock to continue running while waiting for user input. With
+/// `big` set the time is drawn in seven-segment block digits, and each of
+/// `alarms` rings the terminal bell.
+fn run_clock<T: TimeSource>(
+    mut clock: Clock<T>, alarms: &[Duration], big: bool
+) {
+    alarms.iter().for_each(|&alarm| clock.add_alarm(alarm, "alarm"));
+    let stdin = termion::async_stdin();
+    let mut stdout = TerminalGuard::new().unwrap();
+    let mut keys = stdin.keys();
+
+    clock.on_event(|change| {
+        if let ClockChange::Alarm(_) = change {
+            print!("\x07");
+        }
+    });
+    clock.start();
+    while let ClockState::Running(_) | ClockState::Paused = clock.state() {
+        async_process_keys(&mut clock, &mut keys);
+        clock.read_and_update();
+        if big {
+            display_big_clock(&clock, &mut stdout);
+        } else {
+            display_clock(&clock, &mut stdout);
+        }
+        sleep(10.millis());
+    }
+    if big {
+        print!("{}", cursor::Down(render::ART_HEIGHT as u16));
+    }
+    print!("\rClock stopped at: {}", clock);
+    for (number, lap) in clock.laps().iter().enumerate() {
+        print!("\r\nLap {}: {:#}", number + 1, DurationDisplay::from(*lap));
+    }
+}
+
+fn display_clock<T: TimeSource>(
+    clock: &Clock<T>,
+    stdout: &mut TerminalGuard
+) {
+    let paused = if clock.is_paused() { " (paused)" } else { "" };
+    let lap = match clock.laps().last() {
+        Some(&lap) => format!(
+            " | Lap {}: {:#}", clock.laps().len(), DurationDisplay::from(lap)
+        ),
+        None => String::new(),
+    };
+    print!("\r{}Clock: {:#}{}{}", clear::CurrentLine, clock, lap, paused);
+    stdout.flush().unwrap();
+}
+
+fn display_big_clock<T: TimeSource>(
+    clock: &Clock<T>,
+    stdout: &mut TerminalGuard
+) {
+    for line in render::block_art(clock.read_parts()) {
+        print!("\r{}{}\r\n", clear::CurrentLine, line);
+    }
+    print!("{}", cursor::Up(render::ART_HEIGHT as u16));
+    stdout.flush().unwrap();
+}
+
+fn get_mode() -> Mode {
+    loop {
+        print!(
+            "\r{}Enter the mode of the clock \
+             (1=count up, 2=count down, 3=count down into overtime, \
+             4=speech): ",
+            clear::CurrentLine
+        );
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).expect(
+            "Failed to read line"
+        );
+
+        match input.trim() {
+            "1" => return Mode::Clock(ClockMode::CountUp),
+            "2" => return Mode::Clock(ClockMode::CountDown),
+            "3" => return Mode::Clock(ClockMode::CountDownOvertime),
+            "4" => return Mode::Speech,
+            _ => println!("Invalid input. Please enter 1, 2, 3 or 4."),
+        }
+    }
+}
+
+fn get_start_time() -> Option<Duration> {
+    print!("Enter the start time of the clock in seconds: ");
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).expect("Failed to read line");
+
+    match input.trim().parse::<u64>() {
+        Ok(seconds) => {
+            Some(seconds.seconds())
+        },
+        _ => {
+            println!("Invalid start time, using defaults");
+            None
+        }
+    }
+}
+
+fn get_speech_timer() -> SpeechTimer {
+    print!("Enter the length of the speech in minutes (default 5): ");
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).expect("Failed to read line");
+
+    let length = match input.trim().parse::<u64>() {
+        Ok(minutes) => minutes.minutes(),
+        Err(_) => FIVE_MINUTES,
+    };
+    println!("Yellow card at 1 minute remaining, red card at 30 seconds");
+
+    SpeechTimer::new(length, ONE_MINUTE, THIRTY_SECONDS)
+}
+
+/// Run the speech timer
+///
+/// Like `run_clock`, but showing the card for the time remaining until the
+/// speaker quits with 'q'.
+fn run_speech_timer(speech: &mut SpeechTimer) {
+    let stdin = termion::async_stdin();
+    let mut stdout = TerminalGuard::new().unwrap();
+    let mut keys = stdin.keys();
+
+    speech.start();
+    while speech.timer().is_running() {
+        if let Some(Ok(key)) = keys.next() {
+            match key {
+                termion::event::Key::Char('q') => speech.stop(),
+                termion::event::Key::Char('r') => {
+                    speech.reset();
+                    speech.start();
+                },
+                _ => {}
+            }
+        }
+        display_speech_timer(speech, &mut stdout);
+        sleep(10.millis());
+    }
+    print!(
+        "\r{}Speech stopped at: {}",
+        clear::CurrentLine, DurationDisplay::from(speech.timer().elapsed())
+    );
+}
+
+fn display_speech_timer(
+    speech: &SpeechTimer,
+    stdout: &mut TerminalGuard
+) {
+    let time = match speech.overrun() {
+        Duration::ZERO => format!("{}", DurationDisplay::from(speech.remaining())),
+        overrun => format!("+{}", DurationDisplay::from(overrun)),
+    };
+    let card = speech.card();
+    let background = match card {
+        Card::Green => color::Bg(color::Green).to_string(),
+        Card::Yellow => color::Bg(color::Yellow).to_string(),
+        Card::Red => color::Bg(color::Red).to_string(),
+    };
+    print!(
+        "\r{}{}{} Speech: {} {}{} {} card",
+        clear::CurrentLine,
+        color::Fg(color::Black), background, time,
+        color::Fg(color::Reset), color::Bg(color::Reset), card
+    );
+    stdout.flush().unwrap();
+}
+
+/// Run an interval timer to the end of its last interval
+///
+/// The terminal bell is rung at every boundary between intervals.
+fn run_interval_timer(timer: &mut IntervalTimer) {
+    let stdin = termion::async_stdin();
+    let mut stdout = TerminalGuard::new().unwrap();
+    let mut keys = stdin.keys();
+
+    let mut last_index = 0;
+    timer.start();
+    while let Some((index, interval, remaining)) = timer.current() {
+        if let Some(Ok(termion::event::Key::Char('q'))) = keys.next() {
+            timer.stop();
+            break;
+        }
+        let bell = if index != last_index { "\x07" } else { "" };
+        last_index = index;
+
+        print!(
+            "\r{}{}{}: {}",
+            clear::CurrentLine, bell,
+            interval.label(), DurationDisplay::from(remaining)
+        );
+        stdout.flush().unwrap();
+        sleep(10.millis());
+    }
+    if timer.is_finished() {
+        print!("\x07");
+    }
+    print!(
+        "\r{}Timer stopped at: {}",
+        clear::CurrentLine, DurationDisplay::from(timer.elapsed())
+    );
+}
+
+/// Process terminal input asynchronously
+///
+/// This function processes terminal input asynchronously, allowing the clock to
+/// continue running while waiting for user input.
+fn async_process_keys<T: TimeSource>(
+    clock: &mut Clock<T>, keys: &mut Keys<AsyncReader>
+) {
+    if let Some(Ok(termion::event::Key::Char(c))) = keys.next() {
+        match c {
+            'q' => clock.stop(),
+            'p' if clock.is_paused() => clock.resume(),
+            'p' => clock.pause(),
+            'r' => {
+                clock.reset(None);
+                clock.start();
+            },
+            'l' => {
+                clock.lap();
+            },
+            ']' => clock.add(ONE_SECOND),
+            '[' => clock.subtract(ONE_SECOND),
+            '\'' => clock.add(ONE_MINUTE),
+            ';' => clock.subtract(ONE_MINUTE),
+            '.' => clock.add(ONE_HOUR),
+            ',' => clock.subtract(ONE_HOUR),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! # Twitch
+//! A bridge to a Twitch channel's chat that answers the `!clock` command with
+//! the current times, so that viewers can ask for them on stream.
+//!
+//! Twitch chat is IRC over TCP. The bridge never blocks: the front end polls
+//! it with the clock, and it answers any commands that have arrived since the
+//! last poll. Replies are rate limited, and commands sent while the bridge is
+//! cooling down are ignored rather than queued.
+
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::TcpStream,
+    time::{Duration, Instant},
+};
+use crate::{ChessClock, Status};
+
+/// The address of Twitch's IRC server
+pub const SERVER: &str = "irc.chat.twitch.tv:6667";
+
+/// The chat command the bridge answers
+pub const COMMAND: &str = "!clock";
+
+/// A connection to a Twitch channel's chat that answers `!clock`
+#[derive(Debug)]
+pub struct TwitchBridge {
+    stream: TcpStream,
+    pending: Vec<u8>,
+    responder: Responder,
+}
+
+impl TwitchBridge {
+    /// Connects to the chat of `channel` as the Twitch user `nick`
+    ///
+    /// `token` is an OAuth token for `nick` with the chat scopes, with or
+    /// without its `oauth:` prefix. After a reply, commands are ignored for
+    /// `cooldown`.
+    pub fn connect(
+        nick: &str, token: &str, channel: &str, cooldown: Duration
+    ) -> io::Result<Self> {
+        let mut stream = TcpStream::connect(SERVER)?;
+        let token = token.strip_prefix("oauth:").unwrap_or(token);
+        let channel = channel.trim_start_matches('#').to_lowercase();
+        write!(
+            stream, "PASS oauth:{}\r\nNICK {}\r\nJOIN #{}\r\n",
+            token, nick.to_lowercase(), channel
+        )?;
+        stream.set_nonblocking(true)?;
+
+        Ok(Self {
+            stream,
+            pending: Vec::new(),
+            responder: Responder::new(channel, cooldown),
+        })
+    }
+
+    /// Answers the chat lines that have arrived since the last poll
+    ///
+    /// Returns the number of `!clock` commands answered.
+    pub fn poll(&mut self, clock: &ChessClock) -> io::Result<usize> {
+        let mut buffer = [0; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        ErrorKind::ConnectionAborted, "Twitch closed the chat"
+                    ));
+                },
+                Ok(read) => self.pending.extend_from_slice(&buffer[..read]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {},
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut answered = 0;
+        // A line without its newline yet is kept for the next poll
+        while let Some(end) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let now = Instant::now();
+            if let Some(reply) = self.responder.respond(&line, clock, now) {
+                answered += usize::from(reply.starts_with("PRIVMSG"));
+                self.send(&reply)?;
+            }
+        }
+        Ok(answered)
+    }
+
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        // The socket is non-blocking, but IRC lines are short enough that a
+        // full send buffer means the connection is gone
+        self.stream.write_all(format!("{}\r\n", line).as_bytes())
+    }
+}
+
+/// Works out the reply to each line of chat
+#[derive(Debug)]
+struct Responder {
+    channel: String,
+    cooldown: Duration,
+    last_reply: Option<Instant>,
+}
+
+impl Responder {
+    fn new(channel: String, cooldown: Duration) -> Self {
+        Self { channel, cooldown, last_reply: None }
+    }
+
+    /// Get the IRC line to send in reply to `line` at `now`, if any
+    fn respond(
+        &mut self, line: &str, clock: &ChessClock, now: Instant
+    ) -> Option<String> {
+        let line = line.trim_end();
+        if let Some(server) = line.strip_prefix("PING ") {
+            return Some(format!("PONG {}", server));
+        }
+
+        let target = format!("PRIVMSG #{} :", self.channel);
+        let (_, message) = line.split_once(&target)?;
+        if message.split_whitespace().next() != Some(COMMAND) {
+            return None;
+        }
+        let cooling = self.last_reply
+            .is_some_and(|last| now.duration_since(last) < self.cooldown);
+        if cooling {
+            return None;
+        }
+
+        self.last_reply = Some(now);
+        Some(format!("{}{}", target, describe(clock)))
+    }
+}
+
+/// Describes the clock for chat, e.g.
+/// `Player 1 04:12 | Player 2 03:58, Player 2 to move`
+fn describe(clock: &ChessClock) -> String {
+    let view = clock.view_model();
+    let times = view.players.iter()
+        .map(|side| format!("{} {}", side.label, side.text))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    match view.status {
+        Status::Running => {
+            format!("{}, {} to move", times, view.active().label)
+        },
+        Status::Stopped => format!("{}, paused", times),
+        Status::Finished => format!("{}, game over", times),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::times;
+
+    const CHAT: &str = ":viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #club :";
+
+    fn responder() -> Responder {
+        Responder::new("club".to_string(), times::TEN_SECONDS)
+    }
+
+    #[test]
+    fn test_answers_clock_command() {
+        let mut responder = responder();
+        let clock = ChessClock::default();
+        let reply = responder.respond(
+            &format!("{}!clock please\r\n", CHAT), &clock, Instant::now()
+        );
+        let expected = format!("PRIVMSG #club :{}", describe(&clock));
+        assert_eq!(reply, Some(expected));
+    }
+
+    #[test]
+    fn test_ignores_other_chat() {
+        let mut responder = responder();
+        let clock = ChessClock::default();
+        let now = Instant::now();
+        assert_eq!(responder.respond(&format!("{}hi", CHAT), &clock, now), None);
+        assert_eq!(
+            responder.respond(&format!("{}!clocks", CHAT), &clock, now), None
+        );
+        let elsewhere = ":viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #other :";
+        assert_eq!(
+            responder.respond(&format!("{}!clock", elsewhere), &clock, now),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut responder = responder();
+        let clock = ChessClock::default();
+        let command = format!("{}!clock", CHAT);
+        let start = Instant::now();
+
+        assert!(responder.respond(&command, &clock, start).is_some());
+        let early = start + times::FIVE_SECONDS;
+        assert!(responder.respond(&command, &clock, early).is_none());
+        let late = start + times::TEN_SECONDS;
+        assert!(responder.respond(&command, &clock, late).is_some());
+    }
+
+    #[test]
+    fn test_answers_ping() {
+        let mut responder = responder();
+        let clock = ChessClock::default();
+        assert_eq!(
+            responder.respond("PING :tmi.twitch.tv", &clock, Instant::now()),
+            Some("PONG :tmi.twitch.tv".to_string())
+        );
+    }
+
+    #[test]
+    fn test_describe() {
+        let clock = ChessClock::default();
+        assert!(describe(&clock).starts_with("Player 1 "));
+        assert!(describe(&clock).ends_with(", paused"));
+    }
+}
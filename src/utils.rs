@@ -1,3 +1,5 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 /// Round a number to a specified number of decimal places.
 ///
 /// # Arguments
@@ -21,6 +23,172 @@ pub fn round(num: f64, places: u32) -> f64 {
     (num * factor as f64).round() / factor as f64
 }
 
+/// The step a duration is rounded to by [`round_duration`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Second,
+    Tenth,
+    Hundredth,
+}
+
+impl Resolution {
+    /// The length of one step at this resolution.
+    pub const fn step(self) -> Duration {
+        match self {
+            Resolution::Second => Duration::from_secs(1),
+            Resolution::Tenth => Duration::from_millis(100),
+            Resolution::Hundredth => Duration::from_millis(10),
+        }
+    }
+
+    /// The number of decimal places needed to show this resolution.
+    pub const fn places(self) -> usize {
+        match self {
+            Resolution::Second => 0,
+            Resolution::Tenth => 1,
+            Resolution::Hundredth => 2,
+        }
+    }
+}
+
+/// The direction [`round_duration`] rounds in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round towards zero.
+    Floor,
+    /// Round away from zero.
+    Ceil,
+    /// Round to the nearest step, with halves rounded up.
+    Nearest,
+}
+
+/// Round a duration to a whole number of steps.
+///
+/// The rounding is done on whole nanoseconds, so results near a step
+/// boundary are exact. A result too large for a `Duration` saturates at
+/// `Duration::MAX`.
+///
+/// # Arguments
+/// * `duration` - The duration to round.
+/// * `resolution` - The step to round to.
+/// * `mode` - The direction to round in.
+///
+/// # Returns
+/// The rounded duration.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use rusty_chess_clock::utils::{round_duration, Resolution, RoundingMode};
+///
+/// let time = Duration::from_millis(59_950);
+/// assert_eq!(
+///     round_duration(time, Resolution::Second, RoundingMode::Floor),
+///     Duration::from_secs(59)
+/// );
+/// assert_eq!(
+///     round_duration(time, Resolution::Second, RoundingMode::Nearest),
+///     Duration::from_secs(60)
+/// );
+/// ```
+pub fn round_duration(
+    duration: Duration, resolution: Resolution, mode: RoundingMode
+) -> Duration {
+    let step = resolution.step().as_nanos();
+    let nanos = duration.as_nanos();
+    let (steps, rest) = (nanos / step, nanos % step);
+    let steps = match mode {
+        RoundingMode::Floor => steps,
+        RoundingMode::Ceil if rest > 0 => steps + 1,
+        RoundingMode::Ceil => steps,
+        RoundingMode::Nearest if rest * 2 >= step => steps + 1,
+        RoundingMode::Nearest => steps,
+    };
+    let nanos = steps * step;
+    match u64::try_from(nanos / 1_000_000_000) {
+        Ok(secs) => Duration::new(secs, (nanos % 1_000_000_000) as u32),
+        Err(_) => Duration::MAX,
+    }
+}
+
+/// Parse a duration written as a number followed by a unit.
+///
+/// The units `s`, `m` and `h` are accepted, and a bare number is read as
+/// seconds.
+///
+/// # Arguments
+/// * `input` - The text to parse, such as `"90s"` or `"3m"`.
+///
+/// # Returns
+/// The parsed duration, or `None` if the text is not a valid duration.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+/// use rusty_chess_clock::utils::parse_duration;
+///
+/// assert_eq!(parse_duration("3m"), Some(Duration::from_secs(180)));
+/// assert_eq!(parse_duration("3 minutes"), None);
+/// ```
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    let input = input.trim();
+    let (number, factor) = match input.chars().last()? {
+        's' => (&input[..input.len() - 1], 1),
+        'm' => (&input[..input.len() - 1], 60),
+        'h' => (&input[..input.len() - 1], 3600),
+        _ => (input, 1),
+    };
+    number.parse::<u64>().ok()
+        .and_then(|n| n.checked_mul(factor))
+        .map(Duration::from_secs)
+}
+
+/// Format a wall-clock time as an RFC 3339 UTC timestamp.
+///
+/// Times before the Unix epoch are formatted as the epoch.
+///
+/// # Arguments
+/// * `time` - The time to format.
+///
+/// # Returns
+/// The timestamp, with millisecond precision.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use rusty_chess_clock::utils::format_rfc3339;
+///
+/// let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+/// assert_eq!(format_rfc3339(time), "2023-11-14T22:13:20.123Z");
+/// ```
+pub fn format_rfc3339(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Convert days since the epoch to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year, month, day,
+        secs_of_day / 3600, secs_of_day / 60 % 60, secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
 // tests
 #[cfg(test)]
 mod tests {
@@ -35,4 +203,81 @@ mod tests {
         assert_eq!(round(1.5, 0), 2.0);
         assert_eq!(round(1.0, 5), 1.0);
     }
+
+    #[test]
+    fn test_round_duration() {
+        let time = Duration::from_millis(1_250);
+        let cases = [
+            (Resolution::Second, RoundingMode::Floor, 1_000),
+            (Resolution::Second, RoundingMode::Ceil, 2_000),
+            (Resolution::Second, RoundingMode::Nearest, 1_000),
+            (Resolution::Tenth, RoundingMode::Floor, 1_200),
+            (Resolution::Tenth, RoundingMode::Ceil, 1_300),
+            (Resolution::Tenth, RoundingMode::Nearest, 1_300),
+            (Resolution::Hundredth, RoundingMode::Floor, 1_250),
+            (Resolution::Hundredth, RoundingMode::Ceil, 1_250),
+            (Resolution::Hundredth, RoundingMode::Nearest, 1_250),
+        ];
+        for (resolution, mode, millis) in cases {
+            assert_eq!(
+                round_duration(time, resolution, mode),
+                Duration::from_millis(millis),
+                "{:?} {:?}", resolution, mode
+            );
+        }
+    }
+
+    #[test]
+    fn test_round_duration_boundaries() {
+        let just_under = Duration::from_nanos(59_994_999_999);
+        assert_eq!(
+            round_duration(just_under, Resolution::Hundredth, RoundingMode::Nearest),
+            Duration::from_millis(59_990)
+        );
+        assert_eq!(
+            round_duration(
+                just_under + Duration::from_nanos(1),
+                Resolution::Hundredth,
+                RoundingMode::Nearest
+            ),
+            Duration::from_secs(60)
+        );
+        assert_eq!(
+            round_duration(Duration::from_nanos(1), Resolution::Second, RoundingMode::Ceil),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            round_duration(Duration::MAX, Resolution::Second, RoundingMode::Ceil),
+            Duration::MAX
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("90s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("3m"), Some(Duration::from_secs(180)));
+        assert_eq!(parse_duration("2h"), Some(Duration::from_secs(7200)));
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("-1m"), None);
+        assert_eq!(parse_duration("1.5m"), None);
+    }
+
+    #[test]
+    fn test_format_rfc3339() {
+        assert_eq!(format_rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            format_rfc3339(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "2000-02-29T00:00:00.000Z"
+        );
+        assert_eq!(
+            format_rfc3339(UNIX_EPOCH + Duration::from_millis(4_102_444_799_999)),
+            "2099-12-31T23:59:59.999Z"
+        );
+        assert_eq!(
+            format_rfc3339(UNIX_EPOCH - Duration::from_secs(1)),
+            "1970-01-01T00:00:00.000Z"
+        );
+    }
 }
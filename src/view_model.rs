@@ -0,0 +1,181 @@
+//! # View model
+//! Everything a front end needs to draw a chess clock, worked out in one
+//! call so that every front end shows the same thing.
+//!
+//! The times are formatted as the clock shows them, in hundredths during a
+//! time scramble, and each player's clock carries how urgently it should
+//! catch the eye. Front ends only decide how each is drawn.
+
+use std::time::{Duration, Instant};
+use crate::{
+    display_precision_for, millis::Millis, ChessClock, DurationDisplay, Player,
+    Status, SCRAMBLE_THRESHOLD
+};
+
+/// The fraction of a player's starting time under which their time is low
+pub const LOW_TIME_FRACTION: f32 = 0.1;
+
+/// How urgently a player's clock should catch the eye, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WarningLevel {
+    /// The player has time in hand
+    None,
+    /// The player has less than `LOW_TIME_FRACTION` of their starting time
+    Low,
+    /// The player is in a time scramble, with less than
+    /// `SCRAMBLE_THRESHOLD`, and their time is shown in hundredths
+    Scramble,
+    /// The player has run out of time
+    Flagged,
+}
+
+impl WarningLevel {
+    /// Get the warning for a player with `time` left, out of `start`
+    fn for_time(time: Duration, start: Duration) -> Self {
+        if time.is_zero() {
+            Self::Flagged
+        } else if time < SCRAMBLE_THRESHOLD {
+            Self::Scramble
+        } else if Millis::from(time).fraction_of(Millis::from(start))
+            < LOW_TIME_FRACTION
+        {
+            Self::Low
+        } else {
+            Self::None
+        }
+    }
+}
+
+/// One player's side of the clock, as it is to be drawn
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerView {
+    pub player: Player,
+    /// The name of the player, e.g. `Player 1`
+    pub label: String,
+    /// The time left on the player's clock
+    pub time: Duration,
+    /// The time left as the clock shows it, e.g. `04:12` or `00:09.52`
+    pub text: String,
+    /// Whether the player is on move
+    pub active: bool,
+    pub warning: WarningLevel,
+    /// The time left as a fraction of the player's starting time, from 0.0
+    /// to 1.0
+    pub fraction_remaining: f32,
+    /// The number of moves the player has completed
+    pub moves: u32,
+}
+
+/// Everything needed to draw a chess clock at an instant
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewModel {
+    /// Each player's side of the clock, player 1 first
+    pub players: [PlayerView; 2],
+    pub status: Status,
+    /// The status as a word to show, e.g. `Paused`
+    pub status_text: &'static str,
+    /// The number of the move being played, counting from 1
+    pub move_number: u32,
+}
+
+impl ViewModel {
+    pub fn player(&self, player: Player) -> &PlayerView {
+        &self.players[player.index()]
+    }
+
+    /// Get the side of the player on move
+    pub fn active(&self) -> &PlayerView {
+        self.players.iter()
+            .find(|view| view.active)
+            .unwrap_or(&self.players[0])
+    }
+}
+
+impl ChessClock {
+    /// Get everything needed to draw the clock now
+    pub fn view_model(&self) -> ViewModel {
+        self.view_model_at(self.now())
+    }
+
+    /// Get everything needed to draw the clock at `at`
+    pub fn view_model_at(&self, at: Instant) -> ViewModel {
+        let snapshot = self.snapshot(at);
+        let (t1, t2) = snapshot.times;
+        let player_view = |player: Player, time: Duration| {
+            let start = self.rules().get_time(player);
+            PlayerView {
+                player,
+                label: player.to_string(),
+                time,
+                text: DurationDisplay::from(time)
+                    .to_string_at(display_precision_for(time)),
+                active: player == snapshot.active_player,
+                warning: WarningLevel::for_time(time, start),
+                fraction_remaining: Millis::from(time)
+                    .fraction_of(Millis::from(start)),
+                moves: self.moves_made(player),
+            }
+        };
+        let status_text = match snapshot.status {
+            Status::Stopped => "Paused",
+            Status::Running => "Running",
+            Status::Finished => "Finished",
+        };
+        ViewModel {
+            players: [
+                player_view(Player::Player1, t1),
+                player_view(Player::Player2, t2),
+            ],
+            status: snapshot.status,
+            status_text,
+            move_number: snapshot.move_number,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{times, Rules, TimingMethod};
+
+    #[test]
+    fn test_warning_levels() {
+        let start = times::TEN_MINUTES;
+        let level = |time| WarningLevel::for_time(time, start);
+        assert_eq!(level(times::FIVE_MINUTES), WarningLevel::None);
+        assert_eq!(level(times::THIRTY_SECONDS), WarningLevel::Low);
+        assert_eq!(level(times::FIVE_SECONDS), WarningLevel::Scramble);
+        assert_eq!(level(Duration::ZERO), WarningLevel::Flagged);
+        assert!(WarningLevel::Scramble > WarningLevel::Low);
+    }
+
+    #[test]
+    fn test_view_model() {
+        let mut clock = ChessClock::new(Rules::new(
+            times::FIVE_SECONDS, times::ONE_MINUTE, Duration::ZERO,
+            Player::Player1, TimingMethod::Fischer
+        ));
+        clock.start();
+        clock.switch_player();
+        clock.stop();
+
+        let view = clock.view_model();
+        assert_eq!(view.status, Status::Stopped);
+        assert_eq!(view.status_text, "Paused");
+        assert_eq!(view.move_number, 1);
+        assert_eq!(view.active().player, Player::Player2);
+
+        let p1 = view.player(Player::Player1);
+        assert_eq!(p1.label, "Player 1");
+        assert!(!p1.active);
+        assert_eq!(p1.moves, 1);
+        assert_eq!(p1.warning, WarningLevel::Scramble);
+        assert!(p1.text.starts_with("00:0") && p1.text.contains('.'));
+
+        let p2 = view.player(Player::Player2);
+        assert!(p2.active);
+        assert_eq!(p2.moves, 0);
+        assert_eq!(p2.warning, WarningLevel::None);
+        assert!(p2.fraction_remaining > 0.99);
+    }
+}
@@ -0,0 +1,185 @@
+//! Integration tests for the public `ChessClock` API
+
+use std::time::Duration;
+use rusty_chess_clock::{
+    times, ChessClock, ClockEvent, Player, Rules, Sleep, Status, TimingMethod
+};
+
+/// Slack allowed for scheduling delays when comparing times
+const TOLERANCE: Duration = Duration::from_millis(30);
+
+fn rules(starter: Player, method: TimingMethod) -> Rules {
+    Rules::new(
+        times::TEN_MINUTES, times::TEN_MINUTES, times::FIVE_SECONDS,
+        starter, method
+    )
+}
+
+fn assert_close(actual: Duration, expected: Duration) {
+    let difference = actual.abs_diff(expected);
+    assert!(
+        difference <= TOLERANCE,
+        "expected {:?} to be within {:?} of {:?}", actual, TOLERANCE, expected
+    );
+}
+
+fn events(clock: &ChessClock) -> Vec<ClockEvent> {
+    clock.events().iter().map(|record| record.event().clone()).collect()
+}
+
+#[test]
+fn test_new_clock_is_stopped_with_rules_times() {
+    let clock = ChessClock::new(rules(Player::Player1, TimingMethod::Fischer));
+    assert_eq!(clock.status(), Status::Stopped);
+    assert_eq!(clock.active_player(), Player::Player1);
+    assert_eq!(clock.read(), (times::TEN_MINUTES, times::TEN_MINUTES));
+    assert!(clock.events().is_empty());
+}
+
+#[test]
+fn test_starter_selection() {
+    let mut clock = ChessClock::new(rules(Player::Player2, TimingMethod::Fischer));
+    assert_eq!(clock.active_player(), Player::Player2);
+
+    clock.start();
+    Duration::from_millis(50).sleep();
+    let (p1, p2) = clock.read();
+    assert_eq!(p1, times::TEN_MINUTES);
+    assert_close(p2, times::TEN_MINUTES - Duration::from_millis(50));
+    assert_eq!(events(&clock), vec![ClockEvent::Started(Player::Player2)]);
+}
+
+#[test]
+fn test_status_transitions() {
+    let mut clock = ChessClock::new(rules(Player::Player1, TimingMethod::Fischer));
+    clock.start();
+    assert_eq!(clock.status(), Status::Running);
+
+    clock.stop();
+    assert_eq!(clock.status(), Status::Stopped);
+
+    clock.start();
+    assert_eq!(clock.status(), Status::Running);
+
+    clock.finish();
+    assert_eq!(clock.status(), Status::Finished);
+
+    // A finished game can not be restarted or switched
+    clock.start();
+    clock.switch_player();
+    assert_eq!(clock.status(), Status::Finished);
+    assert_eq!(clock.active_player(), Player::Player1);
+}
+
+#[test]
+fn test_switching_alternates_players() {
+    let mut clock = ChessClock::new(rules(Player::Player1, TimingMethod::Fischer));
+    clock.start();
+    for expected in [Player::Player2, Player::Player1, Player::Player2] {
+        clock.switch_player();
+        assert_eq!(clock.active_player(), expected);
+        assert_eq!(clock.status(), Status::Running);
+    }
+    assert_eq!(clock.moves_made(Player::Player1), 2);
+    assert_eq!(clock.moves_made(Player::Player2), 1);
+    assert_eq!(clock.move_number(), 2);
+}
+
+#[test]
+fn test_switch_while_stopped_changes_starter_without_increment() {
+    let mut clock = ChessClock::new(rules(Player::Player1, TimingMethod::Fischer));
+    clock.switch_player();
+    assert_eq!(clock.active_player(), Player::Player2);
+    assert_eq!(clock.read(), (times::TEN_MINUTES, times::TEN_MINUTES));
+    assert_eq!(
+        events(&clock), vec![ClockEvent::ActivePlayerChanged(Player::Player2)]
+    );
+}
+
+#[test]
+fn test_fischer_adds_full_increment() {
+    let mut clock = ChessClock::new(rules(Player::Player1, TimingMethod::Fischer));
+    clock.start();
+    Duration::from_millis(100).sleep();
+    clock.switch_player();
+
+    let (p1, p2) = clock.read();
+    assert_close(
+        p1, times::TEN_MINUTES - Duration::from_millis(100) + times::FIVE_SECONDS
+    );
+    assert_close(p2, times::TEN_MINUTES);
+}
+
+#[test]
+fn test_bronstein_refunds_time_used_up_to_increment() {
+    let mut clock = ChessClock::new(
+        rules(Player::Player1, TimingMethod::Bronstein)
+    );
+    clock.start();
+    Duration::from_millis(100).sleep();
+    clock.switch_player();
+
+    // Less than the increment was used, so all of it is given back
+    let (p1, _) = clock.read();
+    assert_close(p1, times::TEN_MINUTES);
+
+    let mut rules = rules(Player::Player1, TimingMethod::Bronstein);
+    rules.set_increment(Duration::from_millis(50));
+    let mut clock = ChessClock::new(rules);
+    clock.start();
+    Duration::from_millis(100).sleep();
+    clock.switch_player();
+
+    // More than the increment was used, so only the increment is given back
+    let (p1, _) = clock.read();
+    assert_close(p1, times::TEN_MINUTES - Duration::from_millis(50));
+}
+
+#[test]
+fn test_flag_finishes_game() {
+    let rules = Rules::new(
+        Duration::from_millis(50), times::TEN_MINUTES, Duration::ZERO,
+        Player::Player1, TimingMethod::Fischer
+    );
+    let mut clock = ChessClock::new(rules);
+    clock.start();
+    Duration::from_millis(80).sleep();
+    clock.update();
+
+    assert_eq!(clock.status(), Status::Finished);
+    assert_eq!(clock.read().0, Duration::ZERO);
+    assert_eq!(
+        events(&clock),
+        vec![
+            ClockEvent::Started(Player::Player1),
+            ClockEvent::Flagged(Player::Player1),
+        ]
+    );
+}
+
+#[test]
+fn test_press_only_counts_for_active_player() {
+    let mut clock = ChessClock::new(rules(Player::Player1, TimingMethod::Fischer));
+    clock.start();
+    clock.press(Player::Player2);
+    assert_eq!(clock.active_player(), Player::Player1);
+    clock.press(Player::Player1);
+    assert_eq!(clock.active_player(), Player::Player2);
+}
+
+#[test]
+fn test_adjustments_are_applied_and_logged() {
+    let mut clock = ChessClock::new(rules(Player::Player1, TimingMethod::Fischer));
+    clock.add_time(Player::Player1, times::ONE_MINUTE, None).unwrap();
+    clock.subtract_time(Player::Player2, times::ONE_MINUTE, Some("Late")).unwrap();
+    clock.apply_penalty(Player::Player1, times::TWO_SECONDS, None).unwrap();
+
+    assert_eq!(
+        clock.read(),
+        (
+            times::TEN_MINUTES + times::ONE_MINUTE,
+            times::TEN_MINUTES - times::ONE_MINUTE + times::TWO_SECONDS
+        )
+    );
+    assert!(events(&clock).iter().all(ClockEvent::is_adjustment));
+}
@@ -0,0 +1,125 @@
+//! Golden-file tests for the display formats
+//!
+//! Each test renders a table of edge cases and compares it with a file in
+//! `tests/golden`, so that any change to a format shows up in review as a
+//! diff of that file. Run with `UPDATE_GOLDEN=1` to rewrite the files after
+//! a deliberate change.
+
+use std::{env, fs, path::PathBuf, time::Duration};
+use rusty_chess_clock::{
+    render, times, Clock, ClockEvent, ClockMode, DurationDisplay, Player,
+    TimeParts,
+};
+
+/// Durations either side of every boundary the formats care about
+const EDGE_MILLIS: [u64; 20] = [
+    0, 1, 4, 5, 9, 10, 994, 995, 999, 1_000,
+    59_004, 59_499, 59_500, 59_994, 59_995, 60_000,
+    3_599_994, 3_599_995, 3_600_000, 360_000_000,
+];
+
+fn check_golden(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name);
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| panic!(
+        "missing golden file {}, run with UPDATE_GOLDEN=1", path.display()
+    ));
+    assert_eq!(
+        actual, expected,
+        "output differs from {}, run with UPDATE_GOLDEN=1 if this is \
+         intended", path.display()
+    );
+}
+
+#[test]
+fn test_duration_display_golden() {
+    let mut out = String::new();
+    for millis in EDGE_MILLIS {
+        let display = DurationDisplay::from(Duration::from_millis(millis));
+        out += &format!("{:>9}ms  {:<12} {:#}\n", millis, display, display);
+    }
+    check_golden("duration_display.txt", &out);
+}
+
+#[test]
+fn test_time_parts_golden() {
+    let mut out = String::new();
+    for millis in EDGE_MILLIS {
+        let parts = TimeParts::from(Duration::from_millis(millis));
+        out += &format!(
+            "{:>9}ms  {:?} tenths={} hundredths={}\n",
+            millis, parts, parts.tenths(), parts.hundredths()
+        );
+    }
+    check_golden("time_parts.txt", &out);
+}
+
+#[test]
+fn test_clock_display_golden() {
+    let mut out = String::new();
+    for millis in EDGE_MILLIS {
+        let clock = Clock::new(
+            ClockMode::CountDown, Some(Duration::from_millis(millis))
+        );
+        out += &format!("{:>9}ms  {:<12} {:#}\n", millis, clock, clock);
+    }
+    check_golden("clock_display.txt", &out);
+}
+
+#[test]
+fn test_event_display_golden() {
+    let reason = Some("Illegal move".to_string());
+    let events = [
+        ClockEvent::Started(Player::Player1),
+        ClockEvent::Stopped(Player::Player2),
+        ClockEvent::Pressed(Player::Player1),
+        ClockEvent::ActivePlayerChanged(Player::Player2),
+        ClockEvent::SwitchIgnored(Player::Player1),
+        ClockEvent::Extended(Player::Player1),
+        ClockEvent::Flagged(Player::Player2),
+        ClockEvent::IdlePaused(Player::Player1),
+        ClockEvent::TimeAnomaly(Player::Player2),
+        ClockEvent::Backgrounded(Player::Player1),
+        ClockEvent::Foregrounded {
+            player: Player::Player1, away: Duration::from_secs(95)
+        },
+        ClockEvent::TimeAdded {
+            player: Player::Player1, time: times::ONE_MINUTE, reason: None
+        },
+        ClockEvent::TimeSubtracted {
+            player: Player::Player2, time: times::TEN_SECONDS,
+            reason: reason.clone()
+        },
+        ClockEvent::Penalty {
+            offender: Player::Player1, time: times::ONE_MINUTE * 2, reason
+        },
+        ClockEvent::TickDelay {
+            player: Player::Player2, gap: Duration::from_millis(1_250)
+        },
+        ClockEvent::SessionExpired,
+        ClockEvent::ForfeitDue(Player::Player2),
+        ClockEvent::Finished,
+    ];
+    let out: String = events.iter()
+        .map(|event| format!("{}\n", event))
+        .collect();
+    check_golden("clock_event.txt", &out);
+}
+
+#[test]
+fn test_block_art_golden() {
+    let mut out = String::new();
+    for secs in [0, 1_234, 3_599, 3_600, 45_296] {
+        let parts = TimeParts::from(Duration::from_secs(secs));
+        out += &format!("{}s\n", secs);
+        for line in render::block_art(parts) {
+            out += &format!("{}\n", line.trim_end());
+        }
+    }
+    check_golden("block_art.txt", &out);
+}
@@ -0,0 +1,315 @@
+//! Simulated network tests for the wire protocol and `Follower`
+//!
+//! A clock and a follower talk over links with configurable latency, jitter
+//! and message loss, in virtual time, to check that the follower never
+//! strays further from the clock than the network explains, and that it ends
+//! up with exactly the clock's event log.
+
+use std::{collections::VecDeque, time::{Duration, Instant}};
+use rusty_chess_clock::{
+    protocol::{Message, HEARTBEAT_INTERVAL},
+    times, ChessClock, Follower, Player, Rules, Status, TimingMethod
+};
+
+/// The step of virtual time
+const TICK: Duration = Duration::from_millis(10);
+
+/// How long the follower waits for the clock before taking it as lost
+const TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long each simulated game is played for
+const GAME: Duration = Duration::from_secs(60);
+
+/// How long the network is given to settle after the game
+const SETTLE: Duration = Duration::from_secs(5);
+
+/// Deterministic xorshift random numbers, so every run sees the same network
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Get a duration between zero and `max`
+    fn up_to(&mut self, max: Duration) -> Duration {
+        let millis = max.as_millis() as u64;
+        Duration::from_millis(self.next() % (millis + 1))
+    }
+
+    /// Get whether an event with the given chance per thousand happens
+    fn chance(&mut self, per_mille: u64) -> bool {
+        self.next() % 1000 < per_mille
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Network {
+    latency: Duration,
+    jitter: Duration,
+    drop_per_mille: u64,
+}
+
+/// One direction of a lossy link that keeps the messages it delivers in
+/// order, carrying them as encoded lines
+struct Link {
+    network: Network,
+    random: Random,
+    queue: VecDeque<(Instant, String)>,
+    last_delivery: Option<Instant>,
+    drops: u32,
+    longest_drops: u32,
+}
+
+impl Link {
+    fn new(network: Network, seed: u64) -> Self {
+        Self {
+            network,
+            random: Random(seed),
+            queue: VecDeque::new(),
+            last_delivery: None,
+            drops: 0,
+            longest_drops: 0,
+        }
+    }
+
+    fn send(&mut self, now: Instant, message: &Message) {
+        if self.random.chance(self.network.drop_per_mille) {
+            self.drops += 1;
+            self.longest_drops = self.longest_drops.max(self.drops);
+            return;
+        }
+        self.drops = 0;
+        let delay = self.network.latency
+            + self.random.up_to(self.network.jitter);
+        let at = (now + delay).max(self.last_delivery.unwrap_or(now));
+        self.last_delivery = Some(at);
+        self.queue.push_back((at, message.encode()));
+    }
+
+    fn deliver(&mut self, now: Instant, origin: Instant) -> Vec<Message> {
+        let mut delivered = Vec::new();
+        while self.queue.front().is_some_and(|(at, _)| *at <= now) {
+            let (_, line) = self.queue.pop_front().unwrap();
+            delivered.push(Message::decode(&line, origin).unwrap());
+        }
+        delivered
+    }
+}
+
+/// The clock being followed, keeping its true times in virtual time
+///
+/// The event log comes from a real clock pressed alongside, so the follower
+/// is checked against genuine events.
+struct Authority {
+    clock: ChessClock,
+    times: [Duration; 2],
+    active: Player,
+    since: Instant,
+}
+
+impl Authority {
+    fn new(start: Instant) -> Self {
+        let rules = Rules::new(
+            times::FIVE_MINUTES, times::FIVE_MINUTES, Duration::ZERO,
+            Player::Player1, TimingMethod::Fischer
+        );
+        let mut clock = ChessClock::new(rules);
+        clock.start();
+        Self {
+            clock,
+            times: [times::FIVE_MINUTES; 2],
+            active: Player::Player1,
+            since: start,
+        }
+    }
+
+    fn truth(&self, now: Instant) -> (Duration, Duration) {
+        let mut times = self.times;
+        let active = &mut times[self.active.index()];
+        *active = active.saturating_sub(now - self.since);
+        (times[0], times[1])
+    }
+
+    fn press(&mut self, now: Instant) {
+        let (t1, t2) = self.truth(now);
+        self.times = [t1, t2];
+        self.active = self.active.other();
+        self.since = now;
+        self.clock.switch_player();
+    }
+
+    fn state(&self, now: Instant) -> Message {
+        Message::State {
+            times: self.truth(now),
+            active: self.active,
+            status: Status::Running,
+        }
+    }
+
+    fn event(&self, sequence: usize) -> Message {
+        Message::Event {
+            sequence: sequence as u64,
+            record: self.clock.events()[sequence].clone(),
+        }
+    }
+}
+
+struct Report {
+    /// The furthest the follower strayed from the clock
+    max_divergence: Duration,
+    /// The furthest the follower strayed once a press had had time to arrive
+    settled_divergence: Duration,
+    /// The longest run of clock messages lost in a row
+    longest_drops: u32,
+}
+
+fn divergence(a: (Duration, Duration), b: (Duration, Duration)) -> Duration {
+    a.0.abs_diff(b.0).max(a.1.abs_diff(b.1))
+}
+
+/// Plays a game over the network, then lets it settle and checks that the
+/// follower has exactly the clock's events
+fn simulate(
+    network: Network, lag_compensation: Duration, seed: u64
+) -> Report {
+    let start = Instant::now();
+    let mut authority = Authority::new(start);
+    let mut follower = Follower::new(TIMEOUT);
+    follower.set_lag_compensation(lag_compensation);
+    let mut down = Link::new(network, seed);
+    let mut up = Link::new(network, seed.rotate_left(32) | 1);
+    let mut presses = Random(seed ^ 0x5eed);
+
+    let settle_after = network.latency + network.jitter + TICK;
+    let mut sent_events = 0;
+    let mut last_press = start;
+    let mut next_press = start + times::ONE_SECOND;
+    let mut next_state = start;
+    let mut next_request = start;
+    let mut max_divergence = Duration::ZERO;
+    let mut settled_divergence = Duration::ZERO;
+
+    let mut now = start;
+    while now < start + GAME + SETTLE {
+        // The clock: presses during the game, then its log and state
+        if now < start + GAME && now >= next_press {
+            authority.press(now);
+            last_press = now;
+            next_press = now + Duration::from_millis(500)
+                + presses.up_to(times::TWO_SECONDS);
+            next_state = now;
+        }
+        while sent_events < authority.clock.events().len() {
+            down.send(now, &authority.event(sent_events));
+            sent_events += 1;
+        }
+        if now >= next_state {
+            down.send(now, &authority.state(now));
+            let events = sent_events as u64;
+            down.send(now, &Message::Heartbeat { events });
+            next_state = now + HEARTBEAT_INTERVAL;
+        }
+        for message in up.deliver(now, start) {
+            if let Message::Resume { sequence } = message {
+                down.send(now, &authority.state(now));
+                for resent in sequence as usize..sent_events {
+                    down.send(now, &authority.event(resent));
+                }
+            }
+        }
+
+        // The follower: take what has arrived, and ask for what is missing
+        for message in down.deliver(now, follower.origin()) {
+            follower.receive(message, now);
+        }
+        let lost = follower.check(now);
+        if (lost || follower.behind()) && now >= next_request {
+            up.send(now, &follower.resume());
+            next_request = now + 2 * settle_after;
+        }
+
+        if let Some(read) = follower.read(now) {
+            let off = divergence(read, authority.truth(now));
+            max_divergence = max_divergence.max(off);
+            if now - last_press >= settle_after && !lost {
+                settled_divergence = settled_divergence.max(off);
+            }
+        }
+        now += TICK;
+    }
+
+    let expected: Vec<_> = authority.clock.events().iter()
+        .map(|record| record.event().clone())
+        .collect();
+    let followed: Vec<_> = follower.events().iter()
+        .map(|record| record.event().clone())
+        .collect();
+    assert_eq!(followed, expected, "follower's log differs from the clock's");
+    assert!(!follower.check(now), "follower did not recover");
+
+    Report {
+        max_divergence,
+        settled_divergence,
+        longest_drops: down.longest_drops,
+    }
+}
+
+const CLUB_WIFI: Network = Network {
+    latency: Duration::from_millis(80),
+    jitter: Duration::from_millis(40),
+    drop_per_mille: 0,
+};
+
+#[test]
+fn test_divergence_bounded_by_latency() {
+    for seed in [1, 7, 42] {
+        let report = simulate(CLUB_WIFI, Duration::ZERO, seed);
+        let bound = CLUB_WIFI.latency + CLUB_WIFI.jitter + TICK;
+        assert!(
+            report.max_divergence <= bound,
+            "diverged by {:?}, more than {:?}", report.max_divergence, bound
+        );
+    }
+}
+
+#[test]
+fn test_lag_compensation_leaves_only_jitter() {
+    for seed in [1, 7, 42] {
+        let uncompensated = simulate(CLUB_WIFI, Duration::ZERO, seed);
+        let compensated = simulate(CLUB_WIFI, CLUB_WIFI.latency, seed);
+        let bound = CLUB_WIFI.jitter + TICK;
+        assert!(
+            compensated.settled_divergence <= bound,
+            "settled {:?} off, more than {:?}",
+            compensated.settled_divergence, bound
+        );
+        assert!(uncompensated.settled_divergence > bound);
+    }
+}
+
+#[test]
+fn test_lossy_network_reconciles() {
+    let lossy = Network { drop_per_mille: 150, ..CLUB_WIFI };
+    for seed in [1, 7, 42] {
+        let report = simulate(lossy, lossy.latency, seed);
+        // Each lost state leaves the follower a heartbeat further behind
+        let bound = HEARTBEAT_INTERVAL * (report.longest_drops + 1)
+            + lossy.latency + lossy.jitter + TICK;
+        assert!(
+            report.max_divergence <= bound,
+            "diverged by {:?}, more than {:?}", report.max_divergence, bound
+        );
+    }
+}
+
+#[test]
+fn test_follower_survives_an_outage() {
+    // Losing every message for a while takes the connection as lost, and
+    // the follower resumes without doubling any event
+    let outage = Network { drop_per_mille: 600, ..CLUB_WIFI };
+    simulate(outage, outage.latency, 3);
+}